@@ -3,16 +3,20 @@ use std::{
     time::{Duration, Instant},
 };
 
-use bytebot_agent_rs::database::{DatabaseError, TaskRepositoryTrait};
+use bytebot_agent_rs::database::{DatabaseError, QueueInfo, TaskRepositoryTrait};
 use bytebot_shared_rs::types::{
     api::{CreateTaskDto, PaginationParams},
+    approval::{ApprovalDecision, ApprovalDecisionDto, PendingApproval},
+    budget::TaskUsage,
+    dependency::TaskGraph,
+    message::Message,
+    retry::{FailureKind, TaskAttempt},
     task::{Role, Task, TaskPriority, TaskStatus, TaskType},
 };
 use chrono::Utc;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use serde_json::json;
+use serde_json::{json, Value};
 use tokio::runtime::Runtime;
-use uuid::Uuid;
 
 // Mock repository for load testing
 struct LoadTestRepository {
@@ -49,21 +53,11 @@ impl TaskRepositoryTrait for LoadTestRepository {
         tokio::time::sleep(Duration::from_millis(1)).await;
 
         let task = Task {
-            id: Uuid::new_v4().to_string(),
             description: dto.description.clone(),
             task_type: dto.task_type.unwrap_or_default(),
-            status: TaskStatus::Pending,
             priority: dto.priority.unwrap_or_default(),
-            control: Role::Assistant,
-            created_at: Utc::now(),
             created_by: dto.created_by.unwrap_or(Role::User),
             scheduled_for: dto.scheduled_for,
-            updated_at: Utc::now(),
-            executed_at: None,
-            completed_at: None,
-            queued_at: None,
-            error: None,
-            result: None,
             model: dto.model.clone().unwrap_or_else(|| {
                 json!({
                     "provider": "anthropic",
@@ -72,6 +66,7 @@ impl TaskRepositoryTrait for LoadTestRepository {
                 })
             }),
             user_id: dto.user_id.clone(),
+            ..Default::default()
         };
 
         {
@@ -202,6 +197,269 @@ impl TaskRepositoryTrait for LoadTestRepository {
         }
         Ok(counts)
     }
+
+    async fn claim_next_pending(&self, max_running: u32) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let tasks = self.tasks.read().unwrap();
+        let running = tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Running)
+            .count();
+        if running >= max_running as usize {
+            return Ok(None);
+        }
+
+        Ok(tasks
+            .values()
+            .find(|t| t.status == TaskStatus::Pending)
+            .cloned())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: &str,
+        _error: &str,
+        _kind: FailureKind,
+    ) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let tasks = self.tasks.read().unwrap();
+        Ok(tasks.get(id).cloned())
+    }
+
+    async fn schedule_retry(
+        &self,
+        id: &str,
+        _error: &str,
+        _kind: FailureKind,
+        retry_at: chrono::DateTime<Utc>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let mut tasks = self.tasks.write().unwrap();
+        Ok(tasks.get_mut(id).map(|task| {
+            task.status = TaskStatus::Pending;
+            task.attempt += 1;
+            task.retry_at = Some(retry_at);
+            task.clone()
+        }))
+    }
+
+    async fn list_attempts(&self, _task_id: &str) -> Result<Vec<TaskAttempt>, DatabaseError> {
+        Ok(Vec::new())
+    }
+
+    async fn set_result(&self, id: &str, result: &Value) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let mut tasks = self.tasks.write().unwrap();
+        Ok(tasks.get_mut(id).map(|task| {
+            task.result = Some(result.clone());
+            task.clone()
+        }))
+    }
+
+    async fn mark_queued(&self, id: &str) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let tasks = self.tasks.read().unwrap();
+        Ok(tasks.get(id).cloned())
+    }
+
+    async fn next_scheduled_time(&self) -> Result<Option<chrono::DateTime<Utc>>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let tasks = self.tasks.read().unwrap();
+        Ok(tasks
+            .values()
+            .filter(|t| t.task_type == TaskType::Scheduled && t.queued_at.is_none())
+            .filter_map(|t| t.scheduled_for)
+            .min())
+    }
+
+    async fn spawn_recurring_run(
+        &self,
+        definition: &Task,
+        fire_time: chrono::DateTime<Utc>,
+        _next_run: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let mut run = Task::new(definition.description.clone(), definition.model.clone());
+        run.task_type = TaskType::Scheduled;
+        run.scheduled_for = Some(fire_time);
+        run.recurring_task_id = Some(definition.id.clone());
+
+        self.tasks
+            .write()
+            .unwrap()
+            .insert(run.id.clone(), run.clone());
+        Ok(Some(run))
+    }
+
+    async fn set_recurrence_paused(
+        &self,
+        id: &str,
+        paused: bool,
+        _next_run: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let mut tasks = self.tasks.write().unwrap();
+        Ok(tasks.get_mut(id).map(|task| {
+            task.recurrence_paused = paused;
+            task.clone()
+        }))
+    }
+
+    async fn preempt_for_urgent(&self, _max_running: u32) -> Result<Option<Task>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        Ok(None)
+    }
+
+    async fn requeue_interrupted(&self) -> Result<u64, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(2)).await;
+
+        let mut tasks = self.tasks.write().unwrap();
+        let mut requeued = 0;
+        for task in tasks.values_mut() {
+            if task.status == TaskStatus::Running {
+                task.status = TaskStatus::Pending;
+                task.updated_at = Utc::now();
+                requeued += 1;
+            }
+        }
+
+        Ok(requeued)
+    }
+
+    async fn queue_info(&self, id: &str) -> Result<QueueInfo, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let tasks = self.tasks.read().unwrap();
+        let mut queue: Vec<&Task> = tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .collect();
+        queue.sort_by_key(|t| t.created_at);
+
+        Ok(QueueInfo {
+            position: queue.iter().position(|t| t.id == id).map(|p| p as u64 + 1),
+            depth: queue.len() as u64,
+        })
+    }
+
+    async fn queue_depth_by_priority(
+        &self,
+    ) -> Result<std::collections::HashMap<TaskPriority, u64>, DatabaseError> {
+        self.operation_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        // Simulate database latency
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let tasks = self.tasks.read().unwrap();
+        let mut counts = std::collections::HashMap::new();
+        for task in tasks.values().filter(|t| t.status == TaskStatus::Pending) {
+            *counts.entry(task.priority).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
+    async fn fail_unsatisfiable_dependents(&self) -> Result<Vec<Task>, DatabaseError> {
+        Ok(Vec::new())
+    }
+
+    async fn graph(&self, _task_id: &str) -> Result<Option<TaskGraph>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn fork(&self, source: &Task, _messages: &[Message]) -> Result<Task, DatabaseError> {
+        Ok(source.clone())
+    }
+
+    async fn add_usage(
+        &self,
+        _id: &str,
+        _usage: &TaskUsage,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn mark_needs_help(
+        &self,
+        _id: &str,
+        _reason: &str,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn request_approval(
+        &self,
+        _id: &str,
+        _approval: &PendingApproval,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn decide_approval(
+        &self,
+        _id: &str,
+        _decision: ApprovalDecision,
+        _dto: &ApprovalDecisionDto,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn clear_approval(&self, _id: &str) -> Result<(), DatabaseError> {
+        Ok(())
+    }
 }
 
 // Helper function to create test DTOs
@@ -232,6 +490,12 @@ fn create_load_test_dto(i: usize) -> CreateTaskDto {
         })),
         user_id: Some(format!("user-{}", i % 10)),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     }
 }
 
@@ -4,10 +4,8 @@ use std::{
 };
 
 use bytebot_shared_rs::types::task::{Role, Task, TaskPriority, TaskStatus, TaskType};
-use chrono::Utc;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use serde_json::json;
-use uuid::Uuid;
 
 // Custom allocator to track memory usage
 struct TrackingAllocator {
@@ -112,20 +110,7 @@ struct MemoryDiff {
 // Helper functions for creating test data
 fn create_memory_test_task(i: usize) -> Task {
     Task {
-        id: Uuid::new_v4().to_string(),
         description: format!("Memory test task {} with detailed description that takes up more memory to test allocation patterns", i),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: Utc::now(),
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: Utc::now(),
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
         result: Some(json!({
             "output": format!("Task {} completed with detailed results", i),
             "metrics": {
@@ -155,6 +140,7 @@ fn create_memory_test_task(i: usize) -> Task {
             }
         }),
         user_id: Some(format!("user-{}", i % 100)),
+        ..Default::default()
     }
 }
 
@@ -302,23 +288,13 @@ fn benchmark_memory_efficiency_comparison(c: &mut Criterion) {
                 let start_time = std::time::Instant::now();
 
                 let task = Task {
-                    id: Uuid::new_v4().to_string(),
                     description: "Simple task".to_string(),
                     task_type: TaskType::default(),
                     status: TaskStatus::default(),
                     priority: TaskPriority::default(),
                     control: Role::default(),
-                    created_at: Utc::now(),
-                    created_by: Role::User,
-                    scheduled_for: None,
-                    updated_at: Utc::now(),
-                    executed_at: None,
-                    completed_at: None,
-                    queued_at: None,
-                    error: None,
-                    result: None,
                     model: json!({"provider": "anthropic", "name": "claude-3-sonnet"}),
-                    user_id: None,
+                    ..Default::default()
                 };
 
                 let duration = start_time.elapsed();
@@ -411,21 +387,8 @@ fn benchmark_resource_efficiency(c: &mut Criterion) {
                     .map(|i| Task {
                         id: format!("task-{}", i),
                         description: "Small task".to_string(),
-                        task_type: TaskType::Immediate,
-                        status: TaskStatus::Pending,
-                        priority: TaskPriority::Medium,
-                        control: Role::Assistant,
-                        created_at: Utc::now(),
-                        created_by: Role::User,
-                        scheduled_for: None,
-                        updated_at: Utc::now(),
-                        executed_at: None,
-                        completed_at: None,
-                        queued_at: None,
-                        error: None,
-                        result: None,
                         model: json!({"provider": "anthropic"}),
-                        user_id: None,
+                        ..Default::default()
                     })
                     .collect();
 
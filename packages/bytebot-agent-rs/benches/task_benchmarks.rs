@@ -1,15 +1,19 @@
 use std::{sync::Arc, time::Duration};
 
-use bytebot_agent_rs::database::{DatabaseError, TaskRepositoryTrait};
+use bytebot_agent_rs::database::{DatabaseError, QueueInfo, TaskRepositoryTrait};
 use bytebot_shared_rs::types::{
     api::{CreateTaskDto, PaginationParams, UpdateTaskDto},
+    approval::{ApprovalDecision, ApprovalDecisionDto, PendingApproval},
+    budget::TaskUsage,
+    dependency::TaskGraph,
+    message::Message,
+    retry::{FailureKind, TaskAttempt},
     task::{Role, Task, TaskPriority, TaskStatus, TaskType},
 };
 use chrono::Utc;
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
-use serde_json::json;
+use serde_json::{json, Value};
 use tokio::runtime::Runtime;
-use uuid::Uuid;
 
 // Mock repository for isolated benchmarks
 struct MockTaskRepository {
@@ -26,27 +30,14 @@ impl MockTaskRepository {
     fn with_tasks(mut self, count: usize) -> Self {
         for i in 0..count {
             let task = Task {
-                id: Uuid::new_v4().to_string(),
                 description: format!("Test task {}", i),
-                task_type: TaskType::Immediate,
-                status: TaskStatus::Pending,
-                priority: TaskPriority::Medium,
-                control: Role::Assistant,
-                created_at: Utc::now(),
-                created_by: Role::User,
-                scheduled_for: None,
-                updated_at: Utc::now(),
-                executed_at: None,
-                completed_at: None,
-                queued_at: None,
-                error: None,
-                result: None,
                 model: json!({
                     "provider": "anthropic",
                     "name": "claude-3-sonnet-20240229",
                     "title": "Claude 3 Sonnet"
                 }),
                 user_id: Some("test-user".to_string()),
+                ..Default::default()
             };
             self.tasks.insert(task.id.clone(), task);
         }
@@ -58,21 +49,11 @@ impl MockTaskRepository {
 impl TaskRepositoryTrait for MockTaskRepository {
     async fn create(&self, dto: &CreateTaskDto) -> Result<Task, DatabaseError> {
         let task = Task {
-            id: Uuid::new_v4().to_string(),
             description: dto.description.clone(),
             task_type: dto.task_type.unwrap_or_default(),
-            status: TaskStatus::Pending,
             priority: dto.priority.unwrap_or_default(),
-            control: Role::Assistant,
-            created_at: Utc::now(),
             created_by: dto.created_by.unwrap_or(Role::User),
             scheduled_for: dto.scheduled_for,
-            updated_at: Utc::now(),
-            executed_at: None,
-            completed_at: None,
-            queued_at: None,
-            error: None,
-            result: None,
             model: dto.model.clone().unwrap_or_else(|| {
                 json!({
                     "provider": "anthropic",
@@ -81,6 +62,7 @@ impl TaskRepositoryTrait for MockTaskRepository {
                 })
             }),
             user_id: dto.user_id.clone(),
+            ..Default::default()
         };
         Ok(task)
     }
@@ -148,6 +130,130 @@ impl TaskRepositoryTrait for MockTaskRepository {
         }
         Ok(counts)
     }
+
+    async fn claim_next_pending(&self, _max_running: u32) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn mark_failed(
+        &self,
+        _id: &str,
+        _error: &str,
+        _kind: FailureKind,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn schedule_retry(
+        &self,
+        _id: &str,
+        _error: &str,
+        _kind: FailureKind,
+        _retry_at: chrono::DateTime<Utc>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn list_attempts(&self, _task_id: &str) -> Result<Vec<TaskAttempt>, DatabaseError> {
+        Ok(Vec::new())
+    }
+
+    async fn set_result(&self, _id: &str, _result: &Value) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn mark_queued(&self, _id: &str) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn next_scheduled_time(&self) -> Result<Option<chrono::DateTime<Utc>>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn spawn_recurring_run(
+        &self,
+        _definition: &Task,
+        _fire_time: chrono::DateTime<Utc>,
+        _next_run: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn set_recurrence_paused(
+        &self,
+        _id: &str,
+        _paused: bool,
+        _next_run: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn preempt_for_urgent(&self, _max_running: u32) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn requeue_interrupted(&self) -> Result<u64, DatabaseError> {
+        Ok(0)
+    }
+
+    async fn queue_info(&self, _id: &str) -> Result<QueueInfo, DatabaseError> {
+        Ok(QueueInfo::default())
+    }
+
+    async fn queue_depth_by_priority(
+        &self,
+    ) -> Result<std::collections::HashMap<TaskPriority, u64>, DatabaseError> {
+        Ok(std::collections::HashMap::new())
+    }
+
+    async fn fail_unsatisfiable_dependents(&self) -> Result<Vec<Task>, DatabaseError> {
+        Ok(Vec::new())
+    }
+
+    async fn graph(&self, _task_id: &str) -> Result<Option<TaskGraph>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn fork(&self, source: &Task, _messages: &[Message]) -> Result<Task, DatabaseError> {
+        Ok(source.clone())
+    }
+
+    async fn add_usage(
+        &self,
+        _id: &str,
+        _usage: &TaskUsage,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn mark_needs_help(
+        &self,
+        _id: &str,
+        _reason: &str,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn request_approval(
+        &self,
+        _id: &str,
+        _approval: &PendingApproval,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn decide_approval(
+        &self,
+        _id: &str,
+        _decision: ApprovalDecision,
+        _dto: &ApprovalDecisionDto,
+    ) -> Result<Option<Task>, DatabaseError> {
+        Ok(None)
+    }
+
+    async fn clear_approval(&self, _id: &str) -> Result<(), DatabaseError> {
+        Ok(())
+    }
 }
 
 // Helper functions for creating test data
@@ -165,6 +271,12 @@ fn create_test_task_dto() -> CreateTaskDto {
         })),
         user_id: Some("test-user".to_string()),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     }
 }
 
@@ -175,6 +287,9 @@ fn create_test_update_dto() -> UpdateTaskDto {
         queued_at: Some(Utc::now()),
         executed_at: Some(Utc::now()),
         completed_at: None,
+        control: None,
+        budget: None,
+        labels: None,
     }
 }
 
@@ -273,20 +388,7 @@ fn benchmark_concurrent_task_operations(c: &mut Criterion) {
 // Task serialization benchmarks
 fn benchmark_task_serialization(c: &mut Criterion) {
     let task = Task {
-        id: Uuid::new_v4().to_string(),
         description: "Test task for serialization benchmarking".to_string(),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: Utc::now(),
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: Utc::now(),
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
         result: Some(json!({"result": "test data", "metrics": {"duration": 1000}})),
         model: json!({
             "provider": "anthropic",
@@ -294,6 +396,7 @@ fn benchmark_task_serialization(c: &mut Criterion) {
             "title": "Claude 3 Sonnet"
         }),
         user_id: Some("test-user".to_string()),
+        ..Default::default()
     };
 
     c.bench_function("task_serialization", |b| {
@@ -309,27 +412,14 @@ fn benchmark_task_serialization(c: &mut Criterion) {
 // Task validation benchmarks
 fn benchmark_task_validation(c: &mut Criterion) {
     let task = Task {
-        id: Uuid::new_v4().to_string(),
         description: "Test task for validation benchmarking".to_string(),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: Utc::now(),
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: Utc::now(),
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
-        result: None,
         model: json!({
             "provider": "anthropic",
             "name": "claude-3-sonnet-20240229",
             "title": "Claude 3 Sonnet"
         }),
         user_id: Some("test-user".to_string()),
+        ..Default::default()
     };
 
     c.bench_function("task_validation", |b| {
@@ -349,27 +439,14 @@ fn benchmark_memory_usage(c: &mut Criterion) {
                 b.iter(|| {
                     let tasks: Vec<Task> = (0..count)
                         .map(|i| Task {
-                            id: Uuid::new_v4().to_string(),
                             description: format!("Test task {}", i),
-                            task_type: TaskType::Immediate,
-                            status: TaskStatus::Pending,
-                            priority: TaskPriority::Medium,
-                            control: Role::Assistant,
-                            created_at: Utc::now(),
-                            created_by: Role::User,
-                            scheduled_for: None,
-                            updated_at: Utc::now(),
-                            executed_at: None,
-                            completed_at: None,
-                            queued_at: None,
-                            error: None,
-                            result: None,
                             model: json!({
                                 "provider": "anthropic",
                                 "name": "claude-3-sonnet-20240229",
                                 "title": "Claude 3 Sonnet"
                             }),
                             user_id: Some("test-user".to_string()),
+                            ..Default::default()
                         })
                         .collect();
 
@@ -0,0 +1,11 @@
+-- Recurring tasks: a SCHEDULED task with a cron expression acts as a recurring
+-- definition, and each firing creates a child task linked back to it
+
+ALTER TABLE "Task" ADD COLUMN "cronExpression" TEXT;
+ALTER TABLE "Task" ADD COLUMN "timezone" TEXT;
+ALTER TABLE "Task" ADD COLUMN "recurrencePaused" BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE "Task" ADD COLUMN "recurringTaskId" TEXT;
+
+CREATE INDEX "Task_recurringTaskId_idx" ON "Task"("recurringTaskId");
+
+ALTER TABLE "Task" ADD CONSTRAINT "Task_recurringTaskId_fkey" FOREIGN KEY ("recurringTaskId") REFERENCES "Task"("id") ON DELETE SET NULL ON UPDATE CASCADE;
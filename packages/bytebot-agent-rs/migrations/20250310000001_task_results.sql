@@ -0,0 +1,3 @@
+-- Optional JSON schema that the result submitted by the agent must match
+
+ALTER TABLE "Task" ADD COLUMN "resultSchema" JSONB;
@@ -0,0 +1,22 @@
+-- Automatic retries: each task carries an optional retry policy and the number
+-- of its current attempt, and every failed attempt is recorded
+
+ALTER TABLE "Task" ADD COLUMN "retryPolicy" JSONB;
+ALTER TABLE "Task" ADD COLUMN "attempt" INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE "Task" ADD COLUMN "retryAt" TIMESTAMP(3);
+
+CREATE TABLE "TaskAttempt" (
+    "id" TEXT NOT NULL,
+    "taskId" TEXT NOT NULL,
+    "attempt" INTEGER NOT NULL,
+    "error" TEXT NOT NULL,
+    "failureKind" TEXT NOT NULL,
+    "retryAt" TIMESTAMP(3),
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "TaskAttempt_pkey" PRIMARY KEY ("id")
+);
+
+CREATE INDEX "TaskAttempt_taskId_idx" ON "TaskAttempt"("taskId");
+
+ALTER TABLE "TaskAttempt" ADD CONSTRAINT "TaskAttempt_taskId_fkey" FOREIGN KEY ("taskId") REFERENCES "Task"("id") ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,15 @@
+-- Task dependencies: a task stays pending until every task it depends on has
+-- finished in the state its condition requires
+
+CREATE TABLE "TaskDependency" (
+    "taskId" TEXT NOT NULL,
+    "dependsOnId" TEXT NOT NULL,
+    "condition" TEXT NOT NULL DEFAULT 'on_success',
+
+    CONSTRAINT "TaskDependency_pkey" PRIMARY KEY ("taskId", "dependsOnId")
+);
+
+CREATE INDEX "TaskDependency_dependsOnId_idx" ON "TaskDependency"("dependsOnId");
+
+ALTER TABLE "TaskDependency" ADD CONSTRAINT "TaskDependency_taskId_fkey" FOREIGN KEY ("taskId") REFERENCES "Task"("id") ON DELETE CASCADE ON UPDATE CASCADE;
+ALTER TABLE "TaskDependency" ADD CONSTRAINT "TaskDependency_dependsOnId_fkey" FOREIGN KEY ("dependsOnId") REFERENCES "Task"("id") ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,19 @@
+-- Task templates: reusable descriptions with {{placeholders}} and the default
+-- settings of the tasks created from them
+
+CREATE TABLE "TaskTemplate" (
+    "id" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "description" TEXT NOT NULL,
+    "parameters" JSONB NOT NULL DEFAULT '[]',
+    "model" JSONB,
+    "priority" "TaskPriority" NOT NULL DEFAULT 'MEDIUM',
+    "files" JSONB NOT NULL DEFAULT '[]',
+    "resultSchema" JSONB,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" TIMESTAMP(3) NOT NULL,
+
+    CONSTRAINT "TaskTemplate_pkey" PRIMARY KEY ("id")
+);
+
+CREATE INDEX "TaskTemplate_name_idx" ON "TaskTemplate"("name");
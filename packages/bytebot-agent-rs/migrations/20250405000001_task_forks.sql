@@ -0,0 +1,9 @@
+-- Forked tasks: a fork starts from a copy of another task's conversation up to
+-- one of its messages and records where it branched off
+
+ALTER TABLE "Task" ADD COLUMN "forkedFromId" TEXT;
+ALTER TABLE "Task" ADD COLUMN "forkedFromMessageId" TEXT;
+
+CREATE INDEX "Task_forkedFromId_idx" ON "Task"("forkedFromId");
+
+ALTER TABLE "Task" ADD CONSTRAINT "Task_forkedFromId_fkey" FOREIGN KEY ("forkedFromId") REFERENCES "Task"("id") ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,5 @@
+-- Task budgets: optional limits on model turns, tokens, estimated cost and
+-- run time, and the usage counted against them
+
+ALTER TABLE "Task" ADD COLUMN "budget" JSONB;
+ALTER TABLE "Task" ADD COLUMN "usage" JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,4 @@
+-- Approval gate: desktop action a task is waiting to have approved or
+-- rejected while it needs review
+
+ALTER TABLE "Task" ADD COLUMN "pendingApproval" JSONB;
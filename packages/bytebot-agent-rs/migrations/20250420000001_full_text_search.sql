@@ -0,0 +1,32 @@
+-- Full-text search over task descriptions, task results and the text blocks
+-- of messages
+
+-- Text of the text blocks of a message, including those nested in tool results
+CREATE FUNCTION "message_search_text"(content JSONB) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$
+        SELECT COALESCE(string_agg(value #>> '{}', ' '), '')
+        FROM jsonb_path_query(content, 'strict $.** ? (@.type == "text").text') AS t(value)
+    $$;
+
+-- String values of a JSON document, such as a task result
+CREATE FUNCTION "json_search_text"(doc JSONB) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$
+        SELECT COALESCE(string_agg(value #>> '{}', ' '), '')
+        FROM jsonb_path_query(COALESCE(doc, 'null'), 'strict $.** ? (@.type() == "string")') AS t(value)
+    $$;
+
+ALTER TABLE "Task" ADD COLUMN "searchVector" TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', description), 'A')
+            || setweight(to_tsvector('english', "json_search_text"(result)), 'B')
+    ) STORED;
+
+ALTER TABLE "Message" ADD COLUMN "searchVector" TSVECTOR
+    GENERATED ALWAYS AS (
+        to_tsvector('english', "message_search_text"(content))
+    ) STORED;
+
+CREATE INDEX "Task_searchVector_idx" ON "Task" USING GIN ("searchVector");
+CREATE INDEX "Message_searchVector_idx" ON "Message" USING GIN ("searchVector");
@@ -0,0 +1,21 @@
+-- Free-form `key` or `key=value` labels on tasks
+ALTER TABLE "Task" ADD COLUMN "labels" TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX "Task_labels_idx" ON "Task" USING GIN ("labels");
+
+-- Saved views: named task filters with a sort order, owned by a user
+CREATE TABLE "TaskView" (
+    "id" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "userId" TEXT,
+    "filter" JSONB NOT NULL DEFAULT '{}',
+    "sort" JSONB NOT NULL DEFAULT '{}',
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" TIMESTAMP(3) NOT NULL,
+
+    CONSTRAINT "TaskView_pkey" PRIMARY KEY ("id")
+);
+
+CREATE INDEX "TaskView_userId_idx" ON "TaskView"("userId");
+
+ALTER TABLE "TaskView" ADD CONSTRAINT "TaskView_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User"("id") ON DELETE CASCADE ON UPDATE CASCADE;
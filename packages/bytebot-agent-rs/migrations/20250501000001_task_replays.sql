@@ -0,0 +1,18 @@
+-- Replays: the desktop actions of a finished task re-executed without the
+-- model, with the outcome of every step
+CREATE TABLE "TaskReplay" (
+    "id" TEXT NOT NULL,
+    "taskId" TEXT NOT NULL,
+    "status" TEXT NOT NULL,
+    "options" JSONB NOT NULL,
+    "steps" JSONB NOT NULL DEFAULT '[]',
+    "error" TEXT,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "completedAt" TIMESTAMP(3),
+
+    CONSTRAINT "TaskReplay_pkey" PRIMARY KEY ("id")
+);
+
+CREATE INDEX "TaskReplay_taskId_idx" ON "TaskReplay"("taskId");
+
+ALTER TABLE "TaskReplay" ADD CONSTRAINT "TaskReplay_taskId_fkey" FOREIGN KEY ("taskId") REFERENCES "Task"("id") ON DELETE CASCADE ON UPDATE CASCADE;
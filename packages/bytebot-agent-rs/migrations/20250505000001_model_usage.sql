@@ -0,0 +1,27 @@
+-- Model usage: tokens, latency and estimated cost of every model call, with
+-- the message holding the response
+CREATE TABLE "ModelUsage" (
+    "id" TEXT NOT NULL,
+    "taskId" TEXT NOT NULL,
+    "messageId" TEXT,
+    "userId" TEXT,
+    "provider" TEXT NOT NULL,
+    "model" TEXT NOT NULL,
+    "inputTokens" BIGINT NOT NULL DEFAULT 0,
+    "outputTokens" BIGINT NOT NULL DEFAULT 0,
+    "cacheReadTokens" BIGINT NOT NULL DEFAULT 0,
+    "cacheWriteTokens" BIGINT NOT NULL DEFAULT 0,
+    "latencyMs" BIGINT NOT NULL DEFAULT 0,
+    "costUsd" DOUBLE PRECISION,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "ModelUsage_pkey" PRIMARY KEY ("id")
+);
+
+CREATE INDEX "ModelUsage_taskId_idx" ON "ModelUsage"("taskId");
+CREATE INDEX "ModelUsage_createdAt_idx" ON "ModelUsage"("createdAt");
+CREATE INDEX "ModelUsage_userId_createdAt_idx" ON "ModelUsage"("userId", "createdAt");
+
+-- Usage outlives its messages and tasks: deleting a task must not change
+-- what it cost
+ALTER TABLE "ModelUsage" ADD CONSTRAINT "ModelUsage_messageId_fkey" FOREIGN KEY ("messageId") REFERENCES "Message"("id") ON DELETE SET NULL ON UPDATE CASCADE;
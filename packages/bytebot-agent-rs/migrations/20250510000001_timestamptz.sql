@@ -0,0 +1,20 @@
+-- Store timestamps with their time zone. The repositories read them as
+-- `DateTime<Utc>`, which sqlx only decodes from TIMESTAMPTZ; the values
+-- written so far are UTC.
+DO $$
+DECLARE
+    col RECORD;
+BEGIN
+    FOR col IN
+        SELECT table_name, column_name
+        FROM information_schema.columns
+        WHERE table_schema = current_schema()
+            AND data_type = 'timestamp without time zone'
+            AND table_name <> '_sqlx_migrations'
+    LOOP
+        EXECUTE format(
+            'ALTER TABLE %I ALTER COLUMN %I TYPE TIMESTAMPTZ(3) USING %I AT TIME ZONE ''UTC''',
+            col.table_name, col.column_name, col.column_name
+        );
+    END LOOP;
+END $$;
@@ -0,0 +1,100 @@
+use std::{sync::Arc, time::Duration};
+
+use bytebot_shared_rs::{types::task::Task, MetricsCollector};
+use tracing::warn;
+
+use super::budget;
+use crate::{
+    ai::{AIResponse, AIService},
+    database::{CreateModelUsageDto, DatabaseManager, TaskRepositoryTrait, UsageRepositoryTrait},
+    error::{ServiceError, ServiceResult},
+};
+
+/// Accounts for the model calls of tasks.
+///
+/// Every call is stored with its tokens, latency and estimated cost, added
+/// to the usage counted against the task's budget and reported to the
+/// metrics. Prices come from the model registry.
+#[derive(Clone)]
+pub struct UsageRecorder {
+    db: Arc<DatabaseManager>,
+    ai_service: Arc<dyn AIService>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl UsageRecorder {
+    /// Create a new usage recorder
+    pub fn new(
+        db: Arc<DatabaseManager>,
+        ai_service: Arc<dyn AIService>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        Self {
+            db,
+            ai_service,
+            metrics,
+        }
+    }
+
+    /// Record a model call made for `task` that took `latency`, whose
+    /// response was saved as `message_id`. Returns the task with its usage
+    /// updated.
+    pub async fn record(
+        &self,
+        task: &Task,
+        message_id: Option<String>,
+        response: &AIResponse,
+        latency: Duration,
+    ) -> ServiceResult<Option<Task>> {
+        let model = self
+            .ai_service
+            .list_models()
+            .into_iter()
+            .find(|model| model.matches(&response.model));
+        let provider = model
+            .as_ref()
+            .map_or("unknown", |model| model.provider.as_str());
+
+        let cost_usd = model
+            .as_ref()
+            .and_then(|model| model.pricing)
+            .map(|pricing| pricing.cost(&response.usage));
+        if cost_usd.is_none() {
+            warn!(
+                "No price known for model {}, counting its cost as zero",
+                response.model
+            );
+        }
+
+        self.metrics.record_ai_request(
+            provider,
+            &response.model,
+            latency,
+            response.usage.total_tokens(),
+        );
+
+        self.db
+            .usage_repository()
+            .create(&CreateModelUsageDto {
+                task_id: task.id.clone(),
+                message_id,
+                user_id: task.user_id.clone(),
+                provider: provider.to_string(),
+                model: response.model.clone(),
+                input_tokens: response.usage.input_tokens,
+                output_tokens: response.usage.output_tokens,
+                cache_read_tokens: response.usage.cache_read_tokens,
+                cache_write_tokens: response.usage.cache_write_tokens,
+                latency_ms: latency.as_millis() as u64,
+                cost_usd,
+            })
+            .await
+            .map_err(ServiceError::Database)?;
+
+        self.db
+            .task_repository()
+            .add_usage(&task.id, &budget::turn_usage(response, cost_usd))
+            .await
+            .map_err(ServiceError::Database)
+    }
+}
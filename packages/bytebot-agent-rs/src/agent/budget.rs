@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use bytebot_shared_rs::types::{budget::TaskUsage, task::Task};
+use chrono::Utc;
+
+use crate::ai::AIResponse;
+
+/// Usage of one model turn, to be added to the total of its task; a turn of
+/// a model without a known price counts as free
+pub fn turn_usage(response: &AIResponse, cost_usd: Option<f64>) -> TaskUsage {
+    let usage = response.usage;
+
+    TaskUsage {
+        turns: 1,
+        input_tokens: usage.input_tokens + usage.cache_read_tokens + usage.cache_write_tokens,
+        output_tokens: usage.output_tokens,
+        cost_usd: cost_usd.unwrap_or_default(),
+    }
+}
+
+/// Time the task has been running for
+pub fn elapsed(task: &Task) -> Duration {
+    task.executed_at
+        .and_then(|started| (Utc::now() - started).to_std().ok())
+        .unwrap_or_default()
+}
+
+/// Time left before the task reaches its wall-clock limit, if it has one
+pub fn time_left(task: &Task) -> Option<Duration> {
+    let timeout = task.budget.as_ref()?.timeout()?;
+    Some(timeout.saturating_sub(elapsed(task)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::TokenUsage;
+
+    #[test]
+    fn test_turn_usage() {
+        let response = AIResponse {
+            content: vec![],
+            usage: TokenUsage {
+                input_tokens: 2_000,
+                output_tokens: 500,
+                cache_read_tokens: 1_000,
+                cache_write_tokens: 0,
+            },
+            model: "gpt-4o".to_string(),
+        };
+
+        let usage = turn_usage(&response, Some(0.01));
+        assert_eq!(usage.turns, 1);
+        assert_eq!(usage.input_tokens, 3_000);
+        assert_eq!(usage.output_tokens, 500);
+        assert!((usage.cost_usd - 0.01).abs() < 1e-9);
+
+        assert_eq!(turn_usage(&response, None).cost_usd, 0.0);
+    }
+}
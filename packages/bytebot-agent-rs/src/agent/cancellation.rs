@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use tokio_util::sync::CancellationToken;
+
+/// Cancellation tokens of the tasks the processor is running.
+///
+/// Each iteration of a task registers a fresh token that guards its model call
+/// and desktop actions. Cancelling or taking over the task trips the token so
+/// in-flight work is abandoned instead of running to completion.
+///
+/// A task may be claimed again while an earlier run of it is still winding
+/// down, e.g. after it was preempted, so tokens are registered under the run
+/// that owns them.
+#[derive(Debug, Default)]
+pub struct TaskCancellations {
+    tokens: Mutex<HashMap<String, (u64, CancellationToken)>>,
+    runs: AtomicU64,
+}
+
+impl TaskCancellations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new run, returning the ID to register its tokens under
+    pub fn start_run(&self) -> u64 {
+        self.runs.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Register a new token for a run of a task, replacing any previous one
+    pub fn register(&self, task_id: &str, run: u64) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(task_id.to_string(), (run, token.clone()));
+        token
+    }
+
+    /// Cancel the in-flight work of a task.
+    ///
+    /// Returns `false` if the task is not being run by this process.
+    pub fn cancel(&self, task_id: &str) -> bool {
+        match self.tokens.lock().unwrap().get(task_id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget the token of a run that stopped, unless a later run of the
+    /// task has registered its own since
+    pub fn remove(&self, task_id: &str, run: u64) {
+        let mut tokens = self.tokens.lock().unwrap();
+        if tokens.get(task_id).is_some_and(|(owner, _)| *owner == run) {
+            tokens.remove(task_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_registered_task() {
+        let cancellations = TaskCancellations::new();
+        let token = cancellations.register("task-1", cancellations.start_run());
+
+        assert!(cancellations.cancel("task-1"));
+        assert!(token.is_cancelled());
+        assert!(!cancellations.cancel("task-2"));
+    }
+
+    #[test]
+    fn test_register_replaces_token() {
+        let cancellations = TaskCancellations::new();
+        let run = cancellations.start_run();
+        let first = cancellations.register("task-1", run);
+        first.cancel();
+
+        let second = cancellations.register("task-1", run);
+        assert!(!second.is_cancelled());
+
+        cancellations.remove("task-1", run);
+        assert!(!cancellations.cancel("task-1"));
+        assert!(!second.is_cancelled());
+    }
+
+    #[test]
+    fn test_finished_run_keeps_token_of_later_run() {
+        let cancellations = TaskCancellations::new();
+        let preempted = cancellations.start_run();
+        cancellations.register("task-1", preempted);
+
+        // The task is claimed again before the preempted run finishes
+        let current = cancellations.start_run();
+        let token = cancellations.register("task-1", current);
+        cancellations.remove("task-1", preempted);
+
+        assert!(cancellations.cancel("task-1"));
+        assert!(token.is_cancelled());
+    }
+}
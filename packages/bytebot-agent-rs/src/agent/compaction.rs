@@ -0,0 +1,393 @@
+use std::{sync::Arc, time::Instant};
+
+use bytebot_shared_rs::types::{
+    message::{Message, MessageContentBlock, Summary},
+    task::{Role, Task},
+};
+use tracing::info;
+
+use super::{accounting::UsageRecorder, prompts::SUMMARY_SYSTEM_PROMPT};
+use crate::{
+    ai::AIService,
+    config::Config,
+    database::{CreateSummaryDto, DatabaseManager, SummaryRepositoryTrait},
+    error::{ServiceError, ServiceResult},
+};
+
+/// Rough number of characters per token used to estimate history size
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Flat token estimate for an image or document block
+const ATTACHMENT_TOKENS: usize = 1_500;
+
+/// Share of the threshold kept as raw recent messages after compaction
+const KEEP_RECENT_DIVISOR: usize = 4;
+
+/// Keeps a task's prompt history within the model's context window.
+///
+/// Once the messages that are not yet covered by a summary grow past the
+/// threshold, the older ones are summarised by the model, stored as a
+/// `Summary` and linked to it through `summaryId`. Prompts then start with the
+/// latest summary followed by the messages after it.
+pub struct ContextCompactor {
+    db: Arc<DatabaseManager>,
+    ai_service: Arc<dyn AIService>,
+    usage: UsageRecorder,
+    threshold_tokens: usize,
+}
+
+impl ContextCompactor {
+    /// Create a new context compactor
+    pub fn new(
+        config: &Config,
+        db: Arc<DatabaseManager>,
+        ai_service: Arc<dyn AIService>,
+        usage: UsageRecorder,
+    ) -> Self {
+        Self {
+            db,
+            ai_service,
+            usage,
+            threshold_tokens: config.context_compaction_threshold_tokens as usize,
+        }
+    }
+
+    /// Messages to send to the model for `task`, compacting the history first if needed.
+    ///
+    /// `messages` is the task's full history in chronological order.
+    pub async fn prompt_messages(
+        &self,
+        task: &Task,
+        messages: Vec<Message>,
+        model: Option<String>,
+    ) -> ServiceResult<Vec<Message>> {
+        let summary_repo = self.db.summary_repository();
+        let latest = summary_repo
+            .get_latest_by_task_id(&task.id)
+            .await
+            .map_err(ServiceError::Database)?;
+
+        let mut active: Vec<Message> = messages
+            .into_iter()
+            .filter(|message| message.summary_id.is_none())
+            .collect();
+
+        if self.threshold_tokens == 0 || estimate_tokens(&active) <= self.threshold_tokens {
+            return Ok(with_summary(task, latest.as_ref(), active));
+        }
+
+        let Some(split) = split_point(&active, self.threshold_tokens / KEEP_RECENT_DIVISOR) else {
+            return Ok(with_summary(task, latest.as_ref(), active));
+        };
+        let recent = active.split_off(split);
+
+        let content = self
+            .summarize(task, latest.as_ref(), &active, model)
+            .await?;
+        let message_ids: Vec<String> = active.iter().map(|message| message.id.clone()).collect();
+        let summary = summary_repo
+            .create(
+                &CreateSummaryDto {
+                    content,
+                    task_id: task.id.clone(),
+                    parent_id: latest.map(|summary| summary.id),
+                },
+                &message_ids,
+            )
+            .await
+            .map_err(ServiceError::Database)?;
+
+        info!(
+            "Compacted {} messages of task {} into summary {}",
+            message_ids.len(),
+            task.id,
+            summary.id
+        );
+
+        Ok(with_summary(task, Some(&summary), recent))
+    }
+
+    /// Ask the model to summarise `messages`, building on the previous summary
+    async fn summarize(
+        &self,
+        task: &Task,
+        previous: Option<&Summary>,
+        messages: &[Message],
+        model: Option<String>,
+    ) -> ServiceResult<String> {
+        let mut request = String::new();
+        if let Some(previous) = previous {
+            request.push_str(&format!("Earlier summary:\n{}\n\n", previous.content));
+        }
+        request.push_str(&format!("Transcript:\n{}", transcript(messages)));
+
+        let started = Instant::now();
+        let response = self
+            .ai_service
+            .generate_response(
+                SUMMARY_SYSTEM_PROMPT,
+                vec![Message::new(
+                    vec![MessageContentBlock::text(request)],
+                    Role::User,
+                    task.id.clone(),
+                )],
+                model,
+                false,
+                None,
+            )
+            .await?;
+        // Summaries are not messages of the task, but they are paid for
+        self.usage
+            .record(task, None, &response, started.elapsed())
+            .await?;
+
+        let summary = response
+            .content
+            .iter()
+            .filter_map(MessageContentBlock::as_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if summary.trim().is_empty() {
+            return Err(ServiceError::Internal(format!(
+                "Model returned an empty summary for task {}",
+                task.id
+            )));
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Estimated number of tokens the messages take up in a prompt
+pub fn estimate_tokens(messages: &[Message]) -> usize {
+    messages.iter().map(message_tokens).sum()
+}
+
+fn message_tokens(message: &Message) -> usize {
+    message
+        .get_content_blocks()
+        .map(|blocks| blocks.iter().map(block_tokens).sum())
+        // Fall back to the raw JSON size for content that does not parse
+        .unwrap_or_else(|_| message.content.to_string().len() / CHARS_PER_TOKEN)
+}
+
+fn block_tokens(block: &MessageContentBlock) -> usize {
+    match block {
+        MessageContentBlock::Text { text } => text.len() / CHARS_PER_TOKEN,
+        MessageContentBlock::Image { .. } | MessageContentBlock::Document { .. } => {
+            ATTACHMENT_TOKENS
+        }
+        MessageContentBlock::ToolUse { name, input, .. } => {
+            (name.len() + input.to_string().len()) / CHARS_PER_TOKEN
+        }
+        MessageContentBlock::ToolResult { content, .. } => content.iter().map(block_tokens).sum(),
+        MessageContentBlock::Thinking { thinking, .. } => thinking.len() / CHARS_PER_TOKEN,
+        MessageContentBlock::RedactedThinking { data } => data.len() / CHARS_PER_TOKEN,
+    }
+}
+
+/// Index of the first message to keep verbatim, or `None` if nothing can be summarised.
+///
+/// The split always lands on an assistant message so every tool call stays
+/// next to its result. The earliest split whose kept messages fit in
+/// `keep_tokens` is used, falling back to the latest possible split.
+pub fn split_point(messages: &[Message], keep_tokens: usize) -> Option<usize> {
+    let mut kept = 0;
+    let mut split = None;
+
+    for (index, message) in messages.iter().enumerate().skip(1).rev() {
+        kept += message_tokens(message);
+        if message.role != Role::Assistant {
+            continue;
+        }
+        if kept > keep_tokens && split.is_some() {
+            break;
+        }
+        split = Some(index);
+    }
+
+    split
+}
+
+/// Plain-text rendering of messages for the summarisation request.
+///
+/// Attachments are replaced with placeholders so old screenshots are not sent again.
+pub fn transcript(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let blocks = message.get_content_blocks().ok()?;
+            let text = blocks
+                .iter()
+                .filter_map(render_block)
+                .collect::<Vec<_>>()
+                .join("\n");
+            let speaker = match message.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+            };
+            (!text.is_empty()).then(|| format!("{speaker}: {text}"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn render_block(block: &MessageContentBlock) -> Option<String> {
+    match block {
+        MessageContentBlock::Text { text } => Some(text.clone()),
+        MessageContentBlock::Image { .. } => Some("[image]".to_string()),
+        MessageContentBlock::Document { name, .. } => Some(format!(
+            "[document {}]",
+            name.as_deref().unwrap_or("without name")
+        )),
+        MessageContentBlock::ToolUse { name, input, .. } => {
+            Some(format!("[called {name} with {input}]"))
+        }
+        MessageContentBlock::ToolResult {
+            content, is_error, ..
+        } => {
+            let label = if is_error.unwrap_or(false) {
+                "tool error"
+            } else {
+                "tool result"
+            };
+            let output = content
+                .iter()
+                .filter_map(render_block)
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(format!("[{label}: {output}]"))
+        }
+        MessageContentBlock::Thinking { .. } | MessageContentBlock::RedactedThinking { .. } => None,
+    }
+}
+
+/// Prefix the latest summary, if any, to the messages after it
+fn with_summary(task: &Task, summary: Option<&Summary>, messages: Vec<Message>) -> Vec<Message> {
+    let Some(summary) = summary else {
+        return messages;
+    };
+
+    let intro = Message::new(
+        vec![MessageContentBlock::text(format!(
+            "Task: {}\n\nThe earlier part of this task has been summarised:\n\n{}",
+            task.description, summary.content
+        ))],
+        Role::User,
+        task.id.clone(),
+    );
+
+    std::iter::once(intro).chain(messages).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(role: Role, content: Vec<MessageContentBlock>) -> Message {
+        Message::new(content, role, "task-1".to_string())
+    }
+
+    fn text(role: Role, chars: usize) -> Message {
+        message(role, vec![MessageContentBlock::text("x".repeat(chars))])
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        let messages = vec![
+            text(Role::User, 400),
+            message(
+                Role::User,
+                vec![MessageContentBlock::tool_result(
+                    "tool-1",
+                    vec![MessageContentBlock::image("image/png", "a".repeat(100_000))],
+                )],
+            ),
+        ];
+
+        assert_eq!(estimate_tokens(&messages), 100 + ATTACHMENT_TOKENS);
+    }
+
+    #[test]
+    fn test_split_point_keeps_recent_messages_within_budget() {
+        let messages = vec![
+            text(Role::User, 400),
+            text(Role::Assistant, 400),
+            text(Role::User, 400),
+            text(Role::Assistant, 400),
+            text(Role::User, 400),
+        ];
+
+        // The last three messages take 300 tokens
+        assert_eq!(split_point(&messages, 300), Some(3));
+        assert_eq!(split_point(&messages, 500), Some(1));
+    }
+
+    #[test]
+    fn test_split_point_falls_back_to_latest_assistant_message() {
+        let messages = vec![
+            text(Role::User, 400),
+            text(Role::Assistant, 400),
+            text(Role::User, 40_000),
+        ];
+
+        assert_eq!(split_point(&messages, 10), Some(1));
+    }
+
+    #[test]
+    fn test_split_point_needs_an_assistant_message() {
+        let messages = vec![text(Role::User, 400), text(Role::User, 400)];
+        assert_eq!(split_point(&messages, 10), None);
+        assert_eq!(split_point(&[text(Role::Assistant, 400)], 10), None);
+    }
+
+    #[test]
+    fn test_transcript() {
+        let messages = vec![
+            message(Role::User, vec![MessageContentBlock::text("Open Firefox")]),
+            message(
+                Role::Assistant,
+                vec![MessageContentBlock::tool_use(
+                    "application",
+                    "tool-1",
+                    json!({"application": "firefox"}),
+                )],
+            ),
+            message(
+                Role::User,
+                vec![MessageContentBlock::tool_result(
+                    "tool-1",
+                    vec![
+                        MessageContentBlock::text("Opened"),
+                        MessageContentBlock::image("image/png", "aGVsbG8="),
+                    ],
+                )],
+            ),
+        ];
+
+        assert_eq!(
+            transcript(&messages),
+            "User: Open Firefox\n\n\
+             Assistant: [called application with {\"application\":\"firefox\"}]\n\n\
+             User: [tool result: Opened [image]]"
+        );
+    }
+
+    #[test]
+    fn test_with_summary() {
+        let task = Task::new("Book a flight".to_string(), json!({}));
+        let summary = Summary::new("Searched for flights".to_string(), task.id.clone());
+        let recent = vec![text(Role::Assistant, 10)];
+
+        let messages = with_summary(&task, Some(&summary), recent.clone());
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, Role::User);
+        assert!(messages[0].extract_text().contains("Book a flight"));
+        assert!(messages[0].extract_text().contains("Searched for flights"));
+
+        assert_eq!(with_summary(&task, None, recent).len(), 1);
+    }
+}
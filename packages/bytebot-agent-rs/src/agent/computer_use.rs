@@ -0,0 +1,431 @@
+use std::time::Duration;
+
+use bytebot_shared_rs::types::{
+    computer_action::{CancelComputerActionDto, ComputerAction, Validate, ACTION_ID_HEADER},
+    message::MessageContentBlock,
+};
+use reqwest::Client;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::error::AutomationError;
+
+/// Timeout for a single desktop action, long enough for slow typing and waits
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// HTTP client for the bytebotd `/computer-use` endpoint
+#[derive(Clone)]
+pub struct ComputerUseClient {
+    client: Client,
+    base_url: String,
+}
+
+impl ComputerUseClient {
+    /// Create a new client for the desktop daemon at `base_url`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Convert a model tool call into a desktop action.
+    ///
+    /// Tool names match the `action` tag of `ComputerAction`, so the tool input
+    /// is the action payload without the tag.
+    pub fn action_from_tool_use(
+        name: &str,
+        input: &Value,
+    ) -> Result<ComputerAction, AutomationError> {
+        let mut payload = match input {
+            Value::Object(map) => map.clone(),
+            Value::Null => serde_json::Map::new(),
+            _ => {
+                return Err(AutomationError::ActionFailed(format!(
+                    "Input for tool {name} must be a JSON object"
+                )))
+            }
+        };
+        payload.insert("action".to_string(), Value::String(name.to_string()));
+
+        let action: ComputerAction =
+            serde_json::from_value(Value::Object(payload)).map_err(|e| {
+                AutomationError::ActionFailed(format!("Invalid call to tool {name}: {e}"))
+            })?;
+
+        action.validate().map_err(|e| {
+            AutomationError::ActionFailed(format!("Invalid call to tool {name}: {e}"))
+        })?;
+
+        Ok(action)
+    }
+
+    /// Execute an action on the desktop and return the daemon's `result` payload
+    pub async fn execute(&self, action: &ComputerAction) -> Result<Value, AutomationError> {
+        self.send(action, None).await
+    }
+
+    /// Send an action to the daemon, under `action_id` if it may be cancelled
+    async fn send(
+        &self,
+        action: &ComputerAction,
+        action_id: Option<&str>,
+    ) -> Result<Value, AutomationError> {
+        let url = format!("{}/computer-use", self.base_url);
+        debug!("Sending computer action to {}: {:?}", url, action);
+
+        let mut request = self.client.post(&url).json(action);
+        if let Some(action_id) = action_id {
+            request = request.header(ACTION_ID_HEADER, action_id);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_connect() {
+                AutomationError::Unreachable(e.to_string())
+            } else {
+                AutomationError::ActionFailed(format!("Desktop request failed: {e}"))
+            }
+        })?;
+
+        let status = response.status();
+        let body: Value = response.json().await.map_err(|e| {
+            AutomationError::ActionFailed(format!("Invalid response from desktop: {e}"))
+        })?;
+
+        if !status.is_success() || body["success"] != Value::Bool(true) {
+            let message = body["error"]["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Desktop returned status {status}"));
+            warn!("Computer action failed: {}", message);
+            return Err(AutomationError::ActionFailed(message));
+        }
+
+        Ok(body["result"].clone())
+    }
+
+    /// Interrupt the action sent under `action_id`, leaving the actions of
+    /// other tasks running.
+    ///
+    /// Best effort: a daemon that cannot be reached has nothing to interrupt.
+    pub async fn cancel_action(&self, action_id: &str) {
+        let url = format!("{}/computer-use/cancel", self.base_url);
+        let dto = CancelComputerActionDto {
+            action_id: action_id.to_string(),
+        };
+        if let Err(e) = self.client.post(&url).json(&dto).send().await {
+            warn!("Failed to cancel desktop action {}: {}", action_id, e);
+        }
+    }
+
+    /// Run a tool call end to end and wrap the outcome in a `ToolResult` block.
+    ///
+    /// Failures are reported back to the model as error results rather than
+    /// aborting the task, so it can correct itself. If `cancel` fires while the
+    /// action runs, the daemon is told to stop that action only and the call
+    /// reports an error.
+    ///
+    /// Only an unreachable daemon is returned as an error, since the model
+    /// cannot do anything about it.
+    pub async fn execute_tool_use(
+        &self,
+        tool_use_id: &str,
+        name: &str,
+        input: &Value,
+        cancel: &CancellationToken,
+    ) -> Result<MessageContentBlock, AutomationError> {
+        let action_id = Uuid::new_v4().to_string();
+        let outcome = match Self::action_from_tool_use(name, input) {
+            Ok(action) => tokio::select! {
+                biased;
+                _ = cancel.cancelled() => {
+                    self.cancel_action(&action_id).await;
+                    Err(AutomationError::Cancelled)
+                }
+                result = self.send(&action, Some(&action_id)) => {
+                    result.map(|result| Self::result_content(&action, &result))
+                }
+            },
+            Err(e) => Err(e),
+        };
+
+        match outcome {
+            Ok(content) => Ok(MessageContentBlock::tool_result(tool_use_id, content)),
+            Err(e @ AutomationError::Unreachable(_)) => Err(e),
+            Err(e) => {
+                error!("Tool call {} ({}) failed: {}", tool_use_id, name, e);
+                Ok(MessageContentBlock::tool_error(tool_use_id, e.to_string()))
+            }
+        }
+    }
+
+    /// Build the content returned to the model for a successful action
+    pub fn result_content(action: &ComputerAction, result: &Value) -> Vec<MessageContentBlock> {
+        match action {
+            ComputerAction::Screenshot => match result["screenshot"].as_str() {
+                Some(data) => vec![MessageContentBlock::image("image/png", data)],
+                None => vec![MessageContentBlock::text(
+                    "Screenshot returned no image data",
+                )],
+            },
+            _ => vec![MessageContentBlock::text(format!(
+                "Action completed successfully: {result}"
+            ))],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Json, Router};
+    use serde_json::json;
+
+    use super::*;
+
+    async fn spawn_stub_daemon() -> String {
+        let app = Router::new().route(
+            "/computer-use",
+            post(|Json(body): Json<Value>| async move {
+                match body["action"].as_str() {
+                    Some("screenshot") => Json(json!({
+                        "success": true,
+                        "action": "screenshot",
+                        "result": { "screenshot": "aW1hZ2U=", "size": 8 }
+                    })),
+                    Some(action) => Json(json!({
+                        "success": true,
+                        "action": action,
+                        "result": { "ok": true }
+                    })),
+                    None => Json(json!({
+                        "success": false,
+                        "error": { "message": "missing action" }
+                    })),
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_action_from_tool_use() {
+        let action = ComputerUseClient::action_from_tool_use(
+            "move_mouse",
+            &json!({ "coordinates": { "x": 10, "y": 20 } }),
+        )
+        .unwrap();
+        assert!(matches!(action, ComputerAction::MoveMouse { .. }));
+
+        let action = ComputerUseClient::action_from_tool_use("screenshot", &json!({})).unwrap();
+        assert!(matches!(action, ComputerAction::Screenshot));
+
+        let action =
+            ComputerUseClient::action_from_tool_use("cursor_position", &Value::Null).unwrap();
+        assert!(matches!(action, ComputerAction::CursorPosition));
+    }
+
+    #[test]
+    fn test_action_from_tool_use_invalid() {
+        assert!(ComputerUseClient::action_from_tool_use("unknown_tool", &json!({})).is_err());
+        assert!(ComputerUseClient::action_from_tool_use("move_mouse", &json!("bad")).is_err());
+        assert!(ComputerUseClient::action_from_tool_use("move_mouse", &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_result_content_for_screenshot() {
+        let content = ComputerUseClient::result_content(
+            &ComputerAction::Screenshot,
+            &json!({ "screenshot": "abc" }),
+        );
+        assert_eq!(content.len(), 1);
+        assert!(matches!(content[0], MessageContentBlock::Image { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_use_against_daemon() {
+        let client = ComputerUseClient::new(spawn_stub_daemon().await);
+
+        let result = client
+            .execute_tool_use(
+                "tool_1",
+                "screenshot",
+                &json!({}),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        match result {
+            MessageContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => {
+                assert_eq!(tool_use_id, "tool_1");
+                assert!(is_error.is_none());
+                assert!(matches!(content[0], MessageContentBlock::Image { .. }));
+            }
+            other => panic!("Expected tool result, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_use_reports_errors() {
+        let client = ComputerUseClient::new(spawn_stub_daemon().await);
+
+        let result = client
+            .execute_tool_use(
+                "tool_2",
+                "not_a_tool",
+                &json!({}),
+                &CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error_result());
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_use_cancelled() {
+        let client = ComputerUseClient::new(spawn_stub_daemon().await);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = client
+            .execute_tool_use("tool_3", "screenshot", &json!({}), &cancel)
+            .await
+            .unwrap();
+        match result {
+            MessageContentBlock::ToolResult {
+                content, is_error, ..
+            } => {
+                assert_eq!(is_error, Some(true));
+                assert_eq!(content[0].as_text(), Some("Action cancelled"));
+            }
+            other => panic!("Expected tool result, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_use_with_daemon_down() {
+        // Nothing listens on the port once the listener is dropped
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let client = ComputerUseClient::new(format!("http://{addr}"));
+        let result = client
+            .execute_tool_use(
+                "tool_4",
+                "screenshot",
+                &json!({}),
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(matches!(result, Err(AutomationError::Unreachable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_targets_the_action_sent() {
+        use std::{
+            collections::HashMap,
+            sync::{Arc, Mutex},
+        };
+
+        use axum::{extract::State, http::HeaderMap};
+
+        type Waiters = Arc<Mutex<HashMap<String, CancellationToken>>>;
+
+        // A daemon whose waits only end when their ID is cancelled
+        async fn wait(
+            State(waiters): State<Waiters>,
+            headers: HeaderMap,
+            Json(_): Json<Value>,
+        ) -> Json<Value> {
+            let id = headers[ACTION_ID_HEADER].to_str().unwrap().to_string();
+            let token = CancellationToken::new();
+            waiters.lock().unwrap().insert(id, token.clone());
+            token.cancelled().await;
+            Json(json!({ "success": false, "error": { "message": "Action cancelled" } }))
+        }
+
+        async fn cancel(
+            State(waiters): State<Waiters>,
+            Json(dto): Json<CancelComputerActionDto>,
+        ) -> Json<Value> {
+            let token = waiters.lock().unwrap().remove(&dto.action_id);
+            let cancelled = token.map(|token| token.cancel()).is_some();
+            Json(json!({ "success": true, "cancelled": cancelled }))
+        }
+
+        let waiters = Waiters::default();
+        let app = Router::new()
+            .route("/computer-use", post(wait))
+            .route("/computer-use/cancel", post(cancel))
+            .with_state(waiters.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = ComputerUseClient::new(format!("http://{addr}"));
+
+        let cancel_a = CancellationToken::new();
+        let task_a = {
+            let client = client.clone();
+            let cancel_a = cancel_a.clone();
+            tokio::spawn(async move {
+                client
+                    .execute_tool_use("tool_a", "wait", &json!({ "duration": 1000 }), &cancel_a)
+                    .await
+            })
+        };
+        let task_b = {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client
+                    .execute_tool_use(
+                        "tool_b",
+                        "wait",
+                        &json!({ "duration": 1000 }),
+                        &CancellationToken::new(),
+                    )
+                    .await
+            })
+        };
+
+        while waiters.lock().unwrap().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        cancel_a.cancel();
+        let result = task_a.await.unwrap().unwrap();
+        assert!(result.is_error_result());
+
+        // Only the cancelled task's action was stopped on the daemon
+        let remaining: Vec<String> = waiters.lock().unwrap().keys().cloned().collect();
+        assert_eq!(remaining.len(), 1);
+        assert!(!task_b.is_finished());
+
+        waiters
+            .lock()
+            .unwrap()
+            .remove(&remaining[0])
+            .unwrap()
+            .cancel();
+        task_b.await.unwrap().unwrap();
+    }
+}
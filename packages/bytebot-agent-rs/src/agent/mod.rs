@@ -0,0 +1,17 @@
+pub mod accounting;
+pub mod budget;
+pub mod cancellation;
+pub mod compaction;
+pub mod computer_use;
+pub mod processor;
+pub mod prompts;
+pub mod recurrence;
+pub mod replay;
+pub mod results;
+pub mod retry;
+pub mod scheduler;
+pub mod takeover;
+
+pub use cancellation::TaskCancellations;
+pub use processor::*;
+pub use scheduler::*;
@@ -0,0 +1,1109 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytebot_shared_rs::{
+    types::{
+        approval::{ApprovalDecision, ApprovalPolicy, PendingApproval},
+        budget::BudgetAction,
+        message::MessageContentBlock,
+        retry::FailureKind,
+        task::{Role, Task, TaskPriority, TaskStatus},
+        task_tools::{TaskOutcome, TaskToolCall},
+        user::File,
+    },
+    MetricsCollector,
+};
+use chrono::Utc;
+use serde_json::Value;
+use tokio::sync::{mpsc, Notify, Semaphore};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use super::{
+    accounting::UsageRecorder, budget, cancellation::TaskCancellations,
+    compaction::ContextCompactor, computer_use::ComputerUseClient, prompts::AGENT_SYSTEM_PROMPT,
+    results, retry, takeover,
+};
+use crate::{
+    ai::{AIResponse, AIService},
+    config::Config,
+    database::{
+        CreateMessageDto, DatabaseManager, FileRepositoryTrait, MessageRepositoryTrait,
+        TaskRepositoryTrait,
+    },
+    error::{AIError, ServiceError, ServiceResult},
+    websocket::WebSocketGateway,
+};
+
+/// Told to the model when it tries to complete a task that still owes a result
+const RESULT_REQUIRED: &str =
+    "Call submit_result with a result matching the result schema before completing the task";
+
+/// Background processor that claims pending tasks and drives them to completion.
+///
+/// Tasks are taken from the queue by priority and then by age, and at most
+/// `max_concurrent_tasks` of them run on the desktop at once.
+pub struct TaskProcessor {
+    db: Arc<DatabaseManager>,
+    ai_service: Arc<dyn AIService>,
+    websocket_gateway: Arc<WebSocketGateway>,
+    metrics: Arc<MetricsCollector>,
+    computer_use: ComputerUseClient,
+    cancellations: Arc<TaskCancellations>,
+    compactor: ContextCompactor,
+    usage: UsageRecorder,
+    approval_policy: ApprovalPolicy,
+    poll_interval: Duration,
+    max_iterations: u32,
+    max_concurrent_tasks: u32,
+    preemption_enabled: bool,
+    slots: Arc<Semaphore>,
+    task_finished: Notify,
+}
+
+impl TaskProcessor {
+    /// Create a new task processor
+    pub fn new(
+        config: &Config,
+        db: Arc<DatabaseManager>,
+        ai_service: Arc<dyn AIService>,
+        websocket_gateway: Arc<WebSocketGateway>,
+        metrics: Arc<MetricsCollector>,
+        cancellations: Arc<TaskCancellations>,
+    ) -> Self {
+        let max_concurrent_tasks = config.max_concurrent_tasks.max(1);
+        let usage = UsageRecorder::new(db.clone(), ai_service.clone(), metrics.clone());
+
+        Self {
+            compactor: ContextCompactor::new(config, db.clone(), ai_service.clone(), usage.clone()),
+            usage,
+            db,
+            ai_service,
+            websocket_gateway,
+            metrics,
+            computer_use: ComputerUseClient::new(config.bytebot_desktop_base_url.clone()),
+            cancellations,
+            approval_policy: config.approval_policy.clone(),
+            poll_interval: Duration::from_millis(config.task_poll_interval_ms),
+            max_iterations: config.max_task_iterations,
+            max_concurrent_tasks,
+            preemption_enabled: config.task_preemption_enabled,
+            slots: Arc::new(Semaphore::new(max_concurrent_tasks as usize)),
+            task_finished: Notify::new(),
+        }
+    }
+
+    /// Poll for pending tasks until `shutdown` is cancelled
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) {
+        info!(
+            poll_interval_ms = self.poll_interval.as_millis() as u64,
+            max_concurrent_tasks = self.max_concurrent_tasks,
+            preemption_enabled = self.preemption_enabled,
+            "Task processor started"
+        );
+
+        while !shutdown.is_cancelled() {
+            if self.preemption_enabled {
+                self.preempt_for_urgent().await;
+            }
+
+            self.fail_unsatisfiable_dependents().await;
+
+            // Fill every free slot from the queue
+            while let Ok(permit) = self.slots.clone().try_acquire_owned() {
+                match self.claim_next().await {
+                    Ok(Some(task)) => {
+                        let processor = self.clone();
+                        tokio::spawn(async move {
+                            processor.execute(task).await;
+                            drop(permit);
+                            processor.task_finished.notify_one();
+                        });
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        error!("Task processor error: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            self.refresh_queue_metrics().await;
+
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(self.poll_interval) => {}
+                // Look for more work straight away once a slot frees up
+                _ = self.task_finished.notified() => {}
+            }
+        }
+
+        info!("Task processor stopped");
+    }
+
+    /// Claim the next pending task if the running limit allows it
+    async fn claim_next(&self) -> ServiceResult<Option<Task>> {
+        self.db
+            .task_repository()
+            .claim_next_pending(self.max_concurrent_tasks)
+            .await
+            .map_err(ServiceError::Database)
+    }
+
+    /// Run a claimed task, marking it as failed if the run errors
+    async fn execute(&self, task: Task) {
+        let task_id = task.id.clone();
+        let run = self.cancellations.start_run();
+        let result = self.run_task(task, run).await;
+        self.cancellations.remove(&task_id, run);
+
+        if let Err(e) = result {
+            self.handle_failure(&task_id, e).await;
+        }
+    }
+
+    /// Move a running LOW task back to the queue if an URGENT task is waiting
+    /// and every slot is taken.
+    ///
+    /// The preempted task stops at its next iteration and resumes from its
+    /// saved conversation when it is claimed again.
+    async fn preempt_for_urgent(&self) {
+        match self
+            .db
+            .task_repository()
+            .preempt_for_urgent(self.max_concurrent_tasks)
+            .await
+        {
+            Ok(Some(task)) => {
+                info!(
+                    "Task {} moved back to the queue for an urgent task",
+                    task.id
+                );
+                self.cancellations.cancel(&task.id);
+                self.websocket_gateway
+                    .emit_task_update(&task.id, &task)
+                    .await;
+            }
+            Ok(None) => {}
+            Err(e) => error!("Failed to preempt task for urgent work: {}", e),
+        }
+    }
+
+    /// Fail pending tasks whose dependencies ended in the wrong state.
+    ///
+    /// Failing a task can make its own dependents unsatisfiable, so this runs
+    /// until no more tasks fail.
+    async fn fail_unsatisfiable_dependents(&self) {
+        loop {
+            match self
+                .db
+                .task_repository()
+                .fail_unsatisfiable_dependents()
+                .await
+            {
+                Ok(tasks) if tasks.is_empty() => break,
+                Ok(tasks) => {
+                    for task in tasks {
+                        self.websocket_gateway
+                            .emit_task_update(&task.id, &task)
+                            .await;
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to check task dependencies: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Publish queue depth per priority and the number of running tasks
+    async fn refresh_queue_metrics(&self) {
+        let task_repo = self.db.task_repository();
+
+        match task_repo.queue_depth_by_priority().await {
+            Ok(depths) => {
+                for priority in [
+                    TaskPriority::Low,
+                    TaskPriority::Medium,
+                    TaskPriority::High,
+                    TaskPriority::Urgent,
+                ] {
+                    self.metrics.update_task_queue_depth(
+                        &priority.to_string(),
+                        depths.get(&priority).copied().unwrap_or(0),
+                    );
+                }
+            }
+            Err(e) => warn!("Failed to read task queue depth: {}", e),
+        }
+
+        match task_repo.count_by_status().await {
+            Ok(counts) => self
+                .metrics
+                .update_tasks_running(counts.get(&TaskStatus::Running).copied().unwrap_or(0)),
+            Err(e) => warn!("Failed to count running tasks: {}", e),
+        }
+    }
+
+    /// Run the model/tool loop for a claimed task, registering its
+    /// cancellation tokens under `run`
+    async fn run_task(&self, task: Task, run: u64) -> ServiceResult<()> {
+        info!("Executing task {}: {}", task.id, task.description);
+
+        let task_repo = self.db.task_repository();
+        let message_repo = self.db.message_repository();
+
+        self.websocket_gateway
+            .emit_task_update(&task.id, &task)
+            .await;
+
+        // Seed the conversation with the task description and its attachments
+        let message_count = message_repo
+            .count_by_task_id(&task.id)
+            .await
+            .map_err(ServiceError::Database)?;
+        if message_count == 0 {
+            // Runs of a recurring task share the attachments of their definition
+            let files_owner = task.recurring_task_id.as_deref().unwrap_or(&task.id);
+            let files = self
+                .db
+                .file_repository()
+                .get_by_task_id(files_owner)
+                .await
+                .map_err(ServiceError::Database)?;
+
+            let mut content = vec![MessageContentBlock::text(task.description.clone())];
+            content.extend(files.iter().map(File::to_content_block));
+            if let Some(schema) = &task.result_schema {
+                content.push(MessageContentBlock::text(results::result_instructions(
+                    schema,
+                )));
+            }
+
+            self.save_message(&task, Role::User, content).await?;
+        }
+
+        let model = Self::model_name(&task.model);
+        let mut has_result = task.result.is_some();
+
+        for iteration in 0..self.max_iterations {
+            // Stop if the task was cancelled or preempted while we were busy,
+            // and wait while the user has taken over the desktop. The token is
+            // registered before the status check so a cancellation landing
+            // after it still trips this iteration's work.
+            let (cancel, current) = loop {
+                let cancel = self.cancellations.register(&task.id, run);
+                if !self.wait_for_control(&task.id).await? {
+                    return Ok(());
+                }
+                // A task over its budget failed or waits for the user to
+                // raise it, which the next wait finds out
+                let Some(current) = self.enforce_budget(&task.id).await? else {
+                    continue;
+                };
+                // A take-over during the wait tripped the token; start afresh
+                if !cancel.is_cancelled() {
+                    break (cancel, current);
+                }
+            };
+
+            debug!("Task {} iteration {}", task.id, iteration + 1);
+
+            let messages = message_repo
+                .get_by_task_id(&task.id)
+                .await
+                .map_err(ServiceError::Database)?;
+            let messages = self
+                .compactor
+                .prompt_messages(&task, messages, model.clone())
+                .await?;
+            let messages = takeover::describe_user_actions(messages);
+
+            // Clients watch the response as it is generated; the message
+            // saved below is still the complete one
+            let (deltas, mut stream) = mpsc::unbounded_channel();
+            let websocket_gateway = self.websocket_gateway.clone();
+            let task_id = task.id.clone();
+            tokio::spawn(async move {
+                while let Some(delta) = stream.recv().await {
+                    websocket_gateway.emit_message_delta(&task_id, &delta).await;
+                }
+            });
+
+            let started = Instant::now();
+            let call = self.ai_service.generate_response_stream(
+                AGENT_SYSTEM_PROMPT,
+                messages,
+                model.clone(),
+                true,
+                Some(cancel.clone()),
+                deltas,
+            );
+            let response = match budget::time_left(&current) {
+                Some(time_left) => match tokio::time::timeout(time_left, call).await {
+                    Ok(response) => response,
+                    // The next iteration stops the task for running too long
+                    Err(_) => continue,
+                },
+                None => call.await,
+            };
+            let latency = started.elapsed();
+            let response = match response {
+                Ok(response) => response,
+                // Cancelled, preempted or taken over: the next iteration
+                // finds out which and stops or waits accordingly
+                Err(AIError::Cancelled) => {
+                    info!("Model call for task {} was cancelled", task.id);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // An empty response saves no message but is paid for all the same
+            let message_id = if response.content.is_empty() {
+                None
+            } else {
+                Some(
+                    self.save_message(&task, Role::Assistant, response.content.clone())
+                        .await?,
+                )
+            };
+            self.record_usage(&task, message_id, &response, latency)
+                .await?;
+
+            let content = response.content;
+            if content.is_empty() {
+                // Without a saved reply there is no turn to answer with a
+                // reminder, so a missing result fails the attempt
+                if Self::result_missing(&task, has_result) {
+                    return Err(ServiceError::Internal(
+                        "The model stopped without submitting a result".to_string(),
+                    ));
+                }
+                return self.complete_task(&task.id).await;
+            }
+
+            let tool_uses: Vec<(String, String, Value)> = content
+                .into_iter()
+                .filter_map(|block| match block {
+                    MessageContentBlock::ToolUse { id, name, input } => Some((id, name, input)),
+                    _ => None,
+                })
+                .collect();
+
+            // The model signals completion by answering without tool calls
+            if tool_uses.is_empty() {
+                if Self::result_missing(&task, has_result) {
+                    self.save_message(
+                        &task,
+                        Role::User,
+                        vec![MessageContentBlock::text(RESULT_REQUIRED)],
+                    )
+                    .await?;
+                    continue;
+                }
+                return self.complete_task(&task.id).await;
+            }
+
+            // The user may have taken over while the model was responding
+            let user_has_control = task_repo
+                .get_by_id(&task.id)
+                .await
+                .map_err(ServiceError::Database)?
+                .is_some_and(|current| current.control == Role::User);
+
+            let mut results = Vec::with_capacity(tool_uses.len());
+            let mut outcome = None;
+            let mut unreachable = None;
+            for (id, name, input) in &tool_uses {
+                let result = if user_has_control {
+                    MessageContentBlock::tool_error(
+                        id,
+                        "Not performed: the user took control of the desktop",
+                    )
+                } else if let Some(e) = &unreachable {
+                    MessageContentBlock::tool_error(id, format!("Not performed: {e}"))
+                } else if let Some(call) = TaskToolCall::from_tool_use(name, input) {
+                    self.handle_task_tool(&task, id, call, &mut has_result, &mut outcome)
+                        .await?
+                } else if let Some(result) = self.await_approval(&task, id, name, input).await? {
+                    result
+                } else {
+                    match self
+                        .computer_use
+                        .execute_tool_use(id, name, input, &cancel)
+                        .await
+                    {
+                        Ok(result) => result,
+                        Err(e) => {
+                            let result = MessageContentBlock::tool_error(id, e.to_string());
+                            unreachable = Some(e);
+                            result
+                        }
+                    }
+                };
+                results.push(result);
+            }
+
+            // Every tool call gets its result before the attempt ends, so a
+            // retry resumes from a well-formed conversation
+            self.save_message(&task, Role::User, results).await?;
+            if let Some(e) = unreachable {
+                return Err(e.into());
+            }
+
+            match outcome {
+                Some(TaskOutcome::Completed) => return self.complete_task(&task.id).await,
+                Some(TaskOutcome::NeedsHelp) => self.request_help(&task.id).await?,
+                None => {}
+            }
+        }
+
+        Err(ServiceError::Internal(format!(
+            "Task exceeded the maximum of {} iterations",
+            self.max_iterations
+        )))
+    }
+
+    /// Handle a call to a task tool and return the result for the model.
+    ///
+    /// A status change is stored in `outcome` and applied once every tool call
+    /// of the response has been handled.
+    async fn handle_task_tool(
+        &self,
+        task: &Task,
+        tool_use_id: &str,
+        call: Result<TaskToolCall, serde_json::Error>,
+        has_result: &mut bool,
+        outcome: &mut Option<TaskOutcome>,
+    ) -> ServiceResult<MessageContentBlock> {
+        let call = match call {
+            Ok(call) => call,
+            Err(e) => {
+                return Ok(MessageContentBlock::tool_error(
+                    tool_use_id,
+                    format!("Invalid task tool call: {e}"),
+                ))
+            }
+        };
+
+        match call {
+            TaskToolCall::SubmitResult { result } => {
+                if let Err(e) = results::validate_result(task.result_schema.as_ref(), &result) {
+                    return Ok(MessageContentBlock::tool_error(tool_use_id, e));
+                }
+
+                let updated = self
+                    .db
+                    .task_repository()
+                    .set_result(&task.id, &result)
+                    .await
+                    .map_err(ServiceError::Database)?;
+                let Some(updated) = updated else {
+                    return Ok(MessageContentBlock::tool_error(
+                        tool_use_id,
+                        "The task is no longer running",
+                    ));
+                };
+
+                *has_result = true;
+                self.websocket_gateway
+                    .emit_task_update(&task.id, &updated)
+                    .await;
+
+                Ok(MessageContentBlock::tool_result(
+                    tool_use_id,
+                    vec![MessageContentBlock::text("Result submitted")],
+                ))
+            }
+            TaskToolCall::SetTaskStatus {
+                status,
+                description,
+            } => {
+                if status == TaskOutcome::Completed && Self::result_missing(task, *has_result) {
+                    return Ok(MessageContentBlock::tool_error(
+                        tool_use_id,
+                        RESULT_REQUIRED,
+                    ));
+                }
+
+                info!(
+                    "Task {} set to {} by the model: {}",
+                    task.id,
+                    status.status(),
+                    description
+                );
+                *outcome = Some(status);
+
+                Ok(MessageContentBlock::tool_result(
+                    tool_use_id,
+                    vec![MessageContentBlock::text(format!(
+                        "Task status set to {}",
+                        status.status()
+                    ))],
+                ))
+            }
+        }
+    }
+
+    /// Hold a desktop action that the approval policy covers until a reviewer
+    /// decides on it.
+    ///
+    /// Returns `None` if the action may run, or the result to report to the
+    /// model if it was rejected or the task stopped while waiting.
+    async fn await_approval(
+        &self,
+        task: &Task,
+        tool_use_id: &str,
+        name: &str,
+        input: &Value,
+    ) -> ServiceResult<Option<MessageContentBlock>> {
+        let Some(rule) = self.approval_policy.matching_rule(name, input) else {
+            return Ok(None);
+        };
+
+        let not_performed = || {
+            Some(MessageContentBlock::tool_error(
+                tool_use_id,
+                "Not performed: the task is no longer running",
+            ))
+        };
+
+        let task_repo = self.db.task_repository();
+        let approval = PendingApproval {
+            tool_use_id: tool_use_id.to_string(),
+            action: name.to_string(),
+            input: input.clone(),
+            reason: rule.reason(),
+            requested_at: Utc::now(),
+            decision: None,
+            comment: None,
+        };
+        let Some(updated) = task_repo
+            .request_approval(&task.id, &approval)
+            .await
+            .map_err(ServiceError::Database)?
+        else {
+            return Ok(not_performed());
+        };
+
+        info!(
+            "Task {} waiting for approval of {} action: {}",
+            task.id, name, approval.reason
+        );
+        self.websocket_gateway
+            .emit_task_update(&task.id, &updated)
+            .await;
+        self.websocket_gateway
+            .emit_approval_pending(&task.id, &approval)
+            .await;
+
+        loop {
+            tokio::time::sleep(self.poll_interval).await;
+
+            let current = task_repo
+                .get_by_id(&task.id)
+                .await
+                .map_err(ServiceError::Database)?;
+            let current = match current {
+                Some(current) if current.status == TaskStatus::NeedsReview => continue,
+                Some(current) if current.status == TaskStatus::Running => current,
+                _ => return Ok(not_performed()),
+            };
+
+            task_repo
+                .clear_approval(&task.id)
+                .await
+                .map_err(ServiceError::Database)?;
+
+            let decided = current
+                .pending_approval
+                .filter(|decided| decided.tool_use_id == tool_use_id && decided.decision.is_some());
+            return Ok(match decided {
+                Some(PendingApproval {
+                    decision: Some(ApprovalDecision::Approved),
+                    ..
+                }) => {
+                    info!("Task {} {} action approved", task.id, name);
+                    None
+                }
+                Some(PendingApproval { comment, .. }) => {
+                    info!("Task {} {} action rejected", task.id, name);
+                    let message = match comment {
+                        Some(comment) => format!("Rejected by the reviewer: {comment}"),
+                        None => "Rejected by the reviewer".to_string(),
+                    };
+                    Some(MessageContentBlock::tool_error(tool_use_id, message))
+                }
+                None => Some(MessageContentBlock::tool_error(
+                    tool_use_id,
+                    "Not performed: the task resumed without an approval",
+                )),
+            });
+        }
+    }
+
+    /// Wait while the user has control of the task.
+    ///
+    /// Returns `true` once the assistant may continue, or `false` if the task
+    /// stopped running, e.g. because it was cancelled, preempted or deleted.
+    async fn wait_for_control(&self, task_id: &str) -> ServiceResult<bool> {
+        let mut paused = false;
+
+        loop {
+            let current = self
+                .db
+                .task_repository()
+                .get_by_id(task_id)
+                .await
+                .map_err(ServiceError::Database)?;
+
+            match current {
+                Some(current)
+                    if current.status == TaskStatus::Running
+                        && current.control == Role::Assistant =>
+                {
+                    if paused {
+                        info!("Task {} resumed by the assistant", task_id);
+                    }
+                    return Ok(true);
+                }
+                Some(current)
+                    if current.status == TaskStatus::NeedsHelp
+                        || (current.status == TaskStatus::Running
+                            && current.control == Role::User) =>
+                {
+                    if !paused {
+                        info!("Task {} paused while the user has control", task_id);
+                        paused = true;
+                    }
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+                Some(current) => {
+                    info!(
+                        "Task {} is no longer running ({}), stopping",
+                        task_id, current.status
+                    );
+                    return Ok(false);
+                }
+                None => {
+                    warn!("Task {} was deleted during execution", task_id);
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    /// Stop the task if it reached a limit of its budget.
+    ///
+    /// Returns the current task if it may take another turn. Otherwise the
+    /// task is failed or handed to the user, depending on the budget, and
+    /// `None` is returned.
+    async fn enforce_budget(&self, task_id: &str) -> ServiceResult<Option<Task>> {
+        let task_repo = self.db.task_repository();
+        let Some(current) = task_repo
+            .get_by_id(task_id)
+            .await
+            .map_err(ServiceError::Database)?
+        else {
+            return Ok(None);
+        };
+
+        let Some(limits) = &current.budget else {
+            return Ok(Some(current));
+        };
+        let Some(reason) = limits.exceeded(&current.usage, budget::elapsed(&current)) else {
+            return Ok(Some(current));
+        };
+
+        warn!("Task {} stopped: {}", task_id, reason);
+        match limits.on_exceeded {
+            BudgetAction::Fail => self.fail_task(task_id, &reason, FailureKind::Other).await,
+            BudgetAction::NeedsHelp => {
+                let task = task_repo
+                    .mark_needs_help(task_id, &reason)
+                    .await
+                    .map_err(ServiceError::Database)?;
+                if let Some(task) = task {
+                    self.websocket_gateway
+                        .emit_task_update(task_id, &task)
+                        .await;
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Account for a model turn of the task and broadcast its updated usage
+    async fn record_usage(
+        &self,
+        task: &Task,
+        message_id: Option<String>,
+        response: &AIResponse,
+        latency: Duration,
+    ) -> ServiceResult<()> {
+        let updated = self
+            .usage
+            .record(task, message_id, response, latency)
+            .await?;
+
+        if let Some(updated) = updated {
+            self.websocket_gateway
+                .emit_task_update(&task.id, &updated)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Persist a message for the task, broadcast it and return its id
+    async fn save_message(
+        &self,
+        task: &Task,
+        role: Role,
+        content: Vec<MessageContentBlock>,
+    ) -> ServiceResult<String> {
+        let message = self
+            .db
+            .message_repository()
+            .create(&CreateMessageDto {
+                content,
+                role,
+                task_id: task.id.clone(),
+                user_id: task.user_id.clone(),
+                summary_id: None,
+            })
+            .await
+            .map_err(ServiceError::Database)?;
+
+        self.websocket_gateway
+            .emit_new_message(&task.id, &message)
+            .await;
+
+        Ok(message.id)
+    }
+
+    /// Mark the task as completed and broadcast the update
+    async fn complete_task(&self, task_id: &str) -> ServiceResult<()> {
+        let task = self
+            .db
+            .task_repository()
+            .update_status(task_id, TaskStatus::Completed)
+            .await
+            .map_err(ServiceError::Database)?;
+
+        if let Some(task) = task {
+            info!("Task {} completed", task_id);
+            self.websocket_gateway
+                .emit_task_update(task_id, &task)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Hand the task to the user and broadcast the update.
+    ///
+    /// The run pauses at its next iteration until the task is resumed.
+    async fn request_help(&self, task_id: &str) -> ServiceResult<()> {
+        let task = self
+            .db
+            .task_repository()
+            .update_status(task_id, TaskStatus::NeedsHelp)
+            .await
+            .map_err(ServiceError::Database)?;
+
+        if let Some(task) = task {
+            info!("Task {} needs help from the user", task_id);
+            self.websocket_gateway
+                .emit_task_update(task_id, &task)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Put the task back in the queue if its retry policy covers the failure,
+    /// and mark it as failed otherwise
+    async fn handle_failure(&self, task_id: &str, error: ServiceError) {
+        let kind = retry::failure_kind(&error);
+        let reason = error.to_string();
+
+        let task = match self.db.task_repository().get_by_id(task_id).await {
+            Ok(Some(task)) => task,
+            Ok(None) => {
+                warn!("Task {} not found after it failed: {}", task_id, reason);
+                return;
+            }
+            Err(e) => {
+                error!("Failed to load task {} after it failed: {}", task_id, e);
+                return;
+            }
+        };
+
+        let policy = task.retry_policy.unwrap_or_default();
+        if policy.should_retry(kind, task.attempt) {
+            let backoff = chrono::Duration::from_std(policy.backoff(task.attempt))
+                .unwrap_or_else(|_| chrono::Duration::zero());
+            let retry_at = Utc::now() + backoff;
+
+            match self
+                .db
+                .task_repository()
+                .schedule_retry(task_id, &reason, kind, retry_at)
+                .await
+            {
+                Ok(Some(task)) => {
+                    self.websocket_gateway
+                        .emit_task_update(task_id, &task)
+                        .await;
+                    return;
+                }
+                // The task stopped running, e.g. it was cancelled meanwhile
+                Ok(None) => return,
+                Err(e) => error!("Failed to schedule retry of task {}: {}", task_id, e),
+            }
+        }
+
+        error!("Task {} failed: {}", task_id, reason);
+        self.fail_task(task_id, &reason, kind).await;
+    }
+
+    /// Mark the task as failed, logging rather than propagating any error
+    async fn fail_task(&self, task_id: &str, reason: &str, kind: FailureKind) {
+        match self
+            .db
+            .task_repository()
+            .mark_failed(task_id, reason, kind)
+            .await
+        {
+            Ok(Some(task)) => {
+                self.websocket_gateway
+                    .emit_task_update(task_id, &task)
+                    .await
+            }
+            Ok(None) => warn!("Task {} not found while marking as failed", task_id),
+            Err(e) => error!("Failed to mark task {} as failed: {}", task_id, e),
+        }
+    }
+
+    /// Whether the task has a result schema but no result yet, so it cannot
+    /// complete
+    fn result_missing(task: &Task, has_result: bool) -> bool {
+        task.result_schema.is_some() && !has_result
+    }
+
+    /// Extract the model name from the task's model JSON.
+    ///
+    /// The model is stored as `{ provider, name, title }`, but a bare string is
+    /// accepted as well.
+    pub fn model_name(model: &Value) -> Option<String> {
+        match model {
+            Value::String(name) => Some(name.clone()),
+            Value::Object(map) => map.get("name").and_then(Value::as_str).map(str::to_string),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use async_trait::async_trait;
+    use bytebot_shared_rs::types::{api::CreateTaskDto, message::Message, task::TaskType};
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        ai::{ModelInfo, UnifiedAIService},
+        database::tests::{create_isolated_test_pool, drop_isolated_schema, test_metrics},
+    };
+
+    /// Provider answering with scripted replies, then with plain text
+    #[derive(Default)]
+    struct ScriptedService {
+        replies: Mutex<VecDeque<Vec<MessageContentBlock>>>,
+        /// Model of every request, as the provider received it
+        requested_models: Mutex<Vec<Option<String>>>,
+    }
+
+    impl ScriptedService {
+        fn new(replies: Vec<Vec<MessageContentBlock>>) -> Self {
+            Self {
+                replies: Mutex::new(replies.into()),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AIService for ScriptedService {
+        async fn generate_response(
+            &self,
+            _system_prompt: &str,
+            _messages: Vec<Message>,
+            model: Option<String>,
+            _use_tools: bool,
+            _signal: Option<CancellationToken>,
+        ) -> Result<AIResponse, AIError> {
+            self.requested_models.lock().unwrap().push(model.clone());
+            let content = self
+                .replies
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| vec![MessageContentBlock::text("Done")]);
+
+            Ok(AIResponse {
+                content,
+                model: model.unwrap_or_default(),
+                ..Default::default()
+            })
+        }
+
+        fn list_models(&self) -> Vec<ModelInfo> {
+            Vec::new()
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    fn create_test_processor(pool: &PgPool, ai_service: Arc<dyn AIService>) -> TaskProcessor {
+        TaskProcessor::new(
+            &Config::default(),
+            Arc::new(DatabaseManager::from_pool(pool.clone())),
+            ai_service,
+            Arc::new(WebSocketGateway::new()),
+            test_metrics(),
+            Arc::new(TaskCancellations::new()),
+        )
+    }
+
+    fn create_dto(description: &str) -> CreateTaskDto {
+        CreateTaskDto {
+            description: description.to_string(),
+            task_type: Some(TaskType::Immediate),
+            scheduled_for: None,
+            priority: None,
+            created_by: Some(Role::User),
+            user_id: None,
+            model: None,
+            files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
+        }
+    }
+
+    /// Create a task, claim it and run it to the end
+    async fn process(processor: &TaskProcessor, dto: &CreateTaskDto) -> Task {
+        let task_repo = processor.db.task_repository();
+        let task = task_repo.create(dto).await.unwrap();
+        let claimed = task_repo.claim_next_pending(1).await.unwrap().unwrap();
+        assert_eq!(claimed.id, task.id);
+
+        processor.execute(claimed).await;
+        task_repo.get_by_id(&task.id).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_task_without_model_runs_on_default_model() {
+        let pool = create_isolated_test_pool().await;
+        let provider = Arc::new(ScriptedService::default());
+        let mut ai_service = UnifiedAIService::new(&Config::default());
+        ai_service.register("anthropic", provider.clone());
+        let processor = create_test_processor(&pool, Arc::new(ai_service));
+
+        let task = process(&processor, &create_dto("Task without a model")).await;
+
+        assert_eq!(task.model, Value::Null);
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(
+            *provider.requested_models.lock().unwrap(),
+            vec![Some("claude-opus-4-20250514".to_string())]
+        );
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_text_reply_without_required_result_is_answered_with_a_reminder() {
+        let pool = create_isolated_test_pool().await;
+        let result = json!({ "price": 12, "currency": "EUR" });
+        let provider = Arc::new(ScriptedService::new(vec![
+            vec![MessageContentBlock::text("The price is 12 EUR")],
+            vec![MessageContentBlock::tool_use(
+                "submit_result",
+                "toolu_1",
+                json!({ "result": result }),
+            )],
+        ]));
+        let processor = create_test_processor(&pool, provider.clone());
+
+        let dto = CreateTaskDto {
+            result_schema: Some(json!({
+                "type": "object",
+                "required": ["price", "currency"]
+            })),
+            ..create_dto("Find the price")
+        };
+        let task = process(&processor, &dto).await;
+
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.result, Some(result));
+        assert_eq!(provider.requested_models.lock().unwrap().len(), 3);
+
+        let messages = processor
+            .db
+            .message_repository()
+            .get_by_task_id(&task.id)
+            .await
+            .unwrap();
+        let reminder = &messages[2];
+        assert_eq!(reminder.role, Role::User);
+        assert_eq!(
+            reminder.get_content_blocks().unwrap()[0].as_text(),
+            Some(RESULT_REQUIRED)
+        );
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[test]
+    fn test_model_name_from_object() {
+        let model = json!({
+            "provider": "anthropic",
+            "name": "claude-sonnet-4-20250514",
+            "title": "Claude Sonnet 4"
+        });
+        assert_eq!(
+            TaskProcessor::model_name(&model),
+            Some("claude-sonnet-4-20250514".to_string())
+        );
+    }
+
+    #[test]
+    fn test_model_name_from_string() {
+        assert_eq!(
+            TaskProcessor::model_name(&json!("gpt-4o")),
+            Some("gpt-4o".to_string())
+        );
+    }
+
+    #[test]
+    fn test_model_name_missing() {
+        assert_eq!(TaskProcessor::model_name(&json!({})), None);
+        assert_eq!(TaskProcessor::model_name(&Value::Null), None);
+    }
+}
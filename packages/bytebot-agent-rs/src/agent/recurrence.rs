@@ -0,0 +1,117 @@
+use bytebot_shared_rs::types::task::Task;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+
+use crate::error::{ServiceError, ServiceResult};
+
+/// Maximum number of upcoming runs that can be listed at once
+pub const MAX_UPCOMING_RUNS: usize = 100;
+
+/// Parsed cron expression evaluated in a specific timezone
+#[derive(Debug, Clone)]
+pub struct RecurrenceRule {
+    cron: Cron,
+    timezone: Tz,
+}
+
+impl RecurrenceRule {
+    /// Parse a five-field cron expression and an IANA timezone name
+    pub fn parse(expression: &str, timezone: &str) -> ServiceResult<Self> {
+        let timezone: Tz = timezone
+            .parse()
+            .map_err(|_| ServiceError::Validation(format!("Unknown timezone: {timezone}")))?;
+
+        let cron = Cron::new(expression.trim()).parse().map_err(|e| {
+            ServiceError::Validation(format!("Invalid cron expression '{expression}': {e}"))
+        })?;
+
+        Ok(Self { cron, timezone })
+    }
+
+    /// Rule for a recurring task definition, or `None` for a one-off task
+    pub fn from_task(task: &Task) -> Option<ServiceResult<Self>> {
+        task.cron_expression
+            .as_deref()
+            .map(|cron| Self::parse(cron, task.timezone.as_deref().unwrap_or("UTC")))
+    }
+
+    /// First run strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.upcoming_after(after, 1).into_iter().next()
+    }
+
+    /// The next `count` runs strictly after `after`
+    pub fn upcoming_after(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        self.cron
+            .iter_after(after.with_timezone(&self.timezone))
+            .take(count)
+            .map(|run| run.with_timezone(&Utc))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_rejects_invalid_rules() {
+        assert!(RecurrenceRule::parse("not a cron", "UTC").is_err());
+        assert!(RecurrenceRule::parse("0 7 * * MON-FRI", "Mars/Olympus").is_err());
+        assert!(RecurrenceRule::parse("0 7 * * MON-FRI", "Europe/Berlin").is_ok());
+    }
+
+    #[test]
+    fn test_weekday_mornings_skip_weekend() {
+        let rule = RecurrenceRule::parse("0 7 * * MON-FRI", "UTC").unwrap();
+        // Friday 2025-03-07 08:00 UTC
+        let after = Utc.with_ymd_and_hms(2025, 3, 7, 8, 0, 0).unwrap();
+
+        assert_eq!(
+            rule.upcoming_after(after, 2),
+            vec![
+                Utc.with_ymd_and_hms(2025, 3, 10, 7, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2025, 3, 11, 7, 0, 0).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_after_uses_timezone() {
+        let rule = RecurrenceRule::parse("0 9 * * *", "America/New_York").unwrap();
+        let after = Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap();
+
+        // 09:00 EST is 14:00 UTC
+        assert_eq!(
+            rule.next_after(after),
+            Some(Utc.with_ymd_and_hms(2025, 1, 15, 14, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_after_is_exclusive() {
+        let rule = RecurrenceRule::parse("*/15 * * * *", "UTC").unwrap();
+        let at = Utc.with_ymd_and_hms(2025, 1, 15, 10, 15, 0).unwrap();
+
+        assert_eq!(
+            rule.next_after(at),
+            Some(Utc.with_ymd_and_hms(2025, 1, 15, 10, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_from_task() {
+        let mut task = Task::new("Daily report".to_string(), json!({}));
+        assert!(RecurrenceRule::from_task(&task).is_none());
+
+        task.cron_expression = Some("0 7 * * *".to_string());
+        assert!(RecurrenceRule::from_task(&task).unwrap().is_ok());
+
+        task.timezone = Some("Nowhere/Special".to_string());
+        assert!(RecurrenceRule::from_task(&task).unwrap().is_err());
+    }
+}
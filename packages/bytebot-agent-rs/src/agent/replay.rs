@@ -0,0 +1,647 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose, Engine as _};
+use bytebot_shared_rs::types::{
+    computer_action::ComputerAction,
+    message::{Message, MessageContentBlock},
+    replay::{ReplayOptions, ReplayStatus, ReplayStep, ReplayStepStatus, TaskReplay},
+};
+use chrono::{DateTime, Utc};
+use image::RgbaImage;
+use serde_json::Value;
+use tracing::{debug, error, info, warn};
+
+use super::computer_use::ComputerUseClient;
+use crate::database::{DatabaseManager, ReplayRepositoryTrait};
+
+/// Difference allowed per colour channel for two pixels to match, enough to
+/// absorb anti-aliasing and clock ticks without hiding real changes
+const CHANNEL_TOLERANCE: u8 = 16;
+
+/// Tool call recorded in a task, ready to be replayed
+#[derive(Debug, Clone)]
+pub struct RecordedCall {
+    pub tool_use_id: String,
+    pub name: String,
+    pub input: Value,
+    /// Pause between the result of the previous call and this call
+    pub delay: Duration,
+    /// Screenshot the call returned in the recording
+    pub screenshot: Option<String>,
+    /// Screenshot recorded after this call and before the next replayed
+    /// action, to compare the screen with once the call has run
+    pub next_screenshot: Option<String>,
+    /// Why the call is not replayed
+    pub skip_reason: Option<String>,
+}
+
+/// Recorded outcome of a tool call
+struct RecordedResult {
+    at: DateTime<Utc>,
+    is_error: bool,
+    screenshot: Option<String>,
+}
+
+/// Extract the tool calls of a conversation in the order they were made.
+///
+/// Calls that are not desktop actions, that never got a result or whose
+/// result was an error are kept as skipped steps, so step indexes follow the
+/// recording.
+pub fn recorded_calls(messages: &[Message]) -> Vec<RecordedCall> {
+    let conversation: Vec<(DateTime<Utc>, Vec<MessageContentBlock>)> = messages
+        .iter()
+        .filter_map(|message| match message.get_content_blocks() {
+            Ok(blocks) => Some((message.created_at, blocks)),
+            Err(e) => {
+                warn!("Skipping unreadable message {}: {}", message.id, e);
+                None
+            }
+        })
+        .collect();
+
+    let mut results = HashMap::new();
+    for (at, blocks) in &conversation {
+        for block in blocks {
+            if let MessageContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } = block
+            {
+                let screenshot = content.iter().find_map(|block| match block {
+                    MessageContentBlock::Image { source } => Some(source.data.clone()),
+                    _ => None,
+                });
+                results.insert(
+                    tool_use_id.as_str(),
+                    RecordedResult {
+                        at: *at,
+                        is_error: *is_error == Some(true),
+                        screenshot,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut calls = Vec::new();
+    let mut last_result_at: Option<DateTime<Utc>> = None;
+    for (at, blocks) in &conversation {
+        for block in blocks {
+            if let MessageContentBlock::ToolUse { name, id, input } = block {
+                let delay = last_result_at
+                    .and_then(|last| (*at - last).to_std().ok())
+                    .unwrap_or_default();
+                let result = results.get(id.as_str());
+
+                let skip_reason =
+                    match (ComputerUseClient::action_from_tool_use(name, input), result) {
+                        (Err(_), _) => Some(format!("{name} is not a desktop action")),
+                        (Ok(_), None) => Some("No result was recorded for this call".to_string()),
+                        (Ok(_), Some(result)) if result.is_error => {
+                            Some("The call failed in the recording".to_string())
+                        }
+                        (Ok(_), Some(_)) => None,
+                    };
+
+                if let Some(result) = result {
+                    last_result_at = Some(result.at);
+                }
+
+                calls.push(RecordedCall {
+                    tool_use_id: id.clone(),
+                    name: name.clone(),
+                    input: input.clone(),
+                    delay,
+                    screenshot: result.and_then(|result| result.screenshot.clone()),
+                    next_screenshot: None,
+                    skip_reason,
+                });
+            }
+        }
+    }
+
+    // Walk back from the end, so each action sees the first screenshot taken
+    // after it unless another replayed action ran in between
+    let mut next_screenshot = None;
+    for call in calls.iter_mut().rev() {
+        if call.skip_reason.is_some() {
+            continue;
+        }
+        match ComputerUseClient::action_from_tool_use(&call.name, &call.input) {
+            Ok(ComputerAction::Screenshot) => next_screenshot = call.screenshot.clone(),
+            _ => call.next_screenshot = next_screenshot.take(),
+        }
+    }
+
+    calls
+}
+
+/// Share of pixels of two base64 screenshots that match within
+/// `CHANNEL_TOLERANCE`. Screenshots of different sizes never match.
+pub fn screenshot_similarity(expected: &str, actual: &str) -> Result<f64, String> {
+    let expected = decode_screenshot(expected)?;
+    let actual = decode_screenshot(actual)?;
+
+    if expected.dimensions() != actual.dimensions() {
+        return Ok(0.0);
+    }
+
+    let total = expected.width() as usize * expected.height() as usize;
+    if total == 0 {
+        return Ok(1.0);
+    }
+
+    let matching = expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(a, b)| {
+            a.0.iter()
+                .zip(b.0.iter())
+                .all(|(a, b)| a.abs_diff(*b) <= CHANNEL_TOLERANCE)
+        })
+        .count();
+
+    Ok(matching as f64 / total as f64)
+}
+
+fn decode_screenshot(data: &str) -> Result<RgbaImage, String> {
+    let bytes = general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid screenshot encoding: {e}"))?;
+
+    image::load_from_memory(&bytes)
+        .map(|image| image.to_rgba8())
+        .map_err(|e| format!("Invalid screenshot: {e}"))
+}
+
+/// Re-executes the desktop actions recorded in a finished task.
+///
+/// No model is involved: the recorded tool calls are sent to bytebotd as they
+/// are, with the original pauses between them.
+pub struct ReplayRunner {
+    db: Arc<DatabaseManager>,
+    computer_use: ComputerUseClient,
+}
+
+impl ReplayRunner {
+    /// Create a new replay runner
+    pub fn new(db: Arc<DatabaseManager>, computer_use: ComputerUseClient) -> Self {
+        Self { db, computer_use }
+    }
+
+    /// Replay `calls` in order, recording each step on `replay` as it
+    /// finishes, then store the final status
+    pub async fn run(self, replay: TaskReplay, calls: Vec<RecordedCall>) {
+        info!(
+            "Replaying {} recorded calls of task {} as replay {}",
+            calls.len(),
+            replay.task_id,
+            replay.id
+        );
+
+        let (status, error) = self.run_steps(&replay, calls).await;
+
+        if let Err(e) = self
+            .db
+            .replay_repository()
+            .finish(&replay.id, status, error.as_deref())
+            .await
+        {
+            error!("Failed to finish replay {}: {}", replay.id, e);
+        }
+    }
+
+    async fn run_steps(
+        &self,
+        replay: &TaskReplay,
+        calls: Vec<RecordedCall>,
+    ) -> (ReplayStatus, Option<String>) {
+        let options = &replay.options;
+        let mut diverged = false;
+
+        for (index, call) in calls.into_iter().enumerate() {
+            let step = replay_step(&self.computer_use, index, call, options).await;
+
+            if let Err(e) = self
+                .db
+                .replay_repository()
+                .add_step(&replay.id, &step)
+                .await
+            {
+                return (
+                    ReplayStatus::Failed,
+                    Some(format!("Failed to record step {index}: {e}")),
+                );
+            }
+
+            match step.status {
+                ReplayStepStatus::Failed => {
+                    // Later actions would run against the wrong screen
+                    return (
+                        ReplayStatus::Failed,
+                        Some(format!(
+                            "Step {index} ({}) failed: {}",
+                            step.action,
+                            step.error.unwrap_or_default()
+                        )),
+                    );
+                }
+                ReplayStepStatus::Diverged => {
+                    diverged = true;
+                    if options.stop_on_divergence {
+                        break;
+                    }
+                }
+                ReplayStepStatus::Succeeded | ReplayStepStatus::Skipped => {}
+            }
+        }
+
+        if diverged {
+            (ReplayStatus::Diverged, None)
+        } else {
+            (ReplayStatus::Completed, None)
+        }
+    }
+}
+
+/// Run one recorded call against the desktop. Failures are reported on the
+/// returned step.
+async fn replay_step(
+    computer_use: &ComputerUseClient,
+    index: usize,
+    call: RecordedCall,
+    options: &ReplayOptions,
+) -> ReplayStep {
+    let mut step = ReplayStep {
+        index,
+        tool_use_id: call.tool_use_id,
+        action: call.name,
+        input: call.input,
+        status: ReplayStepStatus::Skipped,
+        similarity: None,
+        delay_ms: 0,
+        duration_ms: 0,
+        error: call.skip_reason,
+    };
+
+    if step.error.is_some() {
+        debug!("Skipping step {}: {:?}", index, step.error);
+        return step;
+    }
+
+    let delay = call.delay.min(Duration::from_millis(options.max_delay_ms));
+    tokio::time::sleep(delay).await;
+    step.delay_ms = delay.as_millis() as u64;
+
+    let started = Instant::now();
+    let outcome = match ComputerUseClient::action_from_tool_use(&step.action, &step.input) {
+        Ok(action) => computer_use
+            .execute(&action)
+            .await
+            .map(|result| (action, result)),
+        Err(e) => Err(e),
+    };
+    step.duration_ms = started.elapsed().as_millis() as u64;
+
+    let (action, result) = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            warn!("Replayed step {} ({}) failed: {}", index, step.action, e);
+            step.status = ReplayStepStatus::Failed;
+            step.error = Some(e.to_string());
+            return step;
+        }
+    };
+
+    step.status = ReplayStepStatus::Succeeded;
+
+    if !options.compare_screenshots {
+        return step;
+    }
+
+    // A screenshot is compared with its own recording, any other action with
+    // the screenshot recorded after it, taken once the action has run
+    let similarity = match (&action, &call.screenshot, &call.next_screenshot) {
+        (ComputerAction::Screenshot, Some(expected), _) => Some(compare_result(expected, &result)),
+        (ComputerAction::Screenshot, None, _) | (_, _, None) => None,
+        (_, _, Some(expected)) => Some(
+            match computer_use.execute(&ComputerAction::Screenshot).await {
+                Ok(result) => compare_result(expected, &result),
+                Err(e) => Err(format!("Failed to take a screenshot: {e}")),
+            },
+        ),
+    };
+
+    match similarity {
+        Some(Ok(similarity)) => {
+            step.similarity = Some(similarity);
+            if similarity < options.similarity_threshold {
+                step.status = ReplayStepStatus::Diverged;
+            }
+        }
+        Some(Err(e)) => {
+            step.status = ReplayStepStatus::Diverged;
+            step.error = Some(e);
+        }
+        None => {}
+    }
+
+    step
+}
+
+/// Similarity of the screenshot in a daemon `result` to `expected`
+fn compare_result(expected: &str, result: &Value) -> Result<f64, String> {
+    match result["screenshot"].as_str() {
+        Some(actual) => screenshot_similarity(expected, actual),
+        None => Err("Screenshot returned no image data".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use axum::{routing::post, Json, Router};
+    use bytebot_shared_rs::types::task::Role;
+    use image::{ImageOutputFormat, Rgba};
+    use serde_json::json;
+
+    use super::*;
+
+    fn png(width: u32, height: u32, color: [u8; 4]) -> String {
+        let image = RgbaImage::from_pixel(width, height, Rgba(color));
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, ImageOutputFormat::Png).unwrap();
+        general_purpose::STANDARD.encode(bytes.into_inner())
+    }
+
+    fn message(blocks: Vec<MessageContentBlock>, role: Role, seconds: i64) -> Message {
+        let mut message = Message::new(blocks, role, "task-1".to_string());
+        message.created_at = DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap();
+        message
+    }
+
+    async fn spawn_stub_daemon(screenshot: String) -> String {
+        let app = Router::new().route(
+            "/computer-use",
+            post(move |Json(body): Json<Value>| async move {
+                Json(json!({
+                    "success": true,
+                    "action": body["action"],
+                    "result": { "screenshot": screenshot }
+                }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_recorded_calls() {
+        let messages = vec![
+            message(
+                vec![MessageContentBlock::text("Open the settings")],
+                Role::User,
+                0,
+            ),
+            message(
+                vec![MessageContentBlock::tool_use(
+                    "screenshot",
+                    "call_1",
+                    json!({}),
+                )],
+                Role::Assistant,
+                5,
+            ),
+            message(
+                vec![MessageContentBlock::tool_result(
+                    "call_1",
+                    vec![MessageContentBlock::image("image/png", "c2NyZWVu")],
+                )],
+                Role::User,
+                6,
+            ),
+            message(
+                vec![
+                    MessageContentBlock::tool_use(
+                        "click_mouse",
+                        "call_2",
+                        json!({ "button": "left", "clickCount": 1 }),
+                    ),
+                    MessageContentBlock::tool_use(
+                        "set_task_status",
+                        "call_3",
+                        json!({ "status": "completed" }),
+                    ),
+                ],
+                Role::Assistant,
+                9,
+            ),
+            message(
+                vec![
+                    MessageContentBlock::tool_error("call_2", "Click failed"),
+                    MessageContentBlock::tool_result("call_3", Vec::new()),
+                ],
+                Role::User,
+                10,
+            ),
+        ];
+
+        let calls = recorded_calls(&messages);
+        assert_eq!(calls.len(), 3);
+
+        assert_eq!(calls[0].delay, Duration::ZERO);
+        assert_eq!(calls[0].screenshot.as_deref(), Some("c2NyZWVu"));
+        assert!(calls[0].skip_reason.is_none());
+
+        assert_eq!(calls[1].delay, Duration::from_secs(3));
+        assert!(calls[1].skip_reason.is_some());
+
+        assert_eq!(calls[2].name, "set_task_status");
+        assert!(calls[2].skip_reason.is_some());
+    }
+
+    #[test]
+    fn test_recorded_calls_next_screenshot() {
+        let call = |name: &str, id: &str, input: Value, seconds: i64| {
+            message(
+                vec![MessageContentBlock::tool_use(name, id, input)],
+                Role::Assistant,
+                seconds,
+            )
+        };
+        let result = |id: &str, content: Vec<MessageContentBlock>, seconds: i64| {
+            message(
+                vec![MessageContentBlock::tool_result(id, content)],
+                Role::User,
+                seconds,
+            )
+        };
+        let image = |data: &str| vec![MessageContentBlock::image("image/png", data)];
+
+        let messages = vec![
+            call(
+                "click_mouse",
+                "call_1",
+                json!({ "button": "left", "clickCount": 1 }),
+                0,
+            ),
+            result("call_1", Vec::new(), 1),
+            call(
+                "set_task_status",
+                "call_2",
+                json!({ "status": "completed" }),
+                2,
+            ),
+            result("call_2", Vec::new(), 3),
+            call("screenshot", "call_3", json!({}), 4),
+            result("call_3", image("Zmlyc3Q="), 5),
+            call("type_text", "call_4", json!({ "text": "hello" }), 6),
+            result("call_4", Vec::new(), 7),
+            call(
+                "press_keys",
+                "call_5",
+                json!({ "keys": ["Enter"], "press": "down" }),
+                8,
+            ),
+            result("call_5", Vec::new(), 9),
+            call("screenshot", "call_6", json!({}), 10),
+            result("call_6", image("c2Vjb25k"), 11),
+        ];
+
+        let calls = recorded_calls(&messages);
+        assert_eq!(calls.len(), 6);
+        assert!(calls[4].skip_reason.is_none());
+
+        // Skipped calls do not change the screen
+        assert_eq!(calls[0].next_screenshot.as_deref(), Some("Zmlyc3Q="));
+        assert!(calls[1].next_screenshot.is_none());
+        // Screenshots are compared with their own recording
+        assert!(calls[2].next_screenshot.is_none());
+        // Another action ran before the next screenshot
+        assert!(calls[3].next_screenshot.is_none());
+        assert_eq!(calls[4].next_screenshot.as_deref(), Some("c2Vjb25k"));
+    }
+
+    #[test]
+    fn test_screenshot_similarity() {
+        let black = png(4, 4, [0, 0, 0, 255]);
+        let near_black = png(4, 4, [10, 10, 10, 255]);
+        let white = png(4, 4, [255, 255, 255, 255]);
+
+        assert_eq!(screenshot_similarity(&black, &near_black).unwrap(), 1.0);
+        assert_eq!(screenshot_similarity(&black, &white).unwrap(), 0.0);
+        assert_eq!(
+            screenshot_similarity(&black, &png(2, 2, [0, 0, 0, 255])).unwrap(),
+            0.0
+        );
+        assert!(screenshot_similarity(&black, "not base64!").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_step_flags_divergence() {
+        let client = ComputerUseClient::new(spawn_stub_daemon(png(4, 4, [255, 0, 0, 255])).await);
+        let options = ReplayOptions {
+            compare_screenshots: true,
+            ..ReplayOptions::default()
+        };
+        let call = RecordedCall {
+            tool_use_id: "call_1".to_string(),
+            name: "screenshot".to_string(),
+            input: json!({}),
+            delay: Duration::ZERO,
+            screenshot: Some(png(4, 4, [255, 0, 0, 255])),
+            next_screenshot: None,
+            skip_reason: None,
+        };
+
+        let step = replay_step(&client, 0, call.clone(), &options).await;
+        assert_eq!(step.status, ReplayStepStatus::Succeeded);
+        assert_eq!(step.similarity, Some(1.0));
+
+        let call = RecordedCall {
+            screenshot: Some(png(4, 4, [0, 0, 255, 255])),
+            ..call
+        };
+        let step = replay_step(&client, 1, call, &options).await;
+        assert_eq!(step.status, ReplayStepStatus::Diverged);
+        assert_eq!(step.similarity, Some(0.0));
+    }
+
+    #[tokio::test]
+    async fn test_replay_step_with_daemon_down() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let client = ComputerUseClient::new(format!("http://{addr}"));
+        let call = RecordedCall {
+            tool_use_id: "call_1".to_string(),
+            name: "screenshot".to_string(),
+            input: json!({}),
+            delay: Duration::ZERO,
+            screenshot: None,
+            next_screenshot: None,
+            skip_reason: None,
+        };
+
+        let step = replay_step(&client, 0, call, &ReplayOptions::default()).await;
+        assert_eq!(step.status, ReplayStepStatus::Failed);
+        assert!(step.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_replay_step_compares_screen_after_action() {
+        let client = ComputerUseClient::new(spawn_stub_daemon(png(4, 4, [255, 0, 0, 255])).await);
+        let options = ReplayOptions {
+            compare_screenshots: true,
+            ..ReplayOptions::default()
+        };
+        let call = RecordedCall {
+            tool_use_id: "call_1".to_string(),
+            name: "click_mouse".to_string(),
+            input: json!({ "button": "left", "clickCount": 1 }),
+            delay: Duration::ZERO,
+            screenshot: None,
+            next_screenshot: Some(png(4, 4, [255, 0, 0, 255])),
+            skip_reason: None,
+        };
+
+        let step = replay_step(&client, 0, call.clone(), &options).await;
+        assert_eq!(step.status, ReplayStepStatus::Succeeded);
+        assert_eq!(step.similarity, Some(1.0));
+
+        let diverging = RecordedCall {
+            next_screenshot: Some(png(4, 4, [0, 0, 255, 255])),
+            ..call.clone()
+        };
+        let step = replay_step(&client, 1, diverging.clone(), &options).await;
+        assert_eq!(step.status, ReplayStepStatus::Diverged);
+        assert_eq!(step.similarity, Some(0.0));
+
+        // Without comparison the screen is not checked
+        let step = replay_step(&client, 2, diverging, &ReplayOptions::default()).await;
+        assert_eq!(step.status, ReplayStepStatus::Succeeded);
+        assert!(step.similarity.is_none());
+
+        // Nothing recorded to compare with
+        let call = RecordedCall {
+            next_screenshot: None,
+            ..call
+        };
+        let step = replay_step(&client, 3, call, &options).await;
+        assert_eq!(step.status, ReplayStepStatus::Succeeded);
+        assert!(step.similarity.is_none());
+    }
+}
@@ -0,0 +1,89 @@
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+/// Check that a task's result schema is a valid JSON schema
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    JSONSchema::compile(schema)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid result schema: {e}"))
+}
+
+/// Validate a submitted result against the task's result schema, if it has one
+pub fn validate_result(schema: Option<&Value>, result: &Value) -> Result<(), String> {
+    let Some(schema) = schema else {
+        return Ok(());
+    };
+
+    let compiled =
+        JSONSchema::compile(schema).map_err(|e| format!("Invalid result schema: {e}"))?;
+    compiled.validate(result).map_err(|errors| {
+        let errors: Vec<String> = errors
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                if path.is_empty() {
+                    e.to_string()
+                } else {
+                    format!("{path}: {e}")
+                }
+            })
+            .collect();
+        format!(
+            "Result does not match the result schema: {}",
+            errors.join("; ")
+        )
+    })
+}
+
+/// Instructions added to the first message of a task that expects a result
+pub fn result_instructions(schema: &Value) -> String {
+    format!(
+        "When the task is done, call submit_result with a result matching this JSON schema before completing it:\n{}",
+        serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "price": { "type": "number" },
+                "currency": { "type": "string" }
+            },
+            "required": ["price", "currency"]
+        })
+    }
+
+    #[test]
+    fn test_check_schema() {
+        assert!(check_schema(&schema()).is_ok());
+        assert!(check_schema(&json!({"type": "not-a-type"})).is_err());
+    }
+
+    #[test]
+    fn test_validate_result() {
+        let schema = schema();
+        assert!(validate_result(Some(&schema), &json!({"price": 12.5, "currency": "EUR"})).is_ok());
+
+        let error = validate_result(Some(&schema), &json!({"price": "12.5"})).unwrap_err();
+        assert!(error.contains("/price"));
+        assert!(error.contains("currency"));
+    }
+
+    #[test]
+    fn test_validate_result_without_schema() {
+        assert!(validate_result(None, &json!("anything")).is_ok());
+    }
+
+    #[test]
+    fn test_result_instructions() {
+        let instructions = result_instructions(&schema());
+        assert!(instructions.contains("submit_result"));
+        assert!(instructions.contains("\"currency\""));
+    }
+}
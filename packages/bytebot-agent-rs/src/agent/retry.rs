@@ -0,0 +1,60 @@
+use bytebot_shared_rs::types::retry::FailureKind;
+
+use crate::error::{AIError, AutomationError, ServiceError};
+
+/// Classify the error that ended a task attempt for its retry policy
+pub fn failure_kind(error: &ServiceError) -> FailureKind {
+    match error {
+        ServiceError::AI(AIError::RateLimit) => FailureKind::RateLimit,
+        // Includes 529 Overloaded from Anthropic
+        ServiceError::AI(AIError::Api { status, .. }) if *status >= 500 => {
+            FailureKind::ProviderError
+        }
+        ServiceError::AI(AIError::Http(e)) if !e.is_decode() => FailureKind::ProviderUnavailable,
+        ServiceError::Automation(AutomationError::Unreachable(_)) => {
+            FailureKind::DesktopUnavailable
+        }
+        _ => FailureKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_kind() {
+        assert_eq!(
+            failure_kind(&ServiceError::AI(AIError::RateLimit)),
+            FailureKind::RateLimit
+        );
+        assert_eq!(
+            failure_kind(&ServiceError::AI(AIError::Api {
+                status: 529,
+                message: "Overloaded".to_string(),
+            })),
+            FailureKind::ProviderError
+        );
+        assert_eq!(
+            failure_kind(&ServiceError::Automation(AutomationError::Unreachable(
+                "connection refused".to_string()
+            ))),
+            FailureKind::DesktopUnavailable
+        );
+    }
+
+    #[test]
+    fn test_permanent_failures() {
+        assert_eq!(
+            failure_kind(&ServiceError::AI(AIError::Api {
+                status: 400,
+                message: "Bad request".to_string(),
+            })),
+            FailureKind::Other
+        );
+        assert_eq!(
+            failure_kind(&ServiceError::Internal("Too many iterations".to_string())),
+            FailureKind::Other
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use std::{sync::Arc, time::Duration};
+
+use bytebot_shared_rs::types::task::Task;
+use chrono::{DateTime, Utc};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use super::recurrence::RecurrenceRule;
+use crate::{
+    config::Config,
+    database::{DatabaseManager, TaskRepositoryTrait},
+    error::{ServiceError, ServiceResult},
+    websocket::WebSocketGateway,
+};
+
+/// Background scheduler that queues SCHEDULED tasks once their `scheduledFor` time arrives.
+///
+/// Recurring definitions are never queued themselves; each firing spawns a new
+/// run linked to the definition instead.
+pub struct TaskScheduler {
+    db: Arc<DatabaseManager>,
+    websocket_gateway: Arc<WebSocketGateway>,
+    max_sleep: Duration,
+}
+
+impl TaskScheduler {
+    /// Create a new task scheduler
+    pub fn new(
+        config: &Config,
+        db: Arc<DatabaseManager>,
+        websocket_gateway: Arc<WebSocketGateway>,
+    ) -> Self {
+        Self {
+            db,
+            websocket_gateway,
+            max_sleep: Duration::from_millis(config.scheduler_poll_interval_ms),
+        }
+    }
+
+    /// Queue due tasks until `shutdown` is cancelled.
+    ///
+    /// Tasks whose time passed while the service was down are queued straight
+    /// away on startup.
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) {
+        info!(
+            max_sleep_ms = self.max_sleep.as_millis() as u64,
+            "Task scheduler started"
+        );
+
+        match self.enqueue_due().await {
+            Ok(0) => {}
+            Ok(count) => info!("Recovered {} overdue scheduled tasks", count),
+            Err(e) => error!("Failed to recover overdue scheduled tasks: {}", e),
+        }
+
+        while !shutdown.is_cancelled() {
+            let next_run = match self.db.task_repository().next_scheduled_time().await {
+                Ok(next_run) => next_run,
+                Err(e) => {
+                    error!("Failed to look up next scheduled task: {}", e);
+                    None
+                }
+            };
+
+            let sleep_for = Self::sleep_duration(next_run, Utc::now(), self.max_sleep);
+            debug!("Scheduler sleeping for {:?}", sleep_for);
+
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(sleep_for) => {}
+            }
+
+            if let Err(e) = self.enqueue_due().await {
+                error!("Task scheduler error: {}", e);
+            }
+        }
+
+        info!("Task scheduler stopped");
+    }
+
+    /// Queue every scheduled task that is due, returning how many were queued
+    pub async fn enqueue_due(&self) -> ServiceResult<usize> {
+        let task_repo = self.db.task_repository();
+        let due = task_repo
+            .get_scheduled_tasks(Utc::now())
+            .await
+            .map_err(ServiceError::Database)?;
+
+        let mut queued = 0;
+        for task in due {
+            if task.is_recurring() {
+                if self.spawn_run(&task).await {
+                    queued += 1;
+                }
+                continue;
+            }
+
+            match task_repo.mark_queued(&task.id).await {
+                Ok(Some(task)) => {
+                    info!(
+                        "Scheduled task {} is due (scheduled for {:?}), queued for execution",
+                        task.id, task.scheduled_for
+                    );
+                    self.websocket_gateway
+                        .emit_task_update(&task.id, &task)
+                        .await;
+                    queued += 1;
+                }
+                // Another scheduler instance queued it first
+                Ok(None) => {}
+                Err(e) => error!("Failed to queue scheduled task {}: {}", task.id, e),
+            }
+        }
+
+        Ok(queued)
+    }
+
+    /// Spawn the due run of a recurring definition and advance it to its next firing.
+    ///
+    /// Firings missed while the service was down collapse into a single run.
+    async fn spawn_run(&self, definition: &Task) -> bool {
+        let Some(fire_time) = definition.scheduled_for else {
+            return false;
+        };
+
+        let rule = match RecurrenceRule::from_task(definition) {
+            Some(Ok(rule)) => rule,
+            Some(Err(e)) => {
+                error!(
+                    "Recurring task {} has an invalid rule: {}",
+                    definition.id, e
+                );
+                return false;
+            }
+            None => return false,
+        };
+
+        let next_run = rule.next_after(fire_time.max(Utc::now()));
+        if next_run.is_none() {
+            warn!(
+                "Recurring task {} has no further runs and will be paused",
+                definition.id
+            );
+        }
+
+        match self
+            .db
+            .task_repository()
+            .spawn_recurring_run(definition, fire_time, next_run)
+            .await
+        {
+            Ok(Some(run)) => {
+                info!(
+                    "Recurring task {} fired for {}, spawned run {} (next run {:?})",
+                    definition.id, fire_time, run.id, next_run
+                );
+                self.websocket_gateway.emit_task_created(&run).await;
+                true
+            }
+            // Another scheduler instance spawned this firing first
+            Ok(None) => false,
+            Err(e) => {
+                error!(
+                    "Failed to spawn run of recurring task {}: {}",
+                    definition.id, e
+                );
+                false
+            }
+        }
+    }
+
+    /// How long to sleep before the next check, capped so newly created tasks are noticed
+    pub fn sleep_duration(
+        next_run: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        max_sleep: Duration,
+    ) -> Duration {
+        match next_run {
+            Some(next_run) => (next_run - now)
+                .to_std()
+                .unwrap_or(Duration::ZERO)
+                .min(max_sleep),
+            None => max_sleep,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn test_sleep_duration_without_scheduled_tasks() {
+        assert_eq!(
+            TaskScheduler::sleep_duration(None, Utc::now(), MAX_SLEEP),
+            MAX_SLEEP
+        );
+    }
+
+    #[test]
+    fn test_sleep_duration_until_next_run() {
+        let now = Utc::now();
+        let next_run = now + chrono::Duration::seconds(5);
+        assert_eq!(
+            TaskScheduler::sleep_duration(Some(next_run), now, MAX_SLEEP),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_sleep_duration_is_capped() {
+        let now = Utc::now();
+        let next_run = now + chrono::Duration::hours(8);
+        assert_eq!(
+            TaskScheduler::sleep_duration(Some(next_run), now, MAX_SLEEP),
+            MAX_SLEEP
+        );
+    }
+
+    #[test]
+    fn test_sleep_duration_for_overdue_task() {
+        let now = Utc::now();
+        let next_run = now - chrono::Duration::minutes(10);
+        assert_eq!(
+            TaskScheduler::sleep_duration(Some(next_run), now, MAX_SLEEP),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use bytebot_shared_rs::types::{
+    computer_action::ComputerAction,
+    message::{Message, MessageContentBlock},
+    task::Role,
+};
+use serde_json::Value;
+
+use super::computer_use::ComputerUseClient;
+use crate::error::AutomationError;
+
+/// Content of the message that records a desktop action performed by the user.
+///
+/// The action is stored as a `ToolUse` followed by its `ToolResult`, which is
+/// how take-over actions are told apart from the model's own tool calls.
+pub fn user_action_content(
+    tool_use_id: &str,
+    action: &ComputerAction,
+    outcome: Result<Value, AutomationError>,
+) -> Vec<MessageContentBlock> {
+    let result = match outcome {
+        Ok(result) => MessageContentBlock::tool_result(
+            tool_use_id,
+            ComputerUseClient::result_content(action, &result),
+        ),
+        Err(e) => MessageContentBlock::tool_error(tool_use_id, e.to_string()),
+    };
+
+    vec![
+        MessageContentBlock::tool_use(action.name(), tool_use_id, tool_input(action)),
+        result,
+    ]
+}
+
+/// Tool input for an action: its payload without the `action` tag
+fn tool_input(action: &ComputerAction) -> Value {
+    let mut input = serde_json::to_value(action).unwrap_or_default();
+    if let Value::Object(map) = &mut input {
+        map.remove("action");
+    }
+    input
+}
+
+/// Whether a message records actions the user performed during a take-over
+pub fn is_user_action(message: &Message, blocks: &[MessageContentBlock]) -> bool {
+    message.role == Role::User
+        && blocks
+            .iter()
+            .any(|block| matches!(block, MessageContentBlock::ToolUse { .. }))
+        && blocks.iter().all(|block| {
+            matches!(
+                block,
+                MessageContentBlock::ToolUse { .. } | MessageContentBlock::ToolResult { .. }
+            )
+        })
+}
+
+/// Rewrite take-over actions as plain user content before prompting the model.
+///
+/// Providers only accept tool calls from the assistant, so the user's actions
+/// are described in text, followed by their results and screenshots.
+pub fn describe_user_actions(messages: Vec<Message>) -> Vec<Message> {
+    messages
+        .into_iter()
+        .map(|mut message| {
+            let Ok(blocks) = message.get_content_blocks() else {
+                return message;
+            };
+            if !is_user_action(&message, &blocks) {
+                return message;
+            }
+
+            let mut content = Vec::new();
+            for block in blocks {
+                match block {
+                    MessageContentBlock::ToolUse { name, input, .. } => {
+                        content.push(MessageContentBlock::text(format!(
+                            "While in control of the desktop, the user performed the {name} action with {input}"
+                        )));
+                    }
+                    MessageContentBlock::ToolResult {
+                        content: output,
+                        is_error: Some(true),
+                        ..
+                    } => {
+                        let reason = output
+                            .iter()
+                            .filter_map(MessageContentBlock::as_text)
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        content.push(MessageContentBlock::text(format!(
+                            "The action failed: {reason}"
+                        )));
+                    }
+                    MessageContentBlock::ToolResult { content: output, .. } => {
+                        content.extend(output);
+                    }
+                    _ => {}
+                }
+            }
+
+            // Content that cannot be serialised is left as it was
+            let _ = message.set_content_blocks(content);
+            message
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(role: Role, content: Vec<MessageContentBlock>) -> Message {
+        Message::new(content, role, "task-1".to_string())
+    }
+
+    #[test]
+    fn test_user_action_content() {
+        let action = ComputerAction::TypeText {
+            text: "hello".to_string(),
+            delay: None,
+            sensitive: None,
+        };
+
+        let content = user_action_content("action-1", &action, Ok(json!({})));
+        assert_eq!(content.len(), 2);
+        match &content[0] {
+            MessageContentBlock::ToolUse { id, name, input } => {
+                assert_eq!(id, "action-1");
+                assert_eq!(name, "type_text");
+                assert_eq!(input["text"], "hello");
+                assert!(input.get("action").is_none());
+            }
+            other => panic!("Expected a tool use, got {other:?}"),
+        }
+        assert!(matches!(
+            &content[1],
+            MessageContentBlock::ToolResult { tool_use_id, is_error: None, .. } if tool_use_id == "action-1"
+        ));
+
+        let failed = user_action_content(
+            "action-2",
+            &action,
+            Err(AutomationError::ActionFailed(
+                "Desktop unreachable".to_string(),
+            )),
+        );
+        assert!(matches!(
+            &failed[1],
+            MessageContentBlock::ToolResult {
+                is_error: Some(true),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_is_user_action() {
+        let action = user_action_content("action-1", &ComputerAction::Screenshot, Ok(json!({})));
+        let recorded = message(Role::User, action.clone());
+        assert!(is_user_action(&recorded, &action));
+
+        // Results of the model's own tool calls hold no tool use
+        let results = vec![action[1].clone()];
+        assert!(!is_user_action(
+            &message(Role::User, results.clone()),
+            &results
+        ));
+
+        let assistant = message(Role::Assistant, action.clone());
+        assert!(!is_user_action(&assistant, &action));
+    }
+
+    #[test]
+    fn test_describe_user_actions() {
+        let action = ComputerAction::Screenshot;
+        let messages = vec![
+            message(Role::User, vec![MessageContentBlock::text("Open Firefox")]),
+            message(
+                Role::User,
+                user_action_content("action-1", &action, Ok(json!({"screenshot": "aGVsbG8="}))),
+            ),
+            message(
+                Role::User,
+                user_action_content(
+                    "action-2",
+                    &action,
+                    Err(AutomationError::ActionFailed("Timed out".to_string())),
+                ),
+            ),
+        ];
+
+        let described = describe_user_actions(messages);
+        assert_eq!(described[0].extract_text(), "Open Firefox");
+
+        let blocks = described[1].get_content_blocks().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0]
+            .as_text()
+            .unwrap()
+            .contains("the user performed the screenshot action"));
+        assert!(matches!(blocks[1], MessageContentBlock::Image { .. }));
+
+        let blocks = described[2].get_content_blocks().unwrap();
+        assert_eq!(
+            blocks[1].as_text(),
+            Some("The action failed: Computer action failed: Timed out")
+        );
+    }
+}
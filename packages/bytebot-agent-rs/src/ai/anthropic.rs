@@ -4,34 +4,35 @@ use async_trait::async_trait;
 use bytebot_shared_rs::types::{
     message::{Message, MessageContentBlock},
     task::Role,
+    task_tools::agent_tool_definitions,
 };
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
-use super::{AIService, ModelInfo};
+use super::{
+    stream::{read_sse, StreamAssembler},
+    with_cancellation, AIResponse, AIService, DeltaSender, ModelInfo, ModelRegistry, TokenUsage,
+};
 use crate::{config::Config, error::AIError};
 
 /// Anthropic API constants
 const ANTHROPIC_API_BASE: &str = "https://api.anthropic.com/v1";
-const DEFAULT_MODEL: &str = "claude-opus-4-20250514";
 const MAX_TOKENS: u32 = 8192;
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
 
-/// Get available Anthropic models
-fn get_anthropic_models() -> Vec<ModelInfo> {
-    vec![
-        ModelInfo {
-            provider: "anthropic".to_string(),
-            name: "claude-opus-4-20250514".to_string(),
-            title: "Claude Opus 4".to_string(),
-        },
-        ModelInfo {
-            provider: "anthropic".to_string(),
-            name: "claude-sonnet-4-20250514".to_string(),
-            title: "Claude Sonnet 4".to_string(),
-        },
-    ]
+/// Agent tools in Anthropic's tool format
+fn get_anthropic_tools() -> Vec<serde_json::Value> {
+    agent_tool_definitions()
+        .into_iter()
+        .map(|tool| {
+            serde_json::json!({
+                "name": tool.name,
+                "description": tool.description,
+                "input_schema": tool.input_schema,
+            })
+        })
+        .collect()
 }
 
 /// Anthropic API request structures
@@ -44,6 +45,8 @@ struct AnthropicRequest {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<serde_json::Value>,
     thinking: ThinkingConfig,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -93,6 +96,7 @@ enum AnthropicContentBlock {
     },
     Thinking {
         thinking: String,
+        #[serde(default)]
         signature: String,
     },
     RedactedThinking {
@@ -104,6 +108,78 @@ enum AnthropicContentBlock {
 struct Usage {
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u32>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u32>,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens.into(),
+            output_tokens: usage.output_tokens.into(),
+            cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0).into(),
+            cache_write_tokens: usage.cache_creation_input_tokens.unwrap_or(0).into(),
+        }
+    }
+}
+
+/// Anthropic streaming events
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicStreamEvent {
+    MessageStart {
+        message: AnthropicStreamMessage,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: AnthropicContentBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: AnthropicDelta,
+    },
+    MessageDelta {
+        #[serde(default)]
+        usage: Option<DeltaUsage>,
+    },
+    Error {
+        error: AnthropicError,
+    },
+    /// `ping`, `content_block_stop` and `message_stop`
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicStreamMessage {
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    SignatureDelta {
+        signature: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeltaUsage {
+    output_tokens: u32,
 }
 
 /// Anthropic API error response
@@ -123,6 +199,8 @@ struct AnthropicErrorResponse {
 pub struct AnthropicService {
     client: Client,
     api_key: Option<String>,
+    /// Models of the registry served by this provider
+    models: ModelRegistry,
 }
 
 impl AnthropicService {
@@ -136,9 +214,23 @@ impl AnthropicService {
         Self {
             client,
             api_key: config.anthropic_api_key.clone(),
+            models: config.model_registry.for_provider("anthropic"),
         }
     }
 
+    /// Id of the requested model, or of the default model if none is named
+    fn resolve_model(&self, model: Option<String>) -> Result<String, AIError> {
+        self.models
+            .select(model.as_deref())
+            .map(|info| info.name.clone())
+            .ok_or_else(|| {
+                AIError::InvalidModel(format!(
+                    "Invalid Anthropic model: {}",
+                    model.unwrap_or_default()
+                ))
+            })
+    }
+
     /// Convert internal messages to Anthropic format
     fn format_messages_for_anthropic(
         &self,
@@ -220,6 +312,39 @@ impl AnthropicService {
 
                         result
                     }
+                    MessageContentBlock::Document { source, name, .. } => {
+                        let title = name.unwrap_or_else(|| "unnamed".to_string());
+                        if source.media_type == "application/pdf" {
+                            serde_json::json!({
+                                "type": "document",
+                                "title": title,
+                                "source": {
+                                    "type": source.source_type,
+                                    "media_type": source.media_type,
+                                    "data": source.data
+                                }
+                            })
+                        } else if let Some(text) = source.decode_text() {
+                            serde_json::json!({
+                                "type": "document",
+                                "title": title,
+                                "source": {
+                                    "type": "text",
+                                    "media_type": "text/plain",
+                                    "data": text
+                                }
+                            })
+                        } else {
+                            // Anthropic only reads PDFs and plain text documents
+                            serde_json::json!({
+                                "type": "text",
+                                "text": format!(
+                                    "Attached file {title} ({}) cannot be displayed",
+                                    source.media_type
+                                )
+                            })
+                        }
+                    }
                     _ => {
                         // Handle other content types as text for now
                         serde_json::json!({
@@ -321,36 +446,20 @@ impl AnthropicService {
             }
         }
     }
-}
 
-#[async_trait]
-impl AIService for AnthropicService {
-    async fn generate_response(
+    /// Validate the model and build the request body
+    fn build_request(
         &self,
         system_prompt: &str,
         messages: Vec<Message>,
         model: Option<String>,
         use_tools: bool,
-        _signal: Option<tokio_util::sync::CancellationToken>,
-    ) -> Result<Vec<MessageContentBlock>, AIError> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
-            status: 401,
-            message: "Anthropic API key not configured".to_string(),
-        })?;
-
-        let model = model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
-
-        // Validate model
-        let models = get_anthropic_models();
-        if !models.iter().any(|m| m.name == model) {
-            return Err(AIError::InvalidModel(format!(
-                "Invalid Anthropic model: {model}"
-            )));
-        }
+    ) -> Result<AnthropicRequest, AIError> {
+        let model = self.resolve_model(model)?;
 
         let anthropic_messages = self.format_messages_for_anthropic(messages)?;
 
-        let request = AnthropicRequest {
+        Ok(AnthropicRequest {
             model,
             max_tokens: MAX_TOKENS * 2, // Match TypeScript implementation
             system: vec![SystemMessage {
@@ -362,30 +471,121 @@ impl AIService for AnthropicService {
             }],
             messages: anthropic_messages,
             tools: if use_tools {
-                // TODO: Implement tools from agent.tools - for now return empty array
-                // This will be implemented in a future task
-                vec![]
+                get_anthropic_tools()
             } else {
                 vec![]
             },
             thinking: ThinkingConfig {
                 thinking_type: "disabled".to_string(),
             },
-        };
+            stream: false,
+        })
+    }
 
-        let response = self
-            .client
+    async fn send_request(
+        &self,
+        request: &AnthropicRequest,
+        api_key: &str,
+    ) -> Result<reqwest::Response, AIError> {
+        self.client
             .post(format!("{ANTHROPIC_API_BASE}/messages"))
             .header("Content-Type", "application/json")
             .header("x-api-key", api_key)
             .header("anthropic-version", "2023-06-01")
-            .json(&request)
+            .json(request)
             .send()
             .await
-            .map_err(AIError::Http)?;
+            .map_err(AIError::Http)
+    }
+
+    /// Apply one event of a streamed response
+    fn apply_stream_event(
+        assembler: &mut StreamAssembler,
+        usage: &mut TokenUsage,
+        data: &str,
+    ) -> Result<(), AIError> {
+        match serde_json::from_str(data).map_err(AIError::Serialization)? {
+            AnthropicStreamEvent::MessageStart { message } => {
+                if let Some(start) = message.usage {
+                    *usage = start.into();
+                }
+            }
+            AnthropicStreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => match content_block {
+                AnthropicContentBlock::Text { text } => assembler.text(index, &text),
+                AnthropicContentBlock::ToolUse { id, name, .. } => {
+                    // The input follows as JSON fragments
+                    assembler.tool_use_start(index, id, name)
+                }
+                AnthropicContentBlock::Thinking {
+                    thinking,
+                    signature,
+                } => {
+                    assembler.thinking(index, &thinking);
+                    assembler.signature(index, &signature);
+                }
+                AnthropicContentBlock::RedactedThinking { data } => {
+                    assembler.redacted_thinking(index, data)
+                }
+            },
+            AnthropicStreamEvent::ContentBlockDelta { index, delta } => match delta {
+                AnthropicDelta::TextDelta { text } => assembler.text(index, &text),
+                AnthropicDelta::InputJsonDelta { partial_json } => {
+                    assembler.tool_use_input(index, &partial_json)
+                }
+                AnthropicDelta::ThinkingDelta { thinking } => assembler.thinking(index, &thinking),
+                AnthropicDelta::SignatureDelta { signature } => {
+                    assembler.signature(index, &signature)
+                }
+                AnthropicDelta::Other => {}
+            },
+            AnthropicStreamEvent::MessageDelta { usage: Some(delta) } => {
+                usage.output_tokens = delta.output_tokens.into();
+            }
+            AnthropicStreamEvent::Error { error } => {
+                error!("Anthropic stream error: {}", error.message);
+                return Err(match error.error_type.as_str() {
+                    "rate_limit_error" => AIError::RateLimit,
+                    _ => AIError::Api {
+                        status: 500,
+                        message: error.message,
+                    },
+                });
+            }
+            AnthropicStreamEvent::MessageDelta { usage: None } | AnthropicStreamEvent::Other => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AIService for AnthropicService {
+    async fn generate_response(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+    ) -> Result<AIResponse, AIError> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
+            status: 401,
+            message: "Anthropic API key not configured".to_string(),
+        })?;
 
-        let status = response.status();
-        let body = response.text().await.map_err(AIError::Http)?;
+        let request = self.build_request(system_prompt, messages, model, use_tools)?;
+
+        let (status, body) = with_cancellation(signal.as_ref(), async {
+            let response = self.send_request(&request, api_key).await?;
+
+            let status = response.status();
+            let body = response.text().await.map_err(AIError::Http)?;
+            Ok((status, body))
+        })
+        .await?;
 
         if !status.is_success() {
             return Err(self.handle_api_error(status, &body));
@@ -394,11 +594,63 @@ impl AIService for AnthropicService {
         let anthropic_response: AnthropicResponse =
             serde_json::from_str(&body).map_err(AIError::Serialization)?;
 
-        Ok(self.format_anthropic_response(anthropic_response.content))
+        let usage = anthropic_response
+            .usage
+            .map(TokenUsage::from)
+            .unwrap_or_default();
+
+        Ok(AIResponse {
+            content: self.format_anthropic_response(anthropic_response.content),
+            usage,
+            model: request.model,
+        })
+    }
+
+    async fn generate_response_stream(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+        deltas: DeltaSender,
+    ) -> Result<AIResponse, AIError> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
+            status: 401,
+            message: "Anthropic API key not configured".to_string(),
+        })?;
+
+        let mut request = self.build_request(system_prompt, messages, model, use_tools)?;
+        request.stream = true;
+
+        let mut assembler = StreamAssembler::new(deltas);
+        let mut usage = TokenUsage::default();
+
+        with_cancellation(signal.as_ref(), async {
+            let response = self.send_request(&request, api_key).await?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.map_err(AIError::Http)?;
+                return Err(self.handle_api_error(status, &body));
+            }
+
+            read_sse(response, |data| {
+                Self::apply_stream_event(&mut assembler, &mut usage, data)
+            })
+            .await
+        })
+        .await?;
+
+        Ok(AIResponse {
+            content: assembler.finish(),
+            usage,
+            model: request.model,
+        })
     }
 
     fn list_models(&self) -> Vec<ModelInfo> {
-        get_anthropic_models()
+        self.models.models().to_vec()
     }
 
     fn is_available(&self) -> bool {
@@ -408,7 +660,7 @@ impl AIService for AnthropicService {
 
 #[cfg(test)]
 mod tests {
-    use bytebot_shared_rs::types::message::Message;
+    use bytebot_shared_rs::types::message::{DocumentSource, Message};
 
     use super::*;
 
@@ -452,6 +704,17 @@ mod tests {
         assert_eq!(models[1].name, "claude-sonnet-4-20250514");
     }
 
+    #[test]
+    fn test_anthropic_tools() {
+        let tools = get_anthropic_tools();
+
+        assert_eq!(tools.len(), agent_tool_definitions().len());
+        let click = tools.iter().find(|t| t["name"] == "click_mouse").unwrap();
+        assert_eq!(click["input_schema"]["type"], "object");
+        assert!(click["input_schema"]["properties"]["clickCount"].is_object());
+        assert!(click["description"].as_str().is_some_and(|d| !d.is_empty()));
+    }
+
     #[test]
     fn test_format_messages_for_anthropic() {
         let config = create_test_config();
@@ -471,6 +734,45 @@ mod tests {
         assert_eq!(result[1].role, "assistant");
     }
 
+    #[test]
+    fn test_format_messages_with_documents() {
+        let config = create_test_config();
+        let service = AnthropicService::new(&config);
+
+        let document = |media_type: &str, name: &str| MessageContentBlock::Document {
+            source: DocumentSource {
+                source_type: "base64".to_string(),
+                media_type: media_type.to_string(),
+                data: "SGVsbG8gV29ybGQ=".to_string(),
+            },
+            name: Some(name.to_string()),
+            size: Some(11),
+        };
+
+        let messages = vec![create_test_message(
+            vec![
+                document("application/pdf", "report.pdf"),
+                document("text/csv", "data.csv"),
+                document("application/zip", "archive.zip"),
+            ],
+            Role::User,
+        )];
+
+        let result = service.format_messages_for_anthropic(messages).unwrap();
+        let content = &result[0].content;
+
+        assert_eq!(content[0]["type"], "document");
+        assert_eq!(content[0]["source"]["media_type"], "application/pdf");
+        assert_eq!(content[0]["title"], "report.pdf");
+
+        assert_eq!(content[1]["type"], "document");
+        assert_eq!(content[1]["source"]["type"], "text");
+        assert_eq!(content[1]["source"]["data"], "Hello World");
+
+        assert_eq!(content[2]["type"], "text");
+        assert!(content[2]["text"].as_str().unwrap().contains("archive.zip"));
+    }
+
     #[test]
     fn test_format_messages_skips_user_tool_use() {
         let config = create_test_config();
@@ -527,6 +829,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_stream_events() {
+        let (deltas, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut assembler = StreamAssembler::new(deltas);
+        let mut usage = TokenUsage::default();
+
+        let events = [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":120,"output_tokens":1,"cache_read_input_tokens":2000,"cache_creation_input_tokens":300}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Taking a "}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"screenshot"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"screenshot","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":""}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":42}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+        for event in events {
+            AnthropicService::apply_stream_event(&mut assembler, &mut usage, event).unwrap();
+        }
+
+        assert_eq!(usage.input_tokens, 120);
+        assert_eq!(usage.output_tokens, 42);
+        assert_eq!(usage.cache_read_tokens, 2000);
+        assert_eq!(usage.cache_write_tokens, 300);
+
+        let content = assembler.finish();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0].as_text(), Some("Taking a screenshot"));
+        assert!(matches!(
+            &content[1],
+            MessageContentBlock::ToolUse { name, .. } if name == "screenshot"
+        ));
+
+        // Two text deltas and the start of the tool call
+        let mut count = 0;
+        while receiver.try_recv().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_apply_stream_error_event() {
+        let (deltas, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut assembler = StreamAssembler::new(deltas);
+
+        let result = AnthropicService::apply_stream_event(
+            &mut assembler,
+            &mut TokenUsage::default(),
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert!(matches!(result, Err(AIError::Api { message, .. }) if message == "Overloaded"));
+    }
+
     /// Integration tests that demonstrate the Anthropic service functionality
     /// These tests don't make actual API calls but verify the service setup
     mod integration_tests {
@@ -1,41 +1,59 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use async_trait::async_trait;
 use bytebot_shared_rs::types::{
     message::{DocumentSource, Message, MessageContentBlock},
     task::Role,
+    task_tools::agent_tool_definitions,
 };
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
-use super::{AIService, ModelInfo};
+use super::{
+    stream::{read_sse, StreamAssembler},
+    with_cancellation, AIResponse, AIService, DeltaSender, ModelInfo, ModelRegistry, TokenUsage,
+};
 use crate::{config::Config, error::AIError};
 
 /// Google Gemini API constants
 const GOOGLE_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
-const DEFAULT_MODEL: &str = "gemini-1.5-pro";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
 
-/// Get available Google Gemini models
-fn get_google_models() -> Vec<ModelInfo> {
-    vec![
-        ModelInfo {
-            provider: "google".to_string(),
-            name: "gemini-1.5-pro".to_string(),
-            title: "Gemini 1.5 Pro".to_string(),
-        },
-        ModelInfo {
-            provider: "google".to_string(),
-            name: "gemini-1.5-flash".to_string(),
-            title: "Gemini 1.5 Flash".to_string(),
-        },
-        ModelInfo {
-            provider: "google".to_string(),
-            name: "gemini-2.0-flash-exp".to_string(),
-            title: "Gemini 2.0 Flash (Experimental)".to_string(),
-        },
-    ]
+/// Agent tools as a single Gemini tool with one function declaration per tool
+fn get_google_tools() -> Vec<serde_json::Value> {
+    let declarations: Vec<serde_json::Value> = agent_tool_definitions()
+        .into_iter()
+        .map(|tool| {
+            let mut declaration = serde_json::json!({
+                "name": tool.name,
+                "description": tool.description,
+            });
+            // Gemini rejects object schemas without properties
+            let has_parameters = tool.input_schema["properties"]
+                .as_object()
+                .is_some_and(|properties| !properties.is_empty());
+            if has_parameters {
+                declaration["parameters"] = to_google_schema(tool.input_schema);
+            }
+            declaration
+        })
+        .collect();
+
+    vec![serde_json::json!({ "function_declarations": declarations })]
+}
+
+/// Strip JSON schema keywords that Gemini's OpenAPI subset does not accept
+fn to_google_schema(schema: serde_json::Value) -> serde_json::Value {
+    match schema {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter(|(key, _)| key != "minimum" && key != "additionalProperties")
+            .map(|(key, value)| (key, to_google_schema(value)))
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(to_google_schema).collect(),
+        other => other,
+    }
 }
 
 /// Google Gemini API request structures
@@ -63,14 +81,26 @@ struct GoogleContent {
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
 enum GooglePart {
-    Text { text: String },
-    InlineData { inline_data: InlineData },
-    FunctionCall { function_call: FunctionCall },
-    FunctionResponse { function_response: FunctionResponse },
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: InlineData,
+    },
+    FunctionCall {
+        #[serde(rename = "functionCall")]
+        function_call: FunctionCall,
+    },
+    FunctionResponse {
+        #[serde(rename = "functionResponse")]
+        function_response: FunctionResponse,
+    },
 }
 
 #[derive(Debug, Serialize)]
 struct InlineData {
+    #[serde(rename = "mimeType")]
     mime_type: String,
     data: String,
 }
@@ -96,8 +126,9 @@ struct GenerationConfig {
 /// Google Gemini API response structures
 #[derive(Debug, Deserialize)]
 struct GoogleResponse {
-    candidates: Vec<Candidate>,
     #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(rename = "usageMetadata", default)]
     usage_metadata: Option<UsageMetadata>,
 }
 
@@ -116,8 +147,13 @@ struct GoogleResponseContent {
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum GoogleResponsePart {
-    Text { text: String },
-    FunctionCall { function_call: ResponseFunctionCall },
+    Text {
+        text: String,
+    },
+    FunctionCall {
+        #[serde(rename = "functionCall", alias = "function_call")]
+        function_call: ResponseFunctionCall,
+    },
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,11 +162,37 @@ struct ResponseFunctionCall {
     args: serde_json::Value,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 struct UsageMetadata {
     prompt_token_count: u32,
     candidates_token_count: u32,
     total_token_count: u32,
+    cached_content_token_count: u32,
+}
+
+impl From<UsageMetadata> for TokenUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        // Cached tokens are part of the prompt tokens
+        let cached = usage
+            .cached_content_token_count
+            .min(usage.prompt_token_count);
+
+        Self {
+            input_tokens: (usage.prompt_token_count - cached).into(),
+            output_tokens: usage.candidates_token_count.into(),
+            cache_read_tokens: cached.into(),
+            cache_write_tokens: 0,
+        }
+    }
+}
+
+/// Blocks of a streamed Gemini response built so far. Text arrives in
+/// pieces that continue the current text block, function calls arrive whole.
+#[derive(Debug, Default)]
+struct GoogleStreamState {
+    next_index: usize,
+    text_index: Option<usize>,
 }
 
 /// Google API error response
@@ -150,6 +212,8 @@ struct GoogleErrorResponse {
 pub struct GoogleService {
     client: Client,
     api_key: Option<String>,
+    /// Models of the registry served by this provider
+    models: ModelRegistry,
 }
 
 impl GoogleService {
@@ -163,9 +227,23 @@ impl GoogleService {
         Self {
             client,
             api_key: config.google_api_key.clone(),
+            models: config.model_registry.for_provider("google"),
         }
     }
 
+    /// Id of the requested model, or of the default model if none is named
+    fn resolve_model(&self, model: Option<String>) -> Result<String, AIError> {
+        self.models
+            .select(model.as_deref())
+            .map(|info| info.name.clone())
+            .ok_or_else(|| {
+                AIError::InvalidModel(format!(
+                    "Invalid Google model: {}",
+                    model.unwrap_or_default()
+                ))
+            })
+    }
+
     /// Convert internal messages to Google Gemini format
     fn format_messages_for_google(
         &self,
@@ -185,12 +263,18 @@ impl GoogleService {
             None
         };
 
+        // Function names of the tool calls so far, by call id
+        let mut call_names = HashMap::new();
+
         for message in messages {
             let content_blocks = message
                 .get_content_blocks()
                 .map_err(AIError::Serialization)?;
 
             let mut parts: Vec<GooglePart> = Vec::new();
+            // Function responses carry only JSON, so images a tool returned
+            // follow them as parts of their own
+            let mut images = Vec::new();
 
             for block in content_blocks {
                 match block {
@@ -205,25 +289,41 @@ impl GoogleService {
                             },
                         });
                     }
-                    MessageContentBlock::ToolUse { name, input, .. } => {
+                    MessageContentBlock::ToolUse { id, name, input } => {
+                        call_names.insert(id, name.clone());
                         parts.push(GooglePart::FunctionCall {
                             function_call: FunctionCall { name, args: input },
                         });
                     }
                     MessageContentBlock::ToolResult {
-                        tool_use_id: _,
+                        tool_use_id,
                         content,
                         is_error,
                     } => {
-                        // Convert tool result content to response format
-                        let result_text = content
-                            .iter()
-                            .filter_map(|block| match block {
-                                MessageContentBlock::Text { text } => Some(text.clone()),
-                                _ => None,
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" ");
+                        let mut result_text = Vec::new();
+                        for block in content {
+                            match block {
+                                MessageContentBlock::Text { text } => result_text.push(text),
+                                MessageContentBlock::Image { source } => {
+                                    images.push(GooglePart::InlineData {
+                                        inline_data: InlineData {
+                                            mime_type: source.media_type,
+                                            data: source.data,
+                                        },
+                                    })
+                                }
+                                _ => {}
+                            }
+                        }
+                        let result_text = result_text.join("\n");
+
+                        // Gemini matches responses to calls by function name
+                        let Some(name) = call_names.get(&tool_use_id) else {
+                            parts.push(GooglePart::Text {
+                                text: format!("Tool result for {tool_use_id}: {result_text}"),
+                            });
+                            continue;
+                        };
 
                         let response_value = if is_error.unwrap_or(false) {
                             serde_json::json!({
@@ -235,23 +335,33 @@ impl GoogleService {
                             })
                         };
 
-                        // For tool results, we need to infer the function name
-                        // This is a limitation of the current message format
                         parts.push(GooglePart::FunctionResponse {
                             function_response: FunctionResponse {
-                                name: "unknown_function".to_string(),
+                                name: name.clone(),
                                 response: response_value,
                             },
                         });
                     }
                     MessageContentBlock::Document { source, name, .. } => {
-                        // Convert document to text representation for now
-                        let doc_text = format!(
-                            "Document: {} (type: {})",
-                            name.unwrap_or_else(|| "unnamed".to_string()),
-                            source.media_type
-                        );
-                        parts.push(GooglePart::Text { text: doc_text });
+                        // Gemini reads PDFs natively, other documents are passed as text
+                        if source.media_type == "application/pdf" {
+                            parts.push(GooglePart::InlineData {
+                                inline_data: InlineData {
+                                    mime_type: source.media_type,
+                                    data: source.data,
+                                },
+                            });
+                        } else {
+                            let mut doc_text = format!(
+                                "Document: {} (type: {})",
+                                name.unwrap_or_else(|| "unnamed".to_string()),
+                                source.media_type
+                            );
+                            if let Some(text) = source.decode_text() {
+                                doc_text.push_str(&format!("\n\n{text}"));
+                            }
+                            parts.push(GooglePart::Text { text: doc_text });
+                        }
                     }
                     MessageContentBlock::Thinking { thinking, .. } => {
                         // Include thinking content as text
@@ -264,6 +374,8 @@ impl GoogleService {
                 }
             }
 
+            parts.extend(images);
+
             // Only add content if it has parts
             if !parts.is_empty() {
                 google_contents.push(GoogleContent {
@@ -351,37 +463,18 @@ impl GoogleService {
             }
         }
     }
-}
 
-#[async_trait]
-impl AIService for GoogleService {
-    async fn generate_response(
+    /// Build the request body
+    fn build_request(
         &self,
         system_prompt: &str,
         messages: Vec<Message>,
-        model: Option<String>,
         use_tools: bool,
-        _signal: Option<tokio_util::sync::CancellationToken>,
-    ) -> Result<Vec<MessageContentBlock>, AIError> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
-            status: 401,
-            message: "Google API key not configured".to_string(),
-        })?;
-
-        let model = model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
-
-        // Validate model
-        let models = get_google_models();
-        if !models.iter().any(|m| m.name == model) {
-            return Err(AIError::InvalidModel(format!(
-                "Invalid Google model: {model}"
-            )));
-        }
-
+    ) -> Result<GoogleRequest, AIError> {
         let (system_instruction, contents) =
             self.format_messages_for_google(system_prompt, messages)?;
 
-        let request = GoogleRequest {
+        Ok(GoogleRequest {
             contents,
             system_instruction,
             generation_config: GenerationConfig {
@@ -389,27 +482,91 @@ impl AIService for GoogleService {
                 max_output_tokens: 8192,
             },
             tools: if use_tools {
-                // TODO: Implement tools from agent.tools - for now return empty array
-                // This will be implemented in a future task
-                vec![]
+                get_google_tools()
             } else {
                 vec![]
             },
+        })
+    }
+
+    /// Apply one chunk of a streamed response
+    fn apply_stream_chunk(
+        assembler: &mut StreamAssembler,
+        state: &mut GoogleStreamState,
+        usage: &mut TokenUsage,
+        data: &str,
+    ) -> Result<(), AIError> {
+        let chunk: GoogleResponse = serde_json::from_str(data).map_err(AIError::Serialization)?;
+
+        if let Some(chunk_usage) = chunk.usage_metadata {
+            // Counts are running totals, the last chunk has the final ones
+            *usage = chunk_usage.into();
+        }
+
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            return Ok(());
         };
 
-        let url = format!("{GOOGLE_API_BASE}/models/{model}:generateContent");
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .query(&[("key", api_key)])
-            .json(&request)
-            .send()
-            .await
-            .map_err(AIError::Http)?;
+        for part in candidate.content.parts {
+            match part {
+                GoogleResponsePart::Text { text } => {
+                    let index = *state.text_index.get_or_insert_with(|| {
+                        state.next_index += 1;
+                        state.next_index - 1
+                    });
+                    assembler.text(index, &text);
+                }
+                GoogleResponsePart::FunctionCall { function_call } => {
+                    let index = state.next_index;
+                    state.next_index += 1;
+                    state.text_index = None;
+
+                    let id = format!("call_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+                    assembler.tool_use_start(index, id, function_call.name);
+                    assembler.tool_use_input(index, &function_call.args.to_string());
+                }
+            }
+        }
 
-        let status = response.status();
-        let body = response.text().await.map_err(AIError::Http)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AIService for GoogleService {
+    async fn generate_response(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+    ) -> Result<AIResponse, AIError> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
+            status: 401,
+            message: "Google API key not configured".to_string(),
+        })?;
+
+        let model = self.resolve_model(model)?;
+        let request = self.build_request(system_prompt, messages, use_tools)?;
+
+        let url = format!("{GOOGLE_API_BASE}/models/{model}:generateContent");
+        let (status, body) = with_cancellation(signal.as_ref(), async {
+            let response = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .query(&[("key", api_key)])
+                .json(&request)
+                .send()
+                .await
+                .map_err(AIError::Http)?;
+
+            let status = response.status();
+            let body = response.text().await.map_err(AIError::Http)?;
+            Ok((status, body))
+        })
+        .await?;
 
         if !status.is_success() {
             return Err(self.handle_api_error(status, &body));
@@ -418,11 +575,73 @@ impl AIService for GoogleService {
         let google_response: GoogleResponse =
             serde_json::from_str(&body).map_err(AIError::Serialization)?;
 
-        Ok(self.format_google_response(google_response.candidates))
+        let usage = google_response
+            .usage_metadata
+            .map(TokenUsage::from)
+            .unwrap_or_default();
+
+        Ok(AIResponse {
+            content: self.format_google_response(google_response.candidates),
+            usage,
+            model,
+        })
+    }
+
+    async fn generate_response_stream(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+        deltas: DeltaSender,
+    ) -> Result<AIResponse, AIError> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
+            status: 401,
+            message: "Google API key not configured".to_string(),
+        })?;
+
+        let model = self.resolve_model(model)?;
+        let request = self.build_request(system_prompt, messages, use_tools)?;
+
+        let mut assembler = StreamAssembler::new(deltas);
+        let mut state = GoogleStreamState::default();
+        let mut usage = TokenUsage::default();
+
+        let url = format!("{GOOGLE_API_BASE}/models/{model}:streamGenerateContent");
+        with_cancellation(signal.as_ref(), async {
+            let response = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .query(&[("key", api_key.as_str()), ("alt", "sse")])
+                .json(&request)
+                .send()
+                .await
+                .map_err(AIError::Http)?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.map_err(AIError::Http)?;
+                return Err(self.handle_api_error(status, &body));
+            }
+
+            read_sse(response, |data| {
+                Self::apply_stream_chunk(&mut assembler, &mut state, &mut usage, data)
+            })
+            .await
+        })
+        .await?;
+
+        Ok(AIResponse {
+            content: assembler.finish(),
+            usage,
+            model,
+        })
     }
 
     fn list_models(&self) -> Vec<ModelInfo> {
-        get_google_models()
+        self.models.models().to_vec()
     }
 
     fn is_available(&self) -> bool {
@@ -477,6 +696,30 @@ mod tests {
         assert_eq!(models[2].name, "gemini-2.0-flash-exp");
     }
 
+    #[test]
+    fn test_google_tools() {
+        let tools = get_google_tools();
+        assert_eq!(tools.len(), 1);
+
+        let declarations = tools[0]["function_declarations"].as_array().unwrap();
+        assert_eq!(declarations.len(), agent_tool_definitions().len());
+
+        let screenshot = declarations
+            .iter()
+            .find(|d| d["name"] == "screenshot")
+            .unwrap();
+        assert!(screenshot.get("parameters").is_none());
+
+        let wait = declarations.iter().find(|d| d["name"] == "wait").unwrap();
+        assert_eq!(
+            wait["parameters"]["properties"]["duration"]["type"],
+            "integer"
+        );
+        assert!(wait["parameters"]["properties"]["duration"]
+            .get("minimum")
+            .is_none());
+    }
+
     #[test]
     fn test_format_messages_for_google() {
         let config = create_test_config();
@@ -570,52 +813,103 @@ mod tests {
         let config = create_test_config();
         let service = GoogleService::new(&config);
 
-        let messages = vec![create_test_message(
-            vec![MessageContentBlock::ToolResult {
-                tool_use_id: "123".to_string(),
-                content: vec![MessageContentBlock::text("Tool result")],
-                is_error: Some(false),
-            }],
-            Role::User,
-        )];
+        let messages = vec![
+            create_test_message(
+                vec![MessageContentBlock::tool_use(
+                    "computer_screenshot",
+                    "123",
+                    serde_json::json!({}),
+                )],
+                Role::Assistant,
+            ),
+            create_test_message(
+                vec![MessageContentBlock::ToolResult {
+                    tool_use_id: "123".to_string(),
+                    content: vec![
+                        MessageContentBlock::text("Screenshot taken"),
+                        MessageContentBlock::image("image/png", "base64data"),
+                    ],
+                    is_error: Some(false),
+                }],
+                Role::User,
+            ),
+        ];
+
+        let (_, contents) = service.format_messages_for_google("", messages).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&contents[1]).unwrap(),
+            serde_json::json!({
+                "role": "user",
+                "parts": [
+                    {
+                        "functionResponse": {
+                            "name": "computer_screenshot",
+                            "response": {"result": "Screenshot taken"}
+                        }
+                    },
+                    {"inlineData": {"mimeType": "image/png", "data": "base64data"}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_format_messages_with_tool_error() {
+        let config = create_test_config();
+        let service = GoogleService::new(&config);
+
+        let messages = vec![
+            create_test_message(
+                vec![MessageContentBlock::tool_use(
+                    "test_tool",
+                    "123",
+                    serde_json::json!({}),
+                )],
+                Role::Assistant,
+            ),
+            create_test_message(
+                vec![MessageContentBlock::ToolResult {
+                    tool_use_id: "123".to_string(),
+                    content: vec![MessageContentBlock::text("Error occurred")],
+                    is_error: Some(true),
+                }],
+                Role::User,
+            ),
+        ];
 
         let result = service.format_messages_for_google("", messages).unwrap();
         let (_, contents) = result;
 
-        assert_eq!(contents.len(), 1);
-        assert_eq!(contents[0].parts.len(), 1);
-        match &contents[0].parts[0] {
+        assert_eq!(contents.len(), 2);
+        match &contents[1].parts[0] {
             GooglePart::FunctionResponse { function_response } => {
-                assert_eq!(function_response.name, "unknown_function");
-                assert_eq!(function_response.response["result"], "Tool result");
+                assert_eq!(function_response.name, "test_tool");
+                assert_eq!(function_response.response["error"], "Error occurred");
             }
             _ => panic!("Expected function response part"),
         }
     }
 
     #[test]
-    fn test_format_messages_with_tool_error() {
+    fn test_format_messages_with_unmatched_tool_result() {
         let config = create_test_config();
         let service = GoogleService::new(&config);
 
         let messages = vec![create_test_message(
             vec![MessageContentBlock::ToolResult {
                 tool_use_id: "123".to_string(),
-                content: vec![MessageContentBlock::text("Error occurred")],
-                is_error: Some(true),
+                content: vec![MessageContentBlock::text("Tool result")],
+                is_error: Some(false),
             }],
             Role::User,
         )];
 
-        let result = service.format_messages_for_google("", messages).unwrap();
-        let (_, contents) = result;
+        let (_, contents) = service.format_messages_for_google("", messages).unwrap();
 
-        assert_eq!(contents.len(), 1);
         match &contents[0].parts[0] {
-            GooglePart::FunctionResponse { function_response } => {
-                assert_eq!(function_response.response["error"], "Error occurred");
-            }
-            _ => panic!("Expected function response part"),
+            GooglePart::Text { text } => assert_eq!(text, "Tool result for 123: Tool result"),
+            _ => panic!("Expected text part"),
         }
     }
 
@@ -650,6 +944,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_format_messages_with_pdf_and_text_documents() {
+        let config = create_test_config();
+        let service = GoogleService::new(&config);
+
+        let document = |media_type: &str, name: &str| MessageContentBlock::Document {
+            source: DocumentSource {
+                media_type: media_type.to_string(),
+                source_type: "base64".to_string(),
+                data: "SGVsbG8gV29ybGQ=".to_string(),
+            },
+            name: Some(name.to_string()),
+            size: Some(11),
+        };
+
+        let messages = vec![create_test_message(
+            vec![
+                document("application/pdf", "report.pdf"),
+                document("text/plain", "notes.txt"),
+            ],
+            Role::User,
+        )];
+
+        let (_, contents) = service.format_messages_for_google("", messages).unwrap();
+
+        match &contents[0].parts[0] {
+            GooglePart::InlineData { inline_data } => {
+                assert_eq!(inline_data.mime_type, "application/pdf");
+            }
+            _ => panic!("Expected inline data part"),
+        }
+        match &contents[0].parts[1] {
+            GooglePart::Text { text } => {
+                assert!(text.contains("Document: notes.txt"));
+                assert!(text.ends_with("Hello World"));
+            }
+            _ => panic!("Expected text part"),
+        }
+    }
+
     #[test]
     fn test_format_messages_with_thinking() {
         let config = create_test_config();
@@ -848,6 +1182,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_stream_chunks() {
+        let (deltas, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut assembler = StreamAssembler::new(deltas);
+        let mut state = GoogleStreamState::default();
+        let mut usage = TokenUsage::default();
+
+        let chunks = [
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Opening "}]}}],"usageMetadata":{"promptTokenCount":50,"candidatesTokenCount":2}}"#,
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"the menu"}]}}],"usageMetadata":{"promptTokenCount":50,"candidatesTokenCount":4}}"#,
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"functionCall":{"name":"screenshot","args":{}}}]}}],"usageMetadata":{"promptTokenCount":50,"candidatesTokenCount":9,"cachedContentTokenCount":32}}"#,
+        ];
+        for chunk in chunks {
+            GoogleService::apply_stream_chunk(&mut assembler, &mut state, &mut usage, chunk)
+                .unwrap();
+        }
+
+        assert_eq!(usage.input_tokens, 18);
+        assert_eq!(usage.output_tokens, 9);
+        assert_eq!(usage.cache_read_tokens, 32);
+
+        let content = assembler.finish();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0].as_text(), Some("Opening the menu"));
+        assert!(matches!(
+            &content[1],
+            MessageContentBlock::ToolUse { name, .. } if name == "screenshot"
+        ));
+    }
+
     /// Integration tests that demonstrate the Google service functionality
     /// These tests don't make actual API calls but verify the service setup
     mod integration_tests {
@@ -1,20 +1,64 @@
 pub mod anthropic;
 pub mod google;
 pub mod openai;
+pub mod registry;
+pub mod stream;
 
-use std::sync::Arc;
+use std::{future::Future, sync::Arc};
 
 use async_trait::async_trait;
 use bytebot_shared_rs::types::message::{Message, MessageContentBlock};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+pub use registry::{ModelInfo, ModelRegistry};
+pub use stream::{DeltaSender, StreamDelta};
 
 use crate::{config::Config, error::AIError};
 
-/// Model information structure
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct ModelInfo {
-    pub provider: String,
-    pub name: String,
-    pub title: String,
+/// Await a provider request, abandoning it as soon as `signal` fires.
+///
+/// Dropping the request future aborts the underlying HTTP request.
+pub(crate) async fn with_cancellation<T>(
+    signal: Option<&CancellationToken>,
+    request: impl Future<Output = Result<T, AIError>>,
+) -> Result<T, AIError> {
+    match signal {
+        Some(signal) => tokio::select! {
+            biased;
+            _ = signal.cancelled() => Err(AIError::Cancelled),
+            result = request => result,
+        },
+        None => request.await,
+    }
+}
+
+/// Tokens a provider reports for one request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    /// Input tokens read neither from nor into the prompt cache
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Input tokens read from the prompt cache
+    pub cache_read_tokens: u64,
+    /// Input tokens written to the prompt cache
+    pub cache_write_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Tokens of the request and the response together
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+}
+
+/// Content generated by a model together with the tokens it took
+#[derive(Debug, Clone, Default)]
+pub struct AIResponse {
+    pub content: Vec<MessageContentBlock>,
+    pub usage: TokenUsage,
+    /// Model that generated the content
+    pub model: String,
 }
 
 /// AI service trait that abstracts over all AI providers
@@ -28,7 +72,25 @@ pub trait AIService: Send + Sync {
         model: Option<String>,
         use_tools: bool,
         signal: Option<tokio_util::sync::CancellationToken>,
-    ) -> Result<Vec<MessageContentBlock>, AIError>;
+    ) -> Result<AIResponse, AIError>;
+
+    /// Generate a response, sending its content to the deltas channel while
+    /// the model generates it. The returned response holds the complete
+    /// content, as `generate_response` would.
+    ///
+    /// Providers that cannot stream send nothing and answer in one piece.
+    async fn generate_response_stream(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+        _deltas: DeltaSender,
+    ) -> Result<AIResponse, AIError> {
+        self.generate_response(system_prompt, messages, model, use_tools, signal)
+            .await
+    }
 
     /// List available models for this provider
     fn list_models(&self) -> Vec<ModelInfo>;
@@ -37,122 +99,187 @@ pub trait AIService: Send + Sync {
     fn is_available(&self) -> bool;
 }
 
-/// Unified AI service that manages all AI providers
+/// Unified AI service that routes each request to the provider serving the
+/// requested model, as declared by the model registry
 pub struct UnifiedAIService {
-    anthropic: Arc<anthropic::AnthropicService>,
-    openai: Arc<openai::OpenAIService>,
-    google: Arc<google::GoogleService>,
+    registry: ModelRegistry,
+    /// Providers by name, in registration order
+    providers: Vec<(String, Arc<dyn AIService>)>,
 }
 
 impl UnifiedAIService {
-    /// Create a new unified AI service with all providers
+    /// Create a new unified AI service with the bundled providers
     pub fn new(config: &Config) -> Self {
-        Self {
-            anthropic: Arc::new(anthropic::AnthropicService::new(config)),
-            openai: Arc::new(openai::OpenAIService::new(config)),
-            google: Arc::new(google::GoogleService::new(config)),
-        }
-    }
+        let mut service = Self {
+            registry: config.model_registry.clone(),
+            providers: Vec::new(),
+        };
 
-    /// Get the appropriate service for a given model
-    fn get_service_for_model(&self, model: &str) -> Result<&dyn AIService, AIError> {
-        if model.starts_with("claude-") {
-            if !self.anthropic.is_available() {
-                return Err(AIError::Api {
-                    status: 401,
-                    message: "Anthropic API key not configured".to_string(),
-                });
+        service.register(
+            "anthropic",
+            Arc::new(anthropic::AnthropicService::new(config)),
+        );
+        service.register("openai", Arc::new(openai::OpenAIService::new(config)));
+        service.register("google", Arc::new(google::GoogleService::new(config)));
+
+        for provider in &config.openai_compatible_providers {
+            match openai::OpenAIService::compatible(provider) {
+                Ok(compatible) => service.register(provider.name.clone(), Arc::new(compatible)),
+                Err(e) => warn!("Skipping provider {}: {}", provider.name, e),
             }
-            Ok(self.anthropic.as_ref())
-        } else if model.starts_with("gpt-") {
-            if !self.openai.is_available() {
-                return Err(AIError::Api {
-                    status: 401,
-                    message: "OpenAI API key not configured".to_string(),
-                });
-            }
-            Ok(self.openai.as_ref())
-        } else if model.starts_with("gemini-") {
-            if !self.google.is_available() {
-                return Err(AIError::Api {
-                    status: 401,
-                    message: "Google API key not configured".to_string(),
-                });
-            }
-            Ok(self.google.as_ref())
-        } else {
-            Err(AIError::InvalidModel(format!("Unknown model: {model}")))
         }
+
+        service
     }
 
-    /// Get the first available service as default
-    fn get_default_service(&self) -> Result<(&dyn AIService, String), AIError> {
-        // Try Anthropic first (Claude Opus 4 as default)
-        if self.anthropic.is_available() {
-            return Ok((
-                self.anthropic.as_ref(),
-                "claude-opus-4-20250514".to_string(),
-            ));
+    /// Register a provider under `name`, replacing any provider of that name.
+    ///
+    /// Models the provider lists that the registry lacks are added to it, so
+    /// a provider can declare its own models.
+    pub fn register(&mut self, name: impl Into<String>, service: Arc<dyn AIService>) {
+        let name = name.into();
+
+        let mut models = self.registry.models().to_vec();
+        for model in service.list_models() {
+            match self.registry.resolve(&model.name) {
+                _ if model.provider != name => {}
+                Some(existing) if existing.provider != name => warn!(
+                    "Model {} of provider {} is already served by {}",
+                    model.name, name, existing.provider
+                ),
+                Some(_) => {}
+                None => models.push(model),
+            }
         }
-
-        // Try OpenAI next (GPT-4o as default)
-        if self.openai.is_available() {
-            return Ok((self.openai.as_ref(), "gpt-4o".to_string()));
+        match ModelRegistry::new(models) {
+            Ok(registry) => self.registry = registry,
+            Err(e) => warn!("Ignoring the models of provider {}: {}", name, e),
         }
 
-        // Try Google last (Gemini 1.5 Pro as default)
-        if self.google.is_available() {
-            return Ok((self.google.as_ref(), "gemini-1.5-pro".to_string()));
+        match self
+            .providers
+            .iter_mut()
+            .find(|(existing, _)| *existing == name)
+        {
+            Some((_, existing)) => *existing = service,
+            None => self.providers.push((name, service)),
         }
+    }
 
-        Err(AIError::Api {
-            status: 503,
-            message: "No AI services are available - please configure at least one API key"
-                .to_string(),
-        })
+    fn provider(&self, name: &str) -> Option<&dyn AIService> {
+        self.providers
+            .iter()
+            .find(|(provider, _)| provider == name)
+            .map(|(_, service)| service.as_ref())
     }
 
-    /// List all available models from all configured providers
-    pub fn list_all_models(&self) -> Vec<ModelInfo> {
-        let mut all_models = Vec::new();
+    fn is_provider_available(&self, name: &str) -> bool {
+        self.provider(name)
+            .is_some_and(|service| service.is_available())
+    }
 
-        if self.anthropic.is_available() {
-            all_models.extend(self.anthropic.list_models());
+    /// Get the service for a model named by id or alias
+    fn get_service_for_model(&self, model: &str) -> Result<&dyn AIService, AIError> {
+        let info = self
+            .registry
+            .resolve(model)
+            .ok_or_else(|| AIError::InvalidModel(format!("Unknown model: {model}")))?;
+
+        let service = self.provider(&info.provider).ok_or_else(|| {
+            AIError::InvalidModel(format!(
+                "Model {model} is served by unknown provider {}",
+                info.provider
+            ))
+        })?;
+
+        if !service.is_available() {
+            return Err(AIError::Api {
+                status: 401,
+                message: format!(
+                    "Provider {} is not available - check its API key",
+                    info.provider
+                ),
+            });
         }
 
-        if self.openai.is_available() {
-            all_models.extend(self.openai.list_models());
-        }
+        Ok(service)
+    }
 
-        if self.google.is_available() {
-            all_models.extend(self.google.list_models());
-        }
+    /// Get the service and id of the default model: the first model marked
+    /// as default whose provider is available, or else the first model of an
+    /// available provider
+    fn get_default_service(&self) -> Result<(&dyn AIService, String), AIError> {
+        let available = |model: &&ModelInfo| self.is_provider_available(&model.provider);
+        let models = self.registry.models();
 
-        all_models
+        models
+            .iter()
+            .filter(|model| model.default)
+            .find(available)
+            .or_else(|| models.iter().find(available))
+            .and_then(|model| {
+                self.provider(&model.provider)
+                    .map(|service| (service, model.name.clone()))
+            })
+            .ok_or_else(|| AIError::Api {
+                status: 503,
+                message: "No AI services are available - please configure at least one API key"
+                    .to_string(),
+            })
+    }
+
+    /// Service, model id and tool use for a request naming `model`
+    fn resolve_request(
+        &self,
+        model: Option<String>,
+        use_tools: bool,
+    ) -> Result<(&dyn AIService, String, bool), AIError> {
+        let (service, model) = match model {
+            Some(model) => {
+                let service = self.get_service_for_model(&model)?;
+                // Aliases are resolved here; providers receive the model id
+                let id = self
+                    .registry
+                    .resolve(&model)
+                    .map_or(model, |info| info.name.clone());
+                (service, id)
+            }
+            None => self.get_default_service()?,
+        };
+
+        // Models that cannot call tools answer in text instead
+        let tools = self
+            .registry
+            .resolve(&model)
+            .map_or(true, |info| info.capabilities.tools);
+
+        Ok((service, model, use_tools && tools))
+    }
+
+    /// List all models of the available providers, in registry order
+    pub fn list_all_models(&self) -> Vec<ModelInfo> {
+        self.registry
+            .models()
+            .iter()
+            .filter(|model| self.is_provider_available(&model.provider))
+            .cloned()
+            .collect()
     }
 
     /// Check if any AI service is available
     pub fn is_any_service_available(&self) -> bool {
-        self.anthropic.is_available() || self.openai.is_available() || self.google.is_available()
+        self.providers
+            .iter()
+            .any(|(_, service)| service.is_available())
     }
 
-    /// Get available providers
+    /// Get available providers, in registration order
     pub fn get_available_providers(&self) -> Vec<String> {
-        let mut providers = Vec::new();
-
-        if self.anthropic.is_available() {
-            providers.push("anthropic".to_string());
-        }
-
-        if self.openai.is_available() {
-            providers.push("openai".to_string());
-        }
-
-        if self.google.is_available() {
-            providers.push("google".to_string());
-        }
-
-        providers
+        self.providers
+            .iter()
+            .filter(|(_, service)| service.is_available())
+            .map(|(name, _)| name.clone())
+            .collect()
     }
 }
 
@@ -165,13 +292,8 @@ impl AIService for UnifiedAIService {
         model: Option<String>,
         use_tools: bool,
         signal: Option<tokio_util::sync::CancellationToken>,
-    ) -> Result<Vec<MessageContentBlock>, AIError> {
-        let (service, resolved_model) = if let Some(model) = model {
-            (self.get_service_for_model(&model)?, model)
-        } else {
-            let (service, default_model) = self.get_default_service()?;
-            (service, default_model)
-        };
+    ) -> Result<AIResponse, AIError> {
+        let (service, resolved_model, use_tools) = self.resolve_request(model, use_tools)?;
 
         service
             .generate_response(
@@ -184,6 +306,29 @@ impl AIService for UnifiedAIService {
             .await
     }
 
+    async fn generate_response_stream(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+        deltas: DeltaSender,
+    ) -> Result<AIResponse, AIError> {
+        let (service, resolved_model, use_tools) = self.resolve_request(model, use_tools)?;
+
+        service
+            .generate_response_stream(
+                system_prompt,
+                messages,
+                Some(resolved_model),
+                use_tools,
+                signal,
+                deltas,
+            )
+            .await
+    }
+
     fn list_models(&self) -> Vec<ModelInfo> {
         self.list_all_models()
     }
@@ -397,7 +542,7 @@ mod tests {
         match result.err().unwrap() {
             AIError::Api { status, message } => {
                 assert_eq!(status, 401);
-                assert!(message.contains("Anthropic API key not configured"));
+                assert!(message.contains("Provider anthropic is not available"));
             }
             _ => panic!("Expected API error"),
         }
@@ -475,6 +620,97 @@ mod tests {
         }
     }
 
+    /// Provider answering every request with the model it was asked for
+    struct StubService {
+        models: Vec<ModelInfo>,
+    }
+
+    #[async_trait]
+    impl AIService for StubService {
+        async fn generate_response(
+            &self,
+            _system_prompt: &str,
+            _messages: Vec<Message>,
+            model: Option<String>,
+            _use_tools: bool,
+            _signal: Option<tokio_util::sync::CancellationToken>,
+        ) -> Result<AIResponse, AIError> {
+            Ok(AIResponse {
+                model: model.unwrap_or_default(),
+                ..Default::default()
+            })
+        }
+
+        fn list_models(&self) -> Vec<ModelInfo> {
+            self.models.clone()
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_custom_provider() {
+        let mut service = UnifiedAIService::new(&create_test_config_no_keys());
+        let registry = ModelRegistry::from_json(
+            r#"[{"provider": "local", "id": "llama-3.1-70b", "title": "Llama 3.1 70B",
+                 "aliases": ["llama"], "default": true}]"#,
+        )
+        .unwrap();
+        service.register(
+            "local",
+            Arc::new(StubService {
+                models: registry.models().to_vec(),
+            }),
+        );
+
+        assert_eq!(service.get_available_providers(), vec!["local"]);
+        assert_eq!(service.list_all_models().len(), 1);
+
+        let (_, default_model) = service.get_default_service().unwrap();
+        assert_eq!(default_model, "llama-3.1-70b");
+
+        let response = service
+            .generate_response(
+                "Test prompt",
+                Vec::new(),
+                Some("llama".to_string()),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.model, "llama-3.1-70b");
+    }
+
+    #[test]
+    fn test_default_model_follows_registry_order() {
+        let config = Config {
+            model_registry: ModelRegistry::from_json(
+                r#"[
+                    {"provider": "google", "id": "gemini-1.5-flash", "title": "Gemini 1.5 Flash"},
+                    {"provider": "anthropic", "id": "claude-sonnet-4-20250514", "title": "Claude Sonnet 4", "default": true}
+                ]"#,
+            )
+            .unwrap(),
+            ..create_test_config_all_keys()
+        };
+        let service = UnifiedAIService::new(&config);
+
+        let (_, default_model) = service.get_default_service().unwrap();
+        assert_eq!(default_model, "claude-sonnet-4-20250514");
+        assert!(service.get_service_for_model("gpt-4o").is_err());
+
+        let config = Config {
+            anthropic_api_key: None,
+            ..config
+        };
+        let service = UnifiedAIService::new(&config);
+        let (_, default_model) = service.get_default_service().unwrap();
+        assert_eq!(default_model, "gemini-1.5-flash");
+    }
+
     /// Integration tests that demonstrate the unified AI service functionality
     /// These tests don't make actual API calls but verify the service routing
     mod integration_tests {
@@ -672,24 +908,17 @@ mod tests {
                 }
             }
 
-            // Test provider-specific invalid models (these will be routed to the provider successfully)
-            // The actual model validation happens when generate_response is called
-            let provider_invalid_models = vec![
-                ("claude-invalid", "anthropic"),
-                ("gpt-invalid", "openai"),
-                ("gemini-invalid", "google"),
-            ];
-
-            for (model, _expected_provider) in provider_invalid_models {
+            // Names are matched against the registry, not by prefix
+            for model in ["claude-invalid", "gpt-invalid", "gemini-invalid"] {
                 let result = service.get_service_for_model(model);
-                // These should succeed in routing to the correct provider
-                assert!(result.is_ok(), "Model {model} should be routed to provider");
-
-                // The actual validation will happen when generate_response is called
-                // Let's verify that the service can be obtained but will fail on actual use
-                let ai_service = result.unwrap();
-                assert!(ai_service.is_available());
+                assert!(
+                    matches!(result, Err(AIError::InvalidModel(_))),
+                    "Model {model} is not in the registry"
+                );
             }
+
+            // Aliases route to the provider of the model they name
+            assert!(service.get_service_for_model("claude-opus-4").is_ok());
         }
     }
 }
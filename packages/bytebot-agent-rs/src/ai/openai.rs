@@ -1,47 +1,48 @@
-use std::time::Duration;
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use bytebot_shared_rs::types::{
-    message::{Message, MessageContentBlock},
+    message::{ImageSource, Message, MessageContentBlock},
     task::Role,
+    task_tools::agent_tool_definitions,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client, StatusCode,
 };
-use reqwest::{Client, StatusCode};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use tracing::{error, warn};
 
-use super::{AIService, ModelInfo};
+use super::{
+    stream::{read_sse, StreamAssembler},
+    with_cancellation, AIResponse, AIService, DeltaSender, ModelInfo, ModelRegistry, TokenUsage,
+};
 use crate::{config::Config, error::AIError};
 
 /// OpenAI API constants
 const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
-const DEFAULT_MODEL: &str = "gpt-4o";
 const MAX_TOKENS: u32 = 4096;
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
 
-/// Get available OpenAI models
-fn get_openai_models() -> Vec<ModelInfo> {
-    vec![
-        ModelInfo {
-            provider: "openai".to_string(),
-            name: "gpt-4o".to_string(),
-            title: "GPT-4o".to_string(),
-        },
-        ModelInfo {
-            provider: "openai".to_string(),
-            name: "gpt-4o-mini".to_string(),
-            title: "GPT-4o Mini".to_string(),
-        },
-        ModelInfo {
-            provider: "openai".to_string(),
-            name: "gpt-4-turbo".to_string(),
-            title: "GPT-4 Turbo".to_string(),
-        },
-        ModelInfo {
-            provider: "openai".to_string(),
-            name: "gpt-3.5-turbo".to_string(),
-            title: "GPT-3.5 Turbo".to_string(),
-        },
-    ]
+/// Agent tools in OpenAI's function calling format
+fn get_openai_tools() -> Vec<serde_json::Value> {
+    agent_tool_definitions()
+        .into_iter()
+        .map(|tool| {
+            serde_json::json!({
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.input_schema,
+                }
+            })
+        })
+        .collect()
 }
 
 /// OpenAI API request structures
@@ -53,12 +54,54 @@ struct OpenAIRequest {
     temperature: f32,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Debug, Serialize)]
 struct OpenAIMessage {
     role: String,
+    /// Left out of assistant messages that only call tools
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_content"
+    )]
     content: Vec<OpenAIContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ToolCall>,
+    /// Call a `tool` message answers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl OpenAIMessage {
+    fn new(role: &str, content: Vec<OpenAIContent>) -> Self {
+        Self {
+            role: role.to_string(),
+            content,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+}
+
+/// Send a lone text part as a plain string, the form every
+/// OpenAI-compatible server accepts for every role
+fn serialize_content<S: Serializer>(
+    content: &[OpenAIContent],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match content {
+        [OpenAIContent::Text { text }] => serializer.serialize_str(text),
+        parts => parts.serialize(serializer),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -95,7 +138,7 @@ struct OpenAIResponseMessage {
     tool_calls: Vec<ToolCall>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct ToolCall {
     id: String,
     #[serde(rename = "type")]
@@ -103,7 +146,7 @@ struct ToolCall {
     function: FunctionCall,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct FunctionCall {
     name: String,
     arguments: String,
@@ -114,6 +157,71 @@ struct Usage {
     prompt_tokens: u32,
     completion_tokens: u32,
     total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u32>,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        // Cached tokens are part of the prompt tokens
+        let cached = usage
+            .prompt_tokens_details
+            .and_then(|details| details.cached_tokens)
+            .unwrap_or(0)
+            .min(usage.prompt_tokens);
+
+        Self {
+            input_tokens: (usage.prompt_tokens - cached).into(),
+            output_tokens: usage.completion_tokens.into(),
+            cache_read_tokens: cached.into(),
+            cache_write_tokens: 0,
+        }
+    }
+}
+
+/// OpenAI streaming chunk; the last one carries only the usage
+#[derive(Debug, Deserialize)]
+struct OpenAIChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    delta: ChunkDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ChunkToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkToolCall {
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<ChunkFunction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkFunction {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
 }
 
 /// OpenAI API error response
@@ -130,10 +238,134 @@ struct OpenAIErrorResponse {
     error: OpenAIError,
 }
 
-/// OpenAI service implementation
+/// Self-hosted endpoint speaking the OpenAI chat completions API, such as
+/// vLLM, Ollama, LM Studio or LiteLLM
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OpenAICompatibleProvider {
+    /// Provider name models are registered under
+    pub name: String,
+
+    /// URL the `/chat/completions` path is appended to, e.g.
+    /// `http://localhost:8000/v1`
+    #[serde(rename = "baseUrl")]
+    pub base_url: String,
+
+    /// Sent as a bearer token when set
+    #[serde(rename = "apiKey", default)]
+    pub api_key: Option<String>,
+
+    /// Environment variable holding the key, so it stays out of the
+    /// provider list
+    #[serde(rename = "apiKeyEnv", default)]
+    pub api_key_env: Option<String>,
+
+    /// Extra headers sent with every request
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// Models served by the endpoint; their provider is the endpoint name
+    pub models: Vec<ModelInfo>,
+}
+
+impl OpenAICompatibleProvider {
+    /// Read providers from a JSON array, as given in
+    /// `OPENAI_COMPATIBLE_PROVIDERS`
+    pub fn list_from_json(json: &str) -> Result<Vec<Self>, String> {
+        let mut providers: Vec<Self> = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid OpenAI-compatible providers: {e}"))?;
+
+        let mut names = Vec::new();
+        for provider in &mut providers {
+            provider.validate()?;
+            if ["anthropic", "openai", "google"].contains(&provider.name.as_str())
+                || names.contains(&provider.name)
+            {
+                return Err(format!("Provider name {} is already taken", provider.name));
+            }
+            names.push(provider.name.clone());
+        }
+
+        Ok(providers)
+    }
+
+    /// Check the entry and fill in what it leaves implicit: the provider of
+    /// its models and the key named by `apiKeyEnv`
+    fn validate(&mut self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("OpenAI-compatible providers need a name".to_string());
+        }
+        if !(self.base_url.starts_with("http://") || self.base_url.starts_with("https://")) {
+            return Err(format!(
+                "Provider {} needs an http(s) base URL, got {}",
+                self.name, self.base_url
+            ));
+        }
+        if self.models.is_empty() {
+            return Err(format!("Provider {} declares no models", self.name));
+        }
+
+        for model in &mut self.models {
+            if model.provider.is_empty() {
+                model.provider = self.name.clone();
+            } else if model.provider != self.name {
+                return Err(format!(
+                    "Model {} of provider {} names provider {}",
+                    model.name, self.name, model.provider
+                ));
+            }
+        }
+        ModelRegistry::new(self.models.clone())?;
+        parse_headers(&self.headers).map_err(|e| format!("Provider {}: {e}", self.name))?;
+
+        if let Some(var) = &self.api_key_env {
+            let key = env::var(var).map_err(|_| {
+                format!(
+                    "Provider {} reads its key from {var}, which is not set",
+                    self.name
+                )
+            })?;
+            self.api_key = Some(key);
+        }
+
+        Ok(())
+    }
+}
+
+/// Image as a data URL content part
+fn image_content(source: &ImageSource) -> OpenAIContent {
+    OpenAIContent::ImageUrl {
+        image_url: ImageUrl {
+            url: format!("data:{};base64,{}", source.media_type, source.data),
+        },
+    }
+}
+
+/// Turn configured headers into request headers
+fn parse_headers(headers: &BTreeMap<String, String>) -> Result<HeaderMap, String> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.insert(
+            HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name {name}"))?,
+            HeaderValue::from_str(value).map_err(|_| format!("Invalid value of header {name}"))?,
+        );
+    }
+    Ok(map)
+}
+
+/// OpenAI service implementation, also used for OpenAI-compatible endpoints
 pub struct OpenAIService {
     client: Client,
     api_key: Option<String>,
+    /// Models of the registry served by this provider
+    models: ModelRegistry,
+    /// Name used in errors and logs
+    label: String,
+    base_url: String,
+    headers: HeaderMap,
+    /// OpenAI itself refuses requests without a key; self-hosted endpoints
+    /// often need none
+    key_required: bool,
 }
 
 impl OpenAIService {
@@ -147,10 +379,63 @@ impl OpenAIService {
         Self {
             client,
             api_key: config.openai_api_key.clone(),
+            models: config.model_registry.for_provider("openai"),
+            label: "OpenAI".to_string(),
+            base_url: OPENAI_API_BASE.to_string(),
+            headers: HeaderMap::new(),
+            key_required: true,
         }
     }
 
-    /// Convert internal messages to OpenAI format
+    /// Create a service for an OpenAI-compatible endpoint
+    pub fn compatible(provider: &OpenAICompatibleProvider) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Ok(Self {
+            client,
+            api_key: provider.api_key.clone(),
+            models: ModelRegistry::new(provider.models.clone())?,
+            label: provider.name.clone(),
+            base_url: provider.base_url.trim_end_matches('/').to_string(),
+            headers: parse_headers(&provider.headers)?,
+            key_required: false,
+        })
+    }
+
+    /// Key to send, failing when a required key is missing
+    fn api_key(&self) -> Result<Option<&str>, AIError> {
+        match &self.api_key {
+            Some(key) => Ok(Some(key)),
+            None if self.key_required => Err(AIError::Api {
+                status: 401,
+                message: format!("{} API key not configured", self.label),
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Id of the requested model, or of the default model if none is named
+    fn resolve_model(&self, model: Option<String>) -> Result<String, AIError> {
+        self.models
+            .select(model.as_deref())
+            .map(|info| info.name.clone())
+            .ok_or_else(|| {
+                AIError::InvalidModel(format!(
+                    "Invalid {} model: {}",
+                    self.label,
+                    model.unwrap_or_default()
+                ))
+            })
+    }
+
+    /// Convert internal messages to OpenAI format.
+    ///
+    /// Tool calls become the `tool_calls` of the assistant message and their
+    /// results `tool` messages right after it. Tool messages carry only text,
+    /// so images a tool returned follow in a user message.
     fn format_messages_for_openai(
         &self,
         system_prompt: &str,
@@ -160,20 +445,25 @@ impl OpenAIService {
 
         // Add system message first
         if !system_prompt.is_empty() {
-            openai_messages.push(OpenAIMessage {
-                role: "system".to_string(),
-                content: vec![OpenAIContent::Text {
+            openai_messages.push(OpenAIMessage::new(
+                "system",
+                vec![OpenAIContent::Text {
                     text: system_prompt.to_string(),
                 }],
-            });
+            ));
         }
 
+        // Calls of the last assistant message that have no result yet
+        let mut pending_calls = HashSet::new();
+
         for message in messages {
             let content_blocks = message
                 .get_content_blocks()
                 .map_err(AIError::Serialization)?;
 
             let mut content: Vec<OpenAIContent> = Vec::new();
+            let mut tool_calls = Vec::new();
+            let mut tool_messages = Vec::new();
 
             for block in content_blocks {
                 match block {
@@ -181,52 +471,80 @@ impl OpenAIService {
                         content.push(OpenAIContent::Text { text });
                     }
                     MessageContentBlock::Image { source } => {
-                        // Convert base64 image to data URL format
-                        let data_url = format!("data:{};base64,{}", source.media_type, source.data);
-                        content.push(OpenAIContent::ImageUrl {
-                            image_url: ImageUrl { url: data_url },
-                        });
+                        content.push(image_content(&source));
                     }
                     MessageContentBlock::ToolUse { name, id, input } => {
-                        // OpenAI handles tool calls differently - they appear in assistant messages
-                        // For now, convert to text representation
-                        let tool_text = format!("Tool call: {name} (id: {id}) with input: {input}");
-                        content.push(OpenAIContent::Text { text: tool_text });
+                        tool_calls.push(ToolCall {
+                            id,
+                            tool_type: "function".to_string(),
+                            function: FunctionCall {
+                                name,
+                                arguments: input.to_string(),
+                            },
+                        });
                     }
                     MessageContentBlock::ToolResult {
                         tool_use_id,
                         content: tool_content,
                         is_error,
                     } => {
-                        // Convert tool result to text
-                        let result_text = tool_content
-                            .iter()
-                            .filter_map(|block| match block {
-                                MessageContentBlock::Text { text } => Some(text.clone()),
-                                _ => None,
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" ");
-
-                        let error_prefix = if is_error.unwrap_or(false) {
-                            "Tool error"
+                        let mut result_text = Vec::new();
+                        let mut images = Vec::new();
+                        for block in tool_content {
+                            match block {
+                                MessageContentBlock::Text { text } => result_text.push(text),
+                                MessageContentBlock::Image { source } => {
+                                    images.push(image_content(&source))
+                                }
+                                _ => {}
+                            }
+                        }
+
+                        let mut result_text = result_text.join("\n");
+                        if result_text.is_empty() {
+                            result_text = if images.is_empty() {
+                                "Done".to_string()
+                            } else {
+                                "The result is the image that follows".to_string()
+                            };
+                        }
+                        if is_error.unwrap_or(false) {
+                            result_text = format!("Error: {result_text}");
+                        }
+
+                        if pending_calls.remove(&tool_use_id) {
+                            if !images.is_empty() {
+                                content.push(OpenAIContent::Text {
+                                    text: format!("Image returned by tool call {tool_use_id}:"),
+                                });
+                                content.extend(images);
+                            }
+                            tool_messages.push(OpenAIMessage {
+                                tool_call_id: Some(tool_use_id),
+                                ..OpenAIMessage::new(
+                                    "tool",
+                                    vec![OpenAIContent::Text { text: result_text }],
+                                )
+                            });
                         } else {
-                            "Tool result"
-                        };
-
-                        let tool_result_text =
-                            format!("{error_prefix} for {tool_use_id}: {result_text}");
-                        content.push(OpenAIContent::Text {
-                            text: tool_result_text,
-                        });
+                            // The call is no longer in the history, e.g. it was
+                            // summarised away, so a tool message would be rejected
+                            content.push(OpenAIContent::Text {
+                                text: format!("Tool result for {tool_use_id}: {result_text}"),
+                            });
+                            content.extend(images);
+                        }
                     }
                     MessageContentBlock::Document { source, name, .. } => {
                         // Convert document to text representation
-                        let doc_text = format!(
+                        let mut doc_text = format!(
                             "Document: {} (type: {})",
                             name.unwrap_or_else(|| "unnamed".to_string()),
                             source.media_type
                         );
+                        if let Some(text) = source.decode_text() {
+                            doc_text.push_str(&format!("\n\n{text}"));
+                        }
                         content.push(OpenAIContent::Text { text: doc_text });
                     }
                     MessageContentBlock::Thinking { thinking, .. } => {
@@ -240,14 +558,22 @@ impl OpenAIService {
                 }
             }
 
+            // Results must directly follow the message that made the calls
+            openai_messages.extend(tool_messages);
+
+            let role = match message.role {
+                Role::User => "user",
+                Role::Assistant => {
+                    pending_calls = tool_calls.iter().map(|call| call.id.clone()).collect();
+                    "assistant"
+                }
+            };
+
             // Only add message if it has content
-            if !content.is_empty() {
+            if !content.is_empty() || !tool_calls.is_empty() {
                 openai_messages.push(OpenAIMessage {
-                    role: match message.role {
-                        Role::User => "user".to_string(),
-                        Role::Assistant => "assistant".to_string(),
-                    },
-                    content,
+                    tool_calls,
+                    ..OpenAIMessage::new(role, content)
                 });
             }
         }
@@ -289,11 +615,11 @@ impl OpenAIService {
     fn handle_api_error(&self, status: StatusCode, body: &str) -> AIError {
         match status {
             StatusCode::TOO_MANY_REQUESTS => {
-                warn!("OpenAI API rate limit exceeded");
+                warn!("{} API rate limit exceeded", self.label);
                 AIError::RateLimit
             }
             StatusCode::UNAUTHORIZED => {
-                error!("OpenAI API authentication failed");
+                error!("{} API authentication failed", self.label);
                 AIError::Api {
                     status: status.as_u16(),
                     message: "Authentication failed - check API key".to_string(),
@@ -302,13 +628,16 @@ impl OpenAIService {
             StatusCode::BAD_REQUEST => {
                 // Try to parse the error response
                 if let Ok(error_response) = serde_json::from_str::<OpenAIErrorResponse>(body) {
-                    error!("OpenAI API bad request: {}", error_response.error.message);
+                    error!(
+                        "{} API bad request: {}",
+                        self.label, error_response.error.message
+                    );
                     AIError::Api {
                         status: status.as_u16(),
                         message: error_response.error.message,
                     }
                 } else {
-                    error!("OpenAI API bad request: {}", body);
+                    error!("{} API bad request: {}", self.label, body);
                     AIError::Api {
                         status: status.as_u16(),
                         message: "Bad request".to_string(),
@@ -316,14 +645,14 @@ impl OpenAIService {
                 }
             }
             StatusCode::INTERNAL_SERVER_ERROR => {
-                error!("OpenAI API internal server error: {}", body);
+                error!("{} API internal server error: {}", self.label, body);
                 AIError::Api {
                     status: status.as_u16(),
-                    message: "OpenAI server error - please retry".to_string(),
+                    message: format!("{} server error - please retry", self.label),
                 }
             }
             _ => {
-                error!("OpenAI API error {}: {}", status, body);
+                error!("{} API error {}: {}", self.label, status, body);
                 AIError::Api {
                     status: status.as_u16(),
                     message: format!("API error: {status}"),
@@ -332,13 +661,41 @@ impl OpenAIService {
         }
     }
 
-    /// Make API request with retry logic for transient failures
-    async fn make_request_with_retry(
+    /// Validate the model and build the request body
+    fn build_request(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+    ) -> Result<OpenAIRequest, AIError> {
+        let model = self.resolve_model(model)?;
+
+        let openai_messages = self.format_messages_for_openai(system_prompt, messages)?;
+
+        Ok(OpenAIRequest {
+            model,
+            messages: openai_messages,
+            max_tokens: MAX_TOKENS,
+            temperature: 0.7,
+            tools: if use_tools {
+                get_openai_tools()
+            } else {
+                vec![]
+            },
+            stream: false,
+            stream_options: None,
+        })
+    }
+
+    /// Send a request, retrying transient failures, and return the first
+    /// successful response
+    async fn send_with_retry(
         &self,
         request: &OpenAIRequest,
-        api_key: &str,
+        api_key: Option<&str>,
         max_retries: u32,
-    ) -> Result<OpenAIResponse, AIError> {
+    ) -> Result<reqwest::Response, AIError> {
         let mut last_error = None;
 
         for attempt in 0..=max_retries {
@@ -347,31 +704,31 @@ impl OpenAIService {
                 let delay = Duration::from_secs(2_u64.pow(attempt - 1));
                 tokio::time::sleep(delay).await;
                 warn!(
-                    "Retrying OpenAI API request (attempt {}/{})",
+                    "Retrying {} API request (attempt {}/{})",
+                    self.label,
                     attempt + 1,
                     max_retries + 1
                 );
             }
 
-            let response = self
+            let mut builder = self
                 .client
-                .post(format!("{OPENAI_API_BASE}/chat/completions"))
-                .header("Content-Type", "application/json")
-                .header("Authorization", format!("Bearer {api_key}"))
-                .json(request)
-                .send()
-                .await
-                .map_err(AIError::Http)?;
+                .post(format!("{}/chat/completions", self.base_url))
+                .headers(self.headers.clone())
+                .header("Content-Type", "application/json");
+            if let Some(api_key) = api_key {
+                builder = builder.header("Authorization", format!("Bearer {api_key}"));
+            }
+
+            let response = builder.json(request).send().await.map_err(AIError::Http)?;
 
             let status = response.status();
-            let body = response.text().await.map_err(AIError::Http)?;
+            if status.is_success() {
+                return Ok(response);
+            }
 
+            let body = response.text().await.map_err(AIError::Http)?;
             match status {
-                status if status.is_success() => {
-                    let openai_response: OpenAIResponse =
-                        serde_json::from_str(&body).map_err(AIError::Serialization)?;
-                    return Ok(openai_response);
-                }
                 StatusCode::TOO_MANY_REQUESTS | StatusCode::INTERNAL_SERVER_ERROR => {
                     // These are retryable errors
                     last_error = Some(self.handle_api_error(status, &body));
@@ -390,6 +747,64 @@ impl OpenAIService {
             message: "Max retries exceeded".to_string(),
         }))
     }
+
+    /// Make API request with retry logic for transient failures
+    async fn make_request_with_retry(
+        &self,
+        request: &OpenAIRequest,
+        api_key: Option<&str>,
+        max_retries: u32,
+    ) -> Result<OpenAIResponse, AIError> {
+        let response = self.send_with_retry(request, api_key, max_retries).await?;
+        let body = response.text().await.map_err(AIError::Http)?;
+
+        serde_json::from_str(&body).map_err(AIError::Serialization)
+    }
+
+    /// Apply one chunk of a streamed response. The text goes to block 0 and
+    /// tool call `n` to block `n + 1`, matching the order of
+    /// `format_openai_response`.
+    fn apply_stream_chunk(
+        assembler: &mut StreamAssembler,
+        usage: &mut TokenUsage,
+        data: &str,
+    ) -> Result<(), AIError> {
+        if data == "[DONE]" {
+            return Ok(());
+        }
+
+        let chunk: OpenAIChunk = serde_json::from_str(data).map_err(AIError::Serialization)?;
+
+        if let Some(chunk_usage) = chunk.usage {
+            *usage = chunk_usage.into();
+        }
+
+        // Only the first choice is used, as in `generate_response`
+        let Some(choice) = chunk.choices.into_iter().next() else {
+            return Ok(());
+        };
+
+        if let Some(text) = choice.delta.content {
+            assembler.text(0, &text);
+        }
+
+        for tool_call in choice.delta.tool_calls.unwrap_or_default() {
+            let index = tool_call.index + 1;
+            let function = tool_call.function.unwrap_or(ChunkFunction {
+                name: None,
+                arguments: None,
+            });
+
+            if let Some(id) = tool_call.id {
+                assembler.tool_use_start(index, id, function.name.unwrap_or_default());
+            }
+            if let Some(arguments) = function.arguments {
+                assembler.tool_use_input(index, &arguments);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -400,59 +815,83 @@ impl AIService for OpenAIService {
         messages: Vec<Message>,
         model: Option<String>,
         use_tools: bool,
-        _signal: Option<tokio_util::sync::CancellationToken>,
-    ) -> Result<Vec<MessageContentBlock>, AIError> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| AIError::Api {
-            status: 401,
-            message: "OpenAI API key not configured".to_string(),
-        })?;
-
-        let model = model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
-
-        // Validate model
-        let models = get_openai_models();
-        if !models.iter().any(|m| m.name == model) {
-            return Err(AIError::InvalidModel(format!(
-                "Invalid OpenAI model: {model}"
-            )));
-        }
-
-        let openai_messages = self.format_messages_for_openai(system_prompt, messages)?;
+        signal: Option<tokio_util::sync::CancellationToken>,
+    ) -> Result<AIResponse, AIError> {
+        let api_key = self.api_key()?;
 
-        let request = OpenAIRequest {
-            model,
-            messages: openai_messages,
-            max_tokens: MAX_TOKENS,
-            temperature: 0.7,
-            tools: if use_tools {
-                // TODO: Implement tools from agent.tools - for now return empty array
-                // This will be implemented in a future task
-                vec![]
-            } else {
-                vec![]
-            },
-        };
+        let request = self.build_request(system_prompt, messages, model, use_tools)?;
 
         // Use retry logic for API calls
-        let response = self.make_request_with_retry(&request, api_key, 3).await?;
+        let mut response = with_cancellation(
+            signal.as_ref(),
+            self.make_request_with_retry(&request, api_key, 3),
+        )
+        .await?;
 
         if response.choices.is_empty() {
             return Err(AIError::Api {
                 status: 500,
-                message: "No response choices returned from OpenAI".to_string(),
+                message: format!("No response choices returned from {}", self.label),
             });
         }
 
+        let usage = response
+            .usage
+            .take()
+            .map(TokenUsage::from)
+            .unwrap_or_default();
+
         let choice = &response.choices[0];
-        Ok(self.format_openai_response(choice.message.clone()))
+        Ok(AIResponse {
+            content: self.format_openai_response(choice.message.clone()),
+            usage,
+            model: request.model,
+        })
+    }
+
+    async fn generate_response_stream(
+        &self,
+        system_prompt: &str,
+        messages: Vec<Message>,
+        model: Option<String>,
+        use_tools: bool,
+        signal: Option<tokio_util::sync::CancellationToken>,
+        deltas: DeltaSender,
+    ) -> Result<AIResponse, AIError> {
+        let api_key = self.api_key()?;
+
+        let mut request = self.build_request(system_prompt, messages, model, use_tools)?;
+        request.stream = true;
+        request.stream_options = Some(StreamOptions {
+            include_usage: true,
+        });
+
+        let mut assembler = StreamAssembler::new(deltas);
+        let mut usage = TokenUsage::default();
+
+        with_cancellation(signal.as_ref(), async {
+            // Retries are safe as long as nothing has been streamed yet
+            let response = self.send_with_retry(&request, api_key, 3).await?;
+            read_sse(response, |data| {
+                Self::apply_stream_chunk(&mut assembler, &mut usage, data)
+            })
+            .await
+        })
+        .await?;
+
+        Ok(AIResponse {
+            content: assembler.finish(),
+            usage,
+            model: request.model,
+        })
     }
 
     fn list_models(&self) -> Vec<ModelInfo> {
-        get_openai_models()
+        self.models.models().to_vec()
     }
 
     fn is_available(&self) -> bool {
-        self.api_key.is_some()
+        !self.key_required || self.api_key.is_some()
     }
 }
 
@@ -504,6 +943,19 @@ mod tests {
         assert_eq!(models[3].name, "gpt-3.5-turbo");
     }
 
+    #[test]
+    fn test_openai_tools() {
+        let tools = get_openai_tools();
+
+        assert_eq!(tools.len(), agent_tool_definitions().len());
+        assert!(tools.iter().all(|t| t["type"] == "function"));
+        let scroll = tools
+            .iter()
+            .find(|t| t["function"]["name"] == "scroll")
+            .unwrap();
+        assert!(scroll["function"]["parameters"]["properties"]["scrollCount"].is_object());
+    }
+
     #[test]
     fn test_format_messages_for_openai() {
         let config = create_test_config();
@@ -574,13 +1026,86 @@ mod tests {
         let result = service.format_messages_for_openai("", messages).unwrap();
 
         assert_eq!(result.len(), 1);
-        match &result[0].content[0] {
-            OpenAIContent::Text { text } => {
-                assert!(text.contains("Tool call: test_tool"));
-                assert!(text.contains("id: 123"));
-            }
-            _ => panic!("Expected text content"),
-        }
+        assert!(result[0].content.is_empty());
+        assert_eq!(result[0].tool_calls[0].id, "123");
+        assert_eq!(result[0].tool_calls[0].function.name, "test_tool");
+        assert_eq!(
+            result[0].tool_calls[0].function.arguments,
+            r#"{"param":"value"}"#
+        );
+    }
+
+    #[test]
+    fn test_format_messages_with_tool_results() {
+        let config = create_test_config();
+        let service = OpenAIService::new(&config);
+
+        let messages = vec![
+            create_test_message(
+                vec![
+                    MessageContentBlock::text("Taking a screenshot"),
+                    MessageContentBlock::tool_use(
+                        "computer_screenshot",
+                        "call_1",
+                        serde_json::json!({}),
+                    ),
+                    MessageContentBlock::tool_use(
+                        "computer_type_text",
+                        "call_2",
+                        serde_json::json!({"text": "hi"}),
+                    ),
+                ],
+                Role::Assistant,
+            ),
+            create_test_message(
+                vec![
+                    MessageContentBlock::ToolResult {
+                        tool_use_id: "call_1".to_string(),
+                        content: vec![MessageContentBlock::image("image/png", "png_data")],
+                        is_error: None,
+                    },
+                    MessageContentBlock::ToolResult {
+                        tool_use_id: "call_2".to_string(),
+                        content: vec![MessageContentBlock::text("Keyboard unavailable")],
+                        is_error: Some(true),
+                    },
+                    // Answers a call that is no longer in the history
+                    MessageContentBlock::ToolResult {
+                        tool_use_id: "call_0".to_string(),
+                        content: vec![MessageContentBlock::text("Clicked")],
+                        is_error: None,
+                    },
+                ],
+                Role::User,
+            ),
+        ];
+
+        let result = service.format_messages_for_openai("", messages).unwrap();
+        let json = serde_json::to_value(&result).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "role": "assistant",
+                    "content": "Taking a screenshot",
+                    "tool_calls": [
+                        {"id": "call_1", "type": "function", "function": {"name": "computer_screenshot", "arguments": "{}"}},
+                        {"id": "call_2", "type": "function", "function": {"name": "computer_type_text", "arguments": "{\"text\":\"hi\"}"}}
+                    ]
+                },
+                {"role": "tool", "tool_call_id": "call_1", "content": "The result is the image that follows"},
+                {"role": "tool", "tool_call_id": "call_2", "content": "Error: Keyboard unavailable"},
+                {
+                    "role": "user",
+                    "content": [
+                        {"type": "text", "text": "Image returned by tool call call_1:"},
+                        {"type": "image_url", "image_url": {"url": "data:image/png;base64,png_data"}},
+                        {"type": "text", "text": "Tool result for call_0: Clicked"}
+                    ]
+                }
+            ])
+        );
     }
 
     #[test]
@@ -679,6 +1204,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_stream_chunks() {
+        let (deltas, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut assembler = StreamAssembler::new(deltas);
+        let mut usage = TokenUsage::default();
+
+        let chunks = [
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"Let me "},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":"click"},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":null,"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"click_mouse","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"button\":"}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"left\"}"}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":80,"completion_tokens":20,"total_tokens":100,"prompt_tokens_details":{"cached_tokens":64}}}"#,
+            "[DONE]",
+        ];
+        for chunk in chunks {
+            OpenAIService::apply_stream_chunk(&mut assembler, &mut usage, chunk).unwrap();
+        }
+
+        assert_eq!(usage.input_tokens, 16);
+        assert_eq!(usage.output_tokens, 20);
+        assert_eq!(usage.cache_read_tokens, 64);
+
+        let content = assembler.finish();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0].as_text(), Some("Let me click"));
+        match &content[1] {
+            MessageContentBlock::ToolUse { id, name, input } => {
+                assert_eq!(id, "call_1");
+                assert_eq!(name, "click_mouse");
+                assert_eq!(input["button"], "left");
+            }
+            _ => panic!("Expected tool use block"),
+        }
+    }
+
+    #[test]
+    fn test_openai_compatible_providers_from_json() {
+        let providers = OpenAICompatibleProvider::list_from_json(
+            r#"[{
+                "name": "vllm",
+                "baseUrl": "http://localhost:8000/v1/",
+                "headers": {"X-Team": "security"},
+                "models": [{"id": "llama-3.1-70b", "title": "Llama 3.1 70B"}]
+            }]"#,
+        )
+        .unwrap();
+        assert_eq!(providers[0].models[0].provider, "vllm");
+
+        let service = OpenAIService::compatible(&providers[0]).unwrap();
+        assert!(service.is_available());
+        assert_eq!(service.base_url, "http://localhost:8000/v1");
+        assert_eq!(service.headers["x-team"], "security");
+        assert_eq!(service.list_models()[0].name, "llama-3.1-70b");
+
+        let rejected = [
+            // Taken by a bundled provider
+            r#"[{"name": "openai", "baseUrl": "http://a", "models": [{"id": "m", "title": "M"}]}]"#,
+            r#"[{"name": "local", "baseUrl": "localhost:8000", "models": [{"id": "m", "title": "M"}]}]"#,
+            r#"[{"name": "local", "baseUrl": "http://a", "models": []}]"#,
+            r#"[{"name": "local", "baseUrl": "http://a", "models": [{"provider": "other", "id": "m", "title": "M"}]}]"#,
+            r#"[{"name": "local", "baseUrl": "http://a", "headers": {"Bad Header": "x"}, "models": [{"id": "m", "title": "M"}]}]"#,
+            r#"[{"name": "local", "baseUrl": "http://a", "apiKeyEnv": "BYTEBOT_TEST_UNSET_KEY", "models": [{"id": "m", "title": "M"}]}]"#,
+            r#"[
+                {"name": "local", "baseUrl": "http://a", "models": [{"id": "m", "title": "M"}]},
+                {"name": "local", "baseUrl": "http://b", "models": [{"id": "n", "title": "N"}]}
+            ]"#,
+        ];
+        for json in rejected {
+            assert!(
+                OpenAICompatibleProvider::list_from_json(json).is_err(),
+                "Accepted {json}"
+            );
+        }
+    }
+
     /// Integration tests that demonstrate the OpenAI service functionality
     /// These tests don't make actual API calls but verify the service setup
     mod integration_tests {
@@ -0,0 +1,353 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::TokenUsage;
+
+/// What a model can do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    /// Reads images, including the screenshots of computer use
+    #[serde(default)]
+    pub vision: bool,
+
+    /// Calls tools
+    #[serde(default)]
+    pub tools: bool,
+
+    /// Produces extended reasoning before answering
+    #[serde(default)]
+    pub thinking: bool,
+}
+
+/// List prices of a model in US dollars per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+
+    pub output: f64,
+
+    /// Input tokens read from the provider's prompt cache
+    #[serde(rename = "cacheRead", default)]
+    pub cache_read: f64,
+
+    /// Input tokens written to the provider's prompt cache
+    #[serde(rename = "cacheWrite", default)]
+    pub cache_write: f64,
+}
+
+impl ModelPricing {
+    /// Cost in US dollars of a request that used `usage`
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_read_tokens as f64 * self.cache_read
+            + usage.cache_write_tokens as f64 * self.cache_write)
+            / 1_000_000.0
+    }
+}
+
+/// Model entry of the registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Name of the provider the model is served by; may be left out where
+    /// the provider is implied, as in the model list of a provider
+    #[serde(default)]
+    pub provider: String,
+
+    /// Model id sent to the provider; `id` in configuration files
+    #[serde(alias = "id")]
+    pub name: String,
+
+    pub title: String,
+
+    /// Most tokens the model reads in one request
+    #[serde(rename = "contextWindow", default)]
+    pub context_window: u32,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+
+    #[serde(default)]
+    pub capabilities: ModelCapabilities,
+
+    /// Other names tasks may use for the model
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// Used for tasks that name no model; the first default model of an
+    /// available provider wins
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
+}
+
+impl ModelInfo {
+    /// Whether `name` is the id or one of the aliases of the model
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+}
+
+/// Models the agent can use, in order of preference.
+///
+/// Loaded from configuration; the built-in list covers the models of the
+/// bundled providers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRegistry {
+    models: Vec<ModelInfo>,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ModelRegistry {
+    /// Create a registry, rejecting entries with missing fields and names
+    /// that would be ambiguous
+    pub fn new(models: Vec<ModelInfo>) -> Result<Self, String> {
+        let mut names = HashSet::new();
+
+        for model in &models {
+            if model.provider.trim().is_empty() || model.name.trim().is_empty() {
+                return Err("Every model needs a provider and an id".to_string());
+            }
+            if model.title.trim().is_empty() {
+                return Err(format!("Model {} has no title", model.name));
+            }
+
+            for name in std::iter::once(&model.name).chain(&model.aliases) {
+                if !names.insert(name.as_str()) {
+                    return Err(format!("Model name {name} is used more than once"));
+                }
+            }
+        }
+
+        Ok(Self { models })
+    }
+
+    /// Read a registry from a JSON array of model entries
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let models =
+            serde_json::from_str(json).map_err(|e| format!("Invalid model registry: {e}"))?;
+        Self::new(models)
+    }
+
+    /// Models of the bundled Anthropic, OpenAI and Google providers
+    pub fn builtin() -> Self {
+        let models = vec![
+            model("anthropic", "claude-opus-4-20250514", "Claude Opus 4")
+                .context_window(200_000)
+                .pricing(15.0, 75.0, 1.5, 18.75)
+                .capabilities(true, true, true)
+                .aliases(&["claude-opus-4"])
+                .default(),
+            model("anthropic", "claude-sonnet-4-20250514", "Claude Sonnet 4")
+                .context_window(200_000)
+                .pricing(3.0, 15.0, 0.3, 3.75)
+                .capabilities(true, true, true)
+                .aliases(&["claude-sonnet-4"]),
+            model("openai", "gpt-4o", "GPT-4o")
+                .context_window(128_000)
+                .pricing(2.5, 10.0, 1.25, 0.0)
+                .capabilities(true, true, false)
+                .default(),
+            model("openai", "gpt-4o-mini", "GPT-4o Mini")
+                .context_window(128_000)
+                .pricing(0.15, 0.6, 0.075, 0.0)
+                .capabilities(true, true, false),
+            model("openai", "gpt-4-turbo", "GPT-4 Turbo")
+                .context_window(128_000)
+                .pricing(10.0, 30.0, 0.0, 0.0)
+                .capabilities(true, true, false),
+            model("openai", "gpt-3.5-turbo", "GPT-3.5 Turbo")
+                .context_window(16_385)
+                .pricing(0.5, 1.5, 0.0, 0.0)
+                .capabilities(false, true, false),
+            model("google", "gemini-1.5-pro", "Gemini 1.5 Pro")
+                .context_window(2_000_000)
+                .pricing(1.25, 5.0, 0.0, 0.0)
+                .capabilities(true, true, false)
+                .default(),
+            model("google", "gemini-1.5-flash", "Gemini 1.5 Flash")
+                .context_window(1_000_000)
+                .pricing(0.075, 0.3, 0.0, 0.0)
+                .capabilities(true, true, false),
+            model(
+                "google",
+                "gemini-2.0-flash-exp",
+                "Gemini 2.0 Flash (Experimental)",
+            )
+            .context_window(1_048_576)
+            .pricing(0.1, 0.4, 0.0, 0.0)
+            .capabilities(true, true, false),
+        ];
+
+        Self { models }
+    }
+
+    /// All models, in order of preference
+    pub fn models(&self) -> &[ModelInfo] {
+        &self.models
+    }
+
+    /// Registry holding only the models of `provider`
+    pub fn for_provider(&self, provider: &str) -> Self {
+        Self {
+            models: self
+                .models
+                .iter()
+                .filter(|model| model.provider == provider)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Find a model by id or alias
+    pub fn resolve(&self, name: &str) -> Option<&ModelInfo> {
+        self.models.iter().find(|model| model.matches(name))
+    }
+
+    /// Model a request should use: the one named, or the default if none is
+    pub fn select(&self, name: Option<&str>) -> Option<&ModelInfo> {
+        match name {
+            Some(name) => self.resolve(name),
+            None => self.default_model(),
+        }
+    }
+
+    /// First model marked as default, or the first model if none is
+    pub fn default_model(&self) -> Option<&ModelInfo> {
+        self.models
+            .iter()
+            .find(|model| model.default)
+            .or_else(|| self.models.first())
+    }
+}
+
+/// Start a built-in model entry
+fn model(provider: &str, name: &str, title: &str) -> ModelInfo {
+    ModelInfo {
+        provider: provider.to_string(),
+        name: name.to_string(),
+        title: title.to_string(),
+        context_window: 0,
+        pricing: None,
+        capabilities: ModelCapabilities::default(),
+        aliases: Vec::new(),
+        default: false,
+    }
+}
+
+impl ModelInfo {
+    fn context_window(mut self, tokens: u32) -> Self {
+        self.context_window = tokens;
+        self
+    }
+
+    fn pricing(mut self, input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        self.pricing = Some(ModelPricing {
+            input,
+            output,
+            cache_read,
+            cache_write,
+        });
+        self
+    }
+
+    fn capabilities(mut self, vision: bool, tools: bool, thinking: bool) -> Self {
+        self.capabilities = ModelCapabilities {
+            vision,
+            tools,
+            thinking,
+        };
+        self
+    }
+
+    fn aliases(mut self, aliases: &[&str]) -> Self {
+        self.aliases = aliases.iter().map(|alias| alias.to_string()).collect();
+        self
+    }
+
+    fn default(mut self) -> Self {
+        self.default = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_cost() {
+        let sonnet = ModelRegistry::builtin()
+            .resolve("claude-sonnet-4")
+            .and_then(|model| model.pricing)
+            .unwrap();
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_tokens: 2_000_000,
+            cache_write_tokens: 400_000,
+        };
+
+        // $3 input + $1.50 output + $0.60 cache reads + $1.50 cache writes
+        assert!((sonnet.cost(&usage) - 6.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_builtin_registry() {
+        let registry = ModelRegistry::builtin();
+        assert!(ModelRegistry::new(registry.models().to_vec()).is_ok());
+
+        let opus = registry.resolve("claude-opus-4").unwrap();
+        assert_eq!(opus.name, "claude-opus-4-20250514");
+        assert!(opus.capabilities.vision);
+
+        let openai = registry.for_provider("openai");
+        assert_eq!(openai.models().len(), 4);
+        assert_eq!(openai.default_model().unwrap().name, "gpt-4o");
+    }
+
+    #[test]
+    fn test_registry_from_json() {
+        let registry = ModelRegistry::from_json(
+            r#"[{
+                "provider": "openai",
+                "id": "gpt-4.1",
+                "title": "GPT-4.1",
+                "contextWindow": 1047576,
+                "pricing": {"input": 2.0, "output": 8.0, "cacheRead": 0.5},
+                "capabilities": {"vision": true, "tools": true},
+                "aliases": ["gpt-4.1-latest"]
+            }]"#,
+        )
+        .unwrap();
+
+        let model = registry.resolve("gpt-4.1-latest").unwrap();
+        assert_eq!(model.name, "gpt-4.1");
+        assert_eq!(model.pricing.unwrap().cache_write, 0.0);
+        assert!(!model.capabilities.thinking);
+        assert_eq!(registry.default_model().unwrap().name, "gpt-4.1");
+    }
+
+    #[test]
+    fn test_registry_rejects_ambiguous_names() {
+        let error = ModelRegistry::from_json(
+            r#"[
+                {"provider": "openai", "id": "gpt-4o", "title": "GPT-4o"},
+                {"provider": "local", "id": "llama", "title": "Llama", "aliases": ["gpt-4o"]}
+            ]"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("gpt-4o"));
+
+        assert!(
+            ModelRegistry::from_json(r#"[{"provider": "openai", "id": "", "title": "X"}]"#)
+                .is_err()
+        );
+        assert!(ModelRegistry::from_json("{}").is_err());
+    }
+}
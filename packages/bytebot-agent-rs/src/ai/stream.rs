@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+
+use bytebot_shared_rs::types::message::MessageContentBlock;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::error::AIError;
+
+/// Piece of a response sent while the model is still generating it.
+///
+/// `index` identifies the content block the piece belongs to; blocks keep
+/// the order of their indexes in the final message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamDelta {
+    /// Text appended to a text block
+    Text { index: usize, text: String },
+    /// Reasoning appended to a thinking block
+    Thinking { index: usize, thinking: String },
+    /// A tool call started
+    ToolUseStart {
+        index: usize,
+        id: String,
+        name: String,
+    },
+    /// Fragment of the JSON input of a tool call
+    ToolUseInput {
+        index: usize,
+        #[serde(rename = "partialJson")]
+        partial_json: String,
+    },
+}
+
+/// Channel the deltas of a streamed response are sent to
+pub type DeltaSender = mpsc::UnboundedSender<StreamDelta>;
+
+/// Splits a server-sent event stream into the data of its events
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the body and return the data of every event it
+    /// completes. Chunks may end anywhere, even inside a UTF-8 character.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data
+                    .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+            // Event names, ids and comments carry nothing the providers need
+        }
+
+        events
+    }
+
+    /// Data of an event left unterminated when the stream ended
+    pub fn finish(mut self) -> Option<String> {
+        let events = self.push(b"\n\n");
+        events.into_iter().next()
+    }
+}
+
+/// Read an SSE response body to the end, handing the data of each event to
+/// `on_event`
+pub async fn read_sse(
+    mut response: reqwest::Response,
+    mut on_event: impl FnMut(&str) -> Result<(), AIError>,
+) -> Result<(), AIError> {
+    let mut parser = SseParser::new();
+
+    while let Some(chunk) = response.chunk().await.map_err(AIError::Http)? {
+        for event in parser.push(&chunk) {
+            on_event(&event)?;
+        }
+    }
+
+    match parser.finish() {
+        Some(event) => on_event(&event),
+        None => Ok(()),
+    }
+}
+
+/// Content block under construction
+#[derive(Debug)]
+enum PartialBlock {
+    Text(String),
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking(String),
+    ToolUse {
+        id: String,
+        name: String,
+        input_json: String,
+    },
+}
+
+/// Builds the content of a streamed response while forwarding each piece
+/// to the listener
+pub struct StreamAssembler {
+    blocks: BTreeMap<usize, PartialBlock>,
+    deltas: DeltaSender,
+}
+
+impl StreamAssembler {
+    pub fn new(deltas: DeltaSender) -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+            deltas,
+        }
+    }
+
+    fn send(&self, delta: StreamDelta) {
+        // Nobody listening is fine; the response is still assembled
+        let _ = self.deltas.send(delta);
+    }
+
+    pub fn text(&mut self, index: usize, text: &str) {
+        match self
+            .blocks
+            .entry(index)
+            .or_insert_with(|| PartialBlock::Text(String::new()))
+        {
+            PartialBlock::Text(content) => content.push_str(text),
+            _ => return,
+        }
+
+        if !text.is_empty() {
+            self.send(StreamDelta::Text {
+                index,
+                text: text.to_string(),
+            });
+        }
+    }
+
+    pub fn thinking(&mut self, index: usize, thinking: &str) {
+        match self
+            .blocks
+            .entry(index)
+            .or_insert_with(|| PartialBlock::Thinking {
+                thinking: String::new(),
+                signature: String::new(),
+            }) {
+            PartialBlock::Thinking {
+                thinking: content, ..
+            } => content.push_str(thinking),
+            _ => return,
+        }
+
+        if !thinking.is_empty() {
+            self.send(StreamDelta::Thinking {
+                index,
+                thinking: thinking.to_string(),
+            });
+        }
+    }
+
+    /// Signature proving a thinking block is unaltered; never shown to users
+    pub fn signature(&mut self, index: usize, signature: &str) {
+        if let Some(PartialBlock::Thinking {
+            signature: content, ..
+        }) = self.blocks.get_mut(&index)
+        {
+            content.push_str(signature);
+        }
+    }
+
+    pub fn redacted_thinking(&mut self, index: usize, data: String) {
+        self.blocks
+            .insert(index, PartialBlock::RedactedThinking(data));
+    }
+
+    pub fn tool_use_start(&mut self, index: usize, id: String, name: String) {
+        self.send(StreamDelta::ToolUseStart {
+            index,
+            id: id.clone(),
+            name: name.clone(),
+        });
+        self.blocks.insert(
+            index,
+            PartialBlock::ToolUse {
+                id,
+                name,
+                input_json: String::new(),
+            },
+        );
+    }
+
+    pub fn tool_use_input(&mut self, index: usize, partial_json: &str) {
+        if let Some(PartialBlock::ToolUse { input_json, .. }) = self.blocks.get_mut(&index) {
+            input_json.push_str(partial_json);
+            if !partial_json.is_empty() {
+                self.send(StreamDelta::ToolUseInput {
+                    index,
+                    partial_json: partial_json.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Content of the complete response, in block order
+    pub fn finish(self) -> Vec<MessageContentBlock> {
+        self.blocks
+            .into_values()
+            .filter_map(|block| match block {
+                PartialBlock::Text(text) if text.is_empty() => None,
+                PartialBlock::Text(text) => Some(MessageContentBlock::Text { text }),
+                PartialBlock::Thinking {
+                    thinking,
+                    signature,
+                } => Some(MessageContentBlock::Thinking {
+                    thinking,
+                    signature,
+                }),
+                PartialBlock::RedactedThinking(data) => {
+                    Some(MessageContentBlock::RedactedThinking { data })
+                }
+                PartialBlock::ToolUse {
+                    id,
+                    name,
+                    input_json,
+                } => {
+                    // A call without arguments streams no input at all
+                    let input =
+                        serde_json::from_str(&input_json).unwrap_or_else(|_| serde_json::json!({}));
+                    Some(MessageContentBlock::ToolUse { id, name, input })
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_split_chunks() {
+        let mut parser = SseParser::new();
+
+        assert!(parser.push(b"event: ping\ndata: {\"a\":").is_empty());
+        assert_eq!(
+            parser.push(b"1}\r\n\r\n: comment\n\ndata: x\n"),
+            vec!["{\"a\":1}"]
+        );
+        assert!(parser.push(b"data: y\n").is_empty());
+        assert_eq!(parser.finish(), Some("x\ny".to_string()));
+    }
+
+    #[test]
+    fn test_sse_parser_split_utf8() {
+        let mut parser = SseParser::new();
+        let bytes = "data: héllo\n\n".as_bytes();
+
+        assert!(parser.push(&bytes[..8]).is_empty());
+        assert_eq!(parser.push(&bytes[8..]), vec!["héllo"]);
+    }
+
+    #[test]
+    fn test_stream_assembler() {
+        let (deltas, mut receiver) = mpsc::unbounded_channel();
+        let mut assembler = StreamAssembler::new(deltas);
+
+        assembler.thinking(0, "Need a ");
+        assembler.thinking(0, "screenshot");
+        assembler.signature(0, "sig");
+        assembler.text(1, "");
+        assembler.tool_use_start(2, "call_1".to_string(), "click_mouse".to_string());
+        assembler.tool_use_input(2, "{\"button\":");
+        assembler.tool_use_input(2, "\"left\"}");
+        assembler.tool_use_start(3, "call_2".to_string(), "screenshot".to_string());
+
+        let content = assembler.finish();
+        assert_eq!(content.len(), 3);
+        assert!(matches!(
+            &content[0],
+            MessageContentBlock::Thinking { thinking, signature }
+                if thinking == "Need a screenshot" && signature == "sig"
+        ));
+        assert!(matches!(
+            &content[1],
+            MessageContentBlock::ToolUse { input, .. } if *input == json!({"button": "left"})
+        ));
+        assert!(matches!(
+            &content[2],
+            MessageContentBlock::ToolUse { input, .. } if *input == json!({})
+        ));
+
+        let mut received = Vec::new();
+        while let Ok(delta) = receiver.try_recv() {
+            received.push(delta);
+        }
+        assert_eq!(received.len(), 6);
+        assert_eq!(
+            received[3],
+            StreamDelta::ToolUseInput {
+                index: 2,
+                partial_json: "{\"button\":".to_string()
+            }
+        );
+    }
+}
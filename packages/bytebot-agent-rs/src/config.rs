@@ -1,7 +1,10 @@
 use std::env;
 
+use bytebot_shared_rs::types::approval::ApprovalPolicy;
 use serde::Deserialize;
 
+use crate::ai::{openai::OpenAICompatibleProvider, registry::ModelRegistry};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub database_url: String,
@@ -14,6 +17,36 @@ pub struct Config {
     pub cors_origins: Vec<String>,
     pub log_level: String,
     pub server: ServerConfig,
+    #[serde(default = "default_bytebot_desktop_base_url")]
+    pub bytebot_desktop_base_url: String,
+    #[serde(default = "default_task_poll_interval_ms")]
+    pub task_poll_interval_ms: u64,
+    #[serde(default = "default_max_task_iterations")]
+    pub max_task_iterations: u32,
+    #[serde(default = "default_scheduler_poll_interval_ms")]
+    pub scheduler_poll_interval_ms: u64,
+    /// Maximum number of tasks running at once on the desktop
+    #[serde(default = "default_max_concurrent_tasks")]
+    pub max_concurrent_tasks: u32,
+    /// Let URGENT tasks push running LOW tasks back to the queue when at capacity
+    #[serde(default)]
+    pub task_preemption_enabled: bool,
+    /// Estimated history size in tokens above which older messages are summarised (0 disables)
+    #[serde(default = "default_context_compaction_threshold_tokens")]
+    pub context_compaction_threshold_tokens: u32,
+    /// Rules for desktop actions that wait for a human approval, read from
+    /// `APPROVAL_POLICY` as a JSON array
+    #[serde(skip)]
+    pub approval_policy: ApprovalPolicy,
+    /// Models and the providers serving them, read from the JSON file at
+    /// `MODEL_REGISTRY_FILE` or inline from `MODEL_REGISTRY`; the built-in
+    /// models otherwise
+    #[serde(skip)]
+    pub model_registry: ModelRegistry,
+    /// Self-hosted OpenAI-compatible endpoints, read from
+    /// `OPENAI_COMPATIBLE_PROVIDERS` as a JSON array
+    #[serde(skip)]
+    pub openai_compatible_providers: Vec<OpenAICompatibleProvider>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -29,13 +62,66 @@ pub enum ConfigError {
     Env(#[from] env::VarError),
     #[error("Configuration parsing error: {0}")]
     Parse(#[from] envy::Error),
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+fn default_bytebot_desktop_base_url() -> String {
+    "http://localhost:9990".to_string()
+}
+
+fn default_task_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_max_task_iterations() -> u32 {
+    100
+}
+
+fn default_scheduler_poll_interval_ms() -> u64 {
+    10000
+}
+
+fn default_max_concurrent_tasks() -> u32 {
+    1
+}
+
+fn default_context_compaction_threshold_tokens() -> u32 {
+    100_000
+}
+
+/// Read the model registry named by the environment, if any
+fn model_registry_from_env() -> Result<Option<ModelRegistry>, String> {
+    if let Ok(path) = env::var("MODEL_REGISTRY_FILE") {
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read model registry {path}: {e}"))?;
+        return ModelRegistry::from_json(&json).map(Some);
+    }
+
+    match env::var("MODEL_REGISTRY") {
+        Ok(json) => ModelRegistry::from_json(&json).map(Some),
+        Err(_) => Ok(None),
+    }
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
         dotenvy::dotenv().ok(); // Load .env file if present
 
-        let config = envy::from_env::<Config>()?;
+        let mut config = envy::from_env::<Config>()?;
+        if let Ok(policy) = env::var("APPROVAL_POLICY") {
+            config.approval_policy =
+                ApprovalPolicy::from_json(&policy).map_err(ConfigError::Invalid)?;
+        }
+        if let Some(registry) = model_registry_from_env().map_err(ConfigError::Invalid)? {
+            config.model_registry = registry;
+        }
+        if let Ok(providers) = env::var("OPENAI_COMPATIBLE_PROVIDERS") {
+            config.openai_compatible_providers =
+                OpenAICompatibleProvider::list_from_json(&providers)
+                    .map_err(ConfigError::Invalid)?;
+        }
+
         Ok(config)
     }
 
@@ -73,6 +159,41 @@ impl Default for Config {
                     .unwrap_or(9991),
                 workers: env::var("WORKERS").ok().and_then(|w| w.parse().ok()),
             },
+            bytebot_desktop_base_url: env::var("BYTEBOT_DESKTOP_BASE_URL")
+                .unwrap_or_else(|_| default_bytebot_desktop_base_url()),
+            task_poll_interval_ms: env::var("TASK_POLL_INTERVAL_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_task_poll_interval_ms),
+            max_task_iterations: env::var("MAX_TASK_ITERATIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_max_task_iterations),
+            scheduler_poll_interval_ms: env::var("SCHEDULER_POLL_INTERVAL_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_scheduler_poll_interval_ms),
+            max_concurrent_tasks: env::var("MAX_CONCURRENT_TASKS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_max_concurrent_tasks),
+            task_preemption_enabled: env::var("TASK_PREEMPTION_ENABLED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false),
+            context_compaction_threshold_tokens: env::var("CONTEXT_COMPACTION_THRESHOLD_TOKENS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(default_context_compaction_threshold_tokens),
+            approval_policy: env::var("APPROVAL_POLICY")
+                .ok()
+                .and_then(|policy| ApprovalPolicy::from_json(&policy).ok())
+                .unwrap_or_default(),
+            model_registry: model_registry_from_env().ok().flatten().unwrap_or_default(),
+            openai_compatible_providers: env::var("OPENAI_COMPATIBLE_PROVIDERS")
+                .ok()
+                .and_then(|providers| OpenAICompatibleProvider::list_from_json(&providers).ok())
+                .unwrap_or_default(),
         }
     }
 }
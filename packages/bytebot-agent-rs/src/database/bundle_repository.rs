@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use bytebot_shared_rs::types::{
+    bundle::TaskBundle,
+    message::Message,
+    task::{Task, TaskStatus},
+};
+use chrono::Utc;
+use sqlx::{types::Json, Pool, Postgres, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+    task_repository::TASK_COLUMNS, DatabaseError, FileRepository, SummaryRepository, TaskRepository,
+};
+
+/// Task bundle repository trait for dependency injection and testing
+#[async_trait]
+pub trait BundleRepositoryTrait: Send + Sync {
+    /// Snapshot a task with its messages, summaries and files, or `None` if
+    /// the task does not exist
+    async fn export(&self, task_id: &str) -> Result<Option<TaskBundle>, DatabaseError>;
+    /// Restore a bundle as a new task owned by `user_id`, with fresh IDs for
+    /// the task and everything in it
+    async fn import(
+        &self,
+        bundle: &TaskBundle,
+        user_id: Option<&str>,
+    ) -> Result<Task, DatabaseError>;
+}
+
+/// SQLx-based task bundle repository implementation
+pub struct BundleRepository {
+    pool: Pool<Postgres>,
+}
+
+impl BundleRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BundleRepositoryTrait for BundleRepository {
+    async fn export(&self, task_id: &str) -> Result<Option<TaskBundle>, DatabaseError> {
+        debug!("Exporting task: {}", task_id);
+
+        // One snapshot, so messages added while exporting cannot tear the bundle
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await
+            .map_err(DatabaseError::QueryError)?;
+
+        let row = sqlx::query(&format!(
+            r#"SELECT {TASK_COLUMNS} FROM "Task" WHERE id = $1"#
+        ))
+        .bind(task_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch task {} for export: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = match row {
+            Some(row) => TaskRepository::task_from_row(&row)?,
+            None => return Ok(None),
+        };
+
+        let message_rows = sqlx::query(
+            r#"
+            SELECT id, content, role, "createdAt", "updatedAt", "taskId", "summaryId", "userId"
+            FROM "Message"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to fetch messages of task {} for export: {}",
+                task_id, e
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        let messages: Vec<Message> = message_rows
+            .iter()
+            .map(|row| Message {
+                id: row.get("id"),
+                content: row.get("content"),
+                role: row.get("role"),
+                created_at: row.get("createdAt"),
+                updated_at: row.get("updatedAt"),
+                task_id: row.get("taskId"),
+                summary_id: row.get("summaryId"),
+                user_id: row.get("userId"),
+            })
+            .collect();
+
+        let summary_rows = sqlx::query(
+            r#"
+            SELECT id, content, "createdAt", "updatedAt", "taskId", "parentId"
+            FROM "Summary"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to fetch summaries of task {} for export: {}",
+                task_id, e
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        let file_rows = sqlx::query(
+            r#"
+            SELECT id, name, type, size, data, "createdAt", "updatedAt", "taskId"
+            FROM "File"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to fetch files of task {} for export: {}",
+                task_id, e
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
+
+        let bundle = TaskBundle::new(
+            task,
+            messages,
+            summary_rows
+                .iter()
+                .map(SummaryRepository::summary_from_row)
+                .collect(),
+            file_rows
+                .iter()
+                .map(FileRepository::file_from_row)
+                .collect(),
+        );
+
+        info!(
+            "Exported task {} with {} messages, {} summaries and {} files",
+            task_id,
+            bundle.messages.len(),
+            bundle.summaries.len(),
+            bundle.files.len()
+        );
+
+        Ok(Some(bundle))
+    }
+
+    async fn import(
+        &self,
+        bundle: &TaskBundle,
+        user_id: Option<&str>,
+    ) -> Result<Task, DatabaseError> {
+        let source = &bundle.task;
+        debug!(
+            "Importing task {} exported at {}",
+            source.id, bundle.exported_at
+        );
+
+        for file in &bundle.files {
+            file.validate().map_err(|e| {
+                DatabaseError::ValidationError(format!("Invalid file {}: {e}", file.name))
+            })?;
+            file.validate_data().map_err(|e| {
+                DatabaseError::ValidationError(format!("Invalid file {}: {e}", file.name))
+            })?;
+        }
+
+        // An unfinished task waits for someone to resume it instead of running
+        // on its own in the new deployment
+        let status = if source.is_terminal() {
+            source.status
+        } else {
+            TaskStatus::NeedsHelp
+        };
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+
+        // Schedules, retries, forks and dependencies point at rows of the old
+        // deployment, so they are left behind
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO "Task" (
+                id, description, type, status, priority, control,
+                "createdAt", "createdBy", "scheduledFor", "updatedAt",
+                "executedAt", "completedAt", "queuedAt", error, result, model,
+                "userId", "resultSchema", "retryPolicy", attempt, budget, usage, labels
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
+                $15, $16, $17, $18, $19, $20, $21, $22, $23
+            )
+            RETURNING
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(&source.description)
+        .bind(source.task_type)
+        .bind(status)
+        .bind(source.priority)
+        .bind(source.control)
+        .bind(source.created_at)
+        .bind(source.created_by)
+        .bind(source.scheduled_for)
+        .bind(now)
+        .bind(source.executed_at)
+        .bind(source.completed_at)
+        .bind(source.queued_at)
+        .bind(&source.error)
+        .bind(&source.result)
+        .bind(&source.model)
+        .bind(user_id)
+        .bind(&source.result_schema)
+        .bind(source.retry_policy.as_ref().map(Json))
+        .bind(source.attempt)
+        .bind(source.budget.as_ref().map(Json))
+        .bind(Json(&source.usage))
+        .bind(&source.labels)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to import task {}: {}", source.id, e);
+            DatabaseError::QueryError(e)
+        })?;
+        let task = TaskRepository::task_from_row(&row)?;
+
+        // Summaries come parents first, so a parent is always remapped before
+        // the summaries built on it
+        let mut summary_ids = HashMap::new();
+        for summary in &bundle.summaries {
+            let id = Uuid::new_v4().to_string();
+            let parent_id = summary
+                .parent_id
+                .as_ref()
+                .and_then(|parent| summary_ids.get(parent));
+
+            sqlx::query(
+                r#"
+                INSERT INTO "Summary" (id, content, "createdAt", "updatedAt", "taskId", "parentId")
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(&id)
+            .bind(&summary.content)
+            .bind(summary.created_at)
+            .bind(now)
+            .bind(&task.id)
+            .bind(parent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to import summary into task {}: {}", task.id, e);
+                DatabaseError::QueryError(e)
+            })?;
+
+            summary_ids.insert(summary.id.clone(), id);
+        }
+
+        for message in &bundle.messages {
+            sqlx::query(
+                r#"
+                INSERT INTO "Message" (
+                    id, content, role, "createdAt", "updatedAt",
+                    "taskId", "summaryId", "userId"
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&message.content)
+            .bind(message.role)
+            .bind(message.created_at)
+            .bind(now)
+            .bind(&task.id)
+            .bind(
+                message
+                    .summary_id
+                    .as_ref()
+                    .and_then(|summary| summary_ids.get(summary)),
+            )
+            .bind(message.user_id.as_ref().and(user_id))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to import message into task {}: {}", task.id, e);
+                DatabaseError::QueryError(e)
+            })?;
+        }
+
+        for file in &bundle.files {
+            sqlx::query(
+                r#"
+                INSERT INTO "File" (id, name, type, size, data, "createdAt", "updatedAt", "taskId")
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&file.name)
+            .bind(&file.file_type)
+            .bind(file.size)
+            .bind(&file.data)
+            .bind(file.created_at)
+            .bind(now)
+            .bind(&task.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to import file {} into task {}: {}",
+                    file.name, task.id, e
+                );
+                DatabaseError::QueryError(e)
+            })?;
+        }
+
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
+
+        info!(
+            "Imported task {} as {} with {} messages",
+            source.id,
+            task.id,
+            bundle.messages.len()
+        );
+
+        Ok(task)
+    }
+}
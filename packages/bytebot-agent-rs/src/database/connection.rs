@@ -4,8 +4,12 @@ use sqlx::{postgres::PgPoolOptions, Pool, Postgres, Row};
 use tracing::{error, info, warn};
 
 use super::{
-    MessageRepository, MessageRepositoryTrait, TaskRepository, TaskRepositoryTrait, UserRepository,
-    UserRepositoryTrait,
+    BundleRepository, BundleRepositoryTrait, FileRepository, FileRepositoryTrait,
+    MessageRepository, MessageRepositoryTrait, ReplayRepository, ReplayRepositoryTrait,
+    SearchRepository, SearchRepositoryTrait, SummaryRepository, SummaryRepositoryTrait,
+    TaskRepository, TaskRepositoryTrait, TemplateRepository, TemplateRepositoryTrait,
+    UsageRepository, UsageRepositoryTrait, UserRepository, UserRepositoryTrait, ViewRepository,
+    ViewRepositoryTrait,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -72,6 +76,11 @@ impl DatabaseManager {
         Ok(manager)
     }
 
+    /// Wrap an existing connection pool
+    pub fn from_pool(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
     /// Get a reference to the connection pool
     pub fn pool(&self) -> &DatabasePool {
         &self.pool
@@ -130,11 +139,51 @@ impl DatabaseManager {
         MessageRepository::new(self.pool.clone())
     }
 
+    /// Get a file repository instance
+    pub fn file_repository(&self) -> impl FileRepositoryTrait {
+        FileRepository::new(self.pool.clone())
+    }
+
+    /// Get a summary repository instance
+    pub fn summary_repository(&self) -> impl SummaryRepositoryTrait {
+        SummaryRepository::new(self.pool.clone())
+    }
+
+    /// Get a task bundle repository instance
+    pub fn bundle_repository(&self) -> impl BundleRepositoryTrait {
+        BundleRepository::new(self.pool.clone())
+    }
+
+    /// Get a task replay repository instance
+    pub fn replay_repository(&self) -> impl ReplayRepositoryTrait {
+        ReplayRepository::new(self.pool.clone())
+    }
+
+    /// Get a search repository instance
+    pub fn search_repository(&self) -> impl SearchRepositoryTrait {
+        SearchRepository::new(self.pool.clone())
+    }
+
+    /// Get a task template repository instance
+    pub fn template_repository(&self) -> impl TemplateRepositoryTrait {
+        TemplateRepository::new(self.pool.clone())
+    }
+
+    /// Get a model usage repository instance
+    pub fn usage_repository(&self) -> impl UsageRepositoryTrait {
+        UsageRepository::new(self.pool.clone())
+    }
+
     /// Get a user repository instance
     pub fn user_repository(&self) -> impl UserRepositoryTrait {
         UserRepository::new(self.pool.clone())
     }
 
+    /// Get a saved view repository instance
+    pub fn view_repository(&self) -> impl ViewRepositoryTrait {
+        ViewRepository::new(self.pool.clone())
+    }
+
     /// Close the database connection pool
     pub async fn close(&self) {
         info!("Closing database connection pool...");
@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+use bytebot_shared_rs::types::{api::TaskFileDto, user::File};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
+use tracing::{debug, error, info};
+use validator::Validate;
+
+use super::DatabaseError;
+
+/// File metadata without its contents, used for listings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub size: i32,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+}
+
+/// File repository trait for dependency injection and testing
+#[async_trait]
+pub trait FileRepositoryTrait: Send + Sync {
+    async fn list_by_task_id(&self, task_id: &str) -> Result<Vec<FileInfo>, DatabaseError>;
+    async fn get_by_task_id(&self, task_id: &str) -> Result<Vec<File>, DatabaseError>;
+    async fn get_by_id(&self, task_id: &str, id: &str) -> Result<Option<File>, DatabaseError>;
+    async fn delete(&self, task_id: &str, id: &str) -> Result<bool, DatabaseError>;
+}
+
+/// SQLx-based file repository implementation
+pub struct FileRepository {
+    pool: Pool<Postgres>,
+}
+
+impl FileRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Validate attachments and insert them for a task inside an open transaction
+    pub async fn insert_for_task(
+        tx: &mut Transaction<'_, Postgres>,
+        task_id: &str,
+        files: &[TaskFileDto],
+    ) -> Result<(), DatabaseError> {
+        for dto in files {
+            let size = i32::try_from(dto.size).map_err(|_| {
+                DatabaseError::ValidationError(format!("File {} is too large", dto.name))
+            })?;
+            let file = File::new(
+                dto.name.clone(),
+                dto.r#type.clone(),
+                size,
+                dto.base64.clone(),
+                task_id.to_string(),
+            );
+            file.validate().map_err(|e| {
+                DatabaseError::ValidationError(format!("Invalid file {}: {e}", dto.name))
+            })?;
+            file.validate_data().map_err(|e| {
+                DatabaseError::ValidationError(format!("Invalid file {}: {e}", dto.name))
+            })?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO "File" (id, name, type, size, data, "createdAt", "updatedAt", "taskId")
+                VALUES ($1, $2, $3, $4, $5, $6, $6, $7)
+                "#,
+            )
+            .bind(&file.id)
+            .bind(&file.name)
+            .bind(&file.file_type)
+            .bind(file.size)
+            .bind(&file.data)
+            .bind(file.created_at)
+            .bind(&file.task_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to store file {} for task {}: {}",
+                    file.name, task_id, e
+                );
+                DatabaseError::QueryError(e)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn file_from_row(row: &PgRow) -> File {
+        File {
+            id: row.get("id"),
+            name: row.get("name"),
+            file_type: row.get("type"),
+            size: row.get("size"),
+            data: row.get("data"),
+            created_at: row.get("createdAt"),
+            updated_at: row.get("updatedAt"),
+            task_id: row.get("taskId"),
+        }
+    }
+}
+
+#[async_trait]
+impl FileRepositoryTrait for FileRepository {
+    async fn list_by_task_id(&self, task_id: &str) -> Result<Vec<FileInfo>, DatabaseError> {
+        debug!("Listing files for task: {}", task_id);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, type, size, "createdAt", "taskId"
+            FROM "File"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list files for task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| FileInfo {
+                id: row.get("id"),
+                name: row.get("name"),
+                file_type: row.get("type"),
+                size: row.get("size"),
+                created_at: row.get("createdAt"),
+                task_id: row.get("taskId"),
+            })
+            .collect())
+    }
+
+    async fn get_by_task_id(&self, task_id: &str) -> Result<Vec<File>, DatabaseError> {
+        debug!("Fetching files for task: {}", task_id);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, type, size, data, "createdAt", "updatedAt", "taskId"
+            FROM "File"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch files for task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.iter().map(Self::file_from_row).collect())
+    }
+
+    async fn get_by_id(&self, task_id: &str, id: &str) -> Result<Option<File>, DatabaseError> {
+        debug!("Fetching file {} of task {}", id, task_id);
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, type, size, data, "createdAt", "updatedAt", "taskId"
+            FROM "File"
+            WHERE id = $1 AND "taskId" = $2
+            "#,
+        )
+        .bind(id)
+        .bind(task_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch file {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.as_ref().map(Self::file_from_row))
+    }
+
+    async fn delete(&self, task_id: &str, id: &str) -> Result<bool, DatabaseError> {
+        debug!("Deleting file {} of task {}", id, task_id);
+
+        let result = sqlx::query(r#"DELETE FROM "File" WHERE id = $1 AND "taskId" = $2"#)
+            .bind(id)
+            .bind(task_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete file {}: {}", id, e);
+                DatabaseError::QueryError(e)
+            })?;
+
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            info!("Deleted file {} of task {}", id, task_id);
+        }
+
+        Ok(deleted)
+    }
+}
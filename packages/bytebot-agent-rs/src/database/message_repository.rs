@@ -226,7 +226,7 @@ impl MessageRepositoryTrait for MessageRepository {
         )
         .bind(&message_id)
         .bind(&content_json)
-        .bind(dto.role.to_string())
+        .bind(dto.role)
         .bind(now)
         .bind(now)
         .bind(&dto.task_id)
@@ -242,10 +242,7 @@ impl MessageRepositoryTrait for MessageRepository {
         let message = Message {
             id: row.get("id"),
             content: row.get("content"),
-            role: row
-                .get::<String, _>("role")
-                .parse()
-                .map_err(|_| DatabaseError::SerializationError("Invalid role".to_string()))?,
+            role: row.get("role"),
             created_at: row.get("createdAt"),
             updated_at: row.get("updatedAt"),
             task_id: row.get("taskId"),
@@ -289,9 +286,7 @@ impl MessageRepositoryTrait for MessageRepository {
                 Some(Message {
                     id: row.get("id"),
                     content: row.get("content"),
-                    role: row.get::<String, _>("role").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid role".to_string())
-                    })?,
+                    role: row.get("role"),
                     created_at: row.get("createdAt"),
                     updated_at: row.get("updatedAt"),
                     task_id: row.get("taskId"),
@@ -376,9 +371,7 @@ impl MessageRepositoryTrait for MessageRepository {
                 Some(Message {
                     id: row.get("id"),
                     content: row.get("content"),
-                    role: row.get::<String, _>("role").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid role".to_string())
-                    })?,
+                    role: row.get("role"),
                     created_at: row.get("createdAt"),
                     updated_at: row.get("updatedAt"),
                     task_id: row.get("taskId"),
@@ -474,9 +467,7 @@ impl MessageRepositoryTrait for MessageRepository {
                 Ok::<Message, DatabaseError>(Message {
                     id: row.get("id"),
                     content: row.get("content"),
-                    role: row.get::<String, _>("role").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid role".to_string())
-                    })?,
+                    role: row.get("role"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
                     task_id: row.get("task_id"),
@@ -525,9 +516,7 @@ impl MessageRepositoryTrait for MessageRepository {
                 Ok::<Message, DatabaseError>(Message {
                     id: row.get("id"),
                     content: row.get("content"),
-                    role: row.get::<String, _>("role").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid role".to_string())
-                    })?,
+                    role: row.get("role"),
                     created_at: row.get("createdAt"),
                     updated_at: row.get("updatedAt"),
                     task_id: row.get("taskId"),
@@ -601,9 +590,7 @@ impl MessageRepositoryTrait for MessageRepository {
                 Ok::<Message, DatabaseError>(Message {
                     id: row.get("id"),
                     content: row.get("content"),
-                    role: row.get::<String, _>("role").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid role".to_string())
-                    })?,
+                    role: row.get("role"),
                     created_at: row.get("createdAt"),
                     updated_at: row.get("updatedAt"),
                     task_id: row.get("taskId"),
@@ -703,9 +690,7 @@ impl MessageRepositoryTrait for MessageRepository {
                 Ok::<Message, DatabaseError>(Message {
                     id: row.get("id"),
                     content: row.get("content"),
-                    role: row.get::<String, _>("role").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid role".to_string())
-                    })?,
+                    role: row.get("role"),
                     created_at: row.get("createdAt"),
                     updated_at: row.get("updatedAt"),
                     task_id: row.get("taskId"),
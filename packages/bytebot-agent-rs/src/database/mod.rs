@@ -1,14 +1,30 @@
+pub mod bundle_repository;
 pub mod connection;
+pub mod file_repository;
 pub mod message_repository;
 pub mod migrations;
+pub mod replay_repository;
+pub mod search_repository;
+pub mod summary_repository;
 pub mod task_repository;
+pub mod template_repository;
+pub mod usage_repository;
 pub mod user_repository;
+pub mod view_repository;
 
 #[cfg(test)]
 pub mod tests;
 
+pub use bundle_repository::*;
 pub use connection::*;
+pub use file_repository::*;
 pub use message_repository::*;
 pub use migrations::*;
+pub use replay_repository::*;
+pub use search_repository::*;
+pub use summary_repository::*;
 pub use task_repository::*;
+pub use template_repository::*;
+pub use usage_repository::*;
 pub use user_repository::*;
+pub use view_repository::*;
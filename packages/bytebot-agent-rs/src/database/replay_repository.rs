@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use bytebot_shared_rs::types::replay::{ReplayOptions, ReplayStatus, ReplayStep, TaskReplay};
+use chrono::Utc;
+use sqlx::{postgres::PgRow, types::Json, Pool, Postgres, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use super::{task_repository::QUEUE_LOCK_KEY, DatabaseError};
+
+const REPLAY_COLUMNS: &str = r#"
+    id,
+    "taskId",
+    status,
+    options,
+    steps,
+    error,
+    "createdAt",
+    "completedAt"
+"#;
+
+/// Task replay repository trait for dependency injection and testing
+#[async_trait]
+pub trait ReplayRepositoryTrait: Send + Sync {
+    /// Record a new running replay of a task, or `None` if a task or another
+    /// replay is using the desktop. No task is claimed while the replay runs.
+    async fn create(
+        &self,
+        task_id: &str,
+        options: &ReplayOptions,
+    ) -> Result<Option<TaskReplay>, DatabaseError>;
+    async fn get_by_id(&self, id: &str) -> Result<Option<TaskReplay>, DatabaseError>;
+    /// Replays of a task, newest first
+    async fn list_by_task_id(&self, task_id: &str) -> Result<Vec<TaskReplay>, DatabaseError>;
+    /// Append the outcome of a step to a running replay
+    async fn add_step(&self, id: &str, step: &ReplayStep) -> Result<(), DatabaseError>;
+    /// Record the final status of a replay
+    async fn finish(
+        &self,
+        id: &str,
+        status: ReplayStatus,
+        error: Option<&str>,
+    ) -> Result<Option<TaskReplay>, DatabaseError>;
+    /// Fail the replays left running by a previous process, which would
+    /// otherwise keep the queue from claiming tasks. Returns how many failed.
+    async fn fail_interrupted(&self) -> Result<u64, DatabaseError>;
+}
+
+/// SQLx-based task replay repository implementation
+pub struct ReplayRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ReplayRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    fn replay_from_row(row: &PgRow) -> Result<TaskReplay, DatabaseError> {
+        Ok(TaskReplay {
+            id: row.get("id"),
+            task_id: row.get("taskId"),
+            status: row
+                .get::<String, _>("status")
+                .parse()
+                .map_err(DatabaseError::SerializationError)?,
+            options: row.get::<Json<_>, _>("options").0,
+            steps: row.get::<Json<_>, _>("steps").0,
+            error: row.get("error"),
+            created_at: row.get("createdAt"),
+            completed_at: row.get("completedAt"),
+        })
+    }
+}
+
+#[async_trait]
+impl ReplayRepositoryTrait for ReplayRepository {
+    async fn create(
+        &self,
+        task_id: &str,
+        options: &ReplayOptions,
+    ) -> Result<Option<TaskReplay>, DatabaseError> {
+        debug!("Creating replay of task {}", task_id);
+
+        // Under the queue lock, so no task is claimed between the check and
+        // the insert
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(QUEUE_LOCK_KEY)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to acquire queue lock: {}", e);
+                DatabaseError::QueryError(e)
+            })?;
+
+        let busy: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM "Task" WHERE status = 'RUNNING')
+                OR EXISTS (SELECT 1 FROM "TaskReplay" WHERE status = $1)
+            "#,
+        )
+        .bind(ReplayStatus::Running.to_string())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to check whether the desktop is in use: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        if busy {
+            debug!("Desktop in use, not replaying task {}", task_id);
+            return Ok(None);
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO "TaskReplay" (id, "taskId", status, options, "createdAt")
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING
+                {REPLAY_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(task_id)
+        .bind(ReplayStatus::Running.to_string())
+        .bind(Json(options))
+        .bind(Utc::now())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to create replay of task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
+
+        let replay = Self::replay_from_row(&row)?;
+        info!("Created replay {} of task {}", replay.id, task_id);
+
+        Ok(Some(replay))
+    }
+
+    async fn get_by_id(&self, id: &str) -> Result<Option<TaskReplay>, DatabaseError> {
+        debug!("Fetching replay: {}", id);
+
+        let row = sqlx::query(&format!(
+            r#"SELECT {REPLAY_COLUMNS} FROM "TaskReplay" WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch replay {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        row.as_ref().map(Self::replay_from_row).transpose()
+    }
+
+    async fn list_by_task_id(&self, task_id: &str) -> Result<Vec<TaskReplay>, DatabaseError> {
+        debug!("Listing replays of task {}", task_id);
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {REPLAY_COLUMNS}
+            FROM "TaskReplay"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" DESC, id ASC
+            "#
+        ))
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list replays of task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.iter().map(Self::replay_from_row).collect()
+    }
+
+    async fn add_step(&self, id: &str, step: &ReplayStep) -> Result<(), DatabaseError> {
+        debug!("Recording step {} of replay {}", step.index, id);
+
+        sqlx::query(
+            r#"UPDATE "TaskReplay" SET steps = steps || jsonb_build_array($2::jsonb) WHERE id = $1"#,
+        )
+        .bind(id)
+        .bind(Json(step))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to record step of replay {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    async fn finish(
+        &self,
+        id: &str,
+        status: ReplayStatus,
+        error: Option<&str>,
+    ) -> Result<Option<TaskReplay>, DatabaseError> {
+        debug!("Finishing replay {} as {}", id, status);
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "TaskReplay"
+            SET status = $2, error = $3, "completedAt" = $4
+            WHERE id = $1
+            RETURNING
+                {REPLAY_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(status.to_string())
+        .bind(error)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to finish replay {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if row.is_some() {
+            info!("Replay {} finished as {}", id, status);
+        }
+
+        row.as_ref().map(Self::replay_from_row).transpose()
+    }
+
+    async fn fail_interrupted(&self) -> Result<u64, DatabaseError> {
+        debug!("Failing interrupted replays");
+
+        let result = sqlx::query(
+            r#"
+            UPDATE "TaskReplay"
+            SET status = $2, error = $3, "completedAt" = $4
+            WHERE status = $1
+            "#,
+        )
+        .bind(ReplayStatus::Running.to_string())
+        .bind(ReplayStatus::Failed.to_string())
+        .bind("Interrupted by a restart of the agent")
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fail interrupted replays: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if result.rows_affected() > 0 {
+            info!("Failed {} interrupted replays", result.rows_affected());
+        }
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use bytebot_shared_rs::types::{
+    api::PaginationParams,
+    search::{SearchHighlight, SearchResult},
+    task::TaskStatus,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, Row};
+use tracing::{debug, error};
+
+use super::{task_repository::TASK_COLUMNS, DatabaseError, TaskRepository};
+
+/// Options passed to `ts_headline` for snippets; matched terms are wrapped in
+/// `<mark>` tags while the surrounding text is returned as stored
+const HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=10, MaxFragments=2";
+
+/// Maximum number of matching messages highlighted per task
+const MAX_MESSAGE_HIGHLIGHTS: i64 = 3;
+
+/// Tasks whose description, result or messages match `$1`, with the rank of
+/// their best match
+const MATCHES: &str = r#"
+    WITH search AS (SELECT websearch_to_tsquery('english', $1) AS query),
+    matches AS (
+        SELECT id AS "taskId", ts_rank("searchVector", search.query) AS rank
+        FROM "Task", search
+        WHERE "searchVector" @@ search.query
+        UNION ALL
+        SELECT "taskId", ts_rank("searchVector", search.query)
+        FROM "Message", search
+        WHERE "searchVector" @@ search.query
+    ),
+    ranked AS (
+        SELECT "taskId", MAX(rank) AS rank
+        FROM matches
+        GROUP BY "taskId"
+    )
+"#;
+
+/// Restrictions on the tasks a search returns, applied after `MATCHES`
+const SEARCH_FILTER: &str = r#"
+    FROM ranked
+    JOIN "Task" ON "Task".id = ranked."taskId"
+    WHERE ($2::text IS NULL OR "userId" = $2)
+        AND ($3::"TaskStatus" IS NULL OR status = $3)
+        AND ($4::timestamptz IS NULL OR "createdAt" >= $4)
+        AND ($5::timestamptz IS NULL OR "createdAt" < $5)
+"#;
+
+/// Full-text search query with optional restrictions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Search terms in web search syntax: quoted phrases, `or` and `-word`
+    pub text: String,
+    /// Only search tasks owned by this user
+    pub user_id: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+/// Search repository trait for dependency injection and testing
+#[async_trait]
+pub trait SearchRepositoryTrait: Send + Sync {
+    /// Tasks matching the query, best match first, and the total number of
+    /// matching tasks
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        pagination: &PaginationParams,
+    ) -> Result<(Vec<SearchResult>, u64), DatabaseError>;
+}
+
+/// SQLx-based full-text search over tasks and their messages
+pub struct SearchRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SearchRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Snippets of the matches in the given tasks, grouped by task
+    async fn highlights(
+        &self,
+        text: &str,
+        task_ids: &[String],
+    ) -> Result<HashMap<String, Vec<SearchHighlight>>, DatabaseError> {
+        let rows = sqlx::query(
+            r#"
+            WITH search AS (SELECT websearch_to_tsquery('english', $1) AS query)
+            SELECT "taskId", "messageId", source, snippet
+            FROM (
+                SELECT id AS "taskId", NULL AS "messageId", 'description' AS source,
+                    ts_headline('english', description, search.query, $3) AS snippet,
+                    1 AS kind, 1::bigint AS position
+                FROM "Task", search
+                WHERE id = ANY($2) AND to_tsvector('english', description) @@ search.query
+                UNION ALL
+                SELECT id, NULL, 'result',
+                    ts_headline('english', "json_search_text"(result), search.query, $3),
+                    2, 1
+                FROM "Task", search
+                WHERE id = ANY($2)
+                    AND to_tsvector('english', "json_search_text"(result)) @@ search.query
+                UNION ALL
+                SELECT "taskId", id, 'message',
+                    ts_headline('english', "message_search_text"(content), search.query, $3),
+                    3, position
+                FROM (
+                    SELECT "taskId", id, content, row_number() OVER (
+                        PARTITION BY "taskId"
+                        ORDER BY ts_rank("searchVector", search.query) DESC, "createdAt"
+                    ) AS position
+                    FROM "Message", search
+                    WHERE "taskId" = ANY($2) AND "searchVector" @@ search.query
+                ) best, search
+                WHERE position <= $4
+            ) highlights
+            ORDER BY kind, position
+            "#,
+        )
+        .bind(text)
+        .bind(task_ids)
+        .bind(HEADLINE_OPTIONS)
+        .bind(MAX_MESSAGE_HIGHLIGHTS)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to highlight search matches: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let mut highlights: HashMap<String, Vec<SearchHighlight>> = HashMap::new();
+        for row in rows {
+            let source: String = row.get("source");
+            highlights
+                .entry(row.get("taskId"))
+                .or_default()
+                .push(SearchHighlight {
+                    source: source.parse().map_err(DatabaseError::ValidationError)?,
+                    message_id: row.get("messageId"),
+                    snippet: row.get("snippet"),
+                });
+        }
+
+        Ok(highlights)
+    }
+}
+
+#[async_trait]
+impl SearchRepositoryTrait for SearchRepository {
+    async fn search(
+        &self,
+        query: &SearchQuery,
+        pagination: &PaginationParams,
+    ) -> Result<(Vec<SearchResult>, u64), DatabaseError> {
+        debug!("Searching tasks: {:?}", query);
+
+        if query.text.trim().is_empty() {
+            return Err(DatabaseError::ValidationError(
+                "Search query cannot be empty".to_string(),
+            ));
+        }
+
+        let page = pagination.page.unwrap_or(1);
+        let limit = pagination.limit.unwrap_or(20);
+        let offset = (page - 1) * limit;
+
+        let total: i64 = sqlx::query(&format!(
+            "{MATCHES} SELECT COUNT(*) AS count {SEARCH_FILTER}"
+        ))
+        .bind(&query.text)
+        .bind(&query.user_id)
+        .bind(query.status)
+        .bind(query.created_after)
+        .bind(query.created_before)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to count search matches: {}", e);
+            DatabaseError::QueryError(e)
+        })?
+        .get("count");
+
+        let rows = sqlx::query(&format!(
+            r#"
+            {MATCHES}
+            SELECT
+                {TASK_COLUMNS},
+                ranked.rank
+            {SEARCH_FILTER}
+            ORDER BY ranked.rank DESC, "createdAt" DESC
+            LIMIT $6 OFFSET $7
+            "#
+        ))
+        .bind(&query.text)
+        .bind(&query.user_id)
+        .bind(query.status)
+        .bind(query.created_after)
+        .bind(query.created_before)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to search tasks: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let ranked = rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    TaskRepository::task_from_row(row)?,
+                    row.get::<f32, _>("rank"),
+                ))
+            })
+            .collect::<Result<Vec<_>, DatabaseError>>()?;
+
+        let task_ids: Vec<String> = ranked.iter().map(|(task, _)| task.id.clone()).collect();
+        let mut highlights = self.highlights(&query.text, &task_ids).await?;
+
+        let results: Vec<SearchResult> = ranked
+            .into_iter()
+            .map(|(task, rank)| SearchResult {
+                highlights: highlights.remove(&task.id).unwrap_or_default(),
+                task,
+                rank,
+            })
+            .collect();
+
+        debug!("Found {} matching tasks (total: {})", results.len(), total);
+        Ok((results, total as u64))
+    }
+}
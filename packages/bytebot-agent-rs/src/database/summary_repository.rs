@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use bytebot_shared_rs::types::message::Summary;
+use chrono::Utc;
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::DatabaseError;
+
+/// Data transfer object for creating a new summary
+#[derive(Debug, Clone)]
+pub struct CreateSummaryDto {
+    pub content: String,
+    pub task_id: String,
+    /// Previous summary of the same task that this one builds on
+    pub parent_id: Option<String>,
+}
+
+/// Summary repository trait for dependency injection and testing
+#[async_trait]
+pub trait SummaryRepositoryTrait: Send + Sync {
+    /// Create a summary and link the given messages to it in one transaction
+    async fn create(
+        &self,
+        dto: &CreateSummaryDto,
+        message_ids: &[String],
+    ) -> Result<Summary, DatabaseError>;
+    async fn get_latest_by_task_id(&self, task_id: &str) -> Result<Option<Summary>, DatabaseError>;
+}
+
+/// SQLx-based summary repository implementation
+pub struct SummaryRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SummaryRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub(super) fn summary_from_row(row: &PgRow) -> Summary {
+        Summary {
+            id: row.get("id"),
+            content: row.get("content"),
+            created_at: row.get("createdAt"),
+            updated_at: row.get("updatedAt"),
+            task_id: row.get("taskId"),
+            parent_id: row.get("parentId"),
+        }
+    }
+}
+
+#[async_trait]
+impl SummaryRepositoryTrait for SummaryRepository {
+    async fn create(
+        &self,
+        dto: &CreateSummaryDto,
+        message_ids: &[String],
+    ) -> Result<Summary, DatabaseError> {
+        debug!(
+            "Creating summary for task {} covering {} messages",
+            dto.task_id,
+            message_ids.len()
+        );
+
+        let mut summary = Summary::new(dto.content.clone(), dto.task_id.clone());
+        summary.parent_id = dto.parent_id.clone();
+        summary
+            .validate()
+            .map_err(|e| DatabaseError::ValidationError(format!("Invalid summary: {e}")))?;
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO "Summary" (id, content, "createdAt", "updatedAt", "taskId", "parentId")
+            VALUES ($1, $2, $3, $3, $4, $5)
+            RETURNING id, content, "createdAt", "updatedAt", "taskId", "parentId"
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&summary.content)
+        .bind(Utc::now())
+        .bind(&summary.task_id)
+        .bind(&summary.parent_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to create summary for task {}: {}", dto.task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+        let summary = Self::summary_from_row(&row);
+
+        sqlx::query(
+            r#"
+            UPDATE "Message"
+            SET "summaryId" = $1, "updatedAt" = $2
+            WHERE id = ANY($3) AND "taskId" = $4
+            "#,
+        )
+        .bind(&summary.id)
+        .bind(Utc::now())
+        .bind(message_ids)
+        .bind(&summary.task_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to link messages to summary {}: {}", summary.id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
+
+        info!(
+            "Created summary {} for task {}",
+            summary.id, summary.task_id
+        );
+        Ok(summary)
+    }
+
+    async fn get_latest_by_task_id(&self, task_id: &str) -> Result<Option<Summary>, DatabaseError> {
+        debug!("Fetching latest summary for task: {}", task_id);
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, content, "createdAt", "updatedAt", "taskId", "parentId"
+            FROM "Summary"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(task_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch latest summary for task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.as_ref().map(Self::summary_from_row))
+    }
+}
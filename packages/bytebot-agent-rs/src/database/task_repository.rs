@@ -1,16 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use async_trait::async_trait;
 use bytebot_shared_rs::types::{
     api::{CreateTaskDto, PaginationParams, UpdateTaskDto},
+    approval::{ApprovalDecision, ApprovalDecisionDto, PendingApproval},
+    budget::TaskUsage,
+    dependency::{TaskDependency, TaskDependencyDto, TaskGraph, TaskGraphNode},
+    label::parse_label,
+    message::Message,
+    retry::{FailureKind, TaskAttempt},
     task::{Role, Task, TaskPriority, TaskStatus, TaskType},
+    view::{SortDirection, TaskSort, TaskSortField, TaskView},
 };
 use chrono::{DateTime, Utc};
-use sqlx::{Pool, Postgres, Row};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    types::Json,
+    Pool, Postgres, Row, Transaction,
+};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::DatabaseError;
+use super::{DatabaseError, FileRepository};
 
 /// Task filtering options for complex queries
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -24,6 +38,52 @@ pub struct TaskFilter {
     pub created_before: Option<DateTime<Utc>>,
     pub scheduled_after: Option<DateTime<Utc>>,
     pub scheduled_before: Option<DateTime<Utc>>,
+    /// Tasks that depend on the given task
+    pub depends_on: Option<String>,
+    /// Pending tasks waiting for their dependencies, or every other task
+    pub blocked: Option<bool>,
+    /// Labels a task must all have; a bare `key` matches the key with any value
+    pub labels: Vec<String>,
+    /// Order of listed tasks, newest first when unset
+    pub sort: Option<TaskSort>,
+}
+
+impl From<&TaskView> for TaskFilter {
+    fn from(view: &TaskView) -> Self {
+        let filter = &view.filter;
+        Self {
+            status: filter.status,
+            priority: filter.priority,
+            task_type: filter.task_type,
+            user_id: filter.user_id.clone(),
+            created_by: filter.created_by,
+            depends_on: filter.depends_on.clone(),
+            blocked: filter.blocked,
+            labels: filter.labels.clone(),
+            sort: Some(view.sort),
+            ..Self::default()
+        }
+    }
+}
+
+/// Value bound to a placeholder of a task filter clause
+#[derive(Debug, Clone, PartialEq)]
+enum FilterParam {
+    Status(TaskStatus),
+    Priority(TaskPriority),
+    TaskType(TaskType),
+    Role(Role),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+}
+
+/// Position of a task in the run queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueueInfo {
+    /// 1-based position, or `None` if the task is not waiting to run
+    pub position: Option<u64>,
+    /// Number of tasks waiting to run
+    pub depth: u64,
 }
 
 /// Task repository trait for dependency injection and testing
@@ -46,8 +106,150 @@ pub trait TaskRepositoryTrait: Send + Sync {
     async fn get_tasks_by_status(&self, status: TaskStatus) -> Result<Vec<Task>, DatabaseError>;
     async fn get_scheduled_tasks(&self, before: DateTime<Utc>) -> Result<Vec<Task>, DatabaseError>;
     async fn count_by_status(&self) -> Result<HashMap<TaskStatus, u64>, DatabaseError>;
+    async fn claim_next_pending(&self, max_running: u32) -> Result<Option<Task>, DatabaseError>;
+    async fn mark_failed(
+        &self,
+        id: &str,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<Option<Task>, DatabaseError>;
+    async fn schedule_retry(
+        &self,
+        id: &str,
+        error: &str,
+        kind: FailureKind,
+        retry_at: DateTime<Utc>,
+    ) -> Result<Option<Task>, DatabaseError>;
+    async fn list_attempts(&self, task_id: &str) -> Result<Vec<TaskAttempt>, DatabaseError>;
+    async fn set_result(&self, id: &str, result: &Value) -> Result<Option<Task>, DatabaseError>;
+    /// Add the usage of a model turn to the total of a task
+    async fn add_usage(&self, id: &str, usage: &TaskUsage) -> Result<Option<Task>, DatabaseError>;
+    /// Hand a running task to the user, recording why
+    async fn mark_needs_help(&self, id: &str, reason: &str) -> Result<Option<Task>, DatabaseError>;
+    /// Move a running task to review until `approval` is decided on
+    async fn request_approval(
+        &self,
+        id: &str,
+        approval: &PendingApproval,
+    ) -> Result<Option<Task>, DatabaseError>;
+    /// Record the decision on the undecided approval of a task in review and
+    /// let it run again
+    async fn decide_approval(
+        &self,
+        id: &str,
+        decision: ApprovalDecision,
+        dto: &ApprovalDecisionDto,
+    ) -> Result<Option<Task>, DatabaseError>;
+    async fn clear_approval(&self, id: &str) -> Result<(), DatabaseError>;
+    async fn mark_queued(&self, id: &str) -> Result<Option<Task>, DatabaseError>;
+    async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, DatabaseError>;
+    async fn spawn_recurring_run(
+        &self,
+        definition: &Task,
+        fire_time: DateTime<Utc>,
+        next_run: Option<DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError>;
+    async fn set_recurrence_paused(
+        &self,
+        id: &str,
+        paused: bool,
+        next_run: Option<DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError>;
+    async fn preempt_for_urgent(&self, max_running: u32) -> Result<Option<Task>, DatabaseError>;
+    /// Move the tasks left running by a previous process back to the queue,
+    /// where they would otherwise hold their slots forever. Returns how many
+    /// moved.
+    async fn requeue_interrupted(&self) -> Result<u64, DatabaseError>;
+    async fn queue_info(&self, id: &str) -> Result<QueueInfo, DatabaseError>;
+    async fn queue_depth_by_priority(&self) -> Result<HashMap<TaskPriority, u64>, DatabaseError>;
+    async fn fail_unsatisfiable_dependents(&self) -> Result<Vec<Task>, DatabaseError>;
+    async fn graph(&self, task_id: &str) -> Result<Option<TaskGraph>, DatabaseError>;
+    /// Create a pending copy of `source` whose history is `messages`, a prefix
+    /// of the source conversation ending at the fork point
+    async fn fork(&self, source: &Task, messages: &[Message]) -> Result<Task, DatabaseError>;
+}
+
+/// Columns selected for every query that returns full tasks
+pub(super) const TASK_COLUMNS: &str = r#"
+    id,
+    description,
+    type,
+    status,
+    priority,
+    control,
+    "createdAt",
+    "createdBy",
+    "scheduledFor",
+    "updatedAt",
+    "executedAt",
+    "completedAt",
+    "queuedAt",
+    error,
+    result,
+    model,
+    "userId",
+    "cronExpression",
+    timezone,
+    "recurrencePaused",
+    "recurringTaskId",
+    "resultSchema",
+    "retryPolicy",
+    attempt,
+    "retryAt",
+    "forkedFromId",
+    "forkedFromMessageId",
+    budget,
+    usage,
+    "pendingApproval",
+    labels
+"#;
+
+/// Whether a row of `"Task"` has a dependency whose condition is not met yet
+macro_rules! has_unmet_dependency {
+    () => {
+        r#"EXISTS (
+        SELECT 1 FROM "TaskDependency" d
+        JOIN "Task" dep ON dep.id = d."dependsOnId"
+        WHERE d."taskId" = "Task".id
+        AND NOT (
+            (d.condition = 'on_success' AND dep.status = 'COMPLETED')
+            OR (d.condition = 'on_failure' AND dep.status = 'FAILED')
+            OR (d.condition = 'always' AND dep.status IN ('COMPLETED', 'FAILED', 'CANCELLED'))
+        )
+    )"#
+    };
 }
 
+/// Tasks that are waiting for a processor to pick them up, unless a user has
+/// taken them over, they are backing off before a retry, or their dependencies
+/// have not finished
+const RUNNABLE_CONDITION: &str = concat!(
+    r#"status = 'PENDING' AND control = 'ASSISTANT'
+    AND (type = 'IMMEDIATE' OR "queuedAt" IS NOT NULL)
+    AND ("retryAt" IS NULL OR "retryAt" <= NOW())
+    AND NOT "#,
+    has_unmet_dependency!()
+);
+
+/// Pending tasks held back by a dependency that has not finished
+const BLOCKED_CONDITION: &str = concat!("status = 'PENDING' AND ", has_unmet_dependency!());
+
+/// Run queue order: highest priority first, then longest waiting
+const QUEUE_ORDER: &str = r#"
+    CASE priority
+        WHEN 'URGENT' THEN 3
+        WHEN 'HIGH' THEN 2
+        WHEN 'MEDIUM' THEN 1
+        ELSE 0
+    END DESC,
+    COALESCE("queuedAt", "createdAt") ASC,
+    id ASC
+"#;
+
+/// Advisory lock serializing claims so the running limit holds across
+/// processors, and replays start only on an idle desktop
+pub(super) const QUEUE_LOCK_KEY: i64 = 0x0062_7974_6562_6f74;
+
 /// SQLx-based task repository implementation
 pub struct TaskRepository {
     pool: Pool<Postgres>,
@@ -83,68 +285,206 @@ impl TaskRepository {
         }
     }
 
-    /// Build WHERE clause for task filtering
-    fn build_filter_clause(
-        filter: &TaskFilter,
-    ) -> (String, Vec<Box<dyn sqlx::Encode<'_, Postgres> + Send>>) {
+    /// Start a transaction holding the queue lock until it ends
+    async fn begin_queue_transaction(
+        &self,
+    ) -> Result<sqlx::Transaction<'_, Postgres>, DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            error!("Failed to start transaction: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(QUEUE_LOCK_KEY)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to acquire queue lock: {}", e);
+                DatabaseError::QueryError(e)
+            })?;
+
+        Ok(tx)
+    }
+
+    /// Number of tasks currently running
+    async fn count_running(tx: &mut sqlx::Transaction<'_, Postgres>) -> Result<i64, DatabaseError> {
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Task" WHERE status = 'RUNNING'"#)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to count running tasks: {}", e);
+                DatabaseError::QueryError(e)
+            })
+    }
+
+    /// Map a row selected with `TASK_COLUMNS` into a Task
+    pub(super) fn task_from_row(row: &PgRow) -> Result<Task, DatabaseError> {
+        Ok(Task {
+            id: row.get("id"),
+            description: row.get("description"),
+            task_type: row.get("type"),
+            status: row.get("status"),
+            priority: row.get("priority"),
+            control: row.get("control"),
+            created_at: row.get("createdAt"),
+            created_by: row.get("createdBy"),
+            scheduled_for: row.get("scheduledFor"),
+            updated_at: row.get("updatedAt"),
+            executed_at: row.get("executedAt"),
+            completed_at: row.get("completedAt"),
+            queued_at: row.get("queuedAt"),
+            error: row.get("error"),
+            result: row.get("result"),
+            model: row.get("model"),
+            user_id: row.get("userId"),
+            cron_expression: row.get("cronExpression"),
+            timezone: row.get("timezone"),
+            recurrence_paused: row.get("recurrencePaused"),
+            recurring_task_id: row.get("recurringTaskId"),
+            result_schema: row.get("resultSchema"),
+            retry_policy: row
+                .get::<Option<Json<_>>, _>("retryPolicy")
+                .map(|policy| policy.0),
+            attempt: row.get("attempt"),
+            retry_at: row.get("retryAt"),
+            forked_from_id: row.get("forkedFromId"),
+            forked_from_message_id: row.get("forkedFromMessageId"),
+            budget: row
+                .get::<Option<Json<_>>, _>("budget")
+                .map(|budget| budget.0),
+            usage: row.get::<Json<_>, _>("usage").0,
+            pending_approval: row
+                .get::<Option<Json<_>>, _>("pendingApproval")
+                .map(|approval| approval.0),
+            labels: row.get("labels"),
+        })
+    }
+
+    /// Record the end of a task's current attempt inside an open transaction
+    async fn record_attempt(
+        tx: &mut Transaction<'_, Postgres>,
+        task: &Task,
+        error: &str,
+        kind: FailureKind,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO "TaskAttempt" (id, "taskId", attempt, error, "failureKind", "retryAt", "createdAt")
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&task.id)
+        .bind(task.attempt)
+        .bind(error)
+        .bind(kind.to_string())
+        .bind(retry_at)
+        .bind(Utc::now())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to record attempt {} of task {}: {}",
+                task.attempt, task.id, e
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Build WHERE clause for task filtering.
+    ///
+    /// Placeholders are numbered from `$1` in the order of the returned params.
+    fn build_filter_clause(filter: &TaskFilter) -> (String, Vec<FilterParam>) {
         let mut conditions = Vec::new();
-        let mut params: Vec<Box<dyn sqlx::Encode<'_, Postgres> + Send>> = Vec::new();
+        let mut params = Vec::new();
         let mut param_count = 1;
 
         if let Some(status) = filter.status {
             conditions.push(format!("status = ${param_count}"));
-            params.push(Box::new(status));
+            params.push(FilterParam::Status(status));
             param_count += 1;
         }
 
         if let Some(priority) = filter.priority {
             conditions.push(format!("priority = ${param_count}"));
-            params.push(Box::new(priority));
+            params.push(FilterParam::Priority(priority));
             param_count += 1;
         }
 
         if let Some(task_type) = filter.task_type {
             conditions.push(format!("type = ${param_count}"));
-            params.push(Box::new(task_type));
+            params.push(FilterParam::TaskType(task_type));
             param_count += 1;
         }
 
         if let Some(ref user_id) = filter.user_id {
             conditions.push(format!("\"userId\" = ${param_count}"));
-            params.push(Box::new(user_id.clone()));
+            params.push(FilterParam::Text(user_id.clone()));
             param_count += 1;
         }
 
         if let Some(created_by) = filter.created_by {
             conditions.push(format!("\"createdBy\" = ${param_count}"));
-            params.push(Box::new(created_by));
+            params.push(FilterParam::Role(created_by));
             param_count += 1;
         }
 
         if let Some(created_after) = filter.created_after {
             conditions.push(format!("\"createdAt\" >= ${param_count}"));
-            params.push(Box::new(created_after));
+            params.push(FilterParam::Timestamp(created_after));
             param_count += 1;
         }
 
         if let Some(created_before) = filter.created_before {
             conditions.push(format!("\"createdAt\" <= ${param_count}"));
-            params.push(Box::new(created_before));
+            params.push(FilterParam::Timestamp(created_before));
             param_count += 1;
         }
 
         if let Some(scheduled_after) = filter.scheduled_after {
             conditions.push(format!("\"scheduledFor\" >= ${param_count}"));
-            params.push(Box::new(scheduled_after));
+            params.push(FilterParam::Timestamp(scheduled_after));
             param_count += 1;
         }
 
         if let Some(scheduled_before) = filter.scheduled_before {
             conditions.push(format!("\"scheduledFor\" <= ${param_count}"));
-            params.push(Box::new(scheduled_before));
+            params.push(FilterParam::Timestamp(scheduled_before));
+            param_count += 1;
+        }
+
+        for label in &filter.labels {
+            match parse_label(label) {
+                (_, Some(_)) => {
+                    conditions.push(format!("labels @> ARRAY[${param_count}]"));
+                    params.push(FilterParam::Text(label.clone()));
+                }
+                (key, None) => {
+                    conditions.push(format!(
+                        "EXISTS (SELECT 1 FROM unnest(labels) AS label WHERE split_part(label, '=', 1) = ${param_count})"
+                    ));
+                    params.push(FilterParam::Text(key.to_string()));
+                }
+            }
             param_count += 1;
         }
 
+        if let Some(ref depends_on) = filter.depends_on {
+            conditions.push(format!(
+                r#"id IN (SELECT "taskId" FROM "TaskDependency" WHERE "dependsOnId" = ${param_count})"#
+            ));
+            params.push(FilterParam::Text(depends_on.clone()));
+        }
+
+        match filter.blocked {
+            Some(true) => conditions.push(format!("({BLOCKED_CONDITION})")),
+            Some(false) => conditions.push(format!("NOT ({BLOCKED_CONDITION})")),
+            None => {}
+        }
+
         let where_clause = if conditions.is_empty() {
             String::new()
         } else {
@@ -153,25 +493,121 @@ impl TaskRepository {
 
         (where_clause, params)
     }
-}
 
-#[async_trait]
-impl TaskRepositoryTrait for TaskRepository {
-    async fn create(&self, dto: &CreateTaskDto) -> Result<Task, DatabaseError> {
-        debug!("Creating new task with description: {}", dto.description);
+    /// ORDER BY expression for a task list, with the id as tiebreak so pages
+    /// stay stable
+    fn order_clause(sort: TaskSort) -> String {
+        let column = match sort.field {
+            TaskSortField::CreatedAt => r#""createdAt""#,
+            TaskSortField::UpdatedAt => r#""updatedAt""#,
+            TaskSortField::ScheduledFor => r#""scheduledFor""#,
+            TaskSortField::Priority => "priority",
+            TaskSortField::Status => "status",
+        };
+        let direction = match sort.direction {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        };
+
+        format!("{column} {direction} NULLS LAST, id {direction}")
+    }
+
+    /// Bind filter params to a query built from `build_filter_clause`
+    fn bind_filter_params<'q>(
+        mut query: Query<'q, Postgres, PgArguments>,
+        params: &'q [FilterParam],
+    ) -> Query<'q, Postgres, PgArguments> {
+        for param in params {
+            query = match param {
+                FilterParam::Status(status) => query.bind(status),
+                FilterParam::Priority(priority) => query.bind(priority),
+                FilterParam::TaskType(task_type) => query.bind(task_type),
+                FilterParam::Role(role) => query.bind(role),
+                FilterParam::Text(text) => query.bind(text),
+                FilterParam::Timestamp(timestamp) => query.bind(timestamp),
+            };
+        }
+        query
+    }
+
+    /// Record the dependencies of a new task inside an open transaction.
+    ///
+    /// Dependencies must exist and must not already have finished in a state
+    /// that can never meet their condition.
+    async fn insert_dependencies(
+        tx: &mut Transaction<'_, Postgres>,
+        task_id: &str,
+        dependencies: &[TaskDependencyDto],
+    ) -> Result<(), DatabaseError> {
+        let mut seen = HashSet::new();
+
+        for dependency in dependencies {
+            if !seen.insert(dependency.task_id.as_str()) {
+                return Err(DatabaseError::ValidationError(format!(
+                    "Task {} is listed as a dependency more than once",
+                    dependency.task_id
+                )));
+            }
+
+            let status: Option<TaskStatus> =
+                sqlx::query_scalar(r#"SELECT status FROM "Task" WHERE id = $1"#)
+                    .bind(&dependency.task_id)
+                    .fetch_optional(&mut **tx)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to fetch dependency {}: {}", dependency.task_id, e);
+                        DatabaseError::QueryError(e)
+                    })?;
+            let status = status.ok_or_else(|| {
+                DatabaseError::ValidationError(format!(
+                    "Dependency task {} not found",
+                    dependency.task_id
+                ))
+            })?;
+
+            if dependency.condition.is_satisfied_by(status) == Some(false) {
+                return Err(DatabaseError::ValidationError(format!(
+                    "Dependency task {} already ended as {}, so its {} condition can never be met",
+                    dependency.task_id, status, dependency.condition
+                )));
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO "TaskDependency" ("taskId", "dependsOnId", condition)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(task_id)
+            .bind(&dependency.task_id)
+            .bind(dependency.condition.to_string())
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to store dependency of task {} on {}: {}",
+                    task_id, dependency.task_id, e
+                );
+                DatabaseError::QueryError(e)
+            })?;
+        }
+
+        Ok(())
+    }
 
+    /// Validate and insert a task with its files and dependencies inside an
+    /// open transaction
+    async fn insert_task(
+        tx: &mut Transaction<'_, Postgres>,
+        dto: &CreateTaskDto,
+    ) -> Result<Task, DatabaseError> {
         let task_id = Uuid::new_v4().to_string();
         let now = Utc::now();
         let task_type = dto.task_type.unwrap_or_default();
         let priority = dto.priority.unwrap_or_default();
         let created_by = dto.created_by.unwrap_or(Role::User);
-        let model = dto.model.clone().unwrap_or_else(|| {
-            serde_json::json!({
-                "provider": "anthropic",
-                "name": "claude-3-sonnet-20240229",
-                "title": "Claude 3 Sonnet"
-            })
-        });
+        // Tasks without a model run on the registry's default model
+        let model = dto.model.clone().unwrap_or(serde_json::Value::Null);
 
         // Validate scheduled tasks have scheduled_for timestamp
         if task_type == TaskType::Scheduled && dto.scheduled_for.is_none() {
@@ -180,84 +616,80 @@ impl TaskRepositoryTrait for TaskRepository {
             ));
         }
 
-        let row = sqlx::query(
+        if dto.recurrence.is_some() && task_type != TaskType::Scheduled {
+            return Err(DatabaseError::ValidationError(
+                "Recurring tasks must be scheduled tasks".to_string(),
+            ));
+        }
+
+        if dto.recurrence.is_some() && dto.depends_on.is_some() {
+            return Err(DatabaseError::ValidationError(
+                "Recurring tasks cannot depend on other tasks".to_string(),
+            ));
+        }
+
+        let row = sqlx::query(&format!(
             r#"
             INSERT INTO "Task" (
                 id, description, type, status, priority, control, 
                 "createdAt", "createdBy", "scheduledFor", "updatedAt", 
-                model, "userId"
+                model, "userId", "cronExpression", timezone, "resultSchema",
+                "retryPolicy", budget, labels
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING 
-                id,
-                description,
-                type,
-                status,
-                priority,
-                control,
-                "createdAt",
-                "createdBy",
-                "scheduledFor",
-                "updatedAt",
-                "executedAt",
-                "completedAt",
-                "queuedAt",
-                error,
-                result,
-                model,
-                "userId"
-            "#,
-        )
+                {TASK_COLUMNS}
+            "#
+        ))
         .bind(&task_id)
         .bind(&dto.description)
-        .bind(task_type.to_string())
-        .bind(TaskStatus::Pending.to_string())
-        .bind(priority.to_string())
-        .bind(Role::Assistant.to_string())
+        .bind(task_type)
+        .bind(TaskStatus::Pending)
+        .bind(priority)
+        .bind(Role::Assistant)
         .bind(now)
-        .bind(created_by.to_string())
+        .bind(created_by)
         .bind(dto.scheduled_for)
         .bind(now)
         .bind(&model)
         .bind(&dto.user_id)
-        .fetch_one(&self.pool)
+        .bind(dto.recurrence.as_ref().map(|r| &r.cron))
+        .bind(dto.recurrence.as_ref().map(|r| &r.timezone))
+        .bind(&dto.result_schema)
+        .bind(dto.retry_policy.as_ref().map(Json))
+        .bind(dto.budget.as_ref().map(Json))
+        .bind(dto.labels.clone().unwrap_or_default())
+        .fetch_one(&mut **tx)
         .await
         .map_err(|e| {
             error!("Failed to create task: {}", e);
             DatabaseError::QueryError(e)
         })?;
 
-        let task =
-            Task {
-                id: row.get("id"),
-                description: row.get("description"),
-                task_type: row.get::<String, _>("type").parse().map_err(|_| {
-                    DatabaseError::SerializationError("Invalid task type".to_string())
-                })?,
-                status: row
-                    .get::<String, _>("status")
-                    .parse()
-                    .map_err(|_| DatabaseError::SerializationError("Invalid status".to_string()))?,
-                priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                    DatabaseError::SerializationError("Invalid priority".to_string())
-                })?,
-                control: row.get::<String, _>("control").parse().map_err(|_| {
-                    DatabaseError::SerializationError("Invalid control".to_string())
-                })?,
-                created_at: row.get("createdAt"),
-                created_by: row.get::<String, _>("createdBy").parse().map_err(|_| {
-                    DatabaseError::SerializationError("Invalid created_by".to_string())
-                })?,
-                scheduled_for: row.get("scheduledFor"),
-                updated_at: row.get("updatedAt"),
-                executed_at: row.get("executedAt"),
-                completed_at: row.get("completedAt"),
-                queued_at: row.get("queuedAt"),
-                error: row.get("error"),
-                result: row.get("result"),
-                model: row.get("model"),
-                user_id: row.get("userId"),
-            };
+        let task = Self::task_from_row(&row)?;
+
+        if let Some(files) = &dto.files {
+            FileRepository::insert_for_task(tx, &task.id, files).await?;
+        }
+
+        if let Some(dependencies) = &dto.depends_on {
+            Self::insert_dependencies(tx, &task.id, dependencies).await?;
+        }
+
+        Ok(task)
+    }
+}
+
+#[async_trait]
+impl TaskRepositoryTrait for TaskRepository {
+    async fn create(&self, dto: &CreateTaskDto) -> Result<Task, DatabaseError> {
+        debug!("Creating new task with description: {}", dto.description);
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        let task = Self::insert_task(&mut tx, dto).await?;
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
 
         info!("Successfully created task with ID: {}", task.id);
         Ok(task)
@@ -266,30 +698,14 @@ impl TaskRepositoryTrait for TaskRepository {
     async fn get_by_id(&self, id: &str) -> Result<Option<Task>, DatabaseError> {
         debug!("Fetching task by ID: {}", id);
 
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
             SELECT 
-                id,
-                description,
-                type,
-                status,
-                priority,
-                control,
-                "createdAt",
-                "createdBy",
-                "scheduledFor",
-                "updatedAt",
-                "executedAt",
-                "completedAt",
-                "queuedAt",
-                error,
-                result,
-                model,
-                "userId"
+                {TASK_COLUMNS}
             FROM "Task"
             WHERE id = $1
-            "#,
-        )
+            "#
+        ))
         .bind(id)
         .fetch_optional(&self.pool)
         .await
@@ -301,35 +717,7 @@ impl TaskRepositoryTrait for TaskRepository {
         let task = match row {
             Some(row) => {
                 debug!("Found task with ID: {}", id);
-                Some(Task {
-                    id: row.get("id"),
-                    description: row.get("description"),
-                    task_type: row.get::<String, _>("type").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid task type".to_string())
-                    })?,
-                    status: row.get::<String, _>("status").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid status".to_string())
-                    })?,
-                    priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid priority".to_string())
-                    })?,
-                    control: row.get::<String, _>("control").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid control".to_string())
-                    })?,
-                    created_at: row.get("createdAt"),
-                    created_by: row.get::<String, _>("createdBy").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid created_by".to_string())
-                    })?,
-                    scheduled_for: row.get("scheduledFor"),
-                    updated_at: row.get("updatedAt"),
-                    executed_at: row.get("executedAt"),
-                    completed_at: row.get("completedAt"),
-                    queued_at: row.get("queuedAt"),
-                    error: row.get("error"),
-                    result: row.get("result"),
-                    model: row.get("model"),
-                    user_id: row.get("userId"),
-                })
+                Some(Self::task_from_row(&row)?)
             }
             None => {
                 debug!("No task found with ID: {}", id);
@@ -361,45 +749,36 @@ impl TaskRepositoryTrait for TaskRepository {
         let now = Utc::now();
         let status = dto.status.unwrap_or(current_task.status);
         let priority = dto.priority.unwrap_or(current_task.priority);
+        let control = dto.control.unwrap_or(current_task.control);
 
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
             UPDATE "Task"
             SET 
                 status = $2,
                 priority = $3,
-                "queuedAt" = $4,
-                "executedAt" = $5,
-                "completedAt" = $6,
-                "updatedAt" = $7
+                "queuedAt" = COALESCE($4, "queuedAt"),
+                "executedAt" = COALESCE($5, "executedAt"),
+                "completedAt" = COALESCE($6, "completedAt"),
+                "updatedAt" = $7,
+                control = $8,
+                budget = COALESCE($9, budget),
+                labels = COALESCE($10, labels)
             WHERE id = $1
             RETURNING 
-                id,
-                description,
-                type,
-                status,
-                priority,
-                control,
-                "createdAt",
-                "createdBy",
-                "scheduledFor",
-                "updatedAt",
-                "executedAt",
-                "completedAt",
-                "queuedAt",
-                error,
-                result,
-                model,
-                "userId"
-            "#,
-        )
+                {TASK_COLUMNS}
+            "#
+        ))
         .bind(id)
-        .bind(status.to_string())
-        .bind(priority.to_string())
+        .bind(status)
+        .bind(priority)
         .bind(dto.queued_at)
         .bind(dto.executed_at)
         .bind(dto.completed_at)
         .bind(now)
+        .bind(control)
+        .bind(dto.budget.as_ref().map(Json))
+        .bind(&dto.labels)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| {
@@ -410,35 +789,7 @@ impl TaskRepositoryTrait for TaskRepository {
         let task = match row {
             Some(row) => {
                 info!("Successfully updated task with ID: {}", id);
-                Some(Task {
-                    id: row.get("id"),
-                    description: row.get("description"),
-                    task_type: row.get::<String, _>("type").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid task type".to_string())
-                    })?,
-                    status: row.get::<String, _>("status").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid status".to_string())
-                    })?,
-                    priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid priority".to_string())
-                    })?,
-                    control: row.get::<String, _>("control").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid control".to_string())
-                    })?,
-                    created_at: row.get("createdAt"),
-                    created_by: row.get::<String, _>("createdBy").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid created_by".to_string())
-                    })?,
-                    scheduled_for: row.get("scheduledFor"),
-                    updated_at: row.get("updatedAt"),
-                    executed_at: row.get("executedAt"),
-                    completed_at: row.get("completedAt"),
-                    queued_at: row.get("queuedAt"),
-                    error: row.get("error"),
-                    result: row.get("result"),
-                    model: row.get("model"),
-                    user_id: row.get("userId"),
-                })
+                Some(Self::task_from_row(&row)?)
             }
             None => None,
         };
@@ -479,12 +830,12 @@ impl TaskRepositoryTrait for TaskRepository {
         let offset = (page - 1) * limit;
 
         // Build the WHERE clause for filtering
-        let (where_clause, _params) = Self::build_filter_clause(filter);
+        let (where_clause, params) = Self::build_filter_clause(filter);
 
         // Count total matching records
         let count_query = format!(r#"SELECT COUNT(*) as count FROM "Task" {where_clause}"#);
 
-        let total_count: i64 = sqlx::query(&count_query)
+        let total_count: i64 = Self::bind_filter_params(sqlx::query(&count_query), &params)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| {
@@ -497,31 +848,18 @@ impl TaskRepositoryTrait for TaskRepository {
         let data_query = format!(
             r#"
             SELECT 
-                id,
-                description,
-                type as task_type,
-                status,
-                priority,
-                control,
-                "createdAt" as created_at,
-                "createdBy" as created_by,
-                "scheduledFor" as scheduled_for,
-                "updatedAt" as updated_at,
-                "executedAt" as executed_at,
-                "completedAt" as completed_at,
-                "queuedAt" as queued_at,
-                error,
-                result,
-                model,
-                "userId" as user_id
+                {TASK_COLUMNS}
             FROM "Task"
             {where_clause}
-            ORDER BY "createdAt" DESC
-            LIMIT $1 OFFSET $2
-            "#
+            ORDER BY {}
+            LIMIT ${} OFFSET ${}
+            "#,
+            Self::order_clause(filter.sort.unwrap_or_default()),
+            params.len() + 1,
+            params.len() + 2
         );
 
-        let rows = sqlx::query(&data_query)
+        let rows = Self::bind_filter_params(sqlx::query(&data_query), &params)
             .bind(limit as i64)
             .bind(offset as i64)
             .fetch_all(&self.pool)
@@ -533,37 +871,7 @@ impl TaskRepositoryTrait for TaskRepository {
 
         let tasks: Result<Vec<Task>, DatabaseError> = rows
             .into_iter()
-            .map(|row| {
-                Ok::<Task, DatabaseError>(Task {
-                    id: row.get("id"),
-                    description: row.get("description"),
-                    task_type: row.get::<String, _>("task_type").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid task type".to_string())
-                    })?,
-                    status: row.get::<String, _>("status").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid status".to_string())
-                    })?,
-                    priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid priority".to_string())
-                    })?,
-                    control: row.get::<String, _>("control").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid control".to_string())
-                    })?,
-                    created_at: row.get("created_at"),
-                    created_by: row.get::<String, _>("created_by").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid created_by".to_string())
-                    })?,
-                    scheduled_for: row.get("scheduled_for"),
-                    updated_at: row.get("updated_at"),
-                    executed_at: row.get("executed_at"),
-                    completed_at: row.get("completed_at"),
-                    queued_at: row.get("queued_at"),
-                    error: row.get("error"),
-                    result: row.get("result"),
-                    model: row.get("model"),
-                    user_id: row.get("user_id"),
-                })
-            })
+            .map(|row| Self::task_from_row(&row))
             .collect();
 
         let tasks = tasks?;
@@ -607,7 +915,7 @@ impl TaskRepositoryTrait for TaskRepository {
             _ => {}
         }
 
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
             UPDATE "Task"
             SET 
@@ -617,27 +925,11 @@ impl TaskRepositoryTrait for TaskRepository {
                 "updatedAt" = $5
             WHERE id = $1
             RETURNING 
-                id,
-                description,
-                type,
-                status,
-                priority,
-                control,
-                "createdAt",
-                "createdBy",
-                "scheduledFor",
-                "updatedAt",
-                "executedAt",
-                "completedAt",
-                "queuedAt",
-                error,
-                result,
-                model,
-                "userId"
-            "#,
-        )
+                {TASK_COLUMNS}
+            "#
+        ))
         .bind(id)
-        .bind(status.to_string())
+        .bind(status)
         .bind(executed_at)
         .bind(completed_at)
         .bind(now)
@@ -651,35 +943,7 @@ impl TaskRepositoryTrait for TaskRepository {
         let task = match row {
             Some(row) => {
                 info!("Successfully updated task {} status to {:?}", id, status);
-                Some(Task {
-                    id: row.get("id"),
-                    description: row.get("description"),
-                    task_type: row.get::<String, _>("type").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid task type".to_string())
-                    })?,
-                    status: row.get::<String, _>("status").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid status".to_string())
-                    })?,
-                    priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid priority".to_string())
-                    })?,
-                    control: row.get::<String, _>("control").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid control".to_string())
-                    })?,
-                    created_at: row.get("createdAt"),
-                    created_by: row.get::<String, _>("createdBy").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid created_by".to_string())
-                    })?,
-                    scheduled_for: row.get("scheduledFor"),
-                    updated_at: row.get("updatedAt"),
-                    executed_at: row.get("executedAt"),
-                    completed_at: row.get("completedAt"),
-                    queued_at: row.get("queuedAt"),
-                    error: row.get("error"),
-                    result: row.get("result"),
-                    model: row.get("model"),
-                    user_id: row.get("userId"),
-                })
+                Some(Self::task_from_row(&row)?)
             }
             None => None,
         };
@@ -690,32 +954,16 @@ impl TaskRepositoryTrait for TaskRepository {
     async fn get_tasks_by_status(&self, status: TaskStatus) -> Result<Vec<Task>, DatabaseError> {
         debug!("Fetching tasks with status: {:?}", status);
 
-        let rows = sqlx::query(
+        let rows = sqlx::query(&format!(
             r#"
             SELECT 
-                id,
-                description,
-                type,
-                status,
-                priority,
-                control,
-                "createdAt",
-                "createdBy",
-                "scheduledFor",
-                "updatedAt",
-                "executedAt",
-                "completedAt",
-                "queuedAt",
-                error,
-                result,
-                model,
-                "userId"
+                {TASK_COLUMNS}
             FROM "Task"
             WHERE status = $1
             ORDER BY "createdAt" DESC
-            "#,
-        )
-        .bind(status.to_string())
+            "#
+        ))
+        .bind(status)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| {
@@ -725,37 +973,7 @@ impl TaskRepositoryTrait for TaskRepository {
 
         let tasks: Result<Vec<Task>, DatabaseError> = rows
             .into_iter()
-            .map(|row| {
-                Ok::<Task, DatabaseError>(Task {
-                    id: row.get("id"),
-                    description: row.get("description"),
-                    task_type: row.get::<String, _>("type").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid task type".to_string())
-                    })?,
-                    status: row.get::<String, _>("status").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid status".to_string())
-                    })?,
-                    priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid priority".to_string())
-                    })?,
-                    control: row.get::<String, _>("control").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid control".to_string())
-                    })?,
-                    created_at: row.get("createdAt"),
-                    created_by: row.get::<String, _>("createdBy").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid created_by".to_string())
-                    })?,
-                    scheduled_for: row.get("scheduledFor"),
-                    updated_at: row.get("updatedAt"),
-                    executed_at: row.get("executedAt"),
-                    completed_at: row.get("completedAt"),
-                    queued_at: row.get("queuedAt"),
-                    error: row.get("error"),
-                    result: row.get("result"),
-                    model: row.get("model"),
-                    user_id: row.get("userId"),
-                })
-            })
+            .map(|row| Self::task_from_row(&row))
             .collect();
 
         let tasks = tasks?;
@@ -766,36 +984,22 @@ impl TaskRepositoryTrait for TaskRepository {
     async fn get_scheduled_tasks(&self, before: DateTime<Utc>) -> Result<Vec<Task>, DatabaseError> {
         debug!("Fetching scheduled tasks before: {}", before);
 
-        let rows = sqlx::query(
+        let rows = sqlx::query(&format!(
             r#"
             SELECT 
-                id,
-                description,
-                type,
-                status,
-                priority,
-                control,
-                "createdAt",
-                "createdBy",
-                "scheduledFor",
-                "updatedAt",
-                "executedAt",
-                "completedAt",
-                "queuedAt",
-                error,
-                result,
-                model,
-                "userId"
+                {TASK_COLUMNS}
             FROM "Task"
             WHERE type = $1 
             AND "scheduledFor" <= $2 
             AND status = $3
+            AND "queuedAt" IS NULL
+            AND NOT "recurrencePaused"
             ORDER BY "scheduledFor" ASC
-            "#,
-        )
-        .bind(TaskType::Scheduled.to_string())
+            "#
+        ))
+        .bind(TaskType::Scheduled)
         .bind(before)
-        .bind(TaskStatus::Pending.to_string())
+        .bind(TaskStatus::Pending)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| {
@@ -805,37 +1009,7 @@ impl TaskRepositoryTrait for TaskRepository {
 
         let tasks: Result<Vec<Task>, DatabaseError> = rows
             .into_iter()
-            .map(|row| {
-                Ok::<Task, DatabaseError>(Task {
-                    id: row.get("id"),
-                    description: row.get("description"),
-                    task_type: row.get::<String, _>("type").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid task type".to_string())
-                    })?,
-                    status: row.get::<String, _>("status").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid status".to_string())
-                    })?,
-                    priority: row.get::<String, _>("priority").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid priority".to_string())
-                    })?,
-                    control: row.get::<String, _>("control").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid control".to_string())
-                    })?,
-                    created_at: row.get("createdAt"),
-                    created_by: row.get::<String, _>("createdBy").parse().map_err(|_| {
-                        DatabaseError::SerializationError("Invalid created_by".to_string())
-                    })?,
-                    scheduled_for: row.get("scheduledFor"),
-                    updated_at: row.get("updatedAt"),
-                    executed_at: row.get("executedAt"),
-                    completed_at: row.get("completedAt"),
-                    queued_at: row.get("queuedAt"),
-                    error: row.get("error"),
-                    result: row.get("result"),
-                    model: row.get("model"),
-                    user_id: row.get("userId"),
-                })
-            })
+            .map(|row| Self::task_from_row(&row))
             .collect();
 
         let tasks = tasks?;
@@ -862,70 +1036,1208 @@ impl TaskRepositoryTrait for TaskRepository {
 
         let mut counts = HashMap::new();
         for row in rows {
-            let status: TaskStatus = row.get::<String, _>("status").parse().map_err(|_| {
-                DatabaseError::SerializationError("Invalid status in count query".to_string())
-            })?;
+            let status: TaskStatus = row.get("status");
             counts.insert(status, row.get::<i64, _>("count") as u64);
         }
 
         debug!("Task counts by status: {:?}", counts);
         Ok(counts)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use chrono::Duration;
-    use mockall::mock;
 
-    use super::*;
+    async fn claim_next_pending(&self, max_running: u32) -> Result<Option<Task>, DatabaseError> {
+        debug!("Claiming next pending task");
 
-    // Mock implementation for testing
-    mock! {
-        TaskRepo {}
+        let now = Utc::now();
+        let mut tx = self.begin_queue_transaction().await?;
 
-        #[async_trait]
-        impl TaskRepositoryTrait for TaskRepo {
-            async fn create(&self, dto: &CreateTaskDto) -> Result<Task, DatabaseError>;
-            async fn get_by_id(&self, id: &str) -> Result<Option<Task>, DatabaseError>;
-            async fn update(&self, id: &str, dto: &UpdateTaskDto) -> Result<Option<Task>, DatabaseError>;
-            async fn delete(&self, id: &str) -> Result<bool, DatabaseError>;
-            async fn list(
-                &self,
-                filter: &TaskFilter,
-                pagination: &PaginationParams,
-            ) -> Result<(Vec<Task>, u64), DatabaseError>;
-            async fn update_status(&self, id: &str, status: TaskStatus) -> Result<Option<Task>, DatabaseError>;
-            async fn get_tasks_by_status(&self, status: TaskStatus) -> Result<Vec<Task>, DatabaseError>;
-            async fn get_scheduled_tasks(&self, before: DateTime<Utc>) -> Result<Vec<Task>, DatabaseError>;
-            async fn count_by_status(&self) -> Result<HashMap<TaskStatus, u64>, DatabaseError>;
+        if Self::count_running(&mut tx).await? >= max_running as i64 {
+            debug!("Running task limit of {} reached", max_running);
+            return Ok(None);
         }
-    }
 
-    fn create_test_task() -> Task {
-        let now = Utc::now();
-        Task {
-            id: Uuid::new_v4().to_string(),
-            description: "Test task".to_string(),
-            task_type: TaskType::Immediate,
-            status: TaskStatus::Pending,
-            priority: TaskPriority::Medium,
-            control: Role::Assistant,
-            created_at: now,
-            created_by: Role::User,
+        // A replay holds the desktop until it finishes
+        let replaying: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (SELECT 1 FROM "TaskReplay" WHERE status = 'running')"#,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to check for running replays: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        if replaying {
+            debug!("Replay running on the desktop, not claiming tasks");
+            return Ok(None);
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = $1,
+                "executedAt" = COALESCE("executedAt", $2),
+                "retryAt" = NULL,
+                "updatedAt" = $2
+            WHERE id = (
+                SELECT id FROM "Task"
+                WHERE {RUNNABLE_CONDITION}
+                ORDER BY {QUEUE_ORDER}
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(TaskStatus::Running)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to claim pending task: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit task claim: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if let Some(ref task) = task {
+            info!(
+                "Claimed task {} ({} priority) for execution",
+                task.id, task.priority
+            );
+        }
+
+        Ok(task)
+    }
+
+    async fn mark_failed(
+        &self,
+        id: &str,
+        error: &str,
+        kind: FailureKind,
+    ) -> Result<Option<Task>, DatabaseError> {
+        debug!("Marking task {} as failed: {}", id, error);
+
+        let current_task = match self.get_by_id(id).await? {
+            Some(task) => task,
+            None => return Ok(None),
+        };
+
+        Self::validate_status_transition(current_task.status, TaskStatus::Failed)?;
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            error!("Failed to start transaction: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Self::record_attempt(&mut tx, &current_task, error, kind, None).await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = $2,
+                error = $3,
+                "executedAt" = COALESCE("executedAt", $4),
+                "completedAt" = $4,
+                "updatedAt" = $4
+            WHERE id = $1
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(TaskStatus::Failed)
+        .bind(error)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to mark task {} as failed: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit task failure: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if task.is_some() {
+            warn!("Task {} marked as failed: {}", id, error);
+        }
+
+        Ok(task)
+    }
+
+    async fn schedule_retry(
+        &self,
+        id: &str,
+        error: &str,
+        kind: FailureKind,
+        retry_at: DateTime<Utc>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        debug!("Scheduling retry of task {} at {}", id, retry_at);
+
+        let current_task = match self.get_by_id(id).await? {
+            Some(task) => task,
+            None => return Ok(None),
+        };
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            error!("Failed to start transaction: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Self::record_attempt(&mut tx, &current_task, error, kind, Some(retry_at)).await?;
+
+        // The task goes back to the queue with its messages, so the next
+        // attempt carries on from the conversation so far
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = $2,
+                error = $3,
+                attempt = attempt + 1,
+                "retryAt" = $4,
+                "updatedAt" = $5
+            WHERE id = $1 AND status = 'RUNNING'
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(TaskStatus::Pending)
+        .bind(error)
+        .bind(retry_at)
+        .bind(Utc::now())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to schedule retry of task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let Some(row) = row else {
+            // The task stopped running in the meantime, e.g. it was cancelled
+            return Ok(None);
+        };
+        let task = Self::task_from_row(&row)?;
+
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit task retry: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        warn!(
+            "Task {} failed on attempt {}, retrying at {}: {}",
+            id, current_task.attempt, retry_at, error
+        );
+
+        Ok(Some(task))
+    }
+
+    async fn list_attempts(&self, task_id: &str) -> Result<Vec<TaskAttempt>, DatabaseError> {
+        debug!("Listing attempts of task {}", task_id);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, "taskId", attempt, error, "failureKind", "retryAt", "createdAt"
+            FROM "TaskAttempt"
+            WHERE "taskId" = $1
+            ORDER BY attempt ASC, "createdAt" ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list attempts of task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(TaskAttempt {
+                    id: row.get("id"),
+                    task_id: row.get("taskId"),
+                    attempt: row.get("attempt"),
+                    error: row.get("error"),
+                    failure_kind: row.get::<String, _>("failureKind").parse().map_err(|_| {
+                        DatabaseError::SerializationError("Invalid failure kind".to_string())
+                    })?,
+                    retry_at: row.get("retryAt"),
+                    created_at: row.get("createdAt"),
+                })
+            })
+            .collect()
+    }
+
+    async fn set_result(&self, id: &str, result: &Value) -> Result<Option<Task>, DatabaseError> {
+        debug!("Storing result of task {}", id);
+
+        // Results can only be submitted while the agent is working on the task
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                result = $2,
+                "updatedAt" = $3
+            WHERE id = $1 AND status = 'RUNNING'
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(result)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store result of task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+        if task.is_some() {
+            info!("Stored result of task {}", id);
+        }
+
+        Ok(task)
+    }
+
+    async fn add_usage(&self, id: &str, usage: &TaskUsage) -> Result<Option<Task>, DatabaseError> {
+        debug!(
+            "Adding {} input and {} output tokens to task {}",
+            usage.input_tokens, usage.output_tokens, id
+        );
+
+        // Added in SQL so concurrent updates of the task cannot lose usage
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                usage = jsonb_build_object(
+                    'turns', COALESCE((usage->>'turns')::bigint, 0) + $2,
+                    'inputTokens', COALESCE((usage->>'inputTokens')::bigint, 0) + $3,
+                    'outputTokens', COALESCE((usage->>'outputTokens')::bigint, 0) + $4,
+                    'costUsd', COALESCE((usage->>'costUsd')::float8, 0) + $5
+                ),
+                "updatedAt" = $6
+            WHERE id = $1
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(i64::from(usage.turns))
+        .bind(usage.input_tokens as i64)
+        .bind(usage.output_tokens as i64)
+        .bind(usage.cost_usd)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to add usage to task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        row.as_ref().map(Self::task_from_row).transpose()
+    }
+
+    async fn mark_needs_help(&self, id: &str, reason: &str) -> Result<Option<Task>, DatabaseError> {
+        debug!("Marking task {} as needing help: {}", id, reason);
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = $4,
+                error = $2,
+                "updatedAt" = $3
+            WHERE id = $1 AND status = 'RUNNING'
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(reason)
+        .bind(Utc::now())
+        .bind(TaskStatus::NeedsHelp)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to mark task {} as needing help: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+        if task.is_some() {
+            warn!("Task {} needs help: {}", id, reason);
+        }
+
+        Ok(task)
+    }
+
+    async fn request_approval(
+        &self,
+        id: &str,
+        approval: &PendingApproval,
+    ) -> Result<Option<Task>, DatabaseError> {
+        debug!(
+            "Requesting approval of {} action for task {}",
+            approval.action, id
+        );
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = $4,
+                "pendingApproval" = $2,
+                "updatedAt" = $3
+            WHERE id = $1 AND status = 'RUNNING'
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(Json(approval))
+        .bind(Utc::now())
+        .bind(TaskStatus::NeedsReview)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to request approval for task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        row.as_ref().map(Self::task_from_row).transpose()
+    }
+
+    async fn decide_approval(
+        &self,
+        id: &str,
+        decision: ApprovalDecision,
+        dto: &ApprovalDecisionDto,
+    ) -> Result<Option<Task>, DatabaseError> {
+        debug!("Recording {:?} decision for task {}", decision, id);
+
+        // Only an undecided approval can be decided on, so concurrent
+        // reviewers cannot both win
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = 'RUNNING',
+                "pendingApproval" = "pendingApproval" || jsonb_build_object(
+                    'decision', $2::jsonb,
+                    'comment', $3::text
+                ),
+                "updatedAt" = $4
+            WHERE id = $1
+                AND status = $5
+                AND "pendingApproval" IS NOT NULL
+                AND "pendingApproval"->>'decision' IS NULL
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(Json(decision))
+        .bind(dto.comment.as_deref())
+        .bind(Utc::now())
+        .bind(TaskStatus::NeedsReview)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to record approval decision for task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        row.as_ref().map(Self::task_from_row).transpose()
+    }
+
+    async fn clear_approval(&self, id: &str) -> Result<(), DatabaseError> {
+        debug!("Clearing pending approval of task {}", id);
+
+        sqlx::query(r#"UPDATE "Task" SET "pendingApproval" = NULL WHERE id = $1"#)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to clear pending approval of task {}: {}", id, e);
+                DatabaseError::QueryError(e)
+            })?;
+
+        Ok(())
+    }
+
+    async fn mark_queued(&self, id: &str) -> Result<Option<Task>, DatabaseError> {
+        debug!("Queueing task {} for execution", id);
+
+        let now = Utc::now();
+
+        // Only pending tasks that have not been queued yet are affected, so a
+        // task is never enqueued twice
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                "queuedAt" = $2,
+                "updatedAt" = $2
+            WHERE id = $1
+            AND status = 'PENDING'
+            AND "queuedAt" IS NULL
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to queue task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+        if task.is_some() {
+            info!("Queued task {} for execution", id);
+        }
+
+        Ok(task)
+    }
+
+    async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, DatabaseError> {
+        debug!("Fetching next scheduled task time");
+
+        let row = sqlx::query(
+            r#"
+            SELECT MIN("scheduledFor") as next_run
+            FROM "Task"
+            WHERE type = $1
+            AND status = $2
+            AND "queuedAt" IS NULL
+            AND NOT "recurrencePaused"
+            "#,
+        )
+        .bind(TaskType::Scheduled)
+        .bind(TaskStatus::Pending)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch next scheduled task time: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.get("next_run"))
+    }
+
+    async fn preempt_for_urgent(&self, max_running: u32) -> Result<Option<Task>, DatabaseError> {
+        debug!("Checking whether an urgent task needs a slot");
+
+        let mut tx = self.begin_queue_transaction().await?;
+
+        if Self::count_running(&mut tx).await? < max_running as i64 {
+            return Ok(None);
+        }
+
+        // Only preempt when an URGENT task is actually waiting, and move the most
+        // recently started LOW task back to the queue. Its messages are kept, so
+        // the conversation picks up where it stopped once it is claimed again.
+        // A task the user has taken over keeps the desktop until they resume it.
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = $1,
+                "updatedAt" = $2
+            WHERE id = (
+                SELECT id FROM "Task"
+                WHERE status = 'RUNNING'
+                AND priority = 'LOW'
+                AND control = 'ASSISTANT'
+                AND EXISTS (
+                    SELECT 1 FROM "Task"
+                    WHERE {RUNNABLE_CONDITION}
+                    AND priority = 'URGENT'
+                )
+                ORDER BY "executedAt" DESC NULLS LAST
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(TaskStatus::Pending)
+        .bind(Utc::now())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to preempt task: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit task preemption: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if let Some(ref task) = task {
+            warn!("Preempted task {} for an urgent task", task.id);
+        }
+
+        Ok(task)
+    }
+
+    async fn requeue_interrupted(&self) -> Result<u64, DatabaseError> {
+        debug!("Requeueing interrupted tasks");
+
+        // Like a preempted task, a requeued task keeps its messages and picks
+        // up its conversation once it is claimed again
+        let result = sqlx::query(
+            r#"
+            UPDATE "Task"
+            SET status = $2, "updatedAt" = $3
+            WHERE status = $1
+            "#,
+        )
+        .bind(TaskStatus::Running)
+        .bind(TaskStatus::Pending)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to requeue interrupted tasks: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if result.rows_affected() > 0 {
+            info!("Requeued {} interrupted tasks", result.rows_affected());
+        }
+
+        Ok(result.rows_affected())
+    }
+
+    async fn queue_info(&self, id: &str) -> Result<QueueInfo, DatabaseError> {
+        debug!("Fetching queue position of task {}", id);
+
+        let row = sqlx::query(&format!(
+            r#"
+            WITH queue AS (
+                SELECT id, ROW_NUMBER() OVER (ORDER BY {QUEUE_ORDER}) AS position
+                FROM "Task"
+                WHERE {RUNNABLE_CONDITION}
+            )
+            SELECT 
+                (SELECT position FROM queue WHERE id = $1) AS position,
+                (SELECT COUNT(*) FROM queue) AS depth
+            "#
+        ))
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch queue position of task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(QueueInfo {
+            position: row.get::<Option<i64>, _>("position").map(|p| p as u64),
+            depth: row.get::<i64, _>("depth") as u64,
+        })
+    }
+
+    async fn queue_depth_by_priority(&self) -> Result<HashMap<TaskPriority, u64>, DatabaseError> {
+        debug!("Counting queued tasks by priority");
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT priority, COUNT(*) as count
+            FROM "Task"
+            WHERE {RUNNABLE_CONDITION}
+            GROUP BY priority
+            "#
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to count queued tasks by priority: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let mut counts = HashMap::new();
+        for row in rows {
+            let priority: TaskPriority = row.get("priority");
+            counts.insert(priority, row.get::<i64, _>("count") as u64);
+        }
+
+        Ok(counts)
+    }
+
+    async fn fail_unsatisfiable_dependents(&self) -> Result<Vec<Task>, DatabaseError> {
+        debug!("Failing tasks whose dependencies can no longer be met");
+
+        // A dependency that finished in the wrong terminal state can never
+        // release its dependent, so the dependent fails instead of waiting
+        let rows = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                status = 'FAILED',
+                error = blocker.reason,
+                "completedAt" = $1,
+                "updatedAt" = $1
+            FROM (
+                SELECT DISTINCT ON (d."taskId")
+                    d."taskId",
+                    format(
+                        'Dependency %s ended as %s, so its %s condition can never be met',
+                        dep.id, dep.status, d.condition
+                    ) AS reason
+                FROM "TaskDependency" d
+                JOIN "Task" dep ON dep.id = d."dependsOnId"
+                WHERE (d.condition = 'on_success' AND dep.status IN ('FAILED', 'CANCELLED'))
+                OR (d.condition = 'on_failure' AND dep.status IN ('COMPLETED', 'CANCELLED'))
+                ORDER BY d."taskId", dep.id
+            ) blocker
+            WHERE "Task".id = blocker."taskId"
+            AND "Task".status = 'PENDING'
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fail tasks with unmet dependencies: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let tasks = rows
+            .iter()
+            .map(Self::task_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        for task in &tasks {
+            warn!(
+                "Task {} failed: {}",
+                task.id,
+                task.error.as_deref().unwrap_or_default()
+            );
+        }
+
+        Ok(tasks)
+    }
+
+    async fn graph(&self, task_id: &str) -> Result<Option<TaskGraph>, DatabaseError> {
+        debug!("Fetching dependency graph of task {}", task_id);
+
+        // Walk dependency edges in both directions from the task
+        let rows = sqlx::query(&format!(
+            r#"
+            WITH RECURSIVE component(id) AS (
+                SELECT $1::text
+                UNION
+                SELECT CASE WHEN d."taskId" = c.id THEN d."dependsOnId" ELSE d."taskId" END
+                FROM "TaskDependency" d
+                JOIN component c ON c.id IN (d."taskId", d."dependsOnId")
+            )
+            SELECT 
+                "Task".id,
+                "Task".description,
+                "Task".status,
+                ({BLOCKED_CONDITION}) AS blocked
+            FROM "Task"
+            JOIN component ON component.id = "Task".id
+            ORDER BY "Task"."createdAt" ASC, "Task".id ASC
+            "#
+        ))
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to fetch dependency graph of task {}: {}",
+                task_id, e
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let nodes: Vec<TaskGraphNode> = rows
+            .iter()
+            .map(|row| TaskGraphNode {
+                id: row.get("id"),
+                description: row.get("description"),
+                status: row.get("status"),
+                blocked: row.get("blocked"),
+            })
+            .collect();
+        let ids: Vec<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT "taskId", "dependsOnId", condition
+            FROM "TaskDependency"
+            WHERE "taskId" = ANY($1)
+            ORDER BY "taskId", "dependsOnId"
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch dependencies of task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let edges = rows
+            .iter()
+            .map(|row| {
+                Ok(TaskDependency {
+                    task_id: row.get("taskId"),
+                    depends_on_id: row.get("dependsOnId"),
+                    condition: row.get::<String, _>("condition").parse().map_err(|_| {
+                        DatabaseError::SerializationError("Invalid condition".to_string())
+                    })?,
+                })
+            })
+            .collect::<Result<Vec<_>, DatabaseError>>()?;
+
+        Ok(Some(TaskGraph { nodes, edges }))
+    }
+
+    async fn fork(&self, source: &Task, messages: &[Message]) -> Result<Task, DatabaseError> {
+        let fork_point = messages.last().ok_or_else(|| {
+            DatabaseError::ValidationError("A fork needs at least one message".to_string())
+        })?;
+        debug!(
+            "Forking task {} at message {} with {} messages",
+            source.id,
+            fork_point.id,
+            messages.len()
+        );
+
+        // The fork runs once right away, whatever kind of task it came from
+        let dto = CreateTaskDto {
+            description: source.description.clone(),
+            task_type: None,
             scheduled_for: None,
-            updated_at: now,
-            executed_at: None,
-            completed_at: None,
-            queued_at: None,
-            error: None,
-            result: None,
+            priority: Some(source.priority),
+            created_by: Some(source.created_by),
+            user_id: source.user_id.clone(),
+            model: Some(source.model.clone()),
+            files: None,
+            recurrence: None,
+            result_schema: source.result_schema.clone(),
+            retry_policy: source.retry_policy.clone(),
+            depends_on: None,
+            budget: source.budget.clone(),
+            labels: Some(source.labels.clone()),
+        };
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        let task = Self::insert_task(&mut tx, &dto).await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET "forkedFromId" = $2, "forkedFromMessageId" = $3
+            WHERE id = $1
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(&task.id)
+        .bind(&source.id)
+        .bind(&fork_point.id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to record fork of task {}: {}", source.id, e);
+            DatabaseError::QueryError(e)
+        })?;
+        let task = Self::task_from_row(&row)?;
+
+        // Only summaries whose messages were all copied carry over; a summary
+        // that also covers later messages would leak them into the fork
+        let message_ids: Vec<&str> = messages.iter().map(|m| m.id.as_str()).collect();
+        let summary_rows = sqlx::query(
+            r#"
+            SELECT s.id, s.content, s."createdAt", s."parentId"
+            FROM "Summary" s
+            WHERE s."taskId" = $1
+            AND EXISTS (SELECT 1 FROM "Message" m WHERE m."summaryId" = s.id)
+            AND NOT EXISTS (
+                SELECT 1 FROM "Message" m
+                WHERE m."summaryId" = s.id AND NOT (m.id = ANY($2))
+            )
+            ORDER BY s."createdAt" ASC, s.id ASC
+            "#,
+        )
+        .bind(&source.id)
+        .bind(&message_ids)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch summaries of task {}: {}", source.id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let now = Utc::now();
+        let mut summary_ids = HashMap::new();
+        for row in &summary_rows {
+            let id = Uuid::new_v4().to_string();
+            let parent_id = row
+                .get::<Option<String>, _>("parentId")
+                .and_then(|parent| summary_ids.get(&parent).cloned());
+
+            sqlx::query(
+                r#"
+                INSERT INTO "Summary" (id, content, "createdAt", "updatedAt", "taskId", "parentId")
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(&id)
+            .bind(row.get::<String, _>("content"))
+            .bind(row.get::<DateTime<Utc>, _>("createdAt"))
+            .bind(now)
+            .bind(&task.id)
+            .bind(parent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to copy summary into task {}: {}", task.id, e);
+                DatabaseError::QueryError(e)
+            })?;
+
+            summary_ids.insert(row.get::<String, _>("id"), id);
+        }
+
+        // Messages keep their timestamps so the copy sorts like the original
+        for message in messages {
+            sqlx::query(
+                r#"
+                INSERT INTO "Message" (
+                    id, content, role, "createdAt", "updatedAt",
+                    "taskId", "summaryId", "userId"
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&message.content)
+            .bind(message.role)
+            .bind(message.created_at)
+            .bind(now)
+            .bind(&task.id)
+            .bind(
+                message
+                    .summary_id
+                    .as_ref()
+                    .and_then(|summary| summary_ids.get(summary)),
+            )
+            .bind(&message.user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to copy message into task {}: {}", task.id, e);
+                DatabaseError::QueryError(e)
+            })?;
+        }
+
+        let file_ids: Vec<String> =
+            sqlx::query_scalar(r#"SELECT id FROM "File" WHERE "taskId" = $1"#)
+                .bind(&source.id)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| {
+                    error!("Failed to fetch files of task {}: {}", source.id, e);
+                    DatabaseError::QueryError(e)
+                })?;
+
+        for file_id in &file_ids {
+            sqlx::query(
+                r#"
+                INSERT INTO "File" (id, name, type, size, data, "createdAt", "updatedAt", "taskId")
+                SELECT $1, name, type, size, data, $2, $2, $3
+                FROM "File"
+                WHERE id = $4
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(now)
+            .bind(&task.id)
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to copy file {} into task {}: {}",
+                    file_id, task.id, e
+                );
+                DatabaseError::QueryError(e)
+            })?;
+        }
+
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
+
+        info!(
+            "Forked task {} into {} at message {} ({} summaries, {} files)",
+            source.id,
+            task.id,
+            fork_point.id,
+            summary_rows.len(),
+            file_ids.len()
+        );
+        Ok(task)
+    }
+
+    async fn spawn_recurring_run(
+        &self,
+        definition: &Task,
+        fire_time: DateTime<Utc>,
+        next_run: Option<DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        debug!(
+            "Spawning run of recurring task {} for {}",
+            definition.id, fire_time
+        );
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            error!("Failed to start transaction: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        // Advancing the definition only succeeds for the scheduler that saw this
+        // firing first, so each firing spawns exactly one run. A rule without
+        // further runs is paused rather than left overdue.
+        let advanced = sqlx::query(
+            r#"
+            UPDATE "Task"
+            SET 
+                "scheduledFor" = COALESCE($2, "scheduledFor"),
+                "recurrencePaused" = $3,
+                "updatedAt" = $4
+            WHERE id = $1
+            AND "scheduledFor" = $5
+            AND status = 'PENDING'
+            AND NOT "recurrencePaused"
+            "#,
+        )
+        .bind(&definition.id)
+        .bind(next_run)
+        .bind(next_run.is_none())
+        .bind(now)
+        .bind(fire_time)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to advance recurring task {}: {}", definition.id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if advanced.rows_affected() == 0 {
+            debug!(
+                "Recurring task {} already advanced past {}",
+                definition.id, fire_time
+            );
+            return Ok(None);
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO "Task" (
+                id, description, type, status, priority, control, 
+                "createdAt", "createdBy", "scheduledFor", "updatedAt", 
+                "queuedAt", model, "userId", "recurringTaskId", "resultSchema",
+                "retryPolicy", budget, labels
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $7, $7, $10, $11, $12, $13, $14, $15, $16)
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(&definition.description)
+        .bind(TaskType::Scheduled)
+        .bind(TaskStatus::Pending)
+        .bind(definition.priority)
+        .bind(Role::Assistant)
+        .bind(now)
+        .bind(definition.created_by)
+        .bind(fire_time)
+        .bind(&definition.model)
+        .bind(&definition.user_id)
+        .bind(&definition.id)
+        .bind(&definition.result_schema)
+        .bind(definition.retry_policy.as_ref().map(Json))
+        .bind(definition.budget.as_ref().map(Json))
+        .bind(&definition.labels)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to create run of recurring task {}: {}",
+                definition.id, e
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        let run = Self::task_from_row(&row)?;
+
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit recurring run: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        info!("Spawned run {} of recurring task {}", run.id, definition.id);
+        Ok(Some(run))
+    }
+
+    async fn set_recurrence_paused(
+        &self,
+        id: &str,
+        paused: bool,
+        next_run: Option<DateTime<Utc>>,
+    ) -> Result<Option<Task>, DatabaseError> {
+        debug!("Setting recurrence of task {} paused: {}", id, paused);
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "Task"
+            SET 
+                "recurrencePaused" = $2,
+                "scheduledFor" = COALESCE($3, "scheduledFor"),
+                "updatedAt" = $4
+            WHERE id = $1
+            AND "cronExpression" IS NOT NULL
+            RETURNING 
+                {TASK_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(paused)
+        .bind(next_run)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to update recurrence of task {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let task = row.as_ref().map(Self::task_from_row).transpose()?;
+        if task.is_some() {
+            info!(
+                "Recurrence of task {} {}",
+                id,
+                if paused { "paused" } else { "resumed" }
+            );
+        }
+
+        Ok(task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use mockall::mock;
+
+    use super::*;
+
+    // Mock implementation for testing
+    mock! {
+        TaskRepo {}
+
+        #[async_trait]
+        impl TaskRepositoryTrait for TaskRepo {
+            async fn create(&self, dto: &CreateTaskDto) -> Result<Task, DatabaseError>;
+            async fn get_by_id(&self, id: &str) -> Result<Option<Task>, DatabaseError>;
+            async fn update(&self, id: &str, dto: &UpdateTaskDto) -> Result<Option<Task>, DatabaseError>;
+            async fn delete(&self, id: &str) -> Result<bool, DatabaseError>;
+            async fn list(
+                &self,
+                filter: &TaskFilter,
+                pagination: &PaginationParams,
+            ) -> Result<(Vec<Task>, u64), DatabaseError>;
+            async fn update_status(&self, id: &str, status: TaskStatus) -> Result<Option<Task>, DatabaseError>;
+            async fn get_tasks_by_status(&self, status: TaskStatus) -> Result<Vec<Task>, DatabaseError>;
+            async fn get_scheduled_tasks(&self, before: DateTime<Utc>) -> Result<Vec<Task>, DatabaseError>;
+            async fn count_by_status(&self) -> Result<HashMap<TaskStatus, u64>, DatabaseError>;
+            async fn claim_next_pending(&self, max_running: u32) -> Result<Option<Task>, DatabaseError>;
+            async fn mark_failed(
+                &self,
+                id: &str,
+                error: &str,
+                kind: FailureKind,
+            ) -> Result<Option<Task>, DatabaseError>;
+            async fn schedule_retry(
+                &self,
+                id: &str,
+                error: &str,
+                kind: FailureKind,
+                retry_at: DateTime<Utc>,
+            ) -> Result<Option<Task>, DatabaseError>;
+            async fn list_attempts(&self, task_id: &str) -> Result<Vec<TaskAttempt>, DatabaseError>;
+            async fn set_result(&self, id: &str, result: &Value) -> Result<Option<Task>, DatabaseError>;
+            async fn add_usage(&self, id: &str, usage: &TaskUsage) -> Result<Option<Task>, DatabaseError>;
+            async fn mark_needs_help(&self, id: &str, reason: &str) -> Result<Option<Task>, DatabaseError>;
+            async fn request_approval(&self, id: &str, approval: &PendingApproval) -> Result<Option<Task>, DatabaseError>;
+            async fn decide_approval(&self, id: &str, decision: ApprovalDecision, dto: &ApprovalDecisionDto) -> Result<Option<Task>, DatabaseError>;
+            async fn clear_approval(&self, id: &str) -> Result<(), DatabaseError>;
+            async fn mark_queued(&self, id: &str) -> Result<Option<Task>, DatabaseError>;
+            async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, DatabaseError>;
+            async fn spawn_recurring_run(
+                &self,
+                definition: &Task,
+                fire_time: DateTime<Utc>,
+                next_run: Option<DateTime<Utc>>,
+            ) -> Result<Option<Task>, DatabaseError>;
+            async fn set_recurrence_paused(
+                &self,
+                id: &str,
+                paused: bool,
+                next_run: Option<DateTime<Utc>>,
+            ) -> Result<Option<Task>, DatabaseError>;
+            async fn preempt_for_urgent(&self, max_running: u32) -> Result<Option<Task>, DatabaseError>;
+            async fn requeue_interrupted(&self) -> Result<u64, DatabaseError>;
+            async fn queue_info(&self, id: &str) -> Result<QueueInfo, DatabaseError>;
+            async fn queue_depth_by_priority(&self) -> Result<HashMap<TaskPriority, u64>, DatabaseError>;
+            async fn fail_unsatisfiable_dependents(&self) -> Result<Vec<Task>, DatabaseError>;
+            async fn graph(&self, task_id: &str) -> Result<Option<TaskGraph>, DatabaseError>;
+            async fn fork(&self, source: &Task, messages: &[Message]) -> Result<Task, DatabaseError>;
+        }
+    }
+
+    fn create_test_task() -> Task {
+        Task {
+            description: "Test task".to_string(),
             model: serde_json::json!({
                 "provider": "anthropic",
                 "name": "claude-3-sonnet-20240229",
                 "title": "Claude 3 Sonnet"
             }),
-            user_id: None,
+            ..Default::default()
         }
     }
 
@@ -943,6 +2255,12 @@ mod tests {
                 "title": "Claude 3 Sonnet"
             })),
             files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         }
     }
 
@@ -1029,6 +2347,36 @@ mod tests {
         assert_eq!(params.len(), 3);
     }
 
+    #[test]
+    fn test_build_filter_clause_with_labels() {
+        let filter = TaskFilter {
+            status: Some(TaskStatus::Failed),
+            labels: vec!["customer=acme".to_string(), "urgent".to_string()],
+            ..Default::default()
+        };
+        let (where_clause, params) = TaskRepository::build_filter_clause(&filter);
+
+        assert!(where_clause.contains("labels @> ARRAY[$2]"));
+        assert!(where_clause.contains("split_part(label, '=', 1) = $3"));
+        assert_eq!(params[1], FilterParam::Text("customer=acme".to_string()));
+        assert_eq!(params[2], FilterParam::Text("urgent".to_string()));
+    }
+
+    #[test]
+    fn test_order_clause() {
+        assert_eq!(
+            TaskRepository::order_clause(TaskSort::default()),
+            r#""createdAt" DESC NULLS LAST, id DESC"#
+        );
+        assert_eq!(
+            TaskRepository::order_clause(TaskSort {
+                field: TaskSortField::Priority,
+                direction: SortDirection::Asc,
+            }),
+            "priority ASC NULLS LAST, id ASC"
+        );
+    }
+
     #[tokio::test]
     async fn test_mock_repository_create() {
         let mut mock_repo = MockTaskRepo::new();
@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use bytebot_shared_rs::types::{
+    task::TaskPriority,
+    template::{CreateTaskTemplateDto, TaskTemplate},
+};
+use chrono::Utc;
+use sqlx::{postgres::PgRow, types::Json, Pool, Postgres, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::DatabaseError;
+
+const TEMPLATE_COLUMNS: &str = r#"
+    id,
+    name,
+    description,
+    parameters,
+    model,
+    priority,
+    files,
+    "resultSchema",
+    "createdAt",
+    "updatedAt"
+"#;
+
+/// Task template repository trait for dependency injection and testing
+#[async_trait]
+pub trait TemplateRepositoryTrait: Send + Sync {
+    async fn create(&self, dto: &CreateTaskTemplateDto) -> Result<TaskTemplate, DatabaseError>;
+    async fn get_by_id(&self, id: &str) -> Result<Option<TaskTemplate>, DatabaseError>;
+    async fn list(&self) -> Result<Vec<TaskTemplate>, DatabaseError>;
+    /// Replace a template, returning `None` if it does not exist
+    async fn update(
+        &self,
+        id: &str,
+        dto: &CreateTaskTemplateDto,
+    ) -> Result<Option<TaskTemplate>, DatabaseError>;
+    async fn delete(&self, id: &str) -> Result<bool, DatabaseError>;
+}
+
+/// SQLx-based task template repository implementation
+pub struct TemplateRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TemplateRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    fn validate_dto(dto: &CreateTaskTemplateDto) -> Result<(), DatabaseError> {
+        dto.validate()
+            .map_err(|e| DatabaseError::ValidationError(format!("Invalid template: {e}")))?;
+        dto.check_parameters()
+            .map_err(|e| DatabaseError::ValidationError(format!("Invalid template: {e}")))
+    }
+
+    fn template_from_row(row: &PgRow) -> TaskTemplate {
+        TaskTemplate {
+            id: row.get("id"),
+            name: row.get("name"),
+            description: row.get("description"),
+            parameters: row.get::<Json<_>, _>("parameters").0,
+            model: row.get("model"),
+            priority: row.get::<TaskPriority, _>("priority"),
+            files: row.get::<Json<_>, _>("files").0,
+            result_schema: row.get("resultSchema"),
+            created_at: row.get("createdAt"),
+            updated_at: row.get("updatedAt"),
+        }
+    }
+}
+
+#[async_trait]
+impl TemplateRepositoryTrait for TemplateRepository {
+    async fn create(&self, dto: &CreateTaskTemplateDto) -> Result<TaskTemplate, DatabaseError> {
+        debug!("Creating task template: {}", dto.name);
+
+        Self::validate_dto(dto)?;
+
+        let now = Utc::now();
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO "TaskTemplate" (
+                id, name, description, parameters, model, priority, files,
+                "resultSchema", "createdAt", "updatedAt"
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            RETURNING
+                {TEMPLATE_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(&dto.name)
+        .bind(&dto.description)
+        .bind(Json(&dto.parameters))
+        .bind(&dto.model)
+        .bind(dto.priority.unwrap_or_default())
+        .bind(Json(dto.files.as_deref().unwrap_or_default()))
+        .bind(&dto.result_schema)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to create task template: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let template = Self::template_from_row(&row);
+        info!("Created task template {} ({})", template.id, template.name);
+
+        Ok(template)
+    }
+
+    async fn get_by_id(&self, id: &str) -> Result<Option<TaskTemplate>, DatabaseError> {
+        debug!("Fetching task template: {}", id);
+
+        let row = sqlx::query(&format!(
+            r#"SELECT {TEMPLATE_COLUMNS} FROM "TaskTemplate" WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch task template {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.as_ref().map(Self::template_from_row))
+    }
+
+    async fn list(&self) -> Result<Vec<TaskTemplate>, DatabaseError> {
+        debug!("Listing task templates");
+
+        let rows = sqlx::query(&format!(
+            r#"SELECT {TEMPLATE_COLUMNS} FROM "TaskTemplate" ORDER BY name ASC, id ASC"#
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list task templates: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.iter().map(Self::template_from_row).collect())
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        dto: &CreateTaskTemplateDto,
+    ) -> Result<Option<TaskTemplate>, DatabaseError> {
+        debug!("Updating task template: {}", id);
+
+        Self::validate_dto(dto)?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "TaskTemplate"
+            SET
+                name = $2,
+                description = $3,
+                parameters = $4,
+                model = $5,
+                priority = $6,
+                files = $7,
+                "resultSchema" = $8,
+                "updatedAt" = $9
+            WHERE id = $1
+            RETURNING
+                {TEMPLATE_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(&dto.name)
+        .bind(&dto.description)
+        .bind(Json(&dto.parameters))
+        .bind(&dto.model)
+        .bind(dto.priority.unwrap_or_default())
+        .bind(Json(dto.files.as_deref().unwrap_or_default()))
+        .bind(&dto.result_schema)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to update task template {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if row.is_some() {
+            info!("Updated task template {}", id);
+        }
+
+        Ok(row.as_ref().map(Self::template_from_row))
+    }
+
+    async fn delete(&self, id: &str) -> Result<bool, DatabaseError> {
+        debug!("Deleting task template: {}", id);
+
+        let result = sqlx::query(r#"DELETE FROM "TaskTemplate" WHERE id = $1"#)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete task template {}: {}", id, e);
+                DatabaseError::QueryError(e)
+            })?;
+
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            info!("Deleted task template {}", id);
+        }
+
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use bytebot_shared_rs::types::{
+        bundle::TaskBundle,
+        message::{Message, MessageContentBlock, Summary},
+        task::{Role, Task, TaskStatus},
+        user::File,
+    };
+    use chrono::{Duration, Utc};
+
+    use crate::database::{
+        bundle_repository::{BundleRepository, BundleRepositoryTrait},
+        task_repository::{TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+    };
+
+    /// Bundle of a task from another deployment: two chained summaries, a
+    /// message folded into the child summary, a live message and a file
+    fn bundle_with_status(status: TaskStatus) -> TaskBundle {
+        let mut task = Task::new(
+            "Exported task".to_string(),
+            serde_json::json!({"provider": "anthropic", "name": "claude"}),
+        );
+        task.status = status;
+        if task.is_terminal() {
+            task.completed_at = Some(Utc::now());
+        }
+
+        let earlier = Utc::now() - Duration::minutes(5);
+        let mut parent = Summary::new("First part".to_string(), task.id.clone());
+        parent.created_at = earlier;
+        let child = Summary::new_child(
+            "Second part".to_string(),
+            task.id.clone(),
+            parent.id.clone(),
+        );
+
+        let mut summarized = Message::new(
+            vec![MessageContentBlock::text("Open the browser")],
+            Role::User,
+            task.id.clone(),
+        );
+        summarized.summary_id = Some(child.id.clone());
+        summarized.created_at = earlier;
+        let live = Message::new(
+            vec![MessageContentBlock::text("Done")],
+            Role::Assistant,
+            task.id.clone(),
+        );
+
+        let file = File::new(
+            "hello.txt".to_string(),
+            "text/plain".to_string(),
+            11,
+            "SGVsbG8gV29ybGQ=".to_string(),
+            task.id.clone(),
+        );
+
+        TaskBundle::new(
+            task,
+            vec![summarized, live],
+            vec![parent, child],
+            vec![file],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_import_remaps_ids() {
+        let pool = create_isolated_test_pool().await;
+        let repo = BundleRepository::new(pool.clone());
+        let bundle = bundle_with_status(TaskStatus::Completed);
+
+        let task = repo.import(&bundle, None).await.expect("Failed to import");
+        assert_ne!(task.id, bundle.task.id);
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.description, "Exported task");
+
+        let imported = repo.export(&task.id).await.unwrap().unwrap();
+        assert_eq!(imported.task.status, TaskStatus::Completed);
+
+        // Summaries get new IDs, and the child points at the new parent
+        assert_eq!(imported.summaries.len(), 2);
+        let parent = &imported.summaries[0];
+        let child = &imported.summaries[1];
+        assert_eq!(parent.content, "First part");
+        assert!(parent.parent_id.is_none());
+        assert_eq!(child.parent_id.as_ref(), Some(&parent.id));
+        for summary in &imported.summaries {
+            assert_eq!(summary.task_id, task.id);
+            assert!(bundle.summaries.iter().all(|old| old.id != summary.id));
+        }
+
+        // Messages keep their order and point at the new summaries
+        assert_eq!(imported.messages.len(), 2);
+        assert_eq!(imported.messages[0].role, Role::User);
+        assert_eq!(imported.messages[0].summary_id.as_ref(), Some(&child.id));
+        assert_eq!(imported.messages[1].role, Role::Assistant);
+        assert!(imported.messages[1].summary_id.is_none());
+        for message in &imported.messages {
+            assert_eq!(message.task_id, task.id);
+            assert!(bundle.messages.iter().all(|old| old.id != message.id));
+        }
+
+        // Files are copied under new IDs
+        assert_eq!(imported.files.len(), 1);
+        assert_ne!(imported.files[0].id, bundle.files[0].id);
+        assert_eq!(imported.files[0].task_id, task.id);
+        assert_eq!(imported.files[0].data, bundle.files[0].data);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_unfinished_task_needs_help() {
+        let pool = create_isolated_test_pool().await;
+        let repo = BundleRepository::new(pool.clone());
+
+        let task = repo
+            .import(&bundle_with_status(TaskStatus::Running), None)
+            .await
+            .expect("Failed to import");
+        assert_eq!(task.status, TaskStatus::NeedsHelp);
+
+        let stored = TaskRepository::new(pool.clone())
+            .get_by_id(&task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, TaskStatus::NeedsHelp);
+
+        drop_isolated_schema(&pool).await;
+    }
+}
@@ -26,6 +26,12 @@ mod tests {
             user_id: Some("test-user-id".to_string()),
             model: None,
             files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
 
         let task = task_repo
@@ -1,7 +1,9 @@
-use std::sync::Once;
+use std::sync::{Arc, Once, OnceLock};
 
-use sqlx::{PgPool, Pool, Postgres};
+use bytebot_shared_rs::MetricsCollector;
+use sqlx::{postgres::PgPoolOptions, Executor, PgPool, Pool, Postgres};
 use tracing_subscriber;
+use uuid::Uuid;
 
 static INIT: Once = Once::new();
 
@@ -15,19 +17,82 @@ pub fn init_test_logging() {
     });
 }
 
+/// Metrics collector shared by the tests of this process.
+///
+/// The collector installs the global metrics recorder, which can only happen
+/// once per process.
+pub fn test_metrics() -> Arc<MetricsCollector> {
+    static METRICS: OnceLock<Arc<MetricsCollector>> = OnceLock::new();
+
+    METRICS
+        .get_or_init(|| Arc::new(MetricsCollector::new("test-service").unwrap()))
+        .clone()
+}
+
+fn test_database_url() -> String {
+    std::env::var("TEST_DATABASE_URL")
+        .or_else(|_| std::env::var("DATABASE_URL"))
+        .expect("TEST_DATABASE_URL or DATABASE_URL must be set for integration tests")
+}
+
 /// Create a test database pool
 pub async fn create_test_pool() -> Pool<Postgres> {
     init_test_logging();
 
-    let database_url = std::env::var("TEST_DATABASE_URL")
-        .or_else(|_| std::env::var("DATABASE_URL"))
-        .expect("TEST_DATABASE_URL or DATABASE_URL must be set for integration tests");
+    let database_url = test_database_url();
 
     PgPool::connect(&database_url)
         .await
         .expect("Failed to connect to test database")
 }
 
+/// Create a pool on a new, migrated schema of the test database.
+///
+/// Tests using it see no rows but their own, so they can run in parallel
+/// with each other and with tests calling `cleanup_test_data`.
+pub async fn create_isolated_test_pool() -> Pool<Postgres> {
+    let pool = create_test_pool().await;
+    let schema = format!("test_{}", Uuid::new_v4().simple());
+    pool.execute(format!(r#"CREATE SCHEMA "{schema}""#).as_str())
+        .await
+        .expect("Failed to create test schema");
+    pool.close().await;
+
+    let database_url = test_database_url();
+    let pool = PgPoolOptions::new()
+        .max_connections(10)
+        .after_connect(move |conn, _| {
+            let schema = schema.clone();
+            Box::pin(async move {
+                conn.execute(format!(r#"SET search_path TO "{schema}""#).as_str())
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate test schema");
+
+    pool
+}
+
+/// Drop the schema of a pool created by `create_isolated_test_pool`
+pub async fn drop_isolated_schema(pool: &PgPool) {
+    let schema: String = sqlx::query_scalar("SELECT current_schema()")
+        .fetch_one(pool)
+        .await
+        .expect("Failed to read test schema");
+    let _ = pool
+        .execute(format!(r#"DROP SCHEMA "{schema}" CASCADE"#).as_str())
+        .await;
+    pool.close().await;
+}
+
 /// Clean up test data from all tables
 pub async fn cleanup_test_data(pool: &PgPool) {
     // Delete in order to respect foreign key constraints
@@ -41,5 +106,13 @@ pub async fn cleanup_test_data(pool: &PgPool) {
     let _ = sqlx::query(r#"DELETE FROM "User""#).execute(pool).await;
 }
 
+pub mod bundle_repository_tests;
 pub mod message_repository_tests;
+pub mod replay_repository_tests;
+pub mod search_repository_tests;
+pub mod task_dependency_tests;
+pub mod task_fork_tests;
+pub mod task_queue_tests;
+pub mod task_status_tests;
+pub mod usage_repository_tests;
 pub mod user_repository_tests;
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use bytebot_shared_rs::types::{
+        api::CreateTaskDto,
+        replay::{ReplayOptions, ReplayStatus},
+        task::{Role, TaskPriority, TaskStatus, TaskType},
+    };
+    use sqlx::PgPool;
+
+    use crate::database::{
+        replay_repository::{ReplayRepository, ReplayRepositoryTrait},
+        task_repository::{TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+    };
+
+    async fn create_task(pool: &PgPool, description: &str) -> String {
+        TaskRepository::new(pool.clone())
+            .create(&CreateTaskDto {
+                description: description.to_string(),
+                task_type: Some(TaskType::Immediate),
+                scheduled_for: None,
+                priority: Some(TaskPriority::Medium),
+                created_by: Some(Role::User),
+                user_id: None,
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .expect("Failed to create task")
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_replay_holds_the_queue() {
+        let pool = create_isolated_test_pool().await;
+        let replays = ReplayRepository::new(pool.clone());
+        let tasks = TaskRepository::new(pool.clone());
+        let recorded = create_task(&pool, "Recorded task").await;
+        create_task(&pool, "Queued task").await;
+
+        let replay = replays
+            .create(&recorded, &ReplayOptions::default())
+            .await
+            .expect("Failed to create replay")
+            .expect("Desktop was busy");
+        assert_eq!(replay.status, ReplayStatus::Running);
+
+        // Neither a task nor another replay starts while it runs
+        assert!(tasks.claim_next_pending(10).await.unwrap().is_none());
+        assert!(replays
+            .create(&recorded, &ReplayOptions::default())
+            .await
+            .unwrap()
+            .is_none());
+
+        replays
+            .finish(&replay.id, ReplayStatus::Completed, None)
+            .await
+            .unwrap()
+            .expect("Replay not found");
+
+        let claimed = tasks.claim_next_pending(10).await.unwrap().unwrap();
+        assert_eq!(claimed.status, TaskStatus::Running);
+
+        // A running task keeps replays off the desktop
+        assert!(replays
+            .create(&recorded, &ReplayOptions::default())
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(replays.list_by_task_id(&recorded).await.unwrap().len(), 1);
+
+        tasks
+            .update_status(&claimed.id, TaskStatus::Completed)
+            .await
+            .unwrap();
+        assert!(replays
+            .create(&recorded, &ReplayOptions::default())
+            .await
+            .unwrap()
+            .is_some());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_fail_interrupted_replays() {
+        let pool = create_isolated_test_pool().await;
+        let replays = ReplayRepository::new(pool.clone());
+        let tasks = TaskRepository::new(pool.clone());
+        let recorded = create_task(&pool, "Recorded task").await;
+
+        let finished = replays
+            .create(&recorded, &ReplayOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        replays
+            .finish(&finished.id, ReplayStatus::Diverged, None)
+            .await
+            .unwrap();
+        let interrupted = replays
+            .create(&recorded, &ReplayOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(replays.fail_interrupted().await.unwrap(), 1);
+
+        let replay = replays.get_by_id(&interrupted.id).await.unwrap().unwrap();
+        assert_eq!(replay.status, ReplayStatus::Failed);
+        assert!(replay.error.is_some());
+        assert!(replay.completed_at.is_some());
+        let replay = replays.get_by_id(&finished.id).await.unwrap().unwrap();
+        assert_eq!(replay.status, ReplayStatus::Diverged);
+
+        // The queue is free again
+        assert!(tasks.claim_next_pending(10).await.unwrap().is_some());
+        assert_eq!(replays.fail_interrupted().await.unwrap(), 0);
+
+        drop_isolated_schema(&pool).await;
+    }
+}
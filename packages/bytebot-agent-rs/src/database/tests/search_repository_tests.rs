@@ -0,0 +1,237 @@
+#[cfg(test)]
+mod tests {
+    use bytebot_shared_rs::types::{
+        api::{CreateTaskDto, PaginationParams},
+        message::MessageContentBlock,
+        search::SearchSource,
+        task::{Role, TaskPriority, TaskStatus, TaskType},
+    };
+    use sqlx::PgPool;
+
+    use crate::database::{
+        message_repository::{CreateMessageDto, MessageRepository, MessageRepositoryTrait},
+        search_repository::{SearchQuery, SearchRepository, SearchRepositoryTrait},
+        task_repository::{TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+        user_repository::{CreateUserDto, UserRepository, UserRepositoryTrait},
+    };
+
+    /// IDs of the tasks searched by the tests
+    struct Fixture {
+        alice: String,
+        bob: String,
+        /// Alice's task matching "flight lisbon" in its description
+        flight: String,
+        /// Alice's task matching "flight lisbon" in a message only
+        trip: String,
+        /// Bob's task matching "flight" in its description
+        prices: String,
+    }
+
+    async fn create_user(pool: &PgPool, email: &str) -> String {
+        UserRepository::new(pool.clone())
+            .create_user(&CreateUserDto {
+                email: email.to_string(),
+                name: None,
+                email_verified: Some(true),
+                image: None,
+            })
+            .await
+            .expect("Failed to create user")
+            .id
+    }
+
+    async fn create_task(pool: &PgPool, description: &str, user_id: &str) -> String {
+        TaskRepository::new(pool.clone())
+            .create(&CreateTaskDto {
+                description: description.to_string(),
+                task_type: Some(TaskType::Immediate),
+                scheduled_for: None,
+                priority: Some(TaskPriority::Medium),
+                created_by: Some(Role::User),
+                user_id: Some(user_id.to_string()),
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .expect("Failed to create task")
+            .id
+    }
+
+    async fn create_fixture(pool: &PgPool) -> Fixture {
+        let alice = create_user(pool, "alice@example.com").await;
+        let bob = create_user(pool, "bob@example.com").await;
+
+        let flight = create_task(pool, "Book a flight to Lisbon", &alice).await;
+        let trip = create_task(pool, "Plan the weekend", &alice).await;
+        let prices = create_task(pool, "Compare flight prices", &bob).await;
+        create_task(pool, "Water the plants", &alice).await;
+
+        MessageRepository::new(pool.clone())
+            .create(&CreateMessageDto {
+                content: vec![MessageContentBlock::text(
+                    "Look for a cheap flight to Lisbon on Friday",
+                )],
+                role: Role::User,
+                task_id: trip.clone(),
+                user_id: Some(alice.clone()),
+                summary_id: None,
+            })
+            .await
+            .expect("Failed to create message");
+
+        Fixture {
+            alice,
+            bob,
+            flight,
+            trip,
+            prices,
+        }
+    }
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            text: text.to_string(),
+            ..SearchQuery::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_description_matches_first() {
+        let pool = create_isolated_test_pool().await;
+        let fixture = create_fixture(&pool).await;
+        let repo = SearchRepository::new(pool.clone());
+
+        let (results, total) = repo
+            .search(&query("flight lisbon"), &PaginationParams::default())
+            .await
+            .expect("Search failed");
+
+        assert_eq!(total, 2);
+        let ids: Vec<&str> = results.iter().map(|r| r.task.id.as_str()).collect();
+        assert_eq!(ids, [fixture.flight.as_str(), fixture.trip.as_str()]);
+        assert!(results[0].rank > results[1].rank);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_search_owner_filter() {
+        let pool = create_isolated_test_pool().await;
+        let fixture = create_fixture(&pool).await;
+        let repo = SearchRepository::new(pool.clone());
+
+        let (results, total) = repo
+            .search(
+                &SearchQuery {
+                    user_id: Some(fixture.bob.clone()),
+                    ..query("flight")
+                },
+                &PaginationParams::default(),
+            )
+            .await
+            .expect("Search failed");
+        assert_eq!(total, 1);
+        assert_eq!(results[0].task.id, fixture.prices);
+
+        let (results, total) = repo
+            .search(
+                &SearchQuery {
+                    user_id: Some(fixture.alice.clone()),
+                    ..query("flight")
+                },
+                &PaginationParams::default(),
+            )
+            .await
+            .expect("Search failed");
+        assert_eq!(total, 2);
+        assert!(results.iter().all(|r| r.task.id != fixture.prices));
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_search_status_filter() {
+        let pool = create_isolated_test_pool().await;
+        let fixture = create_fixture(&pool).await;
+        let tasks = TaskRepository::new(pool.clone());
+        tasks
+            .update_status(&fixture.flight, TaskStatus::Running)
+            .await
+            .unwrap();
+        tasks
+            .mark_needs_help(&fixture.flight, "Which airport?")
+            .await
+            .unwrap()
+            .expect("Task was not running");
+        let repo = SearchRepository::new(pool.clone());
+
+        let (results, total) = repo
+            .search(
+                &SearchQuery {
+                    status: Some(TaskStatus::NeedsHelp),
+                    ..query("flight")
+                },
+                &PaginationParams::default(),
+            )
+            .await
+            .expect("Search failed");
+        assert_eq!(total, 1);
+        assert_eq!(results[0].task.id, fixture.flight);
+        assert_eq!(results[0].task.status, TaskStatus::NeedsHelp);
+
+        let (_, total) = repo
+            .search(
+                &SearchQuery {
+                    status: Some(TaskStatus::Pending),
+                    ..query("flight")
+                },
+                &PaginationParams::default(),
+            )
+            .await
+            .expect("Search failed");
+        assert_eq!(total, 2);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_search_snippets() {
+        let pool = create_isolated_test_pool().await;
+        let fixture = create_fixture(&pool).await;
+        let repo = SearchRepository::new(pool.clone());
+
+        let (results, _) = repo
+            .search(&query("lisbon"), &PaginationParams::default())
+            .await
+            .expect("Search failed");
+
+        let flight = results
+            .iter()
+            .find(|r| r.task.id == fixture.flight)
+            .unwrap();
+        assert_eq!(flight.highlights.len(), 1);
+        assert_eq!(flight.highlights[0].source, SearchSource::Description);
+        assert!(flight.highlights[0].message_id.is_none());
+        assert_eq!(
+            flight.highlights[0].snippet,
+            "Book a flight to <mark>Lisbon</mark>"
+        );
+
+        let trip = results.iter().find(|r| r.task.id == fixture.trip).unwrap();
+        assert_eq!(trip.highlights.len(), 1);
+        assert_eq!(trip.highlights[0].source, SearchSource::Message);
+        assert!(trip.highlights[0].message_id.is_some());
+        assert!(trip.highlights[0]
+            .snippet
+            .contains("flight to <mark>Lisbon</mark>"));
+
+        drop_isolated_schema(&pool).await;
+    }
+}
@@ -0,0 +1,303 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytebot_shared_rs::types::{
+        api::{CreateTaskDto, PaginationParams},
+        dependency::{DependencyCondition, TaskDependency, TaskDependencyDto},
+        task::{Role, Task, TaskPriority, TaskStatus, TaskType},
+    };
+
+    use crate::database::{
+        task_repository::{TaskFilter, TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+        DatabaseError,
+    };
+
+    async fn create_task(
+        repo: &TaskRepository,
+        description: &str,
+        depends_on: &[(&str, DependencyCondition)],
+    ) -> Result<Task, DatabaseError> {
+        // Keep the stored millisecond timestamps apart, so graph order is defined
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        repo.create(&CreateTaskDto {
+            description: description.to_string(),
+            task_type: Some(TaskType::Immediate),
+            scheduled_for: None,
+            priority: Some(TaskPriority::Medium),
+            created_by: Some(Role::User),
+            user_id: None,
+            model: None,
+            files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: (!depends_on.is_empty()).then(|| {
+                depends_on
+                    .iter()
+                    .map(|(task_id, condition)| TaskDependencyDto {
+                        task_id: task_id.to_string(),
+                        condition: *condition,
+                    })
+                    .collect()
+            }),
+            budget: None,
+            labels: None,
+        })
+        .await
+    }
+
+    /// `build` depends on `fetch` succeeding, `report` on it failing, and
+    /// `cleanup` on both of them finishing either way
+    struct Pipeline {
+        fetch: String,
+        build: String,
+        report: String,
+        cleanup: String,
+        unrelated: String,
+    }
+
+    async fn create_pipeline(repo: &TaskRepository) -> Pipeline {
+        use DependencyCondition::*;
+
+        let fetch = create_task(repo, "Fetch", &[]).await.unwrap().id;
+        let build = create_task(repo, "Build", &[(&fetch, OnSuccess)])
+            .await
+            .unwrap()
+            .id;
+        let report = create_task(repo, "Report", &[(&fetch, OnFailure)])
+            .await
+            .unwrap()
+            .id;
+        let cleanup = create_task(repo, "Cleanup", &[(&build, Always), (&report, Always)])
+            .await
+            .unwrap()
+            .id;
+        let unrelated = create_task(repo, "Unrelated", &[]).await.unwrap().id;
+
+        Pipeline {
+            fetch,
+            build,
+            report,
+            cleanup,
+            unrelated,
+        }
+    }
+
+    fn edge(task_id: &str, depends_on_id: &str, condition: DependencyCondition) -> TaskDependency {
+        TaskDependency {
+            task_id: task_id.to_string(),
+            depends_on_id: depends_on_id.to_string(),
+            condition,
+        }
+    }
+
+    async fn list_ids(repo: &TaskRepository, filter: TaskFilter) -> Vec<String> {
+        let (tasks, _) = repo
+            .list(&filter, &PaginationParams::default())
+            .await
+            .expect("Failed to list tasks");
+        let mut ids: Vec<String> = tasks.into_iter().map(|task| task.id).collect();
+        ids.sort();
+        ids
+    }
+
+    fn sorted(ids: &[&String]) -> Vec<String> {
+        let mut ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_dependency_graph_round_trip() {
+        use DependencyCondition::*;
+
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let p = create_pipeline(&repo).await;
+
+        let graph = repo
+            .graph(&p.fetch)
+            .await
+            .unwrap()
+            .expect("Graph not found");
+        let nodes: Vec<(&str, bool)> = graph
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node.blocked))
+            .collect();
+        assert_eq!(
+            nodes,
+            [
+                (p.fetch.as_str(), false),
+                (p.build.as_str(), true),
+                (p.report.as_str(), true),
+                (p.cleanup.as_str(), true),
+            ]
+        );
+        assert!(graph
+            .nodes
+            .iter()
+            .all(|node| node.status == TaskStatus::Pending));
+        assert_eq!(graph.nodes[1].description, "Build");
+
+        let mut expected = vec![
+            edge(&p.build, &p.fetch, OnSuccess),
+            edge(&p.report, &p.fetch, OnFailure),
+            edge(&p.cleanup, &p.build, Always),
+            edge(&p.cleanup, &p.report, Always),
+        ];
+        expected
+            .sort_by(|a, b| (&a.task_id, &a.depends_on_id).cmp(&(&b.task_id, &b.depends_on_id)));
+        assert_eq!(graph.edges, expected);
+
+        // The graph is the same from any of its tasks
+        assert_eq!(repo.graph(&p.cleanup).await.unwrap(), Some(graph));
+
+        let alone = repo.graph(&p.unrelated).await.unwrap().unwrap();
+        assert_eq!(alone.nodes.len(), 1);
+        assert!(!alone.nodes[0].blocked);
+        assert!(alone.edges.is_empty());
+        assert!(repo.graph("missing-task").await.unwrap().is_none());
+
+        // Dependency filters
+        let dependents = list_ids(
+            &repo,
+            TaskFilter {
+                depends_on: Some(p.fetch.clone()),
+                ..TaskFilter::default()
+            },
+        )
+        .await;
+        assert_eq!(dependents, sorted(&[&p.build, &p.report]));
+
+        let blocked = list_ids(
+            &repo,
+            TaskFilter {
+                blocked: Some(true),
+                ..TaskFilter::default()
+            },
+        )
+        .await;
+        assert_eq!(blocked, sorted(&[&p.build, &p.report, &p.cleanup]));
+
+        let unblocked = list_ids(
+            &repo,
+            TaskFilter {
+                blocked: Some(false),
+                ..TaskFilter::default()
+            },
+        )
+        .await;
+        assert_eq!(unblocked, sorted(&[&p.fetch, &p.unrelated]));
+
+        // Blocked tasks are not claimed
+        let first = repo.claim_next_pending(10).await.unwrap().unwrap();
+        let second = repo.claim_next_pending(10).await.unwrap().unwrap();
+        assert_eq!((first.id, second.id), (p.fetch, p.unrelated));
+        assert!(repo.claim_next_pending(10).await.unwrap().is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_dependency_conditions_release_or_fail_dependents() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let p = create_pipeline(&repo).await;
+        repo.update_status(&p.unrelated, TaskStatus::Cancelled)
+            .await
+            .unwrap();
+
+        let claimed = repo.claim_next_pending(10).await.unwrap().unwrap();
+        assert_eq!(claimed.id, p.fetch);
+        repo.update_status(&p.fetch, TaskStatus::Completed)
+            .await
+            .unwrap();
+
+        // Success releases `build` and rules out `report`
+        let failed = repo.fail_unsatisfiable_dependents().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].id, p.report);
+        assert_eq!(failed[0].status, TaskStatus::Failed);
+        let reason = failed[0].error.as_deref().unwrap();
+        assert!(reason.contains(&p.fetch));
+        assert!(reason.contains("COMPLETED"));
+        assert!(reason.contains("on_failure"));
+        assert!(repo
+            .fail_unsatisfiable_dependents()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let graph = repo.graph(&p.fetch).await.unwrap().unwrap();
+        let blocked = |id: &str| graph.nodes.iter().find(|n| n.id == id).unwrap().blocked;
+        assert!(!blocked(&p.build));
+        assert!(!blocked(&p.report));
+        // `cleanup` still waits for `build`, though `report` already ended
+        assert!(blocked(&p.cleanup));
+
+        let claimed = repo.claim_next_pending(10).await.unwrap().unwrap();
+        assert_eq!(claimed.id, p.build);
+        assert!(repo.claim_next_pending(10).await.unwrap().is_none());
+
+        // `always` is met by a failure too
+        repo.update_status(&p.build, TaskStatus::Failed)
+            .await
+            .unwrap();
+        assert!(repo
+            .fail_unsatisfiable_dependents()
+            .await
+            .unwrap()
+            .is_empty());
+        let claimed = repo.claim_next_pending(10).await.unwrap().unwrap();
+        assert_eq!(claimed.id, p.cleanup);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_invalid_dependencies() {
+        use DependencyCondition::*;
+
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+
+        let first = create_task(&repo, "First", &[]).await.unwrap().id;
+
+        let missing = create_task(&repo, "Missing", &[("missing-task", OnSuccess)]).await;
+        assert!(matches!(missing, Err(DatabaseError::ValidationError(_))));
+
+        let duplicate =
+            create_task(&repo, "Duplicate", &[(&first, OnSuccess), (&first, Always)]).await;
+        assert!(matches!(duplicate, Err(DatabaseError::ValidationError(_))));
+
+        repo.update_status(&first, TaskStatus::Cancelled)
+            .await
+            .unwrap();
+        let unreachable = create_task(&repo, "Unreachable", &[(&first, OnSuccess)]).await;
+        assert!(matches!(
+            unreachable,
+            Err(DatabaseError::ValidationError(_))
+        ));
+
+        // An ended dependency that meets the condition is fine
+        let after = create_task(&repo, "After", &[(&first, Always)])
+            .await
+            .unwrap();
+        let graph = repo.graph(&after.id).await.unwrap().unwrap();
+        assert_eq!(graph.edges, [edge(&after.id, &first, Always)]);
+        assert!(!graph.nodes.iter().any(|node| node.blocked));
+
+        // Rejected tasks leave nothing behind
+        let (_, total) = repo
+            .list(&TaskFilter::default(), &PaginationParams::default())
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+
+        drop_isolated_schema(&pool).await;
+    }
+}
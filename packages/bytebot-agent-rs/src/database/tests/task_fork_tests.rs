@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytebot_shared_rs::types::{
+        api::{CreateTaskDto, TaskFileDto},
+        message::{Message, MessageContentBlock},
+        task::{Role, Task, TaskPriority, TaskStatus, TaskType},
+    };
+    use sqlx::PgPool;
+
+    use crate::database::{
+        bundle_repository::{BundleRepository, BundleRepositoryTrait},
+        message_repository::{CreateMessageDto, MessageRepository, MessageRepositoryTrait},
+        summary_repository::{CreateSummaryDto, SummaryRepository, SummaryRepositoryTrait},
+        task_repository::{TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+    };
+
+    /// A task with four messages and a file. The first message is folded into
+    /// a summary, the second into a child of it, and the last two into a
+    /// summary of their own.
+    async fn create_conversation(pool: &PgPool) -> (Task, Vec<Message>) {
+        let task = TaskRepository::new(pool.clone())
+            .create(&CreateTaskDto {
+                description: "Rename the quarterly report".to_string(),
+                task_type: Some(TaskType::Immediate),
+                scheduled_for: None,
+                priority: Some(TaskPriority::High),
+                created_by: Some(Role::User),
+                user_id: None,
+                model: None,
+                files: Some(vec![TaskFileDto {
+                    name: "hello.txt".to_string(),
+                    base64: "SGVsbG8gV29ybGQ=".to_string(),
+                    r#type: "text/plain".to_string(),
+                    size: 11,
+                }]),
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: Some(vec!["reports".to_string()]),
+            })
+            .await
+            .expect("Failed to create task");
+
+        let messages = MessageRepository::new(pool.clone());
+        let mut created = Vec::new();
+        for (text, role) in [
+            ("Open the file manager", Role::User),
+            ("Opened it", Role::Assistant),
+            ("Rename the report", Role::User),
+            ("Renamed it", Role::Assistant),
+        ] {
+            // Keep the stored millisecond timestamps apart
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            created.push(
+                messages
+                    .create(&CreateMessageDto {
+                        content: vec![MessageContentBlock::text(text)],
+                        role,
+                        task_id: task.id.clone(),
+                        user_id: None,
+                        summary_id: None,
+                    })
+                    .await
+                    .expect("Failed to create message"),
+            );
+        }
+
+        let summaries = SummaryRepository::new(pool.clone());
+        let summarize = |content: &str, parent_id: Option<String>, message_ids: Vec<String>| {
+            let summaries = &summaries;
+            let dto = CreateSummaryDto {
+                content: content.to_string(),
+                task_id: task.id.clone(),
+                parent_id,
+            };
+            async move {
+                summaries
+                    .create(&dto, &message_ids)
+                    .await
+                    .expect("Failed to create summary")
+            }
+        };
+        let first = summarize("Opened the file manager", None, vec![created[0].id.clone()]).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        summarize(
+            "The file manager is open",
+            Some(first.id),
+            vec![created[1].id.clone()],
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        summarize(
+            "Renamed the report",
+            None,
+            vec![created[2].id.clone(), created[3].id.clone()],
+        )
+        .await;
+
+        let messages = messages.get_by_task_id(&task.id).await.unwrap();
+        (task, messages)
+    }
+
+    #[tokio::test]
+    async fn test_fork_copies_prefix_and_remaps_ids() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let bundles = BundleRepository::new(pool.clone());
+        let (source, messages) = create_conversation(&pool).await;
+        let source_bundle = bundles.export(&source.id).await.unwrap().unwrap();
+
+        // Fork at the third message
+        let fork = repo
+            .fork(&source, &messages[..3])
+            .await
+            .expect("Failed to fork");
+
+        // The fork points at its source and starts over as a pending task
+        assert_ne!(fork.id, source.id);
+        assert_eq!(fork.forked_from_id.as_deref(), Some(source.id.as_str()));
+        assert_eq!(
+            fork.forked_from_message_id.as_deref(),
+            Some(messages[2].id.as_str())
+        );
+        assert_eq!(fork.status, TaskStatus::Pending);
+        assert_eq!(fork.description, source.description);
+        assert_eq!(fork.priority, TaskPriority::High);
+        assert_eq!(fork.labels, source.labels);
+
+        let copy = bundles.export(&fork.id).await.unwrap().unwrap();
+        assert_eq!(copy.task.forked_from_id, fork.forked_from_id);
+
+        // Messages up to the fork point are copied in order under new IDs
+        assert_eq!(copy.messages.len(), 3);
+        for (copied, original) in copy.messages.iter().zip(&messages) {
+            assert_ne!(copied.id, original.id);
+            assert_eq!(copied.task_id, fork.id);
+            assert_eq!(copied.role, original.role);
+            assert_eq!(copied.content, original.content);
+            assert_eq!(copied.created_at, original.created_at);
+        }
+
+        // Only the summaries covering copied messages alone carry over, with
+        // new IDs and the child pointing at the new parent
+        assert_eq!(copy.summaries.len(), 2);
+        let parent = &copy.summaries[0];
+        let child = &copy.summaries[1];
+        assert_eq!(parent.content, "Opened the file manager");
+        assert!(parent.parent_id.is_none());
+        assert_eq!(child.content, "The file manager is open");
+        assert_eq!(child.parent_id.as_ref(), Some(&parent.id));
+        for summary in &copy.summaries {
+            assert_eq!(summary.task_id, fork.id);
+            assert!(source_bundle
+                .summaries
+                .iter()
+                .all(|old| old.id != summary.id));
+        }
+        assert_eq!(copy.messages[0].summary_id.as_ref(), Some(&parent.id));
+        assert_eq!(copy.messages[1].summary_id.as_ref(), Some(&child.id));
+        assert!(copy.messages[2].summary_id.is_none());
+
+        // Files are copied under new IDs
+        assert_eq!(copy.files.len(), 1);
+        assert_ne!(copy.files[0].id, source_bundle.files[0].id);
+        assert_eq!(copy.files[0].task_id, fork.id);
+        assert_eq!(copy.files[0].data, source_bundle.files[0].data);
+
+        // The source is left as it was
+        let source_after = bundles.export(&source.id).await.unwrap().unwrap();
+        assert_eq!(source_after.messages.len(), 4);
+        assert_eq!(source_after.summaries.len(), 3);
+        assert!(source_after.task.forked_from_id.is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_fork_at_last_message_copies_everything() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let (source, messages) = create_conversation(&pool).await;
+
+        let fork = repo.fork(&source, &messages).await.expect("Failed to fork");
+        assert_eq!(
+            fork.forked_from_message_id.as_deref(),
+            Some(messages[3].id.as_str())
+        );
+
+        let copy = BundleRepository::new(pool.clone())
+            .export(&fork.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.messages.len(), 4);
+        assert_eq!(copy.summaries.len(), 3);
+        assert_eq!(
+            copy.messages[2].summary_id, copy.messages[3].summary_id,
+            "Messages summarized together stay together"
+        );
+        assert!(copy.messages[3].summary_id.is_some());
+
+        assert!(repo.fork(&source, &[]).await.is_err());
+
+        drop_isolated_schema(&pool).await;
+    }
+}
@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use bytebot_shared_rs::types::{
+        api::{CreateTaskDto, UpdateTaskDto},
+        task::{Role, TaskPriority, TaskStatus, TaskType},
+    };
+    use sqlx::PgPool;
+
+    use crate::database::{
+        task_repository::{TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+    };
+
+    async fn create_task(pool: &PgPool, description: &str, priority: TaskPriority) -> String {
+        // Keep the stored millisecond timestamps apart, so FIFO order is defined
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        TaskRepository::new(pool.clone())
+            .create(&CreateTaskDto {
+                description: description.to_string(),
+                task_type: Some(TaskType::Immediate),
+                scheduled_for: None,
+                priority: Some(priority),
+                created_by: Some(Role::User),
+                user_id: None,
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .expect("Failed to create task")
+            .id
+    }
+
+    async fn claim(repo: &TaskRepository, max_running: u32) -> Option<String> {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        repo.claim_next_pending(max_running)
+            .await
+            .expect("Failed to claim")
+            .map(|task| {
+                assert_eq!(task.status, TaskStatus::Running);
+                assert!(task.executed_at.is_some());
+                task.id
+            })
+    }
+
+    #[tokio::test]
+    async fn test_claim_order_is_priority_then_fifo() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+
+        let low = create_task(&pool, "Low", TaskPriority::Low).await;
+        let first_medium = create_task(&pool, "First medium", TaskPriority::Medium).await;
+        let high = create_task(&pool, "High", TaskPriority::High).await;
+        let second_medium = create_task(&pool, "Second medium", TaskPriority::Medium).await;
+        let urgent = create_task(&pool, "Urgent", TaskPriority::Urgent).await;
+
+        let mut claimed = Vec::new();
+        while let Some(id) = claim(&repo, 10).await {
+            claimed.push(id);
+        }
+        assert_eq!(claimed, [urgent, high, first_medium, second_medium, low]);
+
+        // Tasks wait from the time they were queued, not created
+        let created_first = create_task(&pool, "Created first", TaskPriority::Medium).await;
+        let created_second = create_task(&pool, "Created second", TaskPriority::Medium).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        repo.mark_queued(&created_first)
+            .await
+            .unwrap()
+            .expect("Task was not queued");
+        assert_eq!(claim(&repo, 10).await, Some(created_second));
+        assert_eq!(claim(&repo, 10).await, Some(created_first));
+        assert_eq!(claim(&repo, 10).await, None);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_claims_respect_max_running() {
+        let pool = create_isolated_test_pool().await;
+        for i in 0..8 {
+            create_task(&pool, &format!("Task {i}"), TaskPriority::Medium).await;
+        }
+
+        let claims: Vec<_> = (0..8)
+            .map(|_| {
+                let repo = TaskRepository::new(pool.clone());
+                tokio::spawn(async move { repo.claim_next_pending(3).await })
+            })
+            .collect();
+
+        let mut claimed = HashSet::new();
+        for claim in claims {
+            if let Some(task) = claim.await.unwrap().expect("Failed to claim") {
+                assert!(claimed.insert(task.id), "Task claimed twice");
+            }
+        }
+        assert_eq!(claimed.len(), 3);
+
+        let repo = TaskRepository::new(pool.clone());
+        let counts = repo.count_by_status().await.unwrap();
+        assert_eq!(counts.get(&TaskStatus::Running), Some(&3));
+        assert_eq!(counts.get(&TaskStatus::Pending), Some(&5));
+
+        // A finished task frees exactly one slot
+        let finished = claimed.iter().next().unwrap();
+        repo.update_status(finished, TaskStatus::Completed)
+            .await
+            .unwrap();
+        assert!(claim(&repo, 3).await.is_some());
+        assert!(claim(&repo, 3).await.is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_preempt_requeues_the_latest_low_priority_task() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+
+        let medium = create_task(&pool, "Medium", TaskPriority::Medium).await;
+        let first_low = create_task(&pool, "First low", TaskPriority::Low).await;
+        let second_low = create_task(&pool, "Second low", TaskPriority::Low).await;
+        assert_eq!(claim(&repo, 3).await, Some(medium.clone()));
+        assert_eq!(claim(&repo, 3).await, Some(first_low.clone()));
+        assert_eq!(claim(&repo, 3).await, Some(second_low.clone()));
+
+        // Only an URGENT task preempts
+        let high = create_task(&pool, "High", TaskPriority::High).await;
+        assert!(repo.preempt_for_urgent(3).await.unwrap().is_none());
+
+        let urgent = create_task(&pool, "Urgent", TaskPriority::Urgent).await;
+        // A free slot needs no preemption
+        assert!(repo.preempt_for_urgent(4).await.unwrap().is_none());
+
+        let preempted = repo
+            .preempt_for_urgent(3)
+            .await
+            .unwrap()
+            .expect("No task was preempted");
+        assert_eq!(preempted.id, second_low);
+        assert_eq!(preempted.status, TaskStatus::Pending);
+
+        let status = |id: String| {
+            let repo = &repo;
+            async move { repo.get_by_id(&id).await.unwrap().unwrap().status }
+        };
+        assert_eq!(status(medium).await, TaskStatus::Running);
+        assert_eq!(status(first_low).await, TaskStatus::Running);
+
+        // The freed slot goes to the urgent task, and the preempted task
+        // queues behind the high one
+        assert!(repo.preempt_for_urgent(3).await.unwrap().is_none());
+        assert_eq!(claim(&repo, 3).await, Some(urgent));
+        assert_eq!(claim(&repo, 4).await, Some(high));
+        assert_eq!(claim(&repo, 5).await, Some(second_low));
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_preempt_spares_higher_priority_tasks() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+
+        let medium = create_task(&pool, "Medium", TaskPriority::Medium).await;
+        let high = create_task(&pool, "High", TaskPriority::High).await;
+        assert!(claim(&repo, 2).await.is_some());
+        assert!(claim(&repo, 2).await.is_some());
+
+        create_task(&pool, "Urgent", TaskPriority::Urgent).await;
+        assert!(repo.preempt_for_urgent(2).await.unwrap().is_none());
+
+        for id in [medium, high] {
+            let task = repo.get_by_id(&id).await.unwrap().unwrap();
+            assert_eq!(task.status, TaskStatus::Running);
+        }
+        assert!(claim(&repo, 2).await.is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_preempt_spares_taken_over_tasks() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+
+        let low = create_task(&pool, "Low", TaskPriority::Low).await;
+        assert_eq!(claim(&repo, 1).await, Some(low.clone()));
+        repo.update(
+            &low,
+            &UpdateTaskDto {
+                status: None,
+                priority: None,
+                queued_at: None,
+                executed_at: None,
+                completed_at: None,
+                control: Some(Role::User),
+                budget: None,
+                labels: None,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        create_task(&pool, "Urgent", TaskPriority::Urgent).await;
+        assert!(repo.preempt_for_urgent(1).await.unwrap().is_none());
+
+        let task = repo.get_by_id(&low).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Running);
+        assert_eq!(task.control, Role::User);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_requeue_interrupted_frees_the_slots() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+
+        let interrupted = create_task(&pool, "Interrupted", TaskPriority::Medium).await;
+        let waiting = create_task(&pool, "Waiting", TaskPriority::Medium).await;
+        assert_eq!(claim(&repo, 1).await, Some(interrupted.clone()));
+        // The orphaned task holds the only slot
+        assert_eq!(claim(&repo, 1).await, None);
+
+        assert_eq!(repo.requeue_interrupted().await.unwrap(), 1);
+        let task = repo.get_by_id(&interrupted).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Pending);
+
+        // The requeued task keeps its place ahead of the waiting one
+        assert_eq!(claim(&repo, 1).await, Some(interrupted));
+        assert_eq!(claim(&repo, 2).await, Some(waiting));
+        assert_eq!(repo.requeue_interrupted().await.unwrap(), 2);
+        assert_eq!(repo.requeue_interrupted().await.unwrap(), 0);
+
+        drop_isolated_schema(&pool).await;
+    }
+}
@@ -50,6 +50,12 @@ fn create_test_task_dto() -> CreateTaskDto {
             "title": "Claude 3 Sonnet"
         })),
         user_id: Some("user-123".to_string()),
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     }
 }
 
@@ -58,25 +64,13 @@ fn create_test_task() -> Task {
     Task {
         id: "task-123".to_string(),
         description: "Test task description".to_string(),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: now,
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: now,
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
-        result: None,
         model: serde_json::json!({
             "provider": "anthropic",
             "name": "claude-3-sonnet-20240229",
             "title": "Claude 3 Sonnet"
         }),
         user_id: Some("user-123".to_string()),
+        ..Default::default()
     }
 }
 
@@ -87,6 +81,9 @@ fn create_test_update_dto() -> UpdateTaskDto {
         queued_at: None,
         executed_at: Some(Utc::now()),
         completed_at: None,
+        control: None,
+        budget: None,
+        labels: None,
     }
 }
 
@@ -132,6 +129,12 @@ mod task_repository_tests {
             scheduled_for: None,
             model: None,
             user_id: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
 
         mock_repo
@@ -169,6 +172,12 @@ mod task_repository_tests {
             scheduled_for: None, // Missing required field for scheduled task
             model: None,
             user_id: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
 
         mock_repo
@@ -301,6 +310,9 @@ mod task_repository_tests {
             queued_at: None,
             executed_at: None,
             completed_at: None,
+            control: None,
+            budget: None,
+            labels: None,
         };
 
         mock_repo
@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests {
+    use bytebot_shared_rs::types::{
+        api::{CreateTaskDto, PaginationParams},
+        approval::{ApprovalDecision, ApprovalDecisionDto, PendingApproval},
+        task::{Role, TaskPriority, TaskStatus, TaskType},
+    };
+    use chrono::Utc;
+
+    use crate::database::{
+        task_repository::{TaskFilter, TaskRepository, TaskRepositoryTrait},
+        tests::{create_isolated_test_pool, drop_isolated_schema},
+    };
+
+    async fn create_running_task(repo: &TaskRepository) -> String {
+        let dto = CreateTaskDto {
+            description: "Task for status tests".to_string(),
+            task_type: Some(TaskType::Immediate),
+            scheduled_for: None,
+            priority: Some(TaskPriority::Medium),
+            created_by: Some(Role::User),
+            user_id: None,
+            model: None,
+            files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
+        };
+
+        let task = repo.create(&dto).await.expect("Failed to create task");
+        repo.update_status(&task.id, TaskStatus::Running)
+            .await
+            .expect("Failed to start task")
+            .expect("Task not found");
+        task.id
+    }
+
+    fn pending_approval() -> PendingApproval {
+        PendingApproval {
+            tool_use_id: "toolu_1".to_string(),
+            action: "write_file".to_string(),
+            input: serde_json::json!({"path": "/tmp/a", "data": "YQ=="}),
+            reason: "write_file actions require approval".to_string(),
+            requested_at: Utc::now(),
+            decision: None,
+            comment: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_approval_round_trip() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let task_id = create_running_task(&repo).await;
+
+        let task = repo
+            .request_approval(&task_id, &pending_approval())
+            .await
+            .expect("Failed to request approval")
+            .expect("Task was not running");
+        assert_eq!(task.status, TaskStatus::NeedsReview);
+
+        let stored = repo.get_by_id(&task_id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TaskStatus::NeedsReview);
+        assert_eq!(
+            stored.pending_approval.as_ref().map(|a| a.action.as_str()),
+            Some("write_file")
+        );
+
+        let needs_review = repo
+            .get_tasks_by_status(TaskStatus::NeedsReview)
+            .await
+            .unwrap();
+        assert_eq!(needs_review.len(), 1);
+        assert_eq!(
+            repo.count_by_status()
+                .await
+                .unwrap()
+                .get(&TaskStatus::NeedsReview),
+            Some(&1)
+        );
+
+        // A task waiting for review is not running, so it cannot be asked again
+        assert!(repo
+            .request_approval(&task_id, &pending_approval())
+            .await
+            .unwrap()
+            .is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_mark_needs_help_round_trip() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let task_id = create_running_task(&repo).await;
+
+        let task = repo
+            .mark_needs_help(&task_id, "Stuck on a captcha")
+            .await
+            .expect("Failed to mark task as needing help")
+            .expect("Task was not running");
+        assert_eq!(task.status, TaskStatus::NeedsHelp);
+
+        let stored = repo.get_by_id(&task_id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TaskStatus::NeedsHelp);
+        assert_eq!(stored.error.as_deref(), Some("Stuck on a captcha"));
+        assert_eq!(
+            repo.get_tasks_by_status(TaskStatus::NeedsHelp)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Only a running task can ask for help
+        assert!(repo
+            .mark_needs_help(&task_id, "Again")
+            .await
+            .unwrap()
+            .is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_decide_approval_round_trip() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let task_id = create_running_task(&repo).await;
+
+        repo.request_approval(&task_id, &pending_approval())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let dto = ApprovalDecisionDto {
+            comment: Some("Not that file".to_string()),
+        };
+        let task = repo
+            .decide_approval(&task_id, ApprovalDecision::Rejected, &dto)
+            .await
+            .expect("Failed to decide approval")
+            .expect("Task was not waiting for review");
+        assert_eq!(task.status, TaskStatus::Running);
+
+        let stored = repo.get_by_id(&task_id).await.unwrap().unwrap();
+        let approval = stored.pending_approval.unwrap();
+        assert_eq!(stored.status, TaskStatus::Running);
+        assert_eq!(approval.decision, Some(ApprovalDecision::Rejected));
+        assert_eq!(approval.comment.as_deref(), Some("Not that file"));
+
+        // The decision is final
+        assert!(repo
+            .decide_approval(&task_id, ApprovalDecision::Approved, &dto)
+            .await
+            .unwrap()
+            .is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_approve_and_clear_round_trip() {
+        let pool = create_isolated_test_pool().await;
+        let repo = TaskRepository::new(pool.clone());
+        let waiting = create_running_task(&repo).await;
+        let approved = create_running_task(&repo).await;
+
+        for task_id in [&waiting, &approved] {
+            repo.request_approval(task_id, &pending_approval())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let (tasks, total) = repo
+            .list(
+                &TaskFilter {
+                    status: Some(TaskStatus::NeedsReview),
+                    ..TaskFilter::default()
+                },
+                &PaginationParams::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        assert!(tasks.iter().all(|t| t.status == TaskStatus::NeedsReview));
+
+        let task = repo
+            .decide_approval(
+                &approved,
+                ApprovalDecision::Approved,
+                &ApprovalDecisionDto::default(),
+            )
+            .await
+            .unwrap()
+            .expect("Task was not waiting for review");
+        assert_eq!(task.status, TaskStatus::Running);
+
+        let stored = repo.get_by_id(&approved).await.unwrap().unwrap();
+        let approval = stored.pending_approval.expect("Approval was dropped");
+        assert_eq!(approval.decision, Some(ApprovalDecision::Approved));
+        assert!(approval.comment.is_none());
+        assert_eq!(approval.input, pending_approval().input);
+
+        // Once the action ran, the approval is cleared
+        repo.clear_approval(&approved).await.unwrap();
+        let stored = repo.get_by_id(&approved).await.unwrap().unwrap();
+        assert!(stored.pending_approval.is_none());
+        assert_eq!(stored.status, TaskStatus::Running);
+
+        // A task waiting for review can still be cancelled
+        let cancelled = repo
+            .update_status(&waiting, TaskStatus::Cancelled)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cancelled.status, TaskStatus::Cancelled);
+        assert!(repo
+            .decide_approval(
+                &waiting,
+                ApprovalDecision::Approved,
+                &ApprovalDecisionDto::default()
+            )
+            .await
+            .unwrap()
+            .is_none());
+
+        drop_isolated_schema(&pool).await;
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use bytebot_shared_rs::types::{
+        api::CreateTaskDto,
+        task::{Role, TaskPriority, TaskType},
+    };
+    use chrono::{Duration, SubsecRound, Utc};
+    use sqlx::PgPool;
+
+    use crate::{
+        ai::{registry::ModelRegistry, TokenUsage},
+        database::{
+            task_repository::{TaskRepository, TaskRepositoryTrait},
+            tests::{create_isolated_test_pool, drop_isolated_schema},
+            usage_repository::{
+                CreateModelUsageDto, UsageQuery, UsageRepository, UsageRepositoryTrait,
+            },
+        },
+    };
+
+    async fn create_task(pool: &PgPool) -> String {
+        TaskRepository::new(pool.clone())
+            .create(&CreateTaskDto {
+                description: "Task for usage tests".to_string(),
+                task_type: Some(TaskType::Immediate),
+                scheduled_for: None,
+                priority: Some(TaskPriority::Medium),
+                created_by: Some(Role::User),
+                user_id: None,
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .expect("Failed to create task")
+            .id
+    }
+
+    /// Record a call of `model` priced from the builtin registry, as the
+    /// usage recorder does
+    async fn record(
+        repo: &UsageRepository,
+        task_id: &str,
+        model: &str,
+        usage: TokenUsage,
+    ) -> String {
+        let pricing = ModelRegistry::builtin()
+            .resolve(model)
+            .and_then(|model| model.pricing);
+
+        repo.create(&CreateModelUsageDto {
+            task_id: task_id.to_string(),
+            provider: "anthropic".to_string(),
+            model: model.to_string(),
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_read_tokens: usage.cache_read_tokens,
+            cache_write_tokens: usage.cache_write_tokens,
+            latency_ms: 1_000,
+            cost_usd: pricing.map(|pricing| pricing.cost(&usage)),
+            ..CreateModelUsageDto::default()
+        })
+        .await
+        .expect("Failed to record usage")
+        .id
+    }
+
+    fn tokens(input: u64, output: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_cost_with_cache_tokens() {
+        let pool = create_isolated_test_pool().await;
+        let repo = UsageRepository::new(pool.clone());
+        let task_id = create_task(&pool).await;
+
+        record(
+            &repo,
+            &task_id,
+            "claude-sonnet-4",
+            TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 100_000,
+                cache_read_tokens: 2_000_000,
+                cache_write_tokens: 400_000,
+            },
+        )
+        .await;
+        record(&repo, &task_id, "unknown-model", tokens(500, 50)).await;
+
+        let calls = repo.list_by_task_id(&task_id).await.unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].cache_read_tokens, 2_000_000);
+        assert_eq!(calls[0].cache_write_tokens, 400_000);
+        assert!((calls[0].cost_usd.unwrap() - 6.6).abs() < 1e-9);
+        assert!(calls[1].cost_usd.is_none());
+
+        let report = repo
+            .report(&UsageQuery {
+                task_id: Some(task_id.clone()),
+                ..UsageQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.totals.calls, 2);
+        assert_eq!(report.totals.input_tokens, 1_000_500);
+        assert_eq!(report.totals.cache_read_tokens, 2_000_000);
+        assert_eq!(report.totals.cache_write_tokens, 400_000);
+        assert!((report.totals.cost_usd - 6.6).abs() < 1e-9);
+        assert_eq!(report.totals.unpriced_calls, 1);
+        assert_eq!(report.by_model[0].model, "claude-sonnet-4");
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_usage_report_filters() {
+        let pool = create_isolated_test_pool().await;
+        let repo = UsageRepository::new(pool.clone());
+        let task_id = create_task(&pool).await;
+
+        let old = record(&repo, &task_id, "claude-sonnet-4", tokens(100, 10)).await;
+        record(&repo, &task_id, "claude-sonnet-4", tokens(200, 20)).await;
+        record(&repo, &task_id, "claude-opus-4", tokens(400, 40)).await;
+
+        // Stored timestamps have millisecond precision
+        let two_days_ago = (Utc::now() - Duration::days(2)).trunc_subsecs(3);
+        sqlx::query(r#"UPDATE "ModelUsage" SET "createdAt" = $2 WHERE id = $1"#)
+            .bind(&old)
+            .bind(two_days_ago)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let yesterday = Utc::now() - Duration::days(1);
+
+        let report = |query: UsageQuery| {
+            let repo = &repo;
+            async move { repo.report(&query).await.unwrap().totals }
+        };
+
+        let totals = report(UsageQuery {
+            model: Some("claude-sonnet-4".to_string()),
+            ..UsageQuery::default()
+        })
+        .await;
+        assert_eq!((totals.calls, totals.input_tokens), (2, 300));
+
+        let totals = report(UsageQuery {
+            from: Some(yesterday),
+            ..UsageQuery::default()
+        })
+        .await;
+        assert_eq!((totals.calls, totals.input_tokens), (2, 600));
+
+        let totals = report(UsageQuery {
+            to: Some(yesterday),
+            ..UsageQuery::default()
+        })
+        .await;
+        assert_eq!((totals.calls, totals.input_tokens), (1, 100));
+
+        // `from` is inclusive and `to` exclusive
+        let totals = report(UsageQuery {
+            from: Some(two_days_ago),
+            to: Some(two_days_ago + Duration::milliseconds(1)),
+            ..UsageQuery::default()
+        })
+        .await;
+        assert_eq!(totals.calls, 1);
+
+        let totals = report(UsageQuery {
+            model: Some("claude-sonnet-4".to_string()),
+            from: Some(yesterday),
+            ..UsageQuery::default()
+        })
+        .await;
+        assert_eq!((totals.calls, totals.input_tokens), (1, 200));
+
+        let totals = report(UsageQuery {
+            task_id: Some("another-task".to_string()),
+            ..UsageQuery::default()
+        })
+        .await;
+        assert_eq!(totals.calls, 0);
+
+        drop_isolated_schema(&pool).await;
+    }
+}
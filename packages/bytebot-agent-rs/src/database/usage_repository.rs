@@ -0,0 +1,262 @@
+use async_trait::async_trait;
+use bytebot_shared_rs::types::usage::{
+    ModelUsage, ModelUsageTotals, UsageReport, UsageTotals, UserUsageTotals,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use super::DatabaseError;
+
+const USAGE_COLUMNS: &str = r#"
+    id,
+    "taskId",
+    "messageId",
+    "userId",
+    provider,
+    model,
+    "inputTokens",
+    "outputTokens",
+    "cacheReadTokens",
+    "cacheWriteTokens",
+    "latencyMs",
+    "costUsd",
+    "createdAt"
+"#;
+
+/// Sums over the calls of a group, read by `UsageRepository::totals_from_row`
+const USAGE_TOTALS: &str = r#"
+    COUNT(*) AS calls,
+    COALESCE(SUM("inputTokens"), 0)::bigint AS "inputTokens",
+    COALESCE(SUM("outputTokens"), 0)::bigint AS "outputTokens",
+    COALESCE(SUM("cacheReadTokens"), 0)::bigint AS "cacheReadTokens",
+    COALESCE(SUM("cacheWriteTokens"), 0)::bigint AS "cacheWriteTokens",
+    COALESCE(SUM("costUsd"), 0)::float8 AS "costUsd",
+    COUNT(*) FILTER (WHERE "costUsd" IS NULL) AS "unpricedCalls",
+    COALESCE(AVG("latencyMs"), 0)::float8 AS "averageLatencyMs"
+"#;
+
+/// Restrictions of a `UsageQuery`, bound in the order of its fields
+const USAGE_FILTER: &str = r#"
+    WHERE ($1::text IS NULL OR "taskId" = $1)
+        AND ($2::text IS NULL OR "userId" = $2)
+        AND ($3::text IS NULL OR model = $3)
+        AND ($4::timestamptz IS NULL OR "createdAt" >= $4)
+        AND ($5::timestamptz IS NULL OR "createdAt" < $5)
+"#;
+
+/// Data transfer object for recording a model call
+#[derive(Debug, Clone, Default)]
+pub struct CreateModelUsageDto {
+    pub task_id: String,
+    pub message_id: Option<String>,
+    pub user_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub latency_ms: u64,
+    pub cost_usd: Option<f64>,
+}
+
+/// Model calls to aggregate; unset restrictions do not apply
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageQuery {
+    pub task_id: Option<String>,
+    pub user_id: Option<String>,
+    pub model: Option<String>,
+    /// Calls made at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Calls made before this time
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Model usage repository trait for dependency injection and testing
+#[async_trait]
+pub trait UsageRepositoryTrait: Send + Sync {
+    /// Record one model call
+    async fn create(&self, dto: &CreateModelUsageDto) -> Result<ModelUsage, DatabaseError>;
+    /// Model calls of a task, oldest first
+    async fn list_by_task_id(&self, task_id: &str) -> Result<Vec<ModelUsage>, DatabaseError>;
+    /// Totals of the calls matching the query, by model and by user
+    async fn report(&self, query: &UsageQuery) -> Result<UsageReport, DatabaseError>;
+}
+
+/// SQLx-based model usage repository implementation
+pub struct UsageRepository {
+    pool: Pool<Postgres>,
+}
+
+impl UsageRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    fn usage_from_row(row: &PgRow) -> ModelUsage {
+        ModelUsage {
+            id: row.get("id"),
+            task_id: row.get("taskId"),
+            message_id: row.get("messageId"),
+            user_id: row.get("userId"),
+            provider: row.get("provider"),
+            model: row.get("model"),
+            input_tokens: row.get::<i64, _>("inputTokens") as u64,
+            output_tokens: row.get::<i64, _>("outputTokens") as u64,
+            cache_read_tokens: row.get::<i64, _>("cacheReadTokens") as u64,
+            cache_write_tokens: row.get::<i64, _>("cacheWriteTokens") as u64,
+            latency_ms: row.get::<i64, _>("latencyMs") as u64,
+            cost_usd: row.get("costUsd"),
+            created_at: row.get("createdAt"),
+        }
+    }
+
+    fn totals_from_row(row: &PgRow) -> UsageTotals {
+        UsageTotals {
+            calls: row.get::<i64, _>("calls") as u64,
+            input_tokens: row.get::<i64, _>("inputTokens") as u64,
+            output_tokens: row.get::<i64, _>("outputTokens") as u64,
+            cache_read_tokens: row.get::<i64, _>("cacheReadTokens") as u64,
+            cache_write_tokens: row.get::<i64, _>("cacheWriteTokens") as u64,
+            cost_usd: row.get("costUsd"),
+            unpriced_calls: row.get::<i64, _>("unpricedCalls") as u64,
+            average_latency_ms: row.get("averageLatencyMs"),
+        }
+    }
+
+    /// Rows of the calls matching the query, grouped by `group_by` when set
+    async fn aggregate(
+        &self,
+        query: &UsageQuery,
+        group_by: Option<&str>,
+    ) -> Result<Vec<PgRow>, DatabaseError> {
+        let sql = match group_by {
+            Some(columns) => format!(
+                r#"
+                SELECT {columns}, {USAGE_TOTALS}
+                FROM "ModelUsage"
+                {USAGE_FILTER}
+                GROUP BY {columns}
+                ORDER BY "costUsd" DESC, calls DESC, {columns}
+                "#
+            ),
+            None => format!(r#"SELECT {USAGE_TOTALS} FROM "ModelUsage" {USAGE_FILTER}"#),
+        };
+
+        sqlx::query(&sql)
+            .bind(&query.task_id)
+            .bind(&query.user_id)
+            .bind(&query.model)
+            .bind(query.from)
+            .bind(query.to)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to aggregate model usage: {}", e);
+                DatabaseError::QueryError(e)
+            })
+    }
+}
+
+#[async_trait]
+impl UsageRepositoryTrait for UsageRepository {
+    async fn create(&self, dto: &CreateModelUsageDto) -> Result<ModelUsage, DatabaseError> {
+        debug!("Recording usage of {} for task {}", dto.model, dto.task_id);
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO "ModelUsage" (
+                id, "taskId", "messageId", "userId", provider, model,
+                "inputTokens", "outputTokens", "cacheReadTokens", "cacheWriteTokens",
+                "latencyMs", "costUsd", "createdAt"
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING
+                {USAGE_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(&dto.task_id)
+        .bind(&dto.message_id)
+        .bind(&dto.user_id)
+        .bind(&dto.provider)
+        .bind(&dto.model)
+        .bind(dto.input_tokens as i64)
+        .bind(dto.output_tokens as i64)
+        .bind(dto.cache_read_tokens as i64)
+        .bind(dto.cache_write_tokens as i64)
+        .bind(dto.latency_ms as i64)
+        .bind(dto.cost_usd)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to record usage for task {}: {}", dto.task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(Self::usage_from_row(&row))
+    }
+
+    async fn list_by_task_id(&self, task_id: &str) -> Result<Vec<ModelUsage>, DatabaseError> {
+        debug!("Listing model usage of task {}", task_id);
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {USAGE_COLUMNS}
+            FROM "ModelUsage"
+            WHERE "taskId" = $1
+            ORDER BY "createdAt" ASC, id ASC
+            "#
+        ))
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list model usage of task {}: {}", task_id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.iter().map(Self::usage_from_row).collect())
+    }
+
+    async fn report(&self, query: &UsageQuery) -> Result<UsageReport, DatabaseError> {
+        debug!("Reporting model usage: {:?}", query);
+
+        let totals = self
+            .aggregate(query, None)
+            .await?
+            .first()
+            .map(Self::totals_from_row)
+            .unwrap_or_default();
+
+        let by_model = self
+            .aggregate(query, Some("provider, model"))
+            .await?
+            .iter()
+            .map(|row| ModelUsageTotals {
+                provider: row.get("provider"),
+                model: row.get("model"),
+                totals: Self::totals_from_row(row),
+            })
+            .collect();
+
+        let by_user = self
+            .aggregate(query, Some(r#""userId""#))
+            .await?
+            .iter()
+            .map(|row| UserUsageTotals {
+                user_id: row.get("userId"),
+                totals: Self::totals_from_row(row),
+            })
+            .collect();
+
+        Ok(UsageReport {
+            totals,
+            by_model,
+            by_user,
+        })
+    }
+}
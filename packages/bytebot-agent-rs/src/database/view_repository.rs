@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use bytebot_shared_rs::types::view::{CreateTaskViewDto, TaskView};
+use chrono::Utc;
+use sqlx::{postgres::PgRow, types::Json, Pool, Postgres, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::DatabaseError;
+
+const VIEW_COLUMNS: &str = r#"
+    id,
+    name,
+    "userId",
+    filter,
+    sort,
+    "createdAt",
+    "updatedAt"
+"#;
+
+/// Saved view repository trait for dependency injection and testing.
+///
+/// Every method is scoped to the owner passed as `user_id`; views created
+/// without authentication belong to no user and are shared by every
+/// unauthenticated caller.
+#[async_trait]
+pub trait ViewRepositoryTrait: Send + Sync {
+    async fn create(
+        &self,
+        user_id: Option<&str>,
+        dto: &CreateTaskViewDto,
+    ) -> Result<TaskView, DatabaseError>;
+    async fn get_by_id(
+        &self,
+        user_id: Option<&str>,
+        id: &str,
+    ) -> Result<Option<TaskView>, DatabaseError>;
+    async fn list(&self, user_id: Option<&str>) -> Result<Vec<TaskView>, DatabaseError>;
+    /// Replace a view, returning `None` if the user has no such view
+    async fn update(
+        &self,
+        user_id: Option<&str>,
+        id: &str,
+        dto: &CreateTaskViewDto,
+    ) -> Result<Option<TaskView>, DatabaseError>;
+    async fn delete(&self, user_id: Option<&str>, id: &str) -> Result<bool, DatabaseError>;
+}
+
+/// SQLx-based saved view repository implementation
+pub struct ViewRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ViewRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    fn validate_dto(dto: &CreateTaskViewDto) -> Result<(), DatabaseError> {
+        dto.validate()
+            .map_err(|e| DatabaseError::ValidationError(format!("Invalid view: {e}")))
+    }
+
+    fn view_from_row(row: &PgRow) -> TaskView {
+        TaskView {
+            id: row.get("id"),
+            name: row.get("name"),
+            user_id: row.get("userId"),
+            filter: row.get::<Json<_>, _>("filter").0,
+            sort: row.get::<Json<_>, _>("sort").0,
+            created_at: row.get("createdAt"),
+            updated_at: row.get("updatedAt"),
+        }
+    }
+}
+
+#[async_trait]
+impl ViewRepositoryTrait for ViewRepository {
+    async fn create(
+        &self,
+        user_id: Option<&str>,
+        dto: &CreateTaskViewDto,
+    ) -> Result<TaskView, DatabaseError> {
+        debug!("Creating saved view: {}", dto.name);
+
+        Self::validate_dto(dto)?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO "TaskView" (
+                id, name, "userId", filter, sort, "createdAt", "updatedAt"
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
+            RETURNING
+                {VIEW_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(&dto.name)
+        .bind(user_id)
+        .bind(Json(&dto.filter))
+        .bind(Json(dto.sort))
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to create saved view: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let view = Self::view_from_row(&row);
+        info!("Created saved view {} ({})", view.id, view.name);
+
+        Ok(view)
+    }
+
+    async fn get_by_id(
+        &self,
+        user_id: Option<&str>,
+        id: &str,
+    ) -> Result<Option<TaskView>, DatabaseError> {
+        debug!("Fetching saved view: {}", id);
+
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {VIEW_COLUMNS}
+            FROM "TaskView"
+            WHERE id = $1 AND "userId" IS NOT DISTINCT FROM $2
+            "#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved view {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.as_ref().map(Self::view_from_row))
+    }
+
+    async fn list(&self, user_id: Option<&str>) -> Result<Vec<TaskView>, DatabaseError> {
+        debug!("Listing saved views of user {:?}", user_id);
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {VIEW_COLUMNS}
+            FROM "TaskView"
+            WHERE "userId" IS NOT DISTINCT FROM $1
+            ORDER BY name ASC, id ASC
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list saved views: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.iter().map(Self::view_from_row).collect())
+    }
+
+    async fn update(
+        &self,
+        user_id: Option<&str>,
+        id: &str,
+        dto: &CreateTaskViewDto,
+    ) -> Result<Option<TaskView>, DatabaseError> {
+        debug!("Updating saved view: {}", id);
+
+        Self::validate_dto(dto)?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE "TaskView"
+            SET
+                name = $3,
+                filter = $4,
+                sort = $5,
+                "updatedAt" = $6
+            WHERE id = $1 AND "userId" IS NOT DISTINCT FROM $2
+            RETURNING
+                {VIEW_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .bind(&dto.name)
+        .bind(Json(&dto.filter))
+        .bind(Json(dto.sort))
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to update saved view {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        if row.is_some() {
+            info!("Updated saved view {}", id);
+        }
+
+        Ok(row.as_ref().map(Self::view_from_row))
+    }
+
+    async fn delete(&self, user_id: Option<&str>, id: &str) -> Result<bool, DatabaseError> {
+        debug!("Deleting saved view: {}", id);
+
+        let result = sqlx::query(
+            r#"DELETE FROM "TaskView" WHERE id = $1 AND "userId" IS NOT DISTINCT FROM $2"#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete saved view {}: {}", id, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            info!("Deleted saved view {}", id);
+        }
+
+        Ok(deleted)
+    }
+}
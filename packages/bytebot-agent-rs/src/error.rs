@@ -16,6 +16,9 @@ pub enum ServiceError {
     #[error("AI service error: {0}")]
     AI(#[from] AIError),
 
+    #[error("Automation error: {0}")]
+    Automation(#[from] AutomationError),
+
     #[error("Validation error: {0}")]
     Validation(String),
 
@@ -45,6 +48,9 @@ pub enum AIError {
 
     #[error("Invalid model: {0}")]
     InvalidModel(String),
+
+    #[error("Request cancelled")]
+    Cancelled,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +58,9 @@ pub enum AutomationError {
     #[error("Computer action failed: {0}")]
     ActionFailed(String),
 
+    #[error("Desktop unreachable: {0}")]
+    Unreachable(String),
+
     #[error("Invalid coordinates: {0}")]
     InvalidCoordinates(String),
 
@@ -60,6 +69,9 @@ pub enum AutomationError {
 
     #[error("Screen capture failed: {0}")]
     ScreenCapture(String),
+
+    #[error("Action cancelled")]
+    Cancelled,
 }
 
 // Convert ServiceError to HTTP responses
@@ -69,6 +81,10 @@ impl IntoResponse for ServiceError {
             ServiceError::Validation(msg) => (StatusCode::BAD_REQUEST, msg),
             ServiceError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             ServiceError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
+            // Rejected input such as an attachment whose data does not decode
+            ServiceError::Database(crate::database::DatabaseError::ValidationError(msg)) => {
+                (StatusCode::BAD_REQUEST, msg)
+            }
             ServiceError::Database(e) => {
                 tracing::error!("Database error: {}", e);
                 (
@@ -97,6 +113,10 @@ impl IntoResponse for ServiceError {
                     ),
                 }
             }
+            ServiceError::Automation(e) => {
+                tracing::error!("Automation error: {}", e);
+                (StatusCode::BAD_GATEWAY, "Automation error".to_string())
+            }
             ServiceError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (
@@ -137,6 +157,15 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
+    #[test]
+    fn test_database_validation_error() {
+        let error = ServiceError::Database(crate::database::DatabaseError::ValidationError(
+            "Invalid file".to_string(),
+        ));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn test_unauthorized_error() {
         let error = ServiceError::Unauthorized;
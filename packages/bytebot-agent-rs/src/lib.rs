@@ -1,3 +1,4 @@
+pub mod agent;
 pub mod ai;
 pub mod auth;
 pub mod config;
@@ -7,6 +8,7 @@ pub mod routes;
 pub mod server;
 pub mod websocket;
 
+pub use agent::*;
 pub use ai::*;
 pub use auth::*;
 pub use config::*;
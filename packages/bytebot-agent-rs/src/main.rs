@@ -1,3 +1,4 @@
+mod agent;
 mod ai;
 mod auth;
 mod config;
@@ -9,12 +10,14 @@ mod websocket;
 
 use std::sync::Arc;
 
+use agent::{TaskProcessor, TaskScheduler};
 use anyhow::Result;
 use bytebot_shared_rs::logging::{init_logging, LoggingConfig};
 use config::Config;
-use database::MigrationRunner;
+use database::{MigrationRunner, ReplayRepositoryTrait, TaskRepositoryTrait};
 use server::{create_app, create_app_state};
 use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
 #[tokio::main]
@@ -93,6 +96,47 @@ async fn main() -> Result<()> {
         "Database connection pool initialized"
     );
 
+    // A replay left running would keep the queue from claiming tasks
+    app_state
+        .db
+        .replay_repository()
+        .fail_interrupted()
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to fail interrupted replays");
+            anyhow::anyhow!("Failed to fail interrupted replays: {}", e)
+        })?;
+
+    // Tasks left running would hold their slots, as no processor runs them
+    app_state
+        .db
+        .task_repository()
+        .requeue_interrupted()
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to requeue interrupted tasks");
+            anyhow::anyhow!("Failed to requeue interrupted tasks: {}", e)
+        })?;
+
+    // Start the background task processor and scheduler
+    let background_shutdown = CancellationToken::new();
+    let task_processor = Arc::new(TaskProcessor::new(
+        &app_state.config,
+        app_state.db.clone(),
+        app_state.ai_service.clone(),
+        app_state.websocket_gateway.clone(),
+        app_state.metrics.clone(),
+        app_state.task_cancellations.clone(),
+    ));
+    tokio::spawn(task_processor.run(background_shutdown.clone()));
+
+    let task_scheduler = Arc::new(TaskScheduler::new(
+        &app_state.config,
+        app_state.db.clone(),
+        app_state.websocket_gateway.clone(),
+    ));
+    tokio::spawn(task_scheduler.run(background_shutdown.clone()));
+
     // Create Axum application with middleware
     let app = create_app(app_state);
 
@@ -135,6 +179,8 @@ async fn main() -> Result<()> {
             anyhow::anyhow!("Server error: {}", e)
         })?;
 
+    background_shutdown.cancel();
+
     info!(service = "bytebot-agent-rs", "Service shutdown complete");
     Ok(())
 }
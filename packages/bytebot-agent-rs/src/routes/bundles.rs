@@ -0,0 +1,95 @@
+use axum::{
+    extract::{DefaultBodyLimit, Path, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Extension, Router,
+};
+use bytebot_shared_rs::types::{api::ApiResponse, bundle::TaskBundle, task::Task};
+use serde_json::Value;
+use tracing::{debug, info};
+
+use crate::{
+    auth::AuthContext,
+    database::bundle_repository::BundleRepositoryTrait,
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Largest bundle accepted for import; screenshots make bundles of long runs
+/// far bigger than the default request body limit
+const MAX_BUNDLE_SIZE: usize = 512 * 1024 * 1024;
+
+/// Create task export and import routes
+pub fn create_bundle_routes() -> Router<AppState> {
+    Router::new()
+        .route("/tasks/:id/export", get(export_task))
+        .route(
+            "/tasks/import",
+            post(import_task).layer(DefaultBodyLimit::max(MAX_BUNDLE_SIZE)),
+        )
+}
+
+/// Download a task with its messages, summaries, files and screenshots as a
+/// single bundle.
+///
+/// Signed-in users can only export their own tasks.
+/// GET /tasks/:id/export
+async fn export_task(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Path(id): Path<String>,
+) -> ServiceResult<Response> {
+    debug!("Exporting task: {}", id);
+
+    let bundle = state
+        .db
+        .bundle_repository()
+        .export(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    if let Some(Extension(auth)) = auth {
+        if bundle.task.user_id.as_deref() != Some(auth.user.id.as_str()) {
+            return Err(ServiceError::Unauthorized);
+        }
+    }
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"task-{id}.bundle.json\""),
+        )],
+        Json(bundle),
+    )
+        .into_response())
+}
+
+/// Restore an exported bundle as a new task with new IDs
+/// POST /tasks/import
+async fn import_task(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Json(body): Json<Value>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    let bundle = TaskBundle::from_json(body).map_err(ServiceError::Validation)?;
+    debug!(
+        "Importing task {} from a version {} bundle",
+        bundle.task.id, bundle.version
+    );
+
+    let user_id = auth.map(|Extension(auth)| auth.user.id);
+    let task = state
+        .db
+        .bundle_repository()
+        .import(&bundle, user_id.as_deref())
+        .await
+        .map_err(ServiceError::Database)?;
+
+    state.websocket_gateway.emit_task_created(&task).await;
+
+    info!("Imported task {} as {}", bundle.task.id, task.id);
+
+    Ok(Json(ApiResponse::success(task)))
+}
@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use base64::{engine::general_purpose, Engine as _};
+use bytebot_shared_rs::types::{api::ApiResponse, user::File};
+use tracing::{debug, info};
+
+use crate::{
+    database::{
+        file_repository::{FileInfo, FileRepositoryTrait},
+        task_repository::TaskRepositoryTrait,
+    },
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Create routes for the files attached to a task
+pub fn create_file_routes() -> Router<AppState> {
+    Router::new()
+        .route("/tasks/:id/files", get(list_task_files))
+        .route(
+            "/tasks/:id/files/:file_id",
+            get(download_task_file).delete(delete_task_file),
+        )
+}
+
+/// List the files attached to a task, without their contents
+/// GET /tasks/:id/files
+async fn list_task_files(
+    State(state): State<AppState>,
+    Path(task_id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<Vec<FileInfo>>>> {
+    debug!("Listing files for task: {}", task_id);
+
+    state
+        .db
+        .task_repository()
+        .get_by_id(&task_id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {task_id} not found")))?;
+
+    let files = state
+        .db
+        .file_repository()
+        .list_by_task_id(&task_id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(files)))
+}
+
+/// Download a file attached to a task
+/// GET /tasks/:id/files/:file_id
+async fn download_task_file(
+    State(state): State<AppState>,
+    Path((task_id, file_id)): Path<(String, String)>,
+) -> ServiceResult<Response> {
+    debug!("Downloading file {} of task {}", file_id, task_id);
+
+    let file = state
+        .db
+        .file_repository()
+        .get_by_id(&task_id, &file_id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("File with ID {file_id} not found")))?;
+
+    file_response(&file)
+}
+
+/// Delete a file attached to a task
+/// DELETE /tasks/:id/files/:file_id
+async fn delete_task_file(
+    State(state): State<AppState>,
+    Path((task_id, file_id)): Path<(String, String)>,
+) -> ServiceResult<StatusCode> {
+    debug!("Deleting file {} of task {}", file_id, task_id);
+
+    let deleted = state
+        .db
+        .file_repository()
+        .delete(&task_id, &file_id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    if !deleted {
+        return Err(ServiceError::NotFound(format!(
+            "File with ID {file_id} not found"
+        )));
+    }
+
+    info!("Deleted file {} of task {}", file_id, task_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Decoded file contents with headers that make browsers save it under its name
+fn file_response(file: &File) -> ServiceResult<Response> {
+    let data = general_purpose::STANDARD
+        .decode(&file.data)
+        .map_err(|e| ServiceError::Internal(format!("Stored file {} is corrupt: {e}", file.id)))?;
+
+    // Quotes and control characters would break out of the header value
+    let filename: String = file
+        .name
+        .chars()
+        .map(|c| if c == '"' || c.is_control() { '_' } else { c })
+        .collect();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.file_type.clone()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_response() {
+        let file = File::new(
+            "report \"final\".csv".to_string(),
+            "text/csv".to_string(),
+            5,
+            general_purpose::STANDARD.encode("a,b,c"),
+            "task-1".to_string(),
+        );
+
+        let response = file_response(&file).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"report _final_.csv\""
+        );
+    }
+
+    #[test]
+    fn test_file_response_with_corrupt_data() {
+        let file = File::new(
+            "broken.pdf".to_string(),
+            "application/pdf".to_string(),
+            5,
+            "not base64!".to_string(),
+            "task-1".to_string(),
+        );
+
+        assert!(file_response(&file).is_err());
+    }
+}
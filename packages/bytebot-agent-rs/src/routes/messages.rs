@@ -156,7 +156,7 @@ async fn add_task_message(
     Ok(Json(ApiResponse::success(task)))
 }
 
-/// Get raw messages for a task (unprocessed)
+/// Get raw messages for a task (unprocessed), including those folded into summaries
 /// GET /tasks/:id/messages/raw
 async fn get_task_raw_messages(
     State(state): State<AppState>,
@@ -190,7 +190,7 @@ async fn get_task_raw_messages(
         .validate()
         .map_err(|e| ServiceError::Validation(format!("Invalid pagination: {e}")))?;
 
-    // Get raw messages (same as regular messages)
+    // The full history is kept even after compaction
     let message_repo = state.db.message_repository();
     let (messages, total) = message_repo
         .get_by_task_id_paginated(&task_id, &pagination)
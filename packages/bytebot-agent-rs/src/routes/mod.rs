@@ -1,9 +1,23 @@
 pub mod auth;
+pub mod bundles;
+pub mod files;
 pub mod health;
 pub mod messages;
+pub mod replays;
+pub mod search;
 pub mod tasks;
+pub mod templates;
+pub mod usage;
+pub mod views;
 
 pub use auth::create_auth_routes;
+pub use bundles::create_bundle_routes;
+pub use files::create_file_routes;
 pub use health::*;
 pub use messages::create_message_routes;
+pub use replays::create_replay_routes;
+pub use search::create_search_routes;
 pub use tasks::create_task_routes;
+pub use templates::create_template_routes;
+pub use usage::create_usage_routes;
+pub use views::create_view_routes;
@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use bytebot_shared_rs::types::{
+    api::ApiResponse,
+    replay::{ReplayOptions, TaskReplay},
+};
+use tracing::{debug, info};
+use validator::Validate;
+
+use crate::{
+    agent::{
+        computer_use::ComputerUseClient,
+        replay::{recorded_calls, ReplayRunner},
+    },
+    database::{MessageRepositoryTrait, ReplayRepositoryTrait, TaskRepositoryTrait},
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Create task replay routes
+pub fn create_replay_routes() -> Router<AppState> {
+    Router::new()
+        .route("/tasks/:id/replays", get(list_replays).post(start_replay))
+        .route("/replays/:id", get(get_replay))
+}
+
+/// Re-run the desktop actions recorded in a finished task without calling the
+/// model. The replay runs in the background; poll it for its steps.
+/// POST /tasks/:id/replays
+async fn start_replay(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(options): Json<ReplayOptions>,
+) -> ServiceResult<Json<ApiResponse<TaskReplay>>> {
+    debug!("Starting replay of task {}", id);
+
+    options
+        .validate()
+        .map_err(|e| ServiceError::Validation(format!("Invalid replay options: {e}")))?;
+
+    let task = state
+        .db
+        .task_repository()
+        .get_by_id(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    if !task.is_terminal() {
+        return Err(ServiceError::Validation(
+            "Only finished tasks can be replayed".to_string(),
+        ));
+    }
+
+    let messages = state
+        .db
+        .message_repository()
+        .get_by_task_id(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+    let calls = recorded_calls(&messages);
+    if calls.is_empty() {
+        return Err(ServiceError::Validation(
+            "Task has no recorded tool calls to replay".to_string(),
+        ));
+    }
+
+    let replay = state
+        .db
+        .replay_repository()
+        .create(&id, &options)
+        .await
+        .map_err(ServiceError::Database)?
+        // The desktop is shared, so a replay would fight a running task for it
+        .ok_or_else(|| {
+            ServiceError::Validation(
+                "Cannot replay while a task or another replay is running on the desktop"
+                    .to_string(),
+            )
+        })?;
+
+    let runner = ReplayRunner::new(
+        state.db.clone(),
+        ComputerUseClient::new(state.config.bytebot_desktop_base_url.clone()),
+    );
+    tokio::spawn(runner.run(replay.clone(), calls));
+
+    info!("Started replay {} of task {}", replay.id, id);
+
+    Ok(Json(ApiResponse::success(replay)))
+}
+
+/// List the replays of a task, newest first
+/// GET /tasks/:id/replays
+async fn list_replays(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<Vec<TaskReplay>>>> {
+    debug!("Listing replays of task {}", id);
+
+    let replays = state
+        .db
+        .replay_repository()
+        .list_by_task_id(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(replays)))
+}
+
+/// Get a replay with the steps run so far
+/// GET /replays/:id
+async fn get_replay(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<TaskReplay>>> {
+    debug!("Fetching replay {}", id);
+
+    let replay = state
+        .db
+        .replay_repository()
+        .get_by_id(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Replay with ID {id} not found")))?;
+
+    Ok(Json(ApiResponse::success(replay)))
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+    Extension, Router,
+};
+use bytebot_shared_rs::types::{
+    api::{PaginatedResponse, PaginationParams},
+    search::SearchResult,
+};
+use chrono::{DateTime, Utc};
+use tracing::debug;
+use validator::Validate;
+
+use crate::{
+    auth::AuthContext,
+    database::search_repository::{SearchQuery, SearchRepositoryTrait},
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Create search routes
+pub fn create_search_routes() -> Router<AppState> {
+    Router::new().route("/search", get(search))
+}
+
+/// Full-text search over task descriptions, results and messages.
+///
+/// Signed-in users only see their own tasks.
+/// GET /search?q=
+async fn search(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ServiceResult<Json<PaginatedResponse<SearchResult>>> {
+    debug!("Searching with params: {:?}", params);
+
+    let text = params
+        .get("q")
+        .map(|q| q.trim())
+        .filter(|q| !q.is_empty())
+        .ok_or_else(|| ServiceError::Validation("Missing search query q".to_string()))?;
+
+    let page = params.get("page").and_then(|p| p.parse().ok()).unwrap_or(1);
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(20);
+
+    let pagination = PaginationParams {
+        page: Some(page),
+        limit: Some(limit),
+    };
+
+    pagination
+        .validate()
+        .map_err(|e| ServiceError::Validation(format!("Invalid pagination: {e}")))?;
+
+    let mut query = SearchQuery {
+        text: text.to_string(),
+        user_id: auth.map(|Extension(auth)| auth.user.id),
+        ..SearchQuery::default()
+    };
+
+    if let Some(status_str) = params.get("status") {
+        query.status = Some(
+            status_str
+                .parse()
+                .map_err(|_| ServiceError::Validation("Invalid status".to_string()))?,
+        );
+    }
+
+    if let Some(after) = params.get("createdAfter") {
+        query.created_after = Some(parse_timestamp(after, "createdAfter")?);
+    }
+
+    if let Some(before) = params.get("createdBefore") {
+        query.created_before = Some(parse_timestamp(before, "createdBefore")?);
+    }
+
+    let (results, total) = state
+        .db
+        .search_repository()
+        .search(&query, &pagination)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    debug!("Search found {} tasks (total: {})", results.len(), total);
+
+    Ok(Json(PaginatedResponse::new(results, page, limit, total)))
+}
+
+fn parse_timestamp(value: &str, name: &str) -> ServiceResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|_| ServiceError::Validation(format!("Invalid {name}, expected RFC 3339")))
+}
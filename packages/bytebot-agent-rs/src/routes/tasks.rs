@@ -9,21 +9,50 @@ use axum::{
 };
 use bytebot_shared_rs::{
     types::{
-        api::{ApiResponse, CreateTaskDto, PaginatedResponse, PaginationParams, UpdateTaskDto},
-        task::{Task, TaskStatus},
+        api::{
+            ApiResponse, CreateTaskDto, ForkTaskDto, PaginatedResponse, PaginationParams,
+            UpdateTaskDto,
+        },
+        approval::{ApprovalDecision, ApprovalDecisionDto},
+        computer_action::{ComputerAction, Validate as _},
+        dependency::TaskGraph,
+        label::check_label,
+        message::Message,
+        retry::TaskAttempt,
+        task::{Role, Task, TaskStatus, TaskType},
+        view::TaskSort,
     },
     MetricsCollector,
 };
-use serde_json::{json, Value};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use tracing::{debug, info};
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    database::task_repository::{TaskFilter, TaskRepositoryTrait},
+    agent::{
+        computer_use::ComputerUseClient,
+        recurrence::{RecurrenceRule, MAX_UPCOMING_RUNS},
+        results, takeover,
+    },
+    ai::ModelInfo,
+    database::{
+        message_repository::{CreateMessageDto, MessageRepositoryTrait},
+        task_repository::{QueueInfo, TaskFilter, TaskRepositoryTrait},
+    },
     error::{ServiceError, ServiceResult},
     server::AppState,
 };
 
+/// Task details together with its place in the run queue
+#[derive(Debug, Serialize)]
+pub struct TaskWithQueueInfo {
+    #[serde(flatten)]
+    pub task: Task,
+    pub queue: QueueInfo,
+}
+
 /// Create task-related routes
 pub fn create_task_routes() -> Router<AppState> {
     Router::new()
@@ -34,22 +63,38 @@ pub fn create_task_routes() -> Router<AppState> {
             get(get_task).patch(update_task).delete(delete_task),
         )
         .route("/tasks/:id/takeover", post(takeover_task))
+        .route("/tasks/:id/takeover/actions", post(perform_takeover_action))
         .route("/tasks/:id/resume", post(resume_task))
+        .route("/tasks/:id/approve", post(approve_task_action))
+        .route("/tasks/:id/reject", post(reject_task_action))
         .route("/tasks/:id/cancel", post(cancel_task))
+        .route("/tasks/:id/fork", post(fork_task))
+        .route("/tasks/:id/attempts", get(list_task_attempts))
+        .route("/tasks/:id/graph", get(get_task_graph))
+        .route("/tasks/:id/recurrence/pause", post(pause_recurrence))
+        .route("/tasks/:id/recurrence/resume", post(resume_recurrence))
+        .route("/tasks/:id/recurrence/upcoming", get(get_upcoming_runs))
 }
 
 /// Create a new task
 /// POST /tasks
 async fn create_task(
     State(state): State<AppState>,
-    Json(dto): Json<CreateTaskDto>,
+    Json(mut dto): Json<CreateTaskDto>,
 ) -> ServiceResult<Json<ApiResponse<Task>>> {
-    debug!("Creating new task: {:?}", dto);
+    // Attachments are left out of the log, they can be large
+    debug!("Creating new task: {}", dto.description);
 
     // Validate the DTO
     dto.validate()
         .map_err(|e| ServiceError::Validation(format!("Validation failed: {e}")))?;
 
+    if let Some(schema) = &dto.result_schema {
+        results::check_schema(schema).map_err(ServiceError::Validation)?;
+    }
+
+    apply_recurrence(&mut dto, Utc::now())?;
+
     // Create task using repository
     let task_repo = state.db.task_repository();
     let task = task_repo
@@ -65,6 +110,34 @@ async fn create_task(
     Ok(Json(ApiResponse::success(task)))
 }
 
+/// Turn a task with a recurrence rule into a scheduled definition whose
+/// `scheduledFor` is the first run at or after the requested start
+fn apply_recurrence(dto: &mut CreateTaskDto, now: DateTime<Utc>) -> ServiceResult<()> {
+    let Some(recurrence) = &dto.recurrence else {
+        return Ok(());
+    };
+
+    if dto.task_type == Some(TaskType::Immediate) {
+        return Err(ServiceError::Validation(
+            "Recurring tasks cannot be immediate".to_string(),
+        ));
+    }
+
+    let rule = RecurrenceRule::parse(&recurrence.cron, &recurrence.timezone)?;
+    let start = dto
+        .scheduled_for
+        .filter(|start| *start > now)
+        .unwrap_or(now);
+    let first_run = rule.next_after(start).ok_or_else(|| {
+        ServiceError::Validation(format!("Cron expression '{}' never fires", recurrence.cron))
+    })?;
+
+    dto.task_type = Some(TaskType::Scheduled);
+    dto.scheduled_for = Some(first_run);
+
+    Ok(())
+}
+
 /// List all tasks with optional filtering and pagination
 /// GET /tasks
 async fn list_tasks(
@@ -129,6 +202,40 @@ async fn list_tasks(
         );
     }
 
+    if let Some(depends_on) = params.get("dependsOn") {
+        filter.depends_on = Some(depends_on.clone());
+    }
+
+    if let Some(blocked_str) = params.get("blocked") {
+        filter.blocked = Some(
+            blocked_str
+                .parse()
+                .map_err(|_| ServiceError::Validation("Invalid blocked".to_string()))?,
+        );
+    }
+
+    if let Some(labels_str) = params.get("labels") {
+        for label in labels_str.split(',').filter(|l| !l.is_empty()) {
+            check_label(label).map_err(ServiceError::Validation)?;
+            filter.labels.push(label.to_string());
+        }
+    }
+
+    if params.contains_key("sort") || params.contains_key("order") {
+        let mut sort = TaskSort::default();
+        if let Some(field_str) = params.get("sort") {
+            sort.field = field_str
+                .parse()
+                .map_err(|_| ServiceError::Validation("Invalid sort".to_string()))?;
+        }
+        if let Some(order_str) = params.get("order") {
+            sort.direction = order_str
+                .parse()
+                .map_err(|_| ServiceError::Validation("Invalid order".to_string()))?;
+        }
+        filter.sort = Some(sort);
+    }
+
     // Get tasks from repository
     let task_repo = state.db.task_repository();
     let (tasks, total) = task_repo
@@ -141,41 +248,30 @@ async fn list_tasks(
     Ok(Json(PaginatedResponse::new(tasks, page, limit, total)))
 }
 
-/// Get available AI models
+/// Get the models of the registry served by an available provider
 /// GET /tasks/models
-async fn get_models(State(state): State<AppState>) -> ServiceResult<Json<ApiResponse<Vec<Value>>>> {
+async fn get_models(
+    State(state): State<AppState>,
+) -> ServiceResult<Json<ApiResponse<Vec<ModelInfo>>>> {
     debug!("Getting available AI models");
 
-    // Get models from the unified AI service
     let models = state.ai_service.list_all_models();
 
-    // Convert ModelInfo to JSON format expected by the frontend
-    let model_json: Vec<Value> = models
-        .into_iter()
-        .map(|model| {
-            json!({
-                "provider": model.provider,
-                "name": model.name,
-                "title": model.title
-            })
-        })
-        .collect();
-
     debug!(
         "Returning {} available models from {} providers",
-        model_json.len(),
+        models.len(),
         state.ai_service.get_available_providers().len()
     );
 
-    Ok(Json(ApiResponse::success(model_json)))
+    Ok(Json(ApiResponse::success(models)))
 }
 
-/// Get a specific task by ID
+/// Get a specific task by ID, including its queue position
 /// GET /tasks/:id
 async fn get_task(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> ServiceResult<Json<ApiResponse<Task>>> {
+) -> ServiceResult<Json<ApiResponse<TaskWithQueueInfo>>> {
     debug!("Getting task with ID: {}", id);
 
     let task_repo = state.db.task_repository();
@@ -185,9 +281,17 @@ async fn get_task(
         .map_err(ServiceError::Database)?
         .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
 
-    debug!("Found task: {}", task.id);
+    let queue = task_repo
+        .queue_info(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    debug!("Found task: {} (queue: {:?})", task.id, queue);
 
-    Ok(Json(ApiResponse::success(task)))
+    Ok(Json(ApiResponse::success(TaskWithQueueInfo {
+        task,
+        queue,
+    })))
 }
 
 /// Update a task
@@ -270,7 +374,7 @@ async fn takeover_task(
         ));
     }
 
-    // Update task control to user and status to needs help if running
+    // Hand the desktop to the user; a running agent pauses until it is resumed
     let update_dto = UpdateTaskDto {
         status: if task.status == TaskStatus::Running {
             Some(TaskStatus::NeedsHelp)
@@ -281,6 +385,9 @@ async fn takeover_task(
         queued_at: None,
         executed_at: None,
         completed_at: None,
+        control: Some(Role::User),
+        budget: None,
+        labels: None,
     };
 
     // Update the task
@@ -290,17 +397,75 @@ async fn takeover_task(
         .map_err(ServiceError::Database)?
         .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
 
+    // Stop the model call or desktop action the agent has in flight
+    state.task_cancellations.cancel(&id);
+
     // Emit task update event via WebSocket
     state.websocket_gateway.emit_task_update(&id, &task).await;
 
-    // Note: In a full implementation, we would also update the control field
-    // This requires extending the UpdateTaskDto to include control field
-
     info!("Successfully took over control of task: {}", id);
 
     Ok(Json(ApiResponse::success(task)))
 }
 
+/// Perform a desktop action for the user while they have taken over a task.
+///
+/// The action is recorded in the task's history so the assistant can see what
+/// the user did once it resumes.
+/// POST /tasks/:id/takeover/actions
+async fn perform_takeover_action(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(action): Json<ComputerAction>,
+) -> ServiceResult<Json<ApiResponse<Message>>> {
+    debug!("Performing take-over action on task {}: {:?}", id, action);
+
+    action
+        .validate()
+        .map_err(|e| ServiceError::Validation(format!("Invalid action: {e}")))?;
+
+    let task = state
+        .db
+        .task_repository()
+        .get_by_id(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    if task.is_terminal() || task.control != Role::User {
+        return Err(ServiceError::Validation(
+            "Task must be taken over before performing actions".to_string(),
+        ));
+    }
+
+    let computer_use = ComputerUseClient::new(state.config.bytebot_desktop_base_url.clone());
+    let outcome = computer_use.execute(&action).await;
+    let content = takeover::user_action_content(&Uuid::new_v4().to_string(), &action, outcome);
+
+    let message = state
+        .db
+        .message_repository()
+        .create(&CreateMessageDto {
+            content,
+            role: Role::User,
+            task_id: id.clone(),
+            user_id: task.user_id.clone(),
+            summary_id: None,
+        })
+        .await
+        .map_err(ServiceError::Database)?;
+
+    // Emit new message event via WebSocket
+    state
+        .websocket_gateway
+        .emit_new_message(&id, &message)
+        .await;
+
+    info!("Recorded {} action on task {}", action.name(), id);
+
+    Ok(Json(ApiResponse::success(message)))
+}
+
 /// Resume a task (switch control back to assistant)
 /// POST /tasks/:id/resume
 async fn resume_task(
@@ -324,18 +489,26 @@ async fn resume_task(
             "Cannot resume a completed, cancelled, or failed task".to_string(),
         ));
     }
+    if task.pending_approval.is_some() {
+        return Err(ServiceError::Validation(
+            "Approve or reject the pending action to resume the task".to_string(),
+        ));
+    }
 
-    // Update task status to running if it was in needs help or needs review
+    // Hand control back to the assistant. A paused run picks up where it left
+    // off; a pending task goes back to the queue.
     let update_dto = UpdateTaskDto {
         status: match task.status {
             TaskStatus::NeedsHelp | TaskStatus::NeedsReview => Some(TaskStatus::Running),
-            TaskStatus::Pending => Some(TaskStatus::Running),
             _ => None,
         },
         priority: None,
         queued_at: None,
         executed_at: None,
         completed_at: None,
+        control: Some(Role::Assistant),
+        budget: None,
+        labels: None,
     };
 
     let updated_task = task_repo
@@ -355,6 +528,64 @@ async fn resume_task(
     Ok(Json(ApiResponse::success(updated_task)))
 }
 
+/// Let the desktop action a task is waiting on run
+/// POST /tasks/:id/approve
+async fn approve_task_action(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    dto: Option<Json<ApprovalDecisionDto>>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    debug!("Approving pending action of task: {}", id);
+
+    let dto = dto.map(|Json(dto)| dto).unwrap_or_default();
+    decide_approval(&state, &id, ApprovalDecision::Approved, &dto).await
+}
+
+/// Refuse the desktop action a task is waiting on; the comment is passed to
+/// the model with the error result
+/// POST /tasks/:id/reject
+async fn reject_task_action(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    dto: Option<Json<ApprovalDecisionDto>>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    debug!("Rejecting pending action of task: {}", id);
+
+    let dto = dto.map(|Json(dto)| dto).unwrap_or_default();
+    decide_approval(&state, &id, ApprovalDecision::Rejected, &dto).await
+}
+
+async fn decide_approval(
+    state: &AppState,
+    id: &str,
+    decision: ApprovalDecision,
+    dto: &ApprovalDecisionDto,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    let task_repo = state.db.task_repository();
+
+    let task = task_repo
+        .decide_approval(id, decision, dto)
+        .await
+        .map_err(ServiceError::Database)?;
+    let Some(task) = task else {
+        // Tell a missing task apart from one without an undecided approval
+        task_repo
+            .get_by_id(id)
+            .await
+            .map_err(ServiceError::Database)?
+            .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+        return Err(ServiceError::Validation(
+            "Task has no action waiting for approval".to_string(),
+        ));
+    };
+
+    state.websocket_gateway.emit_task_update(id, &task).await;
+
+    info!("Recorded {:?} decision for task {}", decision, id);
+
+    Ok(Json(ApiResponse::success(task)))
+}
+
 /// Cancel a task
 /// POST /tasks/:id/cancel
 async fn cancel_task(
@@ -386,6 +617,9 @@ async fn cancel_task(
         .map_err(ServiceError::Database)?
         .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
 
+    // Stop the model call or desktop action the agent has in flight
+    state.task_cancellations.cancel(&id);
+
     // Emit task update event via WebSocket
     state
         .websocket_gateway
@@ -397,6 +631,211 @@ async fn cancel_task(
     Ok(Json(ApiResponse::success(updated_task)))
 }
 
+/// Pause a recurring task so it stops spawning runs
+/// POST /tasks/:id/recurrence/pause
+async fn pause_recurrence(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    debug!("Pausing recurring task: {}", id);
+
+    get_recurring_task(&state, &id).await?;
+
+    let task = state
+        .db
+        .task_repository()
+        .set_recurrence_paused(&id, true, None)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    state.websocket_gateway.emit_task_update(&id, &task).await;
+
+    info!("Successfully paused recurring task: {}", id);
+
+    Ok(Json(ApiResponse::success(task)))
+}
+
+/// Resume a paused recurring task from its next run after now
+/// POST /tasks/:id/recurrence/resume
+async fn resume_recurrence(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    debug!("Resuming recurring task: {}", id);
+
+    let (_, rule) = get_recurring_task(&state, &id).await?;
+
+    // Runs missed while paused are skipped rather than fired on resume
+    let next_run = rule
+        .next_after(Utc::now())
+        .ok_or_else(|| ServiceError::Validation("Recurring task never fires again".to_string()))?;
+
+    let task = state
+        .db
+        .task_repository()
+        .set_recurrence_paused(&id, false, Some(next_run))
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    state.websocket_gateway.emit_task_update(&id, &task).await;
+
+    info!("Successfully resumed recurring task: {}", id);
+
+    Ok(Json(ApiResponse::success(task)))
+}
+
+/// List the upcoming runs of a recurring task
+/// GET /tasks/:id/recurrence/upcoming
+async fn get_upcoming_runs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ServiceResult<Json<ApiResponse<Vec<DateTime<Utc>>>>> {
+    debug!("Listing upcoming runs of task: {}", id);
+
+    let count = match params.get("count") {
+        Some(count) => count
+            .parse::<usize>()
+            .ok()
+            .filter(|count| (1..=MAX_UPCOMING_RUNS).contains(count))
+            .ok_or_else(|| {
+                ServiceError::Validation(format!("Count must be between 1 and {MAX_UPCOMING_RUNS}"))
+            })?,
+        None => 5,
+    };
+
+    let (task, rule) = get_recurring_task(&state, &id).await?;
+
+    Ok(Json(ApiResponse::success(upcoming_runs(
+        &task, &rule, count,
+    ))))
+}
+
+/// List the failed attempts of a task, oldest first
+/// GET /tasks/:id/attempts
+async fn list_task_attempts(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<Vec<TaskAttempt>>>> {
+    debug!("Listing attempts of task: {}", id);
+
+    let task_repo = state.db.task_repository();
+
+    task_repo
+        .get_by_id(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    let attempts = task_repo
+        .list_attempts(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(attempts)))
+}
+
+/// Create a new pending task from the conversation of a task up to one of its
+/// messages, to branch off before a step that went wrong
+/// POST /tasks/:id/fork
+async fn fork_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(dto): Json<ForkTaskDto>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    debug!("Forking task {} at message {}", id, dto.message_id);
+
+    dto.validate()
+        .map_err(|e| ServiceError::Validation(format!("Validation failed: {e}")))?;
+
+    let task_repo = state.db.task_repository();
+    let source = task_repo
+        .get_by_id(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    let messages = state
+        .db
+        .message_repository()
+        .get_by_task_id(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+    let fork_point = messages
+        .iter()
+        .position(|message| message.id == dto.message_id)
+        .ok_or_else(|| {
+            ServiceError::NotFound(format!(
+                "Message with ID {} not found in task {id}",
+                dto.message_id
+            ))
+        })?;
+
+    let task = task_repo
+        .fork(&source, &messages[..=fork_point])
+        .await
+        .map_err(ServiceError::Database)?;
+
+    state.websocket_gateway.emit_task_created(&task).await;
+
+    info!("Forked task {} into {}", id, task.id);
+
+    Ok(Json(ApiResponse::success(task)))
+}
+
+/// Get the dependency graph a task belongs to, with the status of every task
+/// GET /tasks/:id/graph
+async fn get_task_graph(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<TaskGraph>>> {
+    debug!("Fetching dependency graph of task: {}", id);
+
+    let graph = state
+        .db
+        .task_repository()
+        .graph(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    Ok(Json(ApiResponse::success(graph)))
+}
+
+/// Fetch a task and its recurrence rule, rejecting one-off tasks
+async fn get_recurring_task(state: &AppState, id: &str) -> ServiceResult<(Task, RecurrenceRule)> {
+    let task = state
+        .db
+        .task_repository()
+        .get_by_id(id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    match RecurrenceRule::from_task(&task) {
+        Some(rule) => Ok((task, rule?)),
+        None => Err(ServiceError::Validation(format!(
+            "Task {id} is not a recurring task"
+        ))),
+    }
+}
+
+/// The next `count` runs of a recurring definition, starting with the pending one.
+///
+/// Paused or finished definitions have no upcoming runs.
+fn upcoming_runs(task: &Task, rule: &RecurrenceRule, count: usize) -> Vec<DateTime<Utc>> {
+    let next_run = match task.scheduled_for {
+        Some(next_run) if task.status == TaskStatus::Pending && !task.recurrence_paused => next_run,
+        _ => return Vec::new(),
+    };
+
+    std::iter::once(next_run)
+        .chain(rule.upcoming_after(next_run, count - 1))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -405,15 +844,17 @@ mod tests {
         body::Body,
         http::{Method, Request, StatusCode},
     };
+    use chrono::TimeZone;
     use serde_json::json;
     use tower::ServiceExt;
 
     use super::*;
     use crate::{
+        agent::TaskCancellations,
         ai::UnifiedAIService,
         auth::{AuthService, AuthServiceTrait},
         config::Config,
-        database::DatabaseManager,
+        database::{tests::test_metrics, DatabaseManager},
         websocket::WebSocketGateway,
     };
 
@@ -442,6 +883,7 @@ mod tests {
                     auth_service,
                     websocket_gateway,
                     metrics: Arc::new(MetricsCollector::new("test-service").unwrap()),
+                    task_cancellations: Arc::new(TaskCancellations::new()),
                     start_time: chrono::Utc::now(),
                 }
             }
@@ -469,6 +911,7 @@ mod tests {
                 auth_service,
                 websocket_gateway: Arc::new(WebSocketGateway::new()),
                 metrics: Arc::new(MetricsCollector::new("test-service").unwrap()),
+                task_cancellations: Arc::new(TaskCancellations::new()),
                 start_time: chrono::Utc::now(),
             })
         } else {
@@ -476,6 +919,26 @@ mod tests {
         }
     }
 
+    // Helper function to create app state over an isolated test schema
+    fn create_isolated_test_state(pool: &sqlx::PgPool) -> AppState {
+        let config = Arc::new(Config::default());
+        let db = DatabaseManager::from_pool(pool.clone());
+        AppState {
+            ai_service: Arc::new(UnifiedAIService::new(&config)),
+            auth_service: Arc::new(AuthService::new(
+                db.get_pool(),
+                config.jwt_secret.clone(),
+                config.auth_enabled,
+            )),
+            config,
+            db: Arc::new(db),
+            websocket_gateway: Arc::new(WebSocketGateway::new()),
+            metrics: test_metrics(),
+            task_cancellations: Arc::new(TaskCancellations::new()),
+            start_time: chrono::Utc::now(),
+        }
+    }
+
     #[tokio::test]
     #[ignore] // Ignore by default since it requires a test database
     async fn test_create_task_endpoint() {
@@ -642,6 +1105,106 @@ mod tests {
         }
     }
 
+    fn recurring_dto(cron: &str, timezone: &str) -> CreateTaskDto {
+        serde_json::from_value(json!({
+            "description": "Send the weekday report",
+            "recurrence": { "cron": cron, "timezone": timezone }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_recurrence_schedules_first_run() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 7, 8, 0, 0).unwrap();
+        let mut dto = recurring_dto("0 7 * * MON-FRI", "UTC");
+
+        apply_recurrence(&mut dto, now).unwrap();
+
+        assert_eq!(dto.task_type, Some(TaskType::Scheduled));
+        assert_eq!(
+            dto.scheduled_for,
+            Some(Utc.with_ymd_and_hms(2025, 3, 10, 7, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_apply_recurrence_respects_future_start() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 7, 8, 0, 0).unwrap();
+        let mut dto = recurring_dto("0 7 * * *", "UTC");
+        dto.scheduled_for = Some(Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap());
+
+        apply_recurrence(&mut dto, now).unwrap();
+
+        assert_eq!(
+            dto.scheduled_for,
+            Some(Utc.with_ymd_and_hms(2025, 4, 1, 7, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_apply_recurrence_rejects_invalid_rules() {
+        let now = Utc::now();
+
+        let mut dto = recurring_dto("every morning", "UTC");
+        assert!(apply_recurrence(&mut dto, now).is_err());
+
+        let mut dto = recurring_dto("0 7 * * *", "Atlantis/Capital");
+        assert!(apply_recurrence(&mut dto, now).is_err());
+
+        let mut dto = recurring_dto("0 7 * * *", "UTC");
+        dto.task_type = Some(TaskType::Immediate);
+        assert!(apply_recurrence(&mut dto, now).is_err());
+    }
+
+    #[test]
+    fn test_apply_recurrence_ignores_one_off_tasks() {
+        let mut dto: CreateTaskDto =
+            serde_json::from_value(json!({ "description": "One-off task" })).unwrap();
+
+        apply_recurrence(&mut dto, Utc::now()).unwrap();
+
+        assert_eq!(dto.task_type, None);
+        assert_eq!(dto.scheduled_for, None);
+    }
+
+    #[test]
+    fn test_task_with_queue_info_serialization() {
+        let task = Task::new("Queued task".to_string(), json!({}));
+        let response = TaskWithQueueInfo {
+            task: task.clone(),
+            queue: QueueInfo {
+                position: Some(2),
+                depth: 5,
+            },
+        };
+
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["id"], json!(task.id));
+        assert_eq!(value["description"], json!("Queued task"));
+        assert_eq!(value["queue"], json!({ "position": 2, "depth": 5 }));
+    }
+
+    #[test]
+    fn test_upcoming_runs() {
+        let rule = RecurrenceRule::parse("0 7 * * *", "UTC").unwrap();
+        let mut task = Task::new("Daily report".to_string(), json!({}));
+        task.task_type = TaskType::Scheduled;
+        task.cron_expression = Some("0 7 * * *".to_string());
+        task.scheduled_for = Some(Utc.with_ymd_and_hms(2025, 3, 7, 7, 0, 0).unwrap());
+
+        assert_eq!(
+            upcoming_runs(&task, &rule, 3),
+            vec![
+                Utc.with_ymd_and_hms(2025, 3, 7, 7, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2025, 3, 8, 7, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2025, 3, 9, 7, 0, 0).unwrap(),
+            ]
+        );
+
+        task.recurrence_paused = true;
+        assert!(upcoming_runs(&task, &rule, 3).is_empty());
+    }
+
     #[tokio::test]
     async fn test_route_registration() {
         // Test that all routes are properly registered
@@ -653,4 +1216,336 @@ mod tests {
         // Verify the router was created successfully
         assert!(!format!("{routes:?}").is_empty());
     }
+
+    #[tokio::test]
+    async fn test_fork_task_endpoint() {
+        use bytebot_shared_rs::types::{message::MessageContentBlock, task::Role};
+
+        use crate::database::{
+            tests::{create_isolated_test_pool, drop_isolated_schema},
+            CreateMessageDto, MessageRepositoryTrait,
+        };
+
+        let pool = create_isolated_test_pool().await;
+        let state = create_isolated_test_state(&pool);
+
+        let source = state
+            .db
+            .task_repository()
+            .create(&CreateTaskDto {
+                description: "Fill in the form".to_string(),
+                task_type: None,
+                scheduled_for: None,
+                priority: None,
+                created_by: None,
+                user_id: None,
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .unwrap();
+        let mut message_ids = Vec::new();
+        for text in ["Open the form", "Opened it", "Submit it"] {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            let message = state
+                .db
+                .message_repository()
+                .create(&CreateMessageDto {
+                    content: vec![MessageContentBlock::text(text)],
+                    role: Role::User,
+                    task_id: source.id.clone(),
+                    user_id: None,
+                    summary_id: None,
+                })
+                .await
+                .unwrap();
+            message_ids.push(message.id);
+        }
+
+        let app = create_task_routes().with_state(state.clone());
+        let fork = |task_id: String, message_id: String| {
+            let app = app.clone();
+            async move {
+                app.oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(format!("/tasks/{task_id}/fork"))
+                        .header("content-type", "application/json")
+                        .body(Body::from(json!({ "messageId": message_id }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        let response = fork(source.id.clone(), message_ids[1].clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let forked = &body["data"];
+        assert_eq!(forked["forkedFromId"], source.id.as_str());
+        assert_eq!(forked["forkedFromMessageId"], message_ids[1].as_str());
+        assert_eq!(forked["status"], "PENDING");
+
+        let copied = state
+            .db
+            .message_repository()
+            .get_by_task_id(forked["id"].as_str().unwrap())
+            .await
+            .unwrap();
+        let texts: Vec<_> = copied
+            .iter()
+            .map(|message| {
+                message.get_content_blocks().unwrap()[0]
+                    .as_text()
+                    .map(str::to_string)
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                Some("Open the form".to_string()),
+                Some("Opened it".to_string())
+            ]
+        );
+
+        // The fork point must be a message of the task
+        let other = fork(
+            forked["id"].as_str().unwrap().to_string(),
+            message_ids[2].clone(),
+        )
+        .await;
+        assert_eq!(other.status(), StatusCode::NOT_FOUND);
+        let missing = fork("missing-task".to_string(), message_ids[0].clone()).await;
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_approval_endpoints() {
+        use bytebot_shared_rs::types::approval::{ApprovalDecision, PendingApproval};
+
+        use crate::database::tests::{create_isolated_test_pool, drop_isolated_schema};
+
+        let pool = create_isolated_test_pool().await;
+        let state = create_isolated_test_state(&pool);
+        let task_repo = state.db.task_repository();
+
+        let mut task_ids = Vec::new();
+        for description in ["Send the invoice", "Write the report"] {
+            let task = task_repo
+                .create(&CreateTaskDto {
+                    description: description.to_string(),
+                    task_type: None,
+                    scheduled_for: None,
+                    priority: None,
+                    created_by: None,
+                    user_id: None,
+                    model: None,
+                    files: None,
+                    recurrence: None,
+                    result_schema: None,
+                    retry_policy: None,
+                    depends_on: None,
+                    budget: None,
+                    labels: None,
+                })
+                .await
+                .unwrap();
+            task_repo
+                .update_status(&task.id, TaskStatus::Running)
+                .await
+                .unwrap();
+            task_repo
+                .request_approval(
+                    &task.id,
+                    &PendingApproval {
+                        tool_use_id: "toolu_1".to_string(),
+                        action: "click_mouse".to_string(),
+                        input: json!({ "button": "left" }),
+                        reason: "Clicking Send requires approval".to_string(),
+                        requested_at: chrono::Utc::now(),
+                        decision: None,
+                        comment: None,
+                    },
+                )
+                .await
+                .unwrap()
+                .unwrap();
+            task_ids.push(task.id);
+        }
+
+        let app = create_task_routes().with_state(state.clone());
+        let decide = |path: String, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder().method(Method::POST).uri(path);
+                let request = match body {
+                    Some(body) => request
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string())),
+                    None => request.body(Body::empty()),
+                };
+                app.oneshot(request.unwrap()).await.unwrap()
+            }
+        };
+
+        let response = decide(format!("/tasks/{}/approve", task_ids[0]), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = decide(
+            format!("/tasks/{}/reject", task_ids[1]),
+            Some(json!({ "comment": "Wrong file" })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let approved = task_repo.get_by_id(&task_ids[0]).await.unwrap().unwrap();
+        assert_eq!(approved.status, TaskStatus::Running);
+        assert_eq!(
+            approved.pending_approval.unwrap().decision,
+            Some(ApprovalDecision::Approved)
+        );
+
+        let rejected = task_repo.get_by_id(&task_ids[1]).await.unwrap().unwrap();
+        let approval = rejected.pending_approval.unwrap();
+        assert_eq!(rejected.status, TaskStatus::Running);
+        assert_eq!(approval.decision, Some(ApprovalDecision::Rejected));
+        assert_eq!(approval.comment.as_deref(), Some("Wrong file"));
+
+        // Decisions are final, and missing tasks are told apart
+        let response = decide(format!("/tasks/{}/reject", task_ids[0]), None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = decide("/tasks/missing-task/approve".to_string(), None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_takeover_and_resume_keep_timestamps() {
+        use crate::database::tests::{create_isolated_test_pool, drop_isolated_schema};
+
+        let pool = create_isolated_test_pool().await;
+        let state = create_isolated_test_state(&pool);
+        let task_repo = state.db.task_repository();
+
+        task_repo
+            .create(&CreateTaskDto {
+                description: "Book the flight".to_string(),
+                task_type: None,
+                scheduled_for: None,
+                priority: None,
+                created_by: None,
+                user_id: None,
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .unwrap();
+        let claimed = task_repo.claim_next_pending(1).await.unwrap().unwrap();
+        assert!(claimed.executed_at.is_some());
+
+        let app = create_task_routes().with_state(state.clone());
+        for (action, status) in [
+            ("takeover", TaskStatus::NeedsHelp),
+            ("resume", TaskStatus::Running),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(format!("/tasks/{}/{action}", claimed.id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // The run time of the budget counts from executedAt
+            let task = task_repo.get_by_id(&claimed.id).await.unwrap().unwrap();
+            assert_eq!(task.status, status);
+            assert_eq!(task.executed_at, claimed.executed_at);
+            assert_eq!(task.queued_at, claimed.queued_at);
+        }
+
+        drop_isolated_schema(&pool).await;
+    }
+
+    #[tokio::test]
+    async fn test_labels_patch_keeps_timestamps() {
+        use crate::database::tests::{create_isolated_test_pool, drop_isolated_schema};
+
+        let pool = create_isolated_test_pool().await;
+        let state = create_isolated_test_state(&pool);
+        let task_repo = state.db.task_repository();
+
+        task_repo
+            .create(&CreateTaskDto {
+                description: "File the expenses".to_string(),
+                task_type: None,
+                scheduled_for: None,
+                priority: None,
+                created_by: None,
+                user_id: None,
+                model: None,
+                files: None,
+                recurrence: None,
+                result_schema: None,
+                retry_policy: None,
+                depends_on: None,
+                budget: None,
+                labels: None,
+            })
+            .await
+            .unwrap();
+        let claimed = task_repo.claim_next_pending(1).await.unwrap().unwrap();
+        let completed = task_repo
+            .update_status(&claimed.id, TaskStatus::Completed)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(completed.completed_at.is_some());
+
+        let app = create_task_routes().with_state(state.clone());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::PATCH)
+                    .uri(format!("/tasks/{}", claimed.id))
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "labels": ["billing"] }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let task = task_repo.get_by_id(&claimed.id).await.unwrap().unwrap();
+        assert_eq!(task.labels, ["billing"]);
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.queued_at, completed.queued_at);
+        assert_eq!(task.executed_at, completed.executed_at);
+        assert_eq!(task.completed_at, completed.completed_at);
+
+        drop_isolated_schema(&pool).await;
+    }
 }
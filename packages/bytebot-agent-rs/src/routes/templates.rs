@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use bytebot_shared_rs::types::{
+    api::ApiResponse,
+    task::Task,
+    template::{CreateTaskTemplateDto, RunTemplateDto, TaskTemplate},
+};
+use tracing::{debug, info};
+
+use crate::{
+    agent::results,
+    database::{
+        task_repository::TaskRepositoryTrait, template_repository::TemplateRepositoryTrait,
+    },
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Create task template routes
+pub fn create_template_routes() -> Router<AppState> {
+    Router::new()
+        .route("/templates", post(create_template).get(list_templates))
+        .route(
+            "/templates/:id",
+            get(get_template)
+                .put(update_template)
+                .delete(delete_template),
+        )
+        .route("/templates/:id/run", post(run_template))
+}
+
+/// Create a task template
+/// POST /templates
+async fn create_template(
+    State(state): State<AppState>,
+    Json(dto): Json<CreateTaskTemplateDto>,
+) -> ServiceResult<Json<ApiResponse<TaskTemplate>>> {
+    debug!("Creating task template: {}", dto.name);
+
+    if let Some(schema) = &dto.result_schema {
+        results::check_schema(schema).map_err(ServiceError::Validation)?;
+    }
+
+    let template = state
+        .db
+        .template_repository()
+        .create(&dto)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// List all task templates
+/// GET /templates
+async fn list_templates(
+    State(state): State<AppState>,
+) -> ServiceResult<Json<ApiResponse<Vec<TaskTemplate>>>> {
+    debug!("Listing task templates");
+
+    let templates = state
+        .db
+        .template_repository()
+        .list()
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(templates)))
+}
+
+/// Get a task template by ID
+/// GET /templates/:id
+async fn get_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<TaskTemplate>>> {
+    debug!("Fetching task template: {}", id);
+
+    let template = find_template(&state, &id).await?;
+
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// Replace a task template
+/// PUT /templates/:id
+async fn update_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(dto): Json<CreateTaskTemplateDto>,
+) -> ServiceResult<Json<ApiResponse<TaskTemplate>>> {
+    debug!("Updating task template: {}", id);
+
+    if let Some(schema) = &dto.result_schema {
+        results::check_schema(schema).map_err(ServiceError::Validation)?;
+    }
+
+    let template = state
+        .db
+        .template_repository()
+        .update(&id, &dto)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Template with ID {id} not found")))?;
+
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// Delete a task template; tasks created from it are kept
+/// DELETE /templates/:id
+async fn delete_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ServiceResult<StatusCode> {
+    debug!("Deleting task template: {}", id);
+
+    let deleted = state
+        .db
+        .template_repository()
+        .delete(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    if !deleted {
+        return Err(ServiceError::NotFound(format!(
+            "Template with ID {id} not found"
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Create a task from a template and a parameter map
+/// POST /templates/:id/run
+async fn run_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(dto): Json<RunTemplateDto>,
+) -> ServiceResult<Json<ApiResponse<Task>>> {
+    debug!("Running task template: {}", id);
+
+    let template = find_template(&state, &id).await?;
+    let task_dto = template
+        .render(&dto.parameters)
+        .map_err(ServiceError::Validation)?;
+
+    let task = state
+        .db
+        .task_repository()
+        .create(&task_dto)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    state.websocket_gateway.emit_task_created(&task).await;
+
+    info!("Created task {} from template {}", task.id, template.id);
+
+    Ok(Json(ApiResponse::success(task)))
+}
+
+async fn find_template(state: &AppState, id: &str) -> ServiceResult<TaskTemplate> {
+    state
+        .db
+        .template_repository()
+        .get_by_id(id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Template with ID {id} not found")))
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    routing::get,
+    Extension, Router,
+};
+use bytebot_shared_rs::types::{
+    api::ApiResponse,
+    usage::{TaskUsageReport, UsageReport},
+};
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::{
+    auth::AuthContext,
+    database::{TaskRepositoryTrait, UsageQuery, UsageRepositoryTrait},
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Create model usage routes
+pub fn create_usage_routes() -> Router<AppState> {
+    Router::new()
+        .route("/tasks/:id/usage", get(get_task_usage))
+        .route("/usage", get(get_usage))
+}
+
+/// Tokens and estimated cost of the model calls of a task, with every call
+/// and the message it produced.
+///
+/// Signed-in users only see the usage of their own tasks.
+/// GET /tasks/:id/usage
+async fn get_task_usage(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<TaskUsageReport>>> {
+    debug!("Getting model usage of task {}", id);
+
+    let task = state
+        .db
+        .task_repository()
+        .get_by_id(&id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Task with ID {id} not found")))?;
+
+    if let Some(Extension(auth)) = auth {
+        if task.user_id.as_deref() != Some(auth.user.id.as_str()) {
+            return Err(ServiceError::Unauthorized);
+        }
+    }
+
+    let usage_repo = state.db.usage_repository();
+    let report = usage_repo
+        .report(&UsageQuery {
+            task_id: Some(id.clone()),
+            ..UsageQuery::default()
+        })
+        .await
+        .map_err(ServiceError::Database)?;
+    let calls = usage_repo
+        .list_by_task_id(&id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(TaskUsageReport {
+        task_id: id,
+        totals: report.totals,
+        by_model: report.by_model,
+        calls,
+    })))
+}
+
+/// Tokens and estimated cost of model calls, by model and by user.
+///
+/// Signed-in users only see the usage of their own tasks.
+/// GET /usage?from=&to=&userId=&model=
+async fn get_usage(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ServiceResult<Json<ApiResponse<UsageReport>>> {
+    debug!("Getting model usage with params: {:?}", params);
+
+    let mut query = UsageQuery {
+        user_id: params.get("userId").cloned(),
+        model: params.get("model").cloned(),
+        ..UsageQuery::default()
+    };
+
+    if let Some(Extension(auth)) = auth {
+        if query.user_id.as_ref().is_some_and(|id| *id != auth.user.id) {
+            return Err(ServiceError::Unauthorized);
+        }
+        query.user_id = Some(auth.user.id);
+    }
+
+    if let Some(from) = params.get("from") {
+        query.from = Some(parse_timestamp(from, "from")?);
+    }
+
+    if let Some(to) = params.get("to") {
+        query.to = Some(parse_timestamp(to, "to")?);
+    }
+
+    let report = state
+        .db
+        .usage_repository()
+        .report(&query)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+fn parse_timestamp(value: &str, name: &str) -> ServiceResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|_| ServiceError::Validation(format!("Invalid {name}, expected RFC 3339")))
+}
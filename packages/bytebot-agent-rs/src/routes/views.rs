@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Extension, Router,
+};
+use bytebot_shared_rs::types::{
+    api::{ApiResponse, PaginatedResponse, PaginationParams},
+    task::Task,
+    view::{CreateTaskViewDto, TaskView},
+};
+use tracing::debug;
+use validator::Validate;
+
+use crate::{
+    auth::AuthContext,
+    database::{
+        task_repository::{TaskFilter, TaskRepositoryTrait},
+        view_repository::ViewRepositoryTrait,
+    },
+    error::{ServiceError, ServiceResult},
+    server::AppState,
+};
+
+/// Create saved view routes
+pub fn create_view_routes() -> Router<AppState> {
+    Router::new()
+        .route("/views", get(list_views).post(create_view))
+        .route(
+            "/views/:id",
+            get(get_view).put(update_view).delete(delete_view),
+        )
+        .route("/views/:id/tasks", get(list_view_tasks))
+}
+
+/// Owner of the views a request works on; `None` when authentication is off
+fn view_owner(auth: Option<Extension<AuthContext>>) -> Option<String> {
+    auth.map(|Extension(auth)| auth.user.id)
+}
+
+/// Create a saved view for the current user
+/// POST /views
+async fn create_view(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Json(dto): Json<CreateTaskViewDto>,
+) -> ServiceResult<Json<ApiResponse<TaskView>>> {
+    debug!("Creating saved view: {}", dto.name);
+
+    let view = state
+        .db
+        .view_repository()
+        .create(view_owner(auth).as_deref(), &dto)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(view)))
+}
+
+/// List the current user's saved views
+/// GET /views
+async fn list_views(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+) -> ServiceResult<Json<ApiResponse<Vec<TaskView>>>> {
+    debug!("Listing saved views");
+
+    let views = state
+        .db
+        .view_repository()
+        .list(view_owner(auth).as_deref())
+        .await
+        .map_err(ServiceError::Database)?;
+
+    Ok(Json(ApiResponse::success(views)))
+}
+
+/// Get a saved view by ID
+/// GET /views/:id
+async fn get_view(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Path(id): Path<String>,
+) -> ServiceResult<Json<ApiResponse<TaskView>>> {
+    debug!("Fetching saved view: {}", id);
+
+    let view = find_view(&state, view_owner(auth).as_deref(), &id).await?;
+
+    Ok(Json(ApiResponse::success(view)))
+}
+
+/// Replace a saved view
+/// PUT /views/:id
+async fn update_view(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Path(id): Path<String>,
+    Json(dto): Json<CreateTaskViewDto>,
+) -> ServiceResult<Json<ApiResponse<TaskView>>> {
+    debug!("Updating saved view: {}", id);
+
+    let view = state
+        .db
+        .view_repository()
+        .update(view_owner(auth).as_deref(), &id, &dto)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("View with ID {id} not found")))?;
+
+    Ok(Json(ApiResponse::success(view)))
+}
+
+/// Delete a saved view
+/// DELETE /views/:id
+async fn delete_view(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Path(id): Path<String>,
+) -> ServiceResult<StatusCode> {
+    debug!("Deleting saved view: {}", id);
+
+    let deleted = state
+        .db
+        .view_repository()
+        .delete(view_owner(auth).as_deref(), &id)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    if !deleted {
+        return Err(ServiceError::NotFound(format!(
+            "View with ID {id} not found"
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the tasks matching a saved view, in the view's order
+/// GET /views/:id/tasks
+async fn list_view_tasks(
+    State(state): State<AppState>,
+    auth: Option<Extension<AuthContext>>,
+    Path(id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ServiceResult<Json<PaginatedResponse<Task>>> {
+    debug!(
+        "Listing tasks of saved view {} with params: {:?}",
+        id, params
+    );
+
+    let page = params.get("page").and_then(|p| p.parse().ok()).unwrap_or(1);
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(20);
+
+    let pagination = PaginationParams {
+        page: Some(page),
+        limit: Some(limit),
+    };
+
+    pagination
+        .validate()
+        .map_err(|e| ServiceError::Validation(format!("Invalid pagination: {e}")))?;
+
+    let view = find_view(&state, view_owner(auth).as_deref(), &id).await?;
+
+    let (tasks, total) = state
+        .db
+        .task_repository()
+        .list(&TaskFilter::from(&view), &pagination)
+        .await
+        .map_err(ServiceError::Database)?;
+
+    debug!("Found {} tasks (total: {})", tasks.len(), total);
+
+    Ok(Json(PaginatedResponse::new(tasks, page, limit, total)))
+}
+
+async fn find_view(state: &AppState, user_id: Option<&str>, id: &str) -> ServiceResult<TaskView> {
+    state
+        .db
+        .view_repository()
+        .get_by_id(user_id, id)
+        .await
+        .map_err(ServiceError::Database)?
+        .ok_or_else(|| ServiceError::NotFound(format!("View with ID {id} not found")))
+}
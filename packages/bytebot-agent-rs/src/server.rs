@@ -12,12 +12,17 @@ use tower_http::{
 use tracing::Level;
 
 use crate::{
+    agent::TaskCancellations,
     ai::UnifiedAIService,
     auth::{auth_middleware, optional_auth_middleware, AuthService, AuthServiceTrait},
     config::Config,
     database::DatabaseManager,
     error::ServiceError,
-    routes::{create_auth_routes, create_message_routes, create_task_routes, health::*},
+    routes::{
+        create_auth_routes, create_bundle_routes, create_file_routes, create_message_routes,
+        create_replay_routes, create_search_routes, create_task_routes, create_template_routes,
+        create_usage_routes, create_view_routes, health::*,
+    },
     websocket::WebSocketGateway,
 };
 
@@ -30,6 +35,7 @@ pub struct AppState {
     pub auth_service: Arc<dyn AuthServiceTrait>,
     pub websocket_gateway: Arc<WebSocketGateway>,
     pub metrics: Arc<MetricsCollector>,
+    pub task_cancellations: Arc<TaskCancellations>,
     pub start_time: chrono::DateTime<Utc>,
 }
 
@@ -70,6 +76,7 @@ pub async fn create_app_state(config: Arc<Config>) -> Result<AppState, ServiceEr
         auth_service,
         websocket_gateway,
         metrics,
+        task_cancellations: Arc::new(TaskCancellations::new()),
         start_time,
     })
 }
@@ -132,6 +139,55 @@ pub fn create_app(state: AppState) -> Router {
                 auth_middleware,
             )),
         )
+        .nest(
+            "/templates",
+            create_template_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/views",
+            create_view_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/search",
+            create_search_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/bundles",
+            create_bundle_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/replays",
+            create_replay_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/usage",
+            create_usage_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/files",
+            create_file_routes().layer(axum::middleware::from_fn_with_state(
+                state.auth_service.clone(),
+                auth_middleware,
+            )),
+        )
         .nest(
             "/messages",
             create_message_routes().layer(axum::middleware::from_fn_with_state(
@@ -254,6 +310,7 @@ mod tests {
             auth_service,
             websocket_gateway,
             metrics,
+            task_cancellations: Arc::new(TaskCancellations::new()),
             start_time: Utc::now(),
         };
         create_app(state)
@@ -1,6 +1,8 @@
-use bytebot_shared_rs::types::{Message, Task};
+use bytebot_shared_rs::types::{Message, PendingApproval, Task};
 use serde::{Deserialize, Serialize};
 
+use crate::ai::StreamDelta;
+
 /// WebSocket event types that match the TypeScript implementation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -41,6 +43,13 @@ pub enum ServerMessage {
     TaskCreated { task: Task },
     /// Task deleted event
     TaskDeleted { task_id: String },
+    /// A desktop action of the task waits for a human approval
+    ApprovalPending {
+        task_id: String,
+        approval: PendingApproval,
+    },
+    /// Piece of a model response that is still being generated
+    MessageDelta { task_id: String, delta: StreamDelta },
     /// Error message
     Error { message: String },
 }
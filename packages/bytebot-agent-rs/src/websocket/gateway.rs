@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use bytebot_shared_rs::{
     logging::websocket_logging,
-    types::{Message, Task},
+    types::{Message, PendingApproval, Task},
 };
 use serde_json::Value;
 use socketioxide::{
@@ -12,7 +12,7 @@ use socketioxide::{
 use tracing::{debug, error, info, warn};
 
 use super::{connection::ConnectionManager, events::ServerMessage};
-use crate::error::ServiceError;
+use crate::{ai::StreamDelta, error::ServiceError};
 
 /// WebSocket gateway that provides Socket.IO compatible interface
 /// This matches the functionality of the TypeScript TasksGateway
@@ -256,6 +256,45 @@ impl WebSocketGateway {
         }
     }
 
+    /// Emit a pending approval to all clients in the task room
+    pub async fn emit_approval_pending(&self, task_id: &str, approval: &PendingApproval) {
+        let room_name = format!("task_{task_id}");
+        let message = ServerMessage::ApprovalPending {
+            task_id: task_id.to_string(),
+            approval: approval.clone(),
+        };
+
+        let client_count = self
+            .connection_manager
+            .get_room_client_count(&room_name)
+            .await;
+        if let Err(e) = self
+            .io
+            .to(room_name.clone())
+            .emit("approval_pending", message)
+        {
+            error!(
+                room = %room_name,
+                error = %e,
+                "Failed to emit approval_pending"
+            );
+        } else {
+            websocket_logging::event_emitted("approval_pending", Some(&room_name), client_count);
+        }
+    }
+
+    /// Emit a piece of a model response that is still being generated to all
+    /// clients in the task room
+    pub async fn emit_message_delta(&self, task_id: &str, delta: &StreamDelta) {
+        let message = ServerMessage::MessageDelta {
+            task_id: task_id.to_string(),
+            delta: delta.clone(),
+        };
+
+        self.broadcast_to_task(task_id, "message_delta", message)
+            .await;
+    }
+
     /// Emit task created to all connected clients
     /// Matches emitTaskCreated from TypeScript implementation
     pub async fn emit_task_created(&self, task: &Task) {
@@ -463,6 +463,7 @@ async fn test_task_endpoints() {
     let takeover_task = &response_json["data"];
     assert_eq!(takeover_task["id"], task_id);
     assert_eq!(takeover_task["status"], "NEEDS_HELP");
+    assert_eq!(takeover_task["control"], "USER");
 
     // Test POST /tasks/:id/resume endpoint
     let response = app
@@ -489,6 +490,7 @@ async fn test_task_endpoints() {
     let resumed_task = &response_json["data"];
     assert_eq!(resumed_task["id"], task_id);
     assert_eq!(resumed_task["status"], "RUNNING");
+    assert_eq!(resumed_task["control"], "ASSISTANT");
 
     // Test POST /tasks/:id/cancel endpoint
     let response = app
@@ -5,6 +5,7 @@ use axum::{
     http::{Method, Request, StatusCode},
 };
 use bytebot_agent_rs::{
+    agent::TaskCancellations,
     ai::UnifiedAIService,
     auth::{AuthService, AuthServiceTrait},
     config::Config,
@@ -56,6 +57,7 @@ async fn test_models_endpoint_integration() {
         auth_service,
         websocket_gateway,
         metrics: Arc::new(MetricsCollector::new("test-service").unwrap()),
+        task_cancellations: Arc::new(TaskCancellations::new()),
         start_time: chrono::Utc::now(),
     };
 
@@ -168,6 +170,7 @@ async fn test_models_endpoint_no_keys() {
         auth_service,
         websocket_gateway,
         metrics: Arc::new(MetricsCollector::new("test-service").unwrap()),
+        task_cancellations: Arc::new(TaskCancellations::new()),
         start_time: chrono::Utc::now(),
     };
 
@@ -0,0 +1,310 @@
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use bytebot_agent_rs::{
+    ai::{openai::OpenAICompatibleProvider, AIService, StreamDelta, UnifiedAIService},
+    config::Config,
+    error::AIError,
+};
+use bytebot_shared_rs::types::{
+    message::{Message, MessageContentBlock},
+    task::Role,
+};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+/// Request received by a stub server
+#[derive(Debug, Clone)]
+struct RecordedRequest {
+    headers: HeaderMap,
+    body: Value,
+}
+
+#[derive(Clone)]
+struct StubState {
+    name: &'static str,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+/// Answer like an OpenAI-compatible server, naming the stub in the reply
+async fn chat_completions(
+    State(state): State<StubState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    state.requests.lock().unwrap().push(RecordedRequest {
+        headers,
+        body: body.clone(),
+    });
+
+    let model = body["model"].as_str().unwrap_or_default().to_string();
+    if model == "broken" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": {"message": "model is loading", "type": "invalid_request_error", "code": null}
+            })),
+        )
+            .into_response();
+    }
+
+    let text = format!("Hello from {}", state.name);
+    if body["stream"] == json!(true) {
+        let (first, second) = text.split_at(6);
+        let events = [
+            json!({"choices": [{"index": 0, "delta": {"role": "assistant", "content": first}}]}),
+            json!({"choices": [{"index": 0, "delta": {"content": second}}]}),
+            json!({"choices": [], "usage": {"prompt_tokens": 12, "completion_tokens": 4, "total_tokens": 16}}),
+        ];
+        let mut stream: String = events
+            .iter()
+            .map(|event| format!("data: {event}\n\n"))
+            .collect();
+        stream.push_str("data: [DONE]\n\n");
+        return ([("content-type", "text/event-stream")], stream).into_response();
+    }
+
+    Json(json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 0,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": text},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 4, "total_tokens": 16}
+    }))
+    .into_response()
+}
+
+/// Start a stub server on a free local port
+async fn start_stub(name: &'static str) -> (SocketAddr, Arc<Mutex<Vec<RecordedRequest>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .with_state(StubState {
+            name,
+            requests: requests.clone(),
+        });
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, requests)
+}
+
+fn create_config(providers: Value) -> Config {
+    Config {
+        anthropic_api_key: None,
+        openai_api_key: None,
+        google_api_key: None,
+        openai_compatible_providers: OpenAICompatibleProvider::list_from_json(
+            &providers.to_string(),
+        )
+        .unwrap(),
+        ..Config::default()
+    }
+}
+
+fn user_message(text: &str) -> Vec<Message> {
+    vec![Message::new(
+        vec![MessageContentBlock::text(text)],
+        Role::User,
+        "test-task-id".to_string(),
+    )]
+}
+
+#[tokio::test]
+async fn test_compatible_provider_sends_key_and_headers() {
+    let (addr, requests) = start_stub("vllm").await;
+    let config = create_config(json!([{
+        "name": "vllm",
+        "baseUrl": format!("http://{addr}/v1"),
+        "apiKey": "secret-key",
+        "headers": {"X-Team": "security"},
+        "models": [{
+            "id": "meta-llama/Llama-3.1-70B-Instruct",
+            "title": "Llama 3.1 70B",
+            "aliases": ["llama-70b"],
+            "capabilities": {"tools": true}
+        }]
+    }]));
+    let service = UnifiedAIService::new(&config);
+
+    let response = service
+        .generate_response(
+            "You are a test",
+            user_message("Hi"),
+            Some("llama-70b".to_string()),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.content[0].as_text(), Some("Hello from vllm"));
+    assert_eq!(response.usage.input_tokens, 12);
+    assert_eq!(response.usage.output_tokens, 4);
+    assert_eq!(response.model, "meta-llama/Llama-3.1-70B-Instruct");
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.headers["authorization"], "Bearer secret-key");
+    assert_eq!(request.headers["x-team"], "security");
+    assert_eq!(request.body["model"], "meta-llama/Llama-3.1-70B-Instruct");
+    assert!(!request.body["tools"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_compatible_providers_side_by_side() {
+    let (vllm_addr, vllm_requests) = start_stub("vllm").await;
+    let (ollama_addr, ollama_requests) = start_stub("ollama").await;
+    let config = create_config(json!([
+        {
+            "name": "vllm",
+            "baseUrl": format!("http://{vllm_addr}/v1"),
+            "apiKey": "secret-key",
+            "models": [{"id": "qwen2.5-72b", "title": "Qwen 2.5 72B"}]
+        },
+        {
+            "name": "ollama",
+            "baseUrl": format!("http://{ollama_addr}/v1/"),
+            "models": [{"id": "llama3.2", "title": "Llama 3.2", "default": true}]
+        }
+    ]));
+    let service = UnifiedAIService::new(&config);
+
+    assert_eq!(service.get_available_providers(), vec!["vllm", "ollama"]);
+    let models = service.list_all_models();
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].provider, "vllm");
+    assert_eq!(models[1].provider, "ollama");
+
+    let vllm = service
+        .generate_response(
+            "",
+            user_message("Hi"),
+            Some("qwen2.5-72b".to_string()),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(vllm.content[0].as_text(), Some("Hello from vllm"));
+
+    // No model named: the default model of the registry is used
+    let ollama = service
+        .generate_response("", user_message("Hi"), None, true, None)
+        .await
+        .unwrap();
+    assert_eq!(ollama.content[0].as_text(), Some("Hello from ollama"));
+    assert_eq!(ollama.model, "llama3.2");
+
+    assert_eq!(vllm_requests.lock().unwrap().len(), 1);
+    let ollama_requests = ollama_requests.lock().unwrap();
+    assert_eq!(ollama_requests.len(), 1);
+    assert!(!ollama_requests[0].headers.contains_key("authorization"));
+    // Models without tool support are asked without tools
+    assert!(ollama_requests[0].body.get("tools").is_none());
+}
+
+#[tokio::test]
+async fn test_compatible_provider_streams_deltas() {
+    let (addr, _) = start_stub("lmstudio").await;
+    let config = create_config(json!([{
+        "name": "lmstudio",
+        "baseUrl": format!("http://{addr}/v1"),
+        "models": [{"id": "mistral-7b", "title": "Mistral 7B"}]
+    }]));
+    let service = UnifiedAIService::new(&config);
+
+    let (deltas, mut receiver) = mpsc::unbounded_channel();
+    let response = service
+        .generate_response_stream(
+            "",
+            user_message("Hi"),
+            Some("mistral-7b".to_string()),
+            false,
+            None,
+            deltas,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.content[0].as_text(), Some("Hello from lmstudio"));
+    assert_eq!(response.usage.output_tokens, 4);
+
+    let mut received = Vec::new();
+    while let Ok(delta) = receiver.try_recv() {
+        received.push(delta);
+    }
+    assert_eq!(
+        received,
+        vec![
+            StreamDelta::Text {
+                index: 0,
+                text: "Hello ".to_string()
+            },
+            StreamDelta::Text {
+                index: 0,
+                text: "from lmstudio".to_string()
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_compatible_provider_errors() {
+    let (addr, _) = start_stub("litellm").await;
+    let config = create_config(json!([{
+        "name": "litellm",
+        "baseUrl": format!("http://{addr}/v1"),
+        "models": [{"id": "broken", "title": "Broken"}]
+    }]));
+    let service = UnifiedAIService::new(&config);
+
+    let error = service
+        .generate_response(
+            "",
+            user_message("Hi"),
+            Some("broken".to_string()),
+            false,
+            None,
+        )
+        .await
+        .unwrap_err();
+    match error {
+        AIError::Api { status, message } => {
+            assert_eq!(status, 400);
+            assert_eq!(message, "model is loading");
+        }
+        other => panic!("Expected API error, got {other:?}"),
+    }
+
+    let error = service
+        .generate_response(
+            "",
+            user_message("Hi"),
+            Some("gpt-4o".to_string()),
+            false,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AIError::Api { status: 401, .. }));
+}
@@ -1,6 +1,7 @@
 use std::{sync::Arc, time::Duration};
 
 use bytebot_agent_rs::{
+    agent::TaskCancellations,
     ai::UnifiedAIService,
     auth::{AuthService, AuthServiceTrait},
     config::Config,
@@ -9,7 +10,7 @@ use bytebot_agent_rs::{
     websocket::WebSocketGateway,
 };
 use bytebot_shared_rs::{
-    types::{Message, Role, Task, TaskPriority, TaskStatus, TaskType},
+    types::{Message, Role, Task, TaskStatus},
     MetricsCollector,
 };
 use serde_json::json;
@@ -20,21 +21,8 @@ fn create_test_task(id: &str) -> Task {
     Task {
         id: id.to_string(),
         description: "Test task".to_string(),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: chrono::Utc::now(),
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: chrono::Utc::now(),
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
-        result: None,
         model: json!({"provider": "test", "model": "test-model"}),
-        user_id: None,
+        ..Default::default()
     }
 }
 
@@ -85,6 +73,7 @@ async fn test_websocket_server_integration() {
             auth_service,
             websocket_gateway: websocket_gateway.clone(),
             metrics: Arc::new(MetricsCollector::new("test-service").unwrap()),
+            task_cancellations: Arc::new(TaskCancellations::new()),
             start_time: chrono::Utc::now(),
         };
 
@@ -6,7 +6,7 @@ use bytebot_agent_rs::{
     server::{create_app, create_app_state},
     websocket::{events::ClientMessage, WebSocketGateway},
 };
-use bytebot_shared_rs::types::{Message, Role, Task, TaskPriority, TaskStatus, TaskType};
+use bytebot_shared_rs::types::{Message, Role, Task};
 use serde_json::json;
 use tokio::{net::TcpListener, time::timeout};
 use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
@@ -56,21 +56,8 @@ fn create_test_task(id: &str) -> Task {
     Task {
         id: id.to_string(),
         description: "WebSocket test task".to_string(),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: chrono::Utc::now(),
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: chrono::Utc::now(),
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
-        result: None,
         model: json!({"provider": "test", "model": "test-model"}),
-        user_id: None,
+        ..Default::default()
     }
 }
 
@@ -15,20 +15,7 @@ use uuid::Uuid;
 // Helper functions for creating test data
 fn create_test_task() -> Task {
     Task {
-        id: Uuid::new_v4().to_string(),
         description: "Test task for benchmarking shared types".to_string(),
-        task_type: TaskType::Immediate,
-        status: TaskStatus::Pending,
-        priority: TaskPriority::Medium,
-        control: Role::Assistant,
-        created_at: Utc::now(),
-        created_by: Role::User,
-        scheduled_for: None,
-        updated_at: Utc::now(),
-        executed_at: None,
-        completed_at: None,
-        queued_at: None,
-        error: None,
         result: Some(json!({
             "output": "Task completed successfully",
             "metrics": {
@@ -43,6 +30,7 @@ fn create_test_task() -> Task {
             "title": "Claude 3 Sonnet"
         }),
         user_id: Some("test-user-id".to_string()),
+        ..Default::default()
     }
 }
 
@@ -96,6 +84,12 @@ fn create_test_create_task_dto() -> CreateTaskDto {
         })),
         user_id: Some("test-user-id".to_string()),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     }
 }
 
@@ -76,6 +76,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "title": "GPT-4"
         })),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     };
 
     println!("CreateTaskDto created");
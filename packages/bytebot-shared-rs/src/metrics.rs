@@ -71,6 +71,17 @@ impl MetricsCollector {
         histogram!("task_duration_seconds", "type" => task_type).record(duration.as_secs_f64());
     }
 
+    /// Update the number of runnable tasks waiting in the queue for a priority
+    pub fn update_task_queue_depth(&self, priority: &str, depth: u64) {
+        let priority = priority.to_string();
+        gauge!("task_queue_depth", "priority" => priority).set(depth as f64);
+    }
+
+    /// Update the number of tasks currently running
+    pub fn update_tasks_running(&self, count: u64) {
+        gauge!("tasks_running").set(count as f64);
+    }
+
     /// Record AI API request
     pub fn record_ai_request(&self, provider: &str, model: &str, duration: Duration, tokens: u64) {
         let provider = provider.to_string();
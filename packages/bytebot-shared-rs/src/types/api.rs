@@ -2,7 +2,13 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use super::task::{Role, TaskPriority, TaskStatus, TaskType};
+use super::{
+    budget::TaskBudget,
+    dependency::TaskDependencyDto,
+    label::validate_labels,
+    retry::RetryPolicy,
+    task::{Role, TaskPriority, TaskStatus, TaskType},
+};
 
 /// File data transfer object for task creation
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
@@ -45,6 +51,48 @@ pub struct CreateTaskDto {
 
     #[validate]
     pub files: Option<Vec<TaskFileDto>>,
+
+    /// Makes the task a recurring definition that spawns a run on every firing
+    #[validate]
+    pub recurrence: Option<RecurrenceDto>,
+
+    /// JSON schema the result submitted by the agent must match
+    #[serde(rename = "resultSchema")]
+    pub result_schema: Option<serde_json::Value>,
+
+    /// How the task is retried after transient failures
+    #[serde(rename = "retryPolicy")]
+    #[validate]
+    pub retry_policy: Option<RetryPolicy>,
+
+    /// Tasks that have to finish before this one runs
+    #[serde(rename = "dependsOn")]
+    #[validate]
+    pub depends_on: Option<Vec<TaskDependencyDto>>,
+
+    /// Limits on the turns, tokens, cost and time the task may use
+    #[validate]
+    pub budget: Option<TaskBudget>,
+
+    /// Free-form `key` or `key=value` labels
+    #[validate(custom = "validate_labels")]
+    pub labels: Option<Vec<String>>,
+}
+
+/// Recurrence rule for a recurring task
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct RecurrenceDto {
+    /// Five-field cron expression, e.g. `0 7 * * MON-FRI`
+    #[validate(length(min = 1, message = "Cron expression cannot be empty"))]
+    pub cron: String,
+
+    /// IANA timezone name, defaults to UTC
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
 }
 
 /// Data transfer object for updating an existing task
@@ -61,6 +109,17 @@ pub struct UpdateTaskDto {
 
     #[serde(rename = "completedAt")]
     pub completed_at: Option<DateTime<Utc>>,
+
+    /// Who is driving the desktop for this task
+    pub control: Option<Role>,
+
+    /// Replaces the budget, e.g. to raise a limit before resuming the task
+    #[validate]
+    pub budget: Option<TaskBudget>,
+
+    /// Replaces all labels of the task
+    #[validate(custom = "validate_labels")]
+    pub labels: Option<Vec<String>>,
 }
 
 /// Data transfer object for adding a message to a task
@@ -70,6 +129,15 @@ pub struct AddTaskMessageDto {
     pub message: String,
 }
 
+/// Data transfer object for forking a task
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ForkTaskDto {
+    /// Last message of the original task to copy into the fork
+    #[serde(rename = "messageId")]
+    #[validate(length(min = 1, message = "Message ID cannot be empty"))]
+    pub message_id: String,
+}
+
 /// Response wrapper for API endpoints
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
@@ -227,6 +295,12 @@ mod tests {
             user_id: None,
             model: None,
             files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
 
         assert!(valid_dto.validate().is_ok());
@@ -240,6 +314,12 @@ mod tests {
             user_id: None,
             model: None,
             files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
 
         assert!(invalid_dto.validate().is_err());
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Rule marking desktop actions that need a human to approve them.
+///
+/// An action matches when both the action kind and the pattern match; an
+/// unset field matches anything.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRule {
+    /// Action kind, the `action` tag of `ComputerAction`, e.g. `write_file`
+    pub action: Option<String>,
+
+    /// Regular expression searched for in the action arguments as JSON
+    pub pattern: Option<String>,
+
+    /// Why actions matching the rule need approval, shown to the reviewer
+    pub reason: Option<String>,
+}
+
+impl ApprovalRule {
+    /// Reason shown to the reviewer
+    pub fn reason(&self) -> String {
+        if let Some(reason) = &self.reason {
+            return reason.clone();
+        }
+
+        match (&self.action, &self.pattern) {
+            (Some(action), Some(pattern)) => {
+                format!("{action} actions matching '{pattern}' require approval")
+            }
+            (Some(action), None) => format!("{action} actions require approval"),
+            (None, Some(pattern)) => format!("Actions matching '{pattern}' require approval"),
+            (None, None) => "All actions require approval".to_string(),
+        }
+    }
+}
+
+/// Set of rules deciding which desktop actions wait for a human approval
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalPolicy {
+    rules: Vec<(ApprovalRule, Option<Regex>)>,
+}
+
+impl ApprovalPolicy {
+    /// Build a policy, compiling the rule patterns
+    pub fn new(rules: Vec<ApprovalRule>) -> Result<Self, String> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let regex = rule
+                    .pattern
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|e| format!("Invalid approval pattern: {e}"))?;
+                Ok((rule, regex))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Build a policy from a JSON array of rules
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let rules =
+            serde_json::from_str(json).map_err(|e| format!("Invalid approval policy: {e}"))?;
+        Self::new(rules)
+    }
+
+    /// First rule requiring approval for the action `name` called with `input`
+    pub fn matching_rule(&self, name: &str, input: &Value) -> Option<&ApprovalRule> {
+        let arguments = input.to_string();
+
+        self.rules
+            .iter()
+            .find(|(rule, regex)| {
+                rule.action.as_deref().map_or(true, |action| action == name)
+                    && regex
+                        .as_ref()
+                        .map_or(true, |regex| regex.is_match(&arguments))
+            })
+            .map(|(rule, _)| rule)
+    }
+
+    /// Whether no action needs approval
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// How a reviewer decided on a pending action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approved,
+    Rejected,
+}
+
+/// Desktop action the agent is waiting to have approved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingApproval {
+    /// ID of the model tool call requesting the action
+    #[serde(rename = "toolUseId")]
+    pub tool_use_id: String,
+
+    /// Action kind, e.g. `write_file`
+    pub action: String,
+
+    /// Action arguments as requested by the model
+    pub input: Value,
+
+    pub reason: String,
+
+    #[serde(rename = "requestedAt")]
+    pub requested_at: DateTime<Utc>,
+
+    /// Set once a reviewer approved or rejected the action
+    pub decision: Option<ApprovalDecision>,
+
+    /// Reviewer comment, passed to the model when the action is rejected
+    pub comment: Option<String>,
+}
+
+/// Data transfer object for approving or rejecting a pending action
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalDecisionDto {
+    pub comment: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_matching_rule() {
+        let policy = ApprovalPolicy::from_json(
+            r#"[
+                {"action": "write_file"},
+                {"action": "type_text", "pattern": "(?i)password", "reason": "Typing a password"},
+                {"pattern": "1Password"}
+            ]"#,
+        )
+        .unwrap();
+
+        let rule = policy
+            .matching_rule("write_file", &json!({"path": "/tmp/a", "data": "YQ=="}))
+            .unwrap();
+        assert_eq!(rule.reason(), "write_file actions require approval");
+
+        let rule = policy
+            .matching_rule("type_text", &json!({"text": "my PASSWORD is"}))
+            .unwrap();
+        assert_eq!(rule.reason(), "Typing a password");
+
+        assert!(policy
+            .matching_rule("application", &json!({"application": "1Password"}))
+            .is_some());
+        assert!(policy
+            .matching_rule("type_text", &json!({"text": "hello"}))
+            .is_none());
+        assert!(policy.matching_rule("screenshot", &Value::Null).is_none());
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        assert!(ApprovalPolicy::from_json(r#"[{"pattern": "("}]"#).is_err());
+        assert!(ApprovalPolicy::from_json(r#"{"action": "write_file"}"#).is_err());
+        assert!(ApprovalPolicy::default().is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::task::TaskStatus;
+
+/// What happens to a task that reaches one of its limits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Fail the task
+    #[default]
+    Fail,
+    /// Hand the task to the user, who can raise the limits and resume it
+    NeedsHelp,
+}
+
+impl BudgetAction {
+    /// Task status a task that exceeded its budget moves to
+    pub fn status(self) -> TaskStatus {
+        match self {
+            BudgetAction::Fail => TaskStatus::Failed,
+            BudgetAction::NeedsHelp => TaskStatus::NeedsHelp,
+        }
+    }
+}
+
+/// Limits on the resources a task may use; unset limits do not apply
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate, PartialEq)]
+pub struct TaskBudget {
+    /// Maximum number of model turns
+    #[serde(rename = "maxTurns")]
+    #[validate(range(min = 1, message = "Max turns must be at least 1"))]
+    pub max_turns: Option<u32>,
+
+    /// Maximum number of input tokens over all model turns
+    #[serde(rename = "maxInputTokens")]
+    pub max_input_tokens: Option<u64>,
+
+    /// Maximum number of output tokens over all model turns
+    #[serde(rename = "maxOutputTokens")]
+    pub max_output_tokens: Option<u64>,
+
+    /// Maximum estimated cost in US dollars
+    #[serde(rename = "maxCostUsd")]
+    #[validate(range(min = 0.0, message = "Max cost cannot be negative"))]
+    pub max_cost_usd: Option<f64>,
+
+    /// Maximum time the task may run, counted from when it started running
+    #[serde(rename = "timeoutSeconds")]
+    #[validate(range(min = 1, message = "Timeout must be at least 1 second"))]
+    pub timeout_seconds: Option<u64>,
+
+    #[serde(rename = "onExceeded", default)]
+    pub on_exceeded: BudgetAction,
+}
+
+impl TaskBudget {
+    /// Describe the first limit reached by `usage` after running for
+    /// `elapsed`, or `None` if the task may take another turn
+    pub fn exceeded(&self, usage: &TaskUsage, elapsed: Duration) -> Option<String> {
+        if let Some(max) = self.max_turns {
+            if usage.turns >= max {
+                return Some(format!(
+                    "Budget exceeded: reached the limit of {max} model turns"
+                ));
+            }
+        }
+        if let Some(max) = self.max_input_tokens {
+            if usage.input_tokens >= max {
+                return Some(format!(
+                    "Budget exceeded: used {} of {max} input tokens",
+                    usage.input_tokens
+                ));
+            }
+        }
+        if let Some(max) = self.max_output_tokens {
+            if usage.output_tokens >= max {
+                return Some(format!(
+                    "Budget exceeded: used {} of {max} output tokens",
+                    usage.output_tokens
+                ));
+            }
+        }
+        if let Some(max) = self.max_cost_usd {
+            if usage.cost_usd >= max {
+                return Some(format!(
+                    "Budget exceeded: estimated cost ${:.4} reached the limit of ${max:.4}",
+                    usage.cost_usd
+                ));
+            }
+        }
+        if let Some(timeout) = self.timeout() {
+            if elapsed >= timeout {
+                return Some(format!(
+                    "Budget exceeded: ran for longer than {} seconds",
+                    timeout.as_secs()
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Wall-clock limit of the task
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_seconds.map(Duration::from_secs)
+    }
+}
+
+/// Resources a task has used so far
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TaskUsage {
+    /// Number of model turns
+    pub turns: u32,
+
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u64,
+
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u64,
+
+    /// Estimated cost in US dollars
+    #[serde(rename = "costUsd")]
+    pub cost_usd: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_exceeded() {
+        let budget = TaskBudget {
+            max_turns: Some(10),
+            max_output_tokens: Some(5_000),
+            max_cost_usd: Some(1.0),
+            timeout_seconds: Some(600),
+            ..TaskBudget::default()
+        };
+        let mut usage = TaskUsage {
+            turns: 3,
+            input_tokens: 90_000,
+            output_tokens: 1_200,
+            cost_usd: 0.25,
+        };
+
+        assert_eq!(budget.exceeded(&usage, Duration::from_secs(60)), None);
+        assert!(budget
+            .exceeded(&usage, Duration::from_secs(600))
+            .unwrap()
+            .contains("600 seconds"));
+
+        usage.cost_usd = 1.5;
+        assert!(budget
+            .exceeded(&usage, Duration::from_secs(60))
+            .unwrap()
+            .contains("estimated cost"));
+
+        usage.turns = 10;
+        assert!(budget
+            .exceeded(&usage, Duration::from_secs(60))
+            .unwrap()
+            .contains("10 model turns"));
+    }
+
+    #[test]
+    fn test_budget_serialization() {
+        let budget: TaskBudget =
+            serde_json::from_str(r#"{"maxTurns": 0, "onExceeded": "needs_help"}"#).unwrap();
+        assert_eq!(budget.on_exceeded, BudgetAction::NeedsHelp);
+        assert_eq!(budget.on_exceeded.status(), TaskStatus::NeedsHelp);
+        assert!(budget.validate().is_err());
+
+        let usage: TaskUsage = serde_json::from_str("{}").unwrap();
+        assert_eq!(usage, TaskUsage::default());
+    }
+}
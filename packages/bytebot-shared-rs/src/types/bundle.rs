@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    message::{Message, Summary},
+    task::Task,
+    user::File,
+};
+
+/// Marker identifying a JSON document as a task bundle
+pub const TASK_BUNDLE_FORMAT: &str = "bytebot-task-bundle";
+
+/// Version of the bundle format written by this build
+pub const TASK_BUNDLE_VERSION: u32 = 1;
+
+/// Portable snapshot of a task with everything needed to restore it in another
+/// deployment. Screenshots travel inside the image blocks of the messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskBundle {
+    pub format: String,
+
+    pub version: u32,
+
+    #[serde(rename = "exportedAt")]
+    pub exported_at: DateTime<Utc>,
+
+    pub task: Task,
+
+    /// Conversation in order, oldest first
+    pub messages: Vec<Message>,
+
+    /// Summaries in order, parents before the summaries built on them
+    pub summaries: Vec<Summary>,
+
+    pub files: Vec<File>,
+}
+
+impl TaskBundle {
+    /// Bundle a task in the current format
+    pub fn new(
+        task: Task,
+        messages: Vec<Message>,
+        summaries: Vec<Summary>,
+        files: Vec<File>,
+    ) -> Self {
+        Self {
+            format: TASK_BUNDLE_FORMAT.to_string(),
+            version: TASK_BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            task,
+            messages,
+            summaries,
+            files,
+        }
+    }
+
+    /// Read a bundle written by this or any earlier version of the format.
+    ///
+    /// When the format changes, bump `TASK_BUNDLE_VERSION` and add a step here
+    /// that converts bundles of the previous version.
+    pub fn from_json(value: Value) -> Result<Self, String> {
+        if value.get("format").and_then(Value::as_str) != Some(TASK_BUNDLE_FORMAT) {
+            return Err("Not a task bundle".to_string());
+        }
+
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| "Task bundle has no version".to_string())?;
+
+        match version {
+            1 => serde_json::from_value(value).map_err(|e| format!("Invalid task bundle: {e}")),
+            _ => Err(format!(
+                "Unsupported task bundle version {version}, this build reads up to {TASK_BUNDLE_VERSION}"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::{message::MessageContentBlock, task::Role};
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let task = Task::new(
+            "Reproduce the login bug".to_string(),
+            json!({"provider": "anthropic", "name": "claude-3-sonnet-20240229"}),
+        );
+        let message = Message::new(
+            vec![MessageContentBlock::text("Open the login page")],
+            Role::User,
+            task.id.clone(),
+        );
+
+        let bundle = TaskBundle::new(task, vec![message], Vec::new(), Vec::new());
+        let restored = TaskBundle::from_json(serde_json::to_value(&bundle).unwrap()).unwrap();
+
+        assert_eq!(restored.version, TASK_BUNDLE_VERSION);
+        assert_eq!(restored.task.id, bundle.task.id);
+        assert_eq!(restored.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_bundle_version_check() {
+        assert!(TaskBundle::from_json(json!({"version": 1})).is_err());
+        assert!(TaskBundle::from_json(json!({"format": TASK_BUNDLE_FORMAT})).is_err());
+
+        let error = TaskBundle::from_json(json!({
+            "format": TASK_BUNDLE_FORMAT,
+            "version": TASK_BUNDLE_VERSION + 1
+        }))
+        .unwrap_err();
+        assert!(error.contains("Unsupported task bundle version"));
+    }
+}
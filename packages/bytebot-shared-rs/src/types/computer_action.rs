@@ -267,6 +267,16 @@ pub enum ComputerAction {
     ReadFile { path: String },
 }
 
+/// Header carrying the ID a `/computer-use` request can be cancelled by
+pub const ACTION_ID_HEADER: &str = "x-action-id";
+
+/// Request to interrupt the `/computer-use` request sent with `action_id`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelComputerActionDto {
+    #[serde(rename = "actionId")]
+    pub action_id: String,
+}
+
 /// Validation error types for computer actions
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
 pub enum ComputerActionValidationError {
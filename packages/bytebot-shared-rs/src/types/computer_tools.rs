@@ -0,0 +1,458 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::computer_action::ComputerAction;
+
+/// Provider-neutral definition of a tool the model can call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the tool input
+    pub input_schema: Value,
+}
+
+impl ToolDefinition {
+    pub fn new(name: &str, description: &str, input_schema: Value) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            input_schema,
+        }
+    }
+}
+
+fn object_schema(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn coordinates_schema(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": {
+            "x": { "type": "integer", "description": "The x-coordinate in pixels" },
+            "y": { "type": "integer", "description": "The y-coordinate in pixels" }
+        },
+        "required": ["x", "y"]
+    })
+}
+
+fn path_schema(description: &str) -> Value {
+    json!({
+        "type": "array",
+        "description": description,
+        "items": coordinates_schema("A point on the path")
+    })
+}
+
+fn keys_schema(description: &str) -> Value {
+    json!({
+        "type": "array",
+        "description": description,
+        "items": { "type": "string" }
+    })
+}
+
+fn hold_keys_schema() -> Value {
+    keys_schema("Optional keys to hold down during the action, e.g. [\"Shift\"]")
+}
+
+fn button_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["left", "right", "middle"],
+        "description": "The mouse button"
+    })
+}
+
+fn press_schema(description: &str) -> Value {
+    json!({
+        "type": "string",
+        "enum": ["down", "up"],
+        "description": description
+    })
+}
+
+fn delay_schema() -> Value {
+    json!({
+        "type": "integer",
+        "minimum": 0,
+        "description": "Optional delay in milliseconds between key presses"
+    })
+}
+
+impl ComputerAction {
+    /// Tool name of this action, which is also its `action` tag on the wire
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComputerAction::MoveMouse { .. } => "move_mouse",
+            ComputerAction::TraceMouse { .. } => "trace_mouse",
+            ComputerAction::ClickMouse { .. } => "click_mouse",
+            ComputerAction::PressMouse { .. } => "press_mouse",
+            ComputerAction::DragMouse { .. } => "drag_mouse",
+            ComputerAction::Scroll { .. } => "scroll",
+            ComputerAction::TypeKeys { .. } => "type_keys",
+            ComputerAction::PasteText { .. } => "paste_text",
+            ComputerAction::PressKeys { .. } => "press_keys",
+            ComputerAction::TypeText { .. } => "type_text",
+            ComputerAction::Wait { .. } => "wait",
+            ComputerAction::Screenshot => "screenshot",
+            ComputerAction::CursorPosition => "cursor_position",
+            ComputerAction::Application { .. } => "application",
+            ComputerAction::WriteFile { .. } => "write_file",
+            ComputerAction::ReadFile { .. } => "read_file",
+        }
+    }
+
+    /// Canonical tool definitions for every computer action.
+    ///
+    /// A tool call's input is the action payload without the `action` tag, so
+    /// it can be sent to the daemon's `/computer-use` endpoint once the tool
+    /// name is added back as the tag.
+    pub fn tool_definitions() -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::new(
+                "move_mouse",
+                "Move the mouse cursor to specific coordinates on the screen",
+                object_schema(
+                    json!({ "coordinates": coordinates_schema("The coordinates to move the mouse to") }),
+                    &["coordinates"],
+                ),
+            ),
+            ToolDefinition::new(
+                "trace_mouse",
+                "Move the mouse cursor along a path of coordinates",
+                object_schema(
+                    json!({
+                        "path": path_schema("The points to move the mouse through, in order"),
+                        "hold_keys": hold_keys_schema()
+                    }),
+                    &["path"],
+                ),
+            ),
+            ToolDefinition::new(
+                "click_mouse",
+                "Click the mouse at specific coordinates or at the current position",
+                object_schema(
+                    json!({
+                        "coordinates": coordinates_schema(
+                            "Optional coordinates for the click. If not provided, clicks at the current position"
+                        ),
+                        "button": button_schema(),
+                        "hold_keys": hold_keys_schema(),
+                        "clickCount": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Number of clicks to perform (e.g. 2 for a double-click)"
+                        }
+                    }),
+                    &["button", "clickCount"],
+                ),
+            ),
+            ToolDefinition::new(
+                "press_mouse",
+                "Press or release a mouse button at specific coordinates or at the current position",
+                object_schema(
+                    json!({
+                        "coordinates": coordinates_schema(
+                            "Optional coordinates to move to first. If not provided, uses the current position"
+                        ),
+                        "button": button_schema(),
+                        "press": press_schema("Whether to press the button down or release it")
+                    }),
+                    &["button", "press"],
+                ),
+            ),
+            ToolDefinition::new(
+                "drag_mouse",
+                "Drag the mouse along a path while holding a button",
+                object_schema(
+                    json!({
+                        "path": path_schema("The points to drag through, starting where the button is pressed"),
+                        "button": button_schema(),
+                        "hold_keys": hold_keys_schema()
+                    }),
+                    &["path", "button"],
+                ),
+            ),
+            ToolDefinition::new(
+                "scroll",
+                "Scroll in a specific direction, optionally at given coordinates",
+                object_schema(
+                    json!({
+                        "coordinates": coordinates_schema(
+                            "Optional coordinates for where the scroll should occur"
+                        ),
+                        "direction": {
+                            "type": "string",
+                            "enum": ["up", "down", "left", "right"],
+                            "description": "The direction to scroll"
+                        },
+                        "scrollCount": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "The number of times to scroll"
+                        },
+                        "hold_keys": hold_keys_schema()
+                    }),
+                    &["direction", "scrollCount"],
+                ),
+            ),
+            ToolDefinition::new(
+                "type_keys",
+                "Press and release a sequence of keys, e.g. [\"Control\", \"c\"] for a shortcut",
+                object_schema(
+                    json!({
+                        "keys": keys_schema("Key names to type in order"),
+                        "delay": delay_schema()
+                    }),
+                    &["keys"],
+                ),
+            ),
+            ToolDefinition::new(
+                "paste_text",
+                "Paste text at the current cursor position via the clipboard",
+                object_schema(
+                    json!({ "text": { "type": "string", "description": "The text to paste" } }),
+                    &["text"],
+                ),
+            ),
+            ToolDefinition::new(
+                "press_keys",
+                "Press or release specific keys",
+                object_schema(
+                    json!({
+                        "keys": keys_schema("Key names to press or release"),
+                        "press": press_schema("Whether to press the keys down or release them")
+                    }),
+                    &["keys", "press"],
+                ),
+            ),
+            ToolDefinition::new(
+                "type_text",
+                "Type text at the current cursor position",
+                object_schema(
+                    json!({
+                        "text": { "type": "string", "description": "The text to type" },
+                        "delay": delay_schema(),
+                        "sensitive": {
+                            "type": "boolean",
+                            "description": "Set for passwords and other secrets so the text is not logged"
+                        }
+                    }),
+                    &["text"],
+                ),
+            ),
+            ToolDefinition::new(
+                "wait",
+                "Wait for a specified duration in milliseconds",
+                object_schema(
+                    json!({
+                        "duration": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "The duration to wait in milliseconds"
+                        }
+                    }),
+                    &["duration"],
+                ),
+            ),
+            ToolDefinition::new(
+                "screenshot",
+                "Take a screenshot of the current screen",
+                object_schema(json!({}), &[]),
+            ),
+            ToolDefinition::new(
+                "cursor_position",
+                "Get the current cursor position",
+                object_schema(json!({}), &[]),
+            ),
+            ToolDefinition::new(
+                "application",
+                "Switch to or open a specific application",
+                object_schema(
+                    json!({
+                        "application": {
+                            "type": "string",
+                            "enum": [
+                                "firefox",
+                                "1password",
+                                "thunderbird",
+                                "vscode",
+                                "terminal",
+                                "desktop",
+                                "directory"
+                            ],
+                            "description": "The application to open or switch to"
+                        }
+                    }),
+                    &["application"],
+                ),
+            ),
+            ToolDefinition::new(
+                "write_file",
+                "Write data to a file",
+                object_schema(
+                    json!({
+                        "path": { "type": "string", "description": "The file path to write to" },
+                        "data": { "type": "string", "description": "Base64 encoded file data" }
+                    }),
+                    &["path", "data"],
+                ),
+            ),
+            ToolDefinition::new(
+                "read_file",
+                "Read the contents of a file",
+                object_schema(
+                    json!({ "path": { "type": "string", "description": "The file path to read from" } }),
+                    &["path"],
+                ),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::types::computer_action::{Application, Button, Coordinates, Press, ScrollDirection};
+
+    /// One instance of every action with all optional fields set
+    fn sample_actions() -> Vec<ComputerAction> {
+        let point = Coordinates { x: 10, y: 20 };
+        let keys = Some(vec!["Shift".to_string()]);
+
+        vec![
+            ComputerAction::MoveMouse { coordinates: point },
+            ComputerAction::TraceMouse {
+                path: vec![point],
+                hold_keys: keys.clone(),
+            },
+            ComputerAction::ClickMouse {
+                coordinates: Some(point),
+                button: Button::Left,
+                hold_keys: keys.clone(),
+                click_count: 2,
+            },
+            ComputerAction::PressMouse {
+                coordinates: Some(point),
+                button: Button::Right,
+                press: Press::Down,
+            },
+            ComputerAction::DragMouse {
+                path: vec![point],
+                button: Button::Left,
+                hold_keys: keys.clone(),
+            },
+            ComputerAction::Scroll {
+                coordinates: Some(point),
+                direction: ScrollDirection::Down,
+                scroll_count: 3,
+                hold_keys: keys,
+            },
+            ComputerAction::TypeKeys {
+                keys: vec!["Control".to_string(), "c".to_string()],
+                delay: Some(10),
+            },
+            ComputerAction::PasteText {
+                text: "hello".to_string(),
+            },
+            ComputerAction::PressKeys {
+                keys: vec!["Alt".to_string()],
+                press: Press::Up,
+            },
+            ComputerAction::TypeText {
+                text: "hello".to_string(),
+                delay: Some(10),
+                sensitive: Some(true),
+            },
+            ComputerAction::Wait { duration: 500 },
+            ComputerAction::Screenshot,
+            ComputerAction::CursorPosition,
+            ComputerAction::Application {
+                application: Application::Firefox,
+            },
+            ComputerAction::WriteFile {
+                path: "/tmp/a.txt".to_string(),
+                data: "aGVsbG8=".to_string(),
+            },
+            ComputerAction::ReadFile {
+                path: "/tmp/a.txt".to_string(),
+            },
+        ]
+    }
+
+    fn schema_keys(schema: &Value, field: &str) -> HashSet<String> {
+        match &schema[field] {
+            Value::Object(map) => map.keys().cloned().collect(),
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect(),
+            _ => HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_tool_names_are_unique() {
+        let definitions = ComputerAction::tool_definitions();
+        let names: HashSet<_> = definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names.len(), definitions.len());
+    }
+
+    #[test]
+    fn test_every_action_has_a_matching_definition() {
+        let definitions = ComputerAction::tool_definitions();
+        let samples = sample_actions();
+        assert_eq!(samples.len(), definitions.len());
+
+        for action in samples {
+            let definition = definitions
+                .iter()
+                .find(|d| d.name == action.name())
+                .unwrap_or_else(|| panic!("No tool definition for {}", action.name()));
+
+            let mut payload = serde_json::to_value(&action).unwrap();
+            let payload = payload.as_object_mut().unwrap();
+            assert_eq!(payload.remove("action"), Some(json!(action.name())));
+
+            let fields: HashSet<String> = payload.keys().cloned().collect();
+            let properties = schema_keys(&definition.input_schema, "properties");
+            let required = schema_keys(&definition.input_schema, "required");
+
+            assert_eq!(fields, properties, "schema mismatch for {}", action.name());
+            assert!(
+                required.is_subset(&fields),
+                "unknown required field for {}",
+                action.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_required_fields_are_enough_to_deserialize() {
+        for action in sample_actions() {
+            let definition = ComputerAction::tool_definitions()
+                .into_iter()
+                .find(|d| d.name == action.name())
+                .unwrap();
+            let required = schema_keys(&definition.input_schema, "required");
+
+            let mut payload = serde_json::to_value(&action).unwrap();
+            payload
+                .as_object_mut()
+                .unwrap()
+                .retain(|key, _| key == "action" || required.contains(key));
+
+            let parsed: ComputerAction = serde_json::from_value(payload).unwrap();
+            assert_eq!(parsed.name(), action.name());
+        }
+    }
+}
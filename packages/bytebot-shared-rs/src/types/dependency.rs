@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::task::TaskStatus;
+
+/// Terminal state a dependency has to reach before its dependent may run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyCondition {
+    #[default]
+    OnSuccess,
+    OnFailure,
+    Always,
+}
+
+impl DependencyCondition {
+    /// Whether a dependency in `status` satisfies the condition.
+    ///
+    /// Returns `None` while the dependency has not finished, and `Some(false)`
+    /// once it finished in a state that can never satisfy the condition.
+    pub fn is_satisfied_by(self, status: TaskStatus) -> Option<bool> {
+        match (self, status) {
+            (_, TaskStatus::Pending | TaskStatus::Running)
+            | (_, TaskStatus::NeedsHelp | TaskStatus::NeedsReview) => None,
+            (Self::OnSuccess, status) => Some(status == TaskStatus::Completed),
+            (Self::OnFailure, status) => Some(status == TaskStatus::Failed),
+            (Self::Always, _) => Some(true),
+        }
+    }
+}
+
+impl std::str::FromStr for DependencyCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "on_success" => Ok(Self::OnSuccess),
+            "on_failure" => Ok(Self::OnFailure),
+            "always" => Ok(Self::Always),
+            _ => Err(format!("Invalid DependencyCondition: {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for DependencyCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OnSuccess => write!(f, "on_success"),
+            Self::OnFailure => write!(f, "on_failure"),
+            Self::Always => write!(f, "always"),
+        }
+    }
+}
+
+/// Dependency declared when creating a task
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct TaskDependencyDto {
+    /// Task that has to finish first
+    #[serde(rename = "taskId")]
+    #[validate(length(min = 1, message = "Dependency task ID cannot be empty"))]
+    pub task_id: String,
+
+    #[serde(default)]
+    pub condition: DependencyCondition,
+}
+
+/// Edge of a task graph: `task_id` runs once `depends_on_id` meets `condition`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskDependency {
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+
+    #[serde(rename = "dependsOnId")]
+    pub depends_on_id: String,
+
+    pub condition: DependencyCondition,
+}
+
+/// Task in a dependency graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskGraphNode {
+    pub id: String,
+    pub description: String,
+    pub status: TaskStatus,
+
+    /// Pending because some dependency has not finished yet
+    pub blocked: bool,
+}
+
+/// Tasks connected to a task through dependencies, in either direction
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskGraph {
+    pub nodes: Vec<TaskGraphNode>,
+    pub edges: Vec<TaskDependency>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_condition_satisfaction() {
+        use DependencyCondition::*;
+
+        assert_eq!(OnSuccess.is_satisfied_by(TaskStatus::Running), None);
+        assert_eq!(OnSuccess.is_satisfied_by(TaskStatus::Completed), Some(true));
+        assert_eq!(
+            OnSuccess.is_satisfied_by(TaskStatus::Cancelled),
+            Some(false)
+        );
+        assert_eq!(OnFailure.is_satisfied_by(TaskStatus::Failed), Some(true));
+        assert_eq!(
+            OnFailure.is_satisfied_by(TaskStatus::Completed),
+            Some(false)
+        );
+        assert_eq!(Always.is_satisfied_by(TaskStatus::Cancelled), Some(true));
+        assert_eq!(Always.is_satisfied_by(TaskStatus::NeedsHelp), None);
+    }
+
+    #[test]
+    fn test_dependency_dto_defaults_to_on_success() {
+        let dto: TaskDependencyDto = serde_json::from_str(r#"{"taskId": "task-1"}"#).unwrap();
+        assert_eq!(dto.condition, DependencyCondition::OnSuccess);
+
+        let dto: TaskDependencyDto =
+            serde_json::from_str(r#"{"taskId": "task-1", "condition": "always"}"#).unwrap();
+        assert_eq!(dto.condition, DependencyCondition::Always);
+        assert_eq!(
+            dto.condition.to_string().parse::<DependencyCondition>(),
+            Ok(DependencyCondition::Always)
+        );
+    }
+}
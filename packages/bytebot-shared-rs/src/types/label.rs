@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use validator::ValidationError;
+
+/// Maximum number of labels on a task
+pub const MAX_LABELS: usize = 50;
+
+/// Maximum length of a label, key and value included
+pub const MAX_LABEL_LENGTH: usize = 200;
+
+/// Split a `key` or `key=value` label into its key and value
+pub fn parse_label(label: &str) -> (&str, Option<&str>) {
+    match label.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (label, None),
+    }
+}
+
+/// Check that a label is `key` or `key=value`, with a key made of letters,
+/// digits, `_`, `-`, `.`, `/` and `:`
+pub fn check_label(label: &str) -> Result<(), String> {
+    let key_regex = Regex::new(r"^[A-Za-z0-9_.:/-]+$").unwrap();
+
+    if label.len() > MAX_LABEL_LENGTH {
+        return Err(format!(
+            "Label '{label}' is longer than {MAX_LABEL_LENGTH} characters"
+        ));
+    }
+
+    let (key, value) = parse_label(label);
+    if !key_regex.is_match(key) {
+        return Err(format!("Invalid label key in '{label}'"));
+    }
+    if value.is_some_and(str::is_empty) {
+        return Err(format!("Label '{label}' has an empty value"));
+    }
+
+    Ok(())
+}
+
+/// Validator for the labels of a task: valid, unique and not too many
+pub fn validate_labels(labels: &[String]) -> Result<(), ValidationError> {
+    let invalid = |message: String| {
+        let mut error = ValidationError::new("labels");
+        error.message = Some(message.into());
+        error
+    };
+
+    if labels.len() > MAX_LABELS {
+        return Err(invalid(format!(
+            "A task can have at most {MAX_LABELS} labels"
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    for label in labels {
+        check_label(label).map_err(invalid)?;
+        if !seen.insert(label) {
+            return Err(invalid(format!("Duplicate label '{label}'")));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_label() {
+        assert_eq!(parse_label("urgent"), ("urgent", None));
+        assert_eq!(parse_label("customer=acme"), ("customer", Some("acme")));
+        assert_eq!(parse_label("query=a=b"), ("query", Some("a=b")));
+    }
+
+    #[test]
+    fn test_validate_labels() {
+        let labels = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        assert!(validate_labels(&labels(&[
+            "urgent",
+            "project=billing",
+            "team/ops=night shift"
+        ]))
+        .is_ok());
+        assert!(validate_labels(&labels(&["has space"])).is_err());
+        assert!(validate_labels(&labels(&["=value"])).is_err());
+        assert!(validate_labels(&labels(&["project="])).is_err());
+        assert!(validate_labels(&labels(&["urgent", "urgent"])).is_err());
+    }
+}
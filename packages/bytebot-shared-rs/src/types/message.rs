@@ -61,6 +61,25 @@ pub struct DocumentSource {
     pub data: String, // Base64 encoded document data
 }
 
+impl DocumentSource {
+    /// Decoded contents of a plain-text document, or `None` for binary formats
+    pub fn decode_text(&self) -> Option<String> {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let is_text = self.media_type.starts_with("text/")
+            || matches!(
+                self.media_type.as_str(),
+                "application/json" | "application/xml" | "application/x-yaml"
+            );
+        if !is_text {
+            return None;
+        }
+
+        let bytes = general_purpose::STANDARD.decode(&self.data).ok()?;
+        String::from_utf8(bytes).ok()
+    }
+}
+
 /// Document content block
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentContentBlock {
@@ -187,6 +206,15 @@ impl MessageContentBlock {
         }
     }
 
+    /// Create a tool result content block reporting a failed tool call
+    pub fn tool_error(tool_use_id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::ToolResult {
+            tool_use_id: tool_use_id.into(),
+            content: vec![Self::text(message)],
+            is_error: Some(true),
+        }
+    }
+
     /// Get the content type of this block
     pub fn content_type(&self) -> MessageContentType {
         match self {
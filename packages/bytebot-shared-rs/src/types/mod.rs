@@ -1,11 +1,37 @@
 pub mod api;
+pub mod approval;
+pub mod budget;
+pub mod bundle;
 pub mod computer_action;
+pub mod computer_tools;
+pub mod dependency;
+pub mod label;
 pub mod message;
+pub mod replay;
+pub mod retry;
+pub mod search;
 pub mod task;
+pub mod task_tools;
+pub mod template;
+pub mod usage;
 pub mod user;
+pub mod view;
 
 pub use api::*;
+pub use approval::*;
+pub use budget::*;
+pub use bundle::*;
 pub use computer_action::*;
+pub use computer_tools::*;
+pub use dependency::*;
+pub use label::*;
 pub use message::*;
+pub use replay::*;
+pub use retry::*;
+pub use search::*;
 pub use task::*;
+pub use task_tools::*;
+pub use template::*;
+pub use usage::*;
 pub use user::*;
+pub use view::*;
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use validator::Validate;
+
+/// State of a replay run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStatus {
+    Running,
+    /// Every step ran and every compared screenshot matched
+    Completed,
+    /// Every step ran but at least one screenshot differed from the recording
+    Diverged,
+    /// A step failed or the desktop could not be reached
+    Failed,
+}
+
+impl std::str::FromStr for ReplayStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(Self::Running),
+            "completed" => Ok(Self::Completed),
+            "diverged" => Ok(Self::Diverged),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("Invalid ReplayStatus: {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for ReplayStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Running => write!(f, "running"),
+            Self::Completed => write!(f, "completed"),
+            Self::Diverged => write!(f, "diverged"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Outcome of one replayed tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStepStatus {
+    Succeeded,
+    /// The action ran but its screenshot differs from the recorded one
+    Diverged,
+    Failed,
+    /// Not a desktop action, or the action already failed in the recording
+    Skipped,
+}
+
+/// Options of a replay run
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct ReplayOptions {
+    /// Compare the screenshots taken during the replay with the recorded ones
+    #[serde(rename = "compareScreenshots", default)]
+    pub compare_screenshots: bool,
+
+    /// Share of matching pixels below which a screenshot counts as diverged
+    #[serde(
+        rename = "similarityThreshold",
+        default = "default_similarity_threshold"
+    )]
+    #[validate(range(
+        min = 0.0,
+        max = 1.0,
+        message = "Similarity threshold must be between 0 and 1"
+    ))]
+    pub similarity_threshold: f64,
+
+    /// Longest pause kept between two steps; the recorded pauses include the
+    /// time the model spent thinking
+    #[serde(rename = "maxDelayMs", default = "default_max_delay_ms")]
+    #[validate(range(max = 600000, message = "Max delay must be at most 10 minutes"))]
+    pub max_delay_ms: u64,
+
+    /// Stop at the first diverged screenshot instead of running to the end
+    #[serde(rename = "stopOnDivergence", default)]
+    pub stop_on_divergence: bool,
+}
+
+fn default_similarity_threshold() -> f64 {
+    0.98
+}
+
+fn default_max_delay_ms() -> u64 {
+    10_000
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            compare_screenshots: false,
+            similarity_threshold: default_similarity_threshold(),
+            max_delay_ms: default_max_delay_ms(),
+            stop_on_divergence: false,
+        }
+    }
+}
+
+/// Result of one recorded tool call in a replay
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplayStep {
+    /// Position of the tool call in the recording, from 0
+    pub index: usize,
+
+    #[serde(rename = "toolUseId")]
+    pub tool_use_id: String,
+
+    pub action: String,
+
+    pub input: Value,
+
+    pub status: ReplayStepStatus,
+
+    /// Share of matching pixels against the recorded screenshot, when compared
+    pub similarity: Option<f64>,
+
+    /// Pause before the step, following the recording
+    #[serde(rename = "delayMs")]
+    pub delay_ms: u64,
+
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+
+    pub error: Option<String>,
+}
+
+/// Replay of the desktop actions recorded in a finished task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskReplay {
+    pub id: String,
+
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+
+    pub status: ReplayStatus,
+
+    pub options: ReplayOptions,
+
+    pub steps: Vec<ReplayStep>,
+
+    pub error: Option<String>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_replay_options_defaults() {
+        let options: ReplayOptions = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(options, ReplayOptions::default());
+
+        let options: ReplayOptions =
+            serde_json::from_value(json!({"similarityThreshold": 1.5})).unwrap();
+        assert!(options.validate().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Cause of a failed task attempt, used to decide whether it is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The model provider rejected the request with a rate limit
+    RateLimit,
+    /// The model provider answered with a server error, e.g. 500 or 529
+    ProviderError,
+    /// The model provider could not be reached
+    ProviderUnavailable,
+    /// The desktop daemon could not be reached
+    DesktopUnavailable,
+    /// Anything else, such as exceeding the iteration limit
+    Other,
+}
+
+impl std::str::FromStr for FailureKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rate_limit" => Ok(Self::RateLimit),
+            "provider_error" => Ok(Self::ProviderError),
+            "provider_unavailable" => Ok(Self::ProviderUnavailable),
+            "desktop_unavailable" => Ok(Self::DesktopUnavailable),
+            "other" => Ok(Self::Other),
+            _ => Err(format!("Invalid FailureKind: {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RateLimit => write!(f, "rate_limit"),
+            Self::ProviderError => write!(f, "provider_error"),
+            Self::ProviderUnavailable => write!(f, "provider_unavailable"),
+            Self::DesktopUnavailable => write!(f, "desktop_unavailable"),
+            Self::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// How a task is retried after a transient failure.
+///
+/// Attempt `n` that fails waits `backoffSeconds * backoffMultiplier^(n - 1)`,
+/// capped at `maxBackoffSeconds`, before the task is picked up again.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    #[serde(rename = "maxAttempts")]
+    #[validate(range(min = 1, max = 20, message = "Max attempts must be between 1 and 20"))]
+    pub max_attempts: u32,
+
+    #[serde(rename = "backoffSeconds")]
+    #[validate(range(max = 86400, message = "Backoff must be at most a day"))]
+    pub backoff_seconds: u64,
+
+    #[serde(rename = "backoffMultiplier")]
+    #[validate(range(
+        min = 1.0,
+        max = 10.0,
+        message = "Backoff multiplier must be between 1 and 10"
+    ))]
+    pub backoff_multiplier: f64,
+
+    #[serde(rename = "maxBackoffSeconds")]
+    #[validate(range(max = 86400, message = "Max backoff must be at most a day"))]
+    pub max_backoff_seconds: u64,
+
+    /// Failure kinds that are retried; any other failure fails the task
+    #[serde(rename = "retryOn")]
+    pub retry_on: Vec<FailureKind>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_seconds: 30,
+            backoff_multiplier: 2.0,
+            max_backoff_seconds: 600,
+            retry_on: vec![
+                FailureKind::RateLimit,
+                FailureKind::ProviderError,
+                FailureKind::ProviderUnavailable,
+                FailureKind::DesktopUnavailable,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether attempt number `attempt` failing with `kind` gets another attempt
+    pub fn should_retry(&self, kind: FailureKind, attempt: i32) -> bool {
+        self.retry_on.contains(&kind) && attempt < self.max_attempts as i32
+    }
+
+    /// Delay before retrying after attempt number `attempt` failed
+    pub fn backoff(&self, attempt: i32) -> Duration {
+        let exponent = attempt.saturating_sub(1).max(0);
+        let seconds = self.backoff_seconds as f64 * self.backoff_multiplier.powi(exponent);
+        Duration::from_secs_f64(seconds.min(self.max_backoff_seconds as f64))
+    }
+}
+
+/// Record of a failed attempt at running a task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskAttempt {
+    pub id: String,
+
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+
+    /// Attempt number, starting at 1
+    pub attempt: i32,
+
+    pub error: String,
+
+    #[serde(rename = "failureKind")]
+    pub failure_kind: FailureKind,
+
+    /// When the next attempt was scheduled, or `None` if the task failed for good
+    #[serde(rename = "retryAt")]
+    pub retry_at: Option<DateTime<Utc>>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.should_retry(FailureKind::ProviderError, 1));
+        assert!(policy.should_retry(FailureKind::RateLimit, 2));
+        assert!(!policy.should_retry(FailureKind::RateLimit, 3));
+        assert!(!policy.should_retry(FailureKind::Other, 1));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            backoff_seconds: 10,
+            backoff_multiplier: 3.0,
+            max_backoff_seconds: 60,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(10));
+        assert_eq!(policy.backoff(2), Duration::from_secs(30));
+        assert_eq!(policy.backoff(3), Duration::from_secs(60));
+        assert_eq!(policy.backoff(10), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_policy_serialization() {
+        let policy: RetryPolicy = serde_json::from_str(
+            r#"{"maxAttempts": 5, "backoffSeconds": 5, "backoffMultiplier": 1.5,
+                "maxBackoffSeconds": 120, "retryOn": ["rate_limit", "desktop_unavailable"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(
+            policy.retry_on,
+            vec![FailureKind::RateLimit, FailureKind::DesktopUnavailable]
+        );
+        assert!(policy.validate().is_ok());
+
+        let invalid = RetryPolicy {
+            max_attempts: 0,
+            ..RetryPolicy::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_failure_kind_round_trip() {
+        for kind in [
+            FailureKind::RateLimit,
+            FailureKind::ProviderError,
+            FailureKind::ProviderUnavailable,
+            FailureKind::DesktopUnavailable,
+            FailureKind::Other,
+        ] {
+            assert_eq!(kind.to_string().parse::<FailureKind>(), Ok(kind));
+            assert_eq!(
+                serde_json::to_value(kind).unwrap(),
+                serde_json::Value::String(kind.to_string())
+            );
+        }
+    }
+}
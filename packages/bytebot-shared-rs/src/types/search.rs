@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use super::task::Task;
+
+/// Part of a task a search match was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSource {
+    Description,
+    Result,
+    Message,
+}
+
+impl std::str::FromStr for SearchSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "description" => Ok(Self::Description),
+            "result" => Ok(Self::Result),
+            "message" => Ok(Self::Message),
+            _ => Err(format!("Invalid SearchSource: {s}")),
+        }
+    }
+}
+
+/// Excerpt around a search match, with matched terms wrapped in `<mark>` tags
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchHighlight {
+    pub source: SearchSource,
+
+    /// Message the excerpt was taken from, for message matches
+    #[serde(rename = "messageId")]
+    pub message_id: Option<String>,
+
+    pub snippet: String,
+}
+
+/// Task matching a search, with the excerpts that matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub task: Task,
+
+    /// Relevance of the best match in the task; higher is better
+    pub rank: f32,
+
+    pub highlights: Vec<SearchHighlight>,
+}
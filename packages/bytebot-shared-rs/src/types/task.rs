@@ -1,13 +1,46 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::Type;
+use sqlx::{
+    postgres::{PgTypeInfo, Postgres},
+    Decode, Encode, Type,
+};
 use uuid::Uuid;
 use validator::Validate;
 
-/// Task status enum matching Prisma schema
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
-#[sqlx(type_name = "TaskStatus", rename_all = "UPPERCASE")]
-#[serde(rename_all = "UPPERCASE")]
+use super::{
+    approval::PendingApproval,
+    budget::{TaskBudget, TaskUsage},
+    retry::RetryPolicy,
+};
+
+/// Implement `sqlx::Type` for an enum stored in the Postgres enum type of
+/// the same name.
+///
+/// The Prisma schema names its enum types in mixed case, so the name has to be
+/// quoted when sqlx looks the type up for a bind parameter, while columns
+/// report it unquoted.
+macro_rules! postgres_enum {
+    ($name:ident) => {
+        impl Type<Postgres> for $name {
+            fn type_info() -> PgTypeInfo {
+                PgTypeInfo::with_name(concat!("\"", stringify!($name), "\""))
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                *ty == PgTypeInfo::with_name(stringify!($name))
+            }
+        }
+    };
+}
+
+/// Task status enum matching Prisma schema.
+///
+/// Serde, sqlx, `Display` and `FromStr` all use the Postgres labels
+/// (`NEEDS_HELP`, not `NEEDSHELP`), so a status can be bound to and read from
+/// a `"TaskStatus"` column directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TaskStatus {
     Pending,
     Running,
@@ -18,6 +51,8 @@ pub enum TaskStatus {
     Failed,
 }
 
+postgres_enum!(TaskStatus);
+
 impl Default for TaskStatus {
     fn default() -> Self {
         Self::Pending
@@ -31,8 +66,8 @@ impl std::str::FromStr for TaskStatus {
         match s.to_uppercase().as_str() {
             "PENDING" => Ok(Self::Pending),
             "RUNNING" => Ok(Self::Running),
-            "NEEDSHELP" => Ok(Self::NeedsHelp),
-            "NEEDSREVIEW" => Ok(Self::NeedsReview),
+            "NEEDS_HELP" => Ok(Self::NeedsHelp),
+            "NEEDS_REVIEW" => Ok(Self::NeedsReview),
             "COMPLETED" => Ok(Self::Completed),
             "CANCELLED" => Ok(Self::Cancelled),
             "FAILED" => Ok(Self::Failed),
@@ -46,8 +81,8 @@ impl std::fmt::Display for TaskStatus {
         match self {
             Self::Pending => write!(f, "PENDING"),
             Self::Running => write!(f, "RUNNING"),
-            Self::NeedsHelp => write!(f, "NEEDSHELP"),
-            Self::NeedsReview => write!(f, "NEEDSREVIEW"),
+            Self::NeedsHelp => write!(f, "NEEDS_HELP"),
+            Self::NeedsReview => write!(f, "NEEDS_REVIEW"),
             Self::Completed => write!(f, "COMPLETED"),
             Self::Cancelled => write!(f, "CANCELLED"),
             Self::Failed => write!(f, "FAILED"),
@@ -56,9 +91,9 @@ impl std::fmt::Display for TaskStatus {
 }
 
 /// Task priority enum matching Prisma schema
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
-#[sqlx(type_name = "TaskPriority", rename_all = "UPPERCASE")]
-#[serde(rename_all = "UPPERCASE")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TaskPriority {
     Low,
     Medium,
@@ -66,6 +101,8 @@ pub enum TaskPriority {
     Urgent,
 }
 
+postgres_enum!(TaskPriority);
+
 impl Default for TaskPriority {
     fn default() -> Self {
         Self::Medium
@@ -98,14 +135,16 @@ impl std::fmt::Display for TaskPriority {
 }
 
 /// Role enum matching Prisma schema
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
-#[sqlx(type_name = "Role", rename_all = "UPPERCASE")]
-#[serde(rename_all = "UPPERCASE")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Role {
     User,
     Assistant,
 }
 
+postgres_enum!(Role);
+
 impl Default for Role {
     fn default() -> Self {
         Self::Assistant
@@ -134,14 +173,16 @@ impl std::fmt::Display for Role {
 }
 
 /// Task type enum matching Prisma schema
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
-#[sqlx(type_name = "TaskType", rename_all = "UPPERCASE")]
-#[serde(rename_all = "UPPERCASE")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TaskType {
     Immediate,
     Scheduled,
 }
 
+postgres_enum!(TaskType);
+
 impl Default for TaskType {
     fn default() -> Self {
         Self::Immediate
@@ -212,12 +253,78 @@ pub struct Task {
     pub error: Option<String>,
     pub result: Option<serde_json::Value>,
 
-    /// Model configuration as JSON
+    /// Model configuration as JSON, or null to run on the registry's default model
     /// Example: {"provider": "anthropic", "name": "claude-opus-4-20250514", "title": "Claude Opus 4"}
     pub model: serde_json::Value,
 
     #[serde(rename = "userId")]
     pub user_id: Option<String>,
+
+    /// Cron expression for recurring task definitions
+    #[serde(rename = "cronExpression", default)]
+    pub cron_expression: Option<String>,
+
+    /// IANA timezone the cron expression is evaluated in
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    #[serde(rename = "recurrencePaused", default)]
+    pub recurrence_paused: bool,
+
+    /// Recurring definition this task was spawned from
+    #[serde(rename = "recurringTaskId", default)]
+    pub recurring_task_id: Option<String>,
+
+    /// JSON schema the result submitted by the agent must match
+    #[serde(rename = "resultSchema", default)]
+    pub result_schema: Option<serde_json::Value>,
+
+    /// Retry policy for transient failures; the default policy applies if unset
+    #[serde(rename = "retryPolicy", default)]
+    pub retry_policy: Option<RetryPolicy>,
+
+    /// Number of the current attempt, starting at 1
+    #[serde(default = "first_attempt")]
+    pub attempt: i32,
+
+    /// Earliest time a task waiting to be retried is picked up again
+    #[serde(rename = "retryAt", default)]
+    pub retry_at: Option<DateTime<Utc>>,
+
+    /// Task this one was forked from
+    #[serde(rename = "forkedFromId", default)]
+    pub forked_from_id: Option<String>,
+
+    /// Last message copied from the parent task when forking
+    #[serde(rename = "forkedFromMessageId", default)]
+    pub forked_from_message_id: Option<String>,
+
+    /// Limits on the resources the task may use
+    #[serde(default)]
+    pub budget: Option<TaskBudget>,
+
+    /// Resources the task has used so far
+    #[serde(default)]
+    pub usage: TaskUsage,
+
+    /// Desktop action waiting for a human to approve or reject it
+    #[serde(rename = "pendingApproval", default)]
+    pub pending_approval: Option<PendingApproval>,
+
+    /// Free-form `key` or `key=value` labels
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+fn first_attempt() -> i32 {
+    1
+}
+
+impl Default for Task {
+    /// A pending task without a description that runs on the default model
+    fn default() -> Self {
+        Self::new(String::new(), serde_json::Value::Null)
+    }
 }
 
 impl Task {
@@ -242,6 +349,20 @@ impl Task {
             result: None,
             model,
             user_id: None,
+            cron_expression: None,
+            timezone: None,
+            recurrence_paused: false,
+            recurring_task_id: None,
+            result_schema: None,
+            retry_policy: None,
+            attempt: first_attempt(),
+            retry_at: None,
+            forked_from_id: None,
+            forked_from_message_id: None,
+            budget: None,
+            usage: Default::default(),
+            pending_approval: None,
+            labels: Vec::new(),
         }
     }
 
@@ -261,6 +382,11 @@ impl Task {
         )
     }
 
+    /// Check if task is a recurring definition rather than a single run
+    pub fn is_recurring(&self) -> bool {
+        self.cron_expression.is_some()
+    }
+
     /// Validate task data integrity
     pub fn validate_integrity(&self) -> Result<(), String> {
         // Validate that completed tasks have completion timestamp
@@ -282,7 +408,11 @@ impl Task {
             return Err("Executed tasks must have execution timestamp".to_string());
         }
 
-        // Validate model structure
+        // Validate model structure; null selects the default model
+        if self.model.is_null() {
+            return Ok(());
+        }
+
         if !self.model.is_object() {
             return Err("Model must be a JSON object".to_string());
         }
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{computer_action::ComputerAction, computer_tools::ToolDefinition, task::TaskStatus};
+
+/// Status the model can set when it stops working on a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOutcome {
+    Completed,
+    NeedsHelp,
+}
+
+impl TaskOutcome {
+    /// Task status the outcome moves the task to
+    pub fn status(self) -> TaskStatus {
+        match self {
+            TaskOutcome::Completed => TaskStatus::Completed,
+            TaskOutcome::NeedsHelp => TaskStatus::NeedsHelp,
+        }
+    }
+}
+
+/// Call to one of the tools the model uses to finish a task, as opposed to a
+/// desktop action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", content = "input", rename_all = "snake_case")]
+pub enum TaskToolCall {
+    SetTaskStatus {
+        status: TaskOutcome,
+        description: String,
+    },
+    SubmitResult {
+        result: Value,
+    },
+}
+
+impl TaskToolCall {
+    /// Names of the task tools
+    pub const NAMES: [&'static str; 2] = ["set_task_status", "submit_result"];
+
+    /// Parse a tool call, or return `None` if `name` is not a task tool
+    pub fn from_tool_use(name: &str, input: &Value) -> Option<Result<Self, serde_json::Error>> {
+        if !Self::NAMES.contains(&name) {
+            return None;
+        }
+
+        Some(serde_json::from_value(
+            json!({ "name": name, "input": input }),
+        ))
+    }
+
+    /// Tool definitions for the task tools
+    pub fn tool_definitions() -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::new(
+                "set_task_status",
+                "Finish working on the task, either because it is completed or because you need help from the user",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "status": {
+                            "type": "string",
+                            "enum": ["completed", "needs_help"],
+                            "description": "Whether the task is completed or needs help from the user"
+                        },
+                        "description": {
+                            "type": "string",
+                            "description": "What was done, or what help is needed"
+                        }
+                    },
+                    "required": ["status", "description"]
+                }),
+            ),
+            ToolDefinition::new(
+                "submit_result",
+                "Submit the machine-readable result of the task before completing it",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "result": {
+                            "description": "The result as JSON, matching the result schema of the task if it has one"
+                        }
+                    },
+                    "required": ["result"]
+                }),
+            ),
+        ]
+    }
+}
+
+/// Every tool offered to the agent: desktop actions followed by task tools
+pub fn agent_tool_definitions() -> Vec<ToolDefinition> {
+    let mut tools = ComputerAction::tool_definitions();
+    tools.extend(TaskToolCall::tool_definitions());
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_task_tool_calls() {
+        let call = TaskToolCall::from_tool_use(
+            "set_task_status",
+            &json!({"status": "needs_help", "description": "Login required"}),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            call,
+            TaskToolCall::SetTaskStatus {
+                status: TaskOutcome::NeedsHelp,
+                description: "Login required".to_string(),
+            }
+        );
+
+        let call = TaskToolCall::from_tool_use("submit_result", &json!({"result": [1, 2]}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            call,
+            TaskToolCall::SubmitResult {
+                result: json!([1, 2])
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_input() {
+        assert!(
+            TaskToolCall::from_tool_use("set_task_status", &json!({"status": "failed"}))
+                .unwrap()
+                .is_err()
+        );
+        assert!(TaskToolCall::from_tool_use("click_mouse", &json!({})).is_none());
+    }
+
+    #[test]
+    fn test_agent_tool_definitions() {
+        let tools = agent_tool_definitions();
+        let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+
+        for name in TaskToolCall::NAMES {
+            assert!(names.contains(&name));
+        }
+        assert_eq!(
+            tools.len(),
+            ComputerAction::tool_definitions().len() + TaskToolCall::NAMES.len()
+        );
+    }
+
+    #[test]
+    fn test_outcome_status() {
+        assert_eq!(TaskOutcome::Completed.status(), TaskStatus::Completed);
+        assert_eq!(TaskOutcome::NeedsHelp.status(), TaskStatus::NeedsHelp);
+    }
+}
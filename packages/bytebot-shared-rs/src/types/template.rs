@@ -0,0 +1,328 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use validator::Validate;
+
+use super::{
+    api::{CreateTaskDto, TaskFileDto},
+    task::TaskPriority,
+};
+
+/// Type a template parameter value must have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateParameterType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+}
+
+impl TemplateParameterType {
+    /// Whether `value` has this type
+    pub fn accepts(self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Boolean => value.is_boolean(),
+        }
+    }
+}
+
+impl std::fmt::Display for TemplateParameterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::Number => write!(f, "number"),
+            Self::Integer => write!(f, "integer"),
+            Self::Boolean => write!(f, "boolean"),
+        }
+    }
+}
+
+/// Parameter filled into the `{{name}}` placeholders of a template
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateParameter {
+    pub name: String,
+
+    #[serde(rename = "type")]
+    pub param_type: TemplateParameterType,
+
+    pub description: Option<String>,
+
+    /// Whether a run has to provide a value when there is no default
+    #[serde(default = "default_required")]
+    pub required: bool,
+
+    pub default: Option<Value>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// Reusable task description with placeholders and default task settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskTemplate {
+    pub id: String,
+    pub name: String,
+
+    /// Task description with `{{name}}` placeholders
+    pub description: String,
+
+    pub parameters: Vec<TemplateParameter>,
+
+    /// AI model configuration of the created tasks as JSON
+    pub model: Option<Value>,
+
+    pub priority: TaskPriority,
+
+    /// Files attached to every created task
+    pub files: Vec<TaskFileDto>,
+
+    #[serde(rename = "resultSchema")]
+    pub result_schema: Option<Value>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskTemplate {
+    /// Build the task for a run, filling placeholders from `values`.
+    ///
+    /// Values must be declared parameters of the right type. Missing values
+    /// fall back to the parameter default, and optional parameters without a
+    /// default render as an empty string.
+    pub fn render(&self, values: &Map<String, Value>) -> Result<CreateTaskDto, String> {
+        for name in values.keys() {
+            if !self.parameters.iter().any(|p| &p.name == name) {
+                return Err(format!("Unknown parameter '{name}'"));
+            }
+        }
+
+        let mut resolved = Map::new();
+        for parameter in &self.parameters {
+            let value = match values.get(&parameter.name).or(parameter.default.as_ref()) {
+                Some(value) if parameter.param_type.accepts(value) => value.clone(),
+                Some(_) => {
+                    return Err(format!(
+                        "Parameter '{}' must be a {}",
+                        parameter.name, parameter.param_type
+                    ))
+                }
+                None if parameter.required => {
+                    return Err(format!("Missing required parameter '{}'", parameter.name))
+                }
+                None => Value::String(String::new()),
+            };
+            resolved.insert(parameter.name.clone(), value);
+        }
+
+        let description = placeholder_regex()
+            .replace_all(&self.description, |caps: &Captures| {
+                match resolved.get(&caps[1]) {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(value) => value.to_string(),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned();
+
+        Ok(CreateTaskDto {
+            description,
+            task_type: None,
+            scheduled_for: None,
+            priority: Some(self.priority),
+            created_by: None,
+            user_id: None,
+            model: self.model.clone(),
+            files: (!self.files.is_empty()).then(|| self.files.clone()),
+            recurrence: None,
+            result_schema: self.result_schema.clone(),
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
+        })
+    }
+}
+
+/// Data transfer object for creating or replacing a task template
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct CreateTaskTemplateDto {
+    #[validate(length(min = 1, message = "Template name cannot be empty"))]
+    pub name: String,
+
+    #[validate(length(min = 1, message = "Template description cannot be empty"))]
+    pub description: String,
+
+    #[serde(default)]
+    pub parameters: Vec<TemplateParameter>,
+
+    pub model: Option<Value>,
+
+    pub priority: Option<TaskPriority>,
+
+    #[validate]
+    pub files: Option<Vec<TaskFileDto>>,
+
+    #[serde(rename = "resultSchema")]
+    pub result_schema: Option<Value>,
+}
+
+impl CreateTaskTemplateDto {
+    /// Check that parameters are unique, defaults have the declared type and
+    /// every placeholder in the description is a declared parameter
+    pub fn check_parameters(&self) -> Result<(), String> {
+        let name_regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+        let mut names = HashSet::new();
+
+        for parameter in &self.parameters {
+            if !name_regex.is_match(&parameter.name) {
+                return Err(format!("Invalid parameter name '{}'", parameter.name));
+            }
+            if !names.insert(parameter.name.as_str()) {
+                return Err(format!("Duplicate parameter '{}'", parameter.name));
+            }
+            if let Some(default) = &parameter.default {
+                if !parameter.param_type.accepts(default) {
+                    return Err(format!(
+                        "Default of parameter '{}' must be a {}",
+                        parameter.name, parameter.param_type
+                    ));
+                }
+            }
+        }
+
+        for caps in placeholder_regex().captures_iter(&self.description) {
+            if !names.contains(&caps[1]) {
+                return Err(format!("Placeholder '{}' is not a parameter", &caps[1]));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Data transfer object for running a template
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RunTemplateDto {
+    /// Parameter values by name
+    #[serde(default)]
+    pub parameters: Map<String, Value>,
+}
+
+/// Matches `{{name}}`, allowing whitespace inside the braces
+fn placeholder_regex() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn template() -> TaskTemplate {
+        let dto: CreateTaskTemplateDto = serde_json::from_value(json!({
+            "name": "Daily export",
+            "description": "Export the {{ report }} report for {{days}} days, compressed: {{zip}}",
+            "parameters": [
+                {"name": "report", "type": "string"},
+                {"name": "days", "type": "integer", "default": 7},
+                {"name": "zip", "type": "boolean", "required": false}
+            ],
+            "priority": "HIGH"
+        }))
+        .unwrap();
+        assert!(dto.check_parameters().is_ok());
+
+        TaskTemplate {
+            id: "template-1".to_string(),
+            name: dto.name,
+            description: dto.description,
+            parameters: dto.parameters,
+            model: dto.model,
+            priority: dto.priority.unwrap_or_default(),
+            files: dto.files.unwrap_or_default(),
+            result_schema: dto.result_schema,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn values(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_render() {
+        let task = template()
+            .render(&values(json!({"report": "sales", "zip": true})))
+            .unwrap();
+
+        assert_eq!(
+            task.description,
+            "Export the sales report for 7 days, compressed: true"
+        );
+        assert_eq!(task.priority, Some(TaskPriority::High));
+        assert!(task.files.is_none());
+
+        let task = template()
+            .render(&values(json!({"report": "sales", "days": 30})))
+            .unwrap();
+        assert_eq!(
+            task.description,
+            "Export the sales report for 30 days, compressed: "
+        );
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_values() {
+        let template = template();
+
+        assert!(template.render(&values(json!({}))).is_err());
+        assert!(template
+            .render(&values(json!({"report": "sales", "days": 1.5})))
+            .is_err());
+        assert!(template
+            .render(&values(json!({"report": "sales", "format": "csv"})))
+            .is_err());
+    }
+
+    #[test]
+    fn test_check_parameters() {
+        let mut dto = CreateTaskTemplateDto {
+            name: "Cleanup".to_string(),
+            description: "Delete files older than {{age}}".to_string(),
+            parameters: vec![],
+            model: None,
+            priority: None,
+            files: None,
+            result_schema: None,
+        };
+        assert!(dto.check_parameters().is_err());
+
+        dto.parameters.push(TemplateParameter {
+            name: "age".to_string(),
+            param_type: TemplateParameterType::Number,
+            description: None,
+            required: true,
+            default: Some(json!("a week")),
+        });
+        assert!(dto.check_parameters().is_err());
+
+        dto.parameters[0].default = Some(json!(7));
+        assert!(dto.check_parameters().is_ok());
+
+        dto.parameters.push(dto.parameters[0].clone());
+        assert!(dto.check_parameters().is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tokens, latency and estimated cost of one model call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelUsage {
+    pub id: String,
+
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+
+    /// Message holding the response, unless the model answered with nothing
+    #[serde(rename = "messageId")]
+    pub message_id: Option<String>,
+
+    /// Owner of the task
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+
+    pub provider: String,
+
+    pub model: String,
+
+    /// Input tokens read neither from nor into the prompt cache
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u64,
+
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u64,
+
+    #[serde(rename = "cacheReadTokens")]
+    pub cache_read_tokens: u64,
+
+    #[serde(rename = "cacheWriteTokens")]
+    pub cache_write_tokens: u64,
+
+    /// Time from sending the request to receiving the complete response
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: u64,
+
+    /// Estimated cost in US dollars, or `None` for a model without a price
+    #[serde(rename = "costUsd")]
+    pub cost_usd: Option<f64>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Sums over a set of model calls
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsageTotals {
+    pub calls: u64,
+
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u64,
+
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u64,
+
+    #[serde(rename = "cacheReadTokens")]
+    pub cache_read_tokens: u64,
+
+    #[serde(rename = "cacheWriteTokens")]
+    pub cache_write_tokens: u64,
+
+    /// Estimated cost in US dollars of the calls with a price
+    #[serde(rename = "costUsd")]
+    pub cost_usd: f64,
+
+    /// Calls to models without a price, left out of the cost
+    #[serde(rename = "unpricedCalls")]
+    pub unpriced_calls: u64,
+
+    #[serde(rename = "averageLatencyMs")]
+    pub average_latency_ms: f64,
+}
+
+/// Usage of one model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelUsageTotals {
+    pub provider: String,
+
+    pub model: String,
+
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Usage of the tasks of one user
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserUsageTotals {
+    /// `None` for tasks created without signing in
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Usage over the calls matching a query, broken down by model and by user
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsageReport {
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+
+    /// Most expensive model first
+    #[serde(rename = "byModel")]
+    pub by_model: Vec<ModelUsageTotals>,
+
+    /// Most expensive user first
+    #[serde(rename = "byUser")]
+    pub by_user: Vec<UserUsageTotals>,
+}
+
+/// Usage of a task with every model call it made
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskUsageReport {
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+
+    /// Most expensive model first
+    #[serde(rename = "byModel")]
+    pub by_model: Vec<ModelUsageTotals>,
+
+    /// Oldest call first
+    pub calls: Vec<ModelUsage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_usage_report_serialization() {
+        let report = UsageReport {
+            totals: UsageTotals {
+                calls: 2,
+                input_tokens: 1_000,
+                output_tokens: 200,
+                cost_usd: 0.006,
+                ..UsageTotals::default()
+            },
+            by_model: vec![ModelUsageTotals {
+                provider: "openai".to_string(),
+                model: "gpt-4o".to_string(),
+                totals: UsageTotals {
+                    calls: 2,
+                    ..UsageTotals::default()
+                },
+            }],
+            by_user: vec![],
+        };
+
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["calls"], 2);
+        assert_eq!(value["inputTokens"], 1_000);
+        assert_eq!(value["costUsd"], 0.006);
+        assert_eq!(
+            value["byModel"][0],
+            json!({
+                "provider": "openai",
+                "model": "gpt-4o",
+                "calls": 2,
+                "inputTokens": 0,
+                "outputTokens": 0,
+                "cacheReadTokens": 0,
+                "cacheWriteTokens": 0,
+                "costUsd": 0.0,
+                "unpricedCalls": 0,
+                "averageLatencyMs": 0.0
+            })
+        );
+    }
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use super::message::{DocumentSource, MessageContentBlock};
+
 /// User entity matching Prisma schema (Better Auth)
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, Validate)]
 pub struct User {
@@ -319,6 +321,24 @@ impl File {
         )
     }
 
+    /// Content block that shows the file to a model: an image block for
+    /// images and a document block for everything else
+    pub fn to_content_block(&self) -> MessageContentBlock {
+        if self.is_image() {
+            return MessageContentBlock::image(self.file_type.clone(), self.data.clone());
+        }
+
+        MessageContentBlock::Document {
+            source: DocumentSource {
+                source_type: "base64".to_string(),
+                media_type: self.file_type.clone(),
+                data: self.data.clone(),
+            },
+            name: Some(self.name.clone()),
+            size: Some(self.size.into()),
+        }
+    }
+
     /// Get human-readable file size
     pub fn human_readable_size(&self) -> String {
         let size = self.size as f64;
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::{
+    label::validate_labels,
+    task::{Role, TaskPriority, TaskStatus, TaskType},
+};
+
+/// Task field a task list can be ordered by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    ScheduledFor,
+    Priority,
+    Status,
+}
+
+impl std::str::FromStr for TaskSortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "createdAt" => Ok(Self::CreatedAt),
+            "updatedAt" => Ok(Self::UpdatedAt),
+            "scheduledFor" => Ok(Self::ScheduledFor),
+            "priority" => Ok(Self::Priority),
+            "status" => Ok(Self::Status),
+            _ => Err(format!("Invalid TaskSortField: {s}")),
+        }
+    }
+}
+
+/// Direction of a task list order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl std::str::FromStr for SortDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(Self::Asc),
+            "desc" => Ok(Self::Desc),
+            _ => Err(format!("Invalid SortDirection: {s}")),
+        }
+    }
+}
+
+/// Order of a task list, newest first by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskSort {
+    #[serde(default)]
+    pub field: TaskSortField,
+
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+/// Filter of a saved view, matching the filters of the task list
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate, PartialEq)]
+pub struct TaskViewFilter {
+    pub status: Option<TaskStatus>,
+
+    pub priority: Option<TaskPriority>,
+
+    #[serde(rename = "type")]
+    pub task_type: Option<TaskType>,
+
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<Role>,
+
+    /// Labels a task must all have; `key` matches the key with any value
+    #[serde(default)]
+    #[validate(custom = "validate_labels")]
+    pub labels: Vec<String>,
+
+    #[serde(rename = "dependsOn")]
+    pub depends_on: Option<String>,
+
+    pub blocked: Option<bool>,
+}
+
+/// Named task filter and order saved by a user
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskView {
+    pub id: String,
+    pub name: String,
+
+    /// Owner of the view; views created without authentication have none
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+
+    pub filter: TaskViewFilter,
+
+    pub sort: TaskSort,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for creating or replacing a saved view
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct CreateTaskViewDto {
+    #[validate(length(min = 1, max = 100, message = "View name must be 1 to 100 characters"))]
+    pub name: String,
+
+    #[serde(default)]
+    #[validate]
+    pub filter: TaskViewFilter,
+
+    #[serde(default)]
+    pub sort: TaskSort,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_view_dto_deserialization() {
+        let dto: CreateTaskViewDto = serde_json::from_value(json!({
+            "name": "Acme failures",
+            "filter": {"status": "FAILED", "labels": ["customer=acme"]},
+            "sort": {"field": "updatedAt"}
+        }))
+        .unwrap();
+
+        assert!(dto.validate().is_ok());
+        assert_eq!(dto.filter.status, Some(TaskStatus::Failed));
+        assert_eq!(dto.sort.field, TaskSortField::UpdatedAt);
+        assert_eq!(dto.sort.direction, SortDirection::Desc);
+
+        let dto: CreateTaskViewDto = serde_json::from_value(json!({
+            "name": "Broken",
+            "filter": {"labels": ["not a label"]}
+        }))
+        .unwrap();
+        assert!(dto.validate().is_err());
+    }
+}
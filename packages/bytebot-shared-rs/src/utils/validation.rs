@@ -75,6 +75,9 @@ impl CustomValidate for UpdateTaskDto {
             && self.queued_at.is_none()
             && self.executed_at.is_none()
             && self.completed_at.is_none()
+            && self.control.is_none()
+            && self.budget.is_none()
+            && self.labels.is_none()
         {
             return Err(ValidationErrorType::InvalidInput(
                 "At least one field must be provided for update".to_string(),
@@ -444,6 +447,12 @@ mod tests {
             user_id: None,
             model: Some(json!({"provider": "anthropic", "name": "claude-3"})),
             files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
         assert!(validate_with_custom(&valid_dto).is_ok());
 
@@ -456,6 +465,12 @@ mod tests {
             user_id: None,
             model: Some(json!({"provider": "anthropic", "name": "claude-3"})),
             files: None,
+            recurrence: None,
+            result_schema: None,
+            retry_policy: None,
+            depends_on: None,
+            budget: None,
+            labels: None,
         };
         assert!(validate_with_custom(&invalid_dto).is_err());
     }
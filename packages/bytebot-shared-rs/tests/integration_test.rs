@@ -2,7 +2,9 @@ use bytebot_shared_rs::{
     error::ServiceError,
     types::{
         api::{AddTaskMessageDto, CreateTaskDto, TaskFileDto, UpdateTaskDto},
+        message::MessageContentBlock,
         task::{Role, TaskPriority, TaskStatus, TaskType},
+        user::File,
     },
     utils::validation::{validate_email, validate_file_path, validate_uuid, validate_with_custom},
 };
@@ -26,6 +28,12 @@ fn test_create_task_dto_integration() {
             "title": "Claude 3 Opus"
         })),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     };
 
     assert!(valid_dto.validate().is_ok());
@@ -41,6 +49,12 @@ fn test_create_task_dto_integration() {
         user_id: None,
         model: None,
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     };
 
     assert!(invalid_dto.validate().is_err());
@@ -58,6 +72,12 @@ fn test_create_task_dto_integration() {
             "name": "claude-3"
         })),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     };
 
     assert!(validate_with_custom(&scheduled_invalid).is_err());
@@ -72,6 +92,9 @@ fn test_update_task_dto_integration() {
         queued_at: Some(Utc::now()),
         executed_at: Some(Utc::now()),
         completed_at: None,
+        control: None,
+        budget: None,
+        labels: None,
     };
 
     assert!(valid_update.validate().is_ok());
@@ -84,6 +107,9 @@ fn test_update_task_dto_integration() {
         queued_at: None,
         executed_at: None,
         completed_at: None,
+        control: None,
+        budget: None,
+        labels: None,
     };
 
     assert!(validate_with_custom(&empty_update).is_err());
@@ -147,6 +173,49 @@ fn test_task_file_dto_integration() {
     assert!(zero_size_file.validate().is_err());
 }
 
+#[test]
+fn test_file_content_blocks() {
+    let image = File::new(
+        "screen.png".to_string(),
+        "image/png".to_string(),
+        11,
+        "SGVsbG8gV29ybGQ=".to_string(),
+        "task-1".to_string(),
+    );
+    assert!(matches!(
+        image.to_content_block(),
+        MessageContentBlock::Image { source } if source.media_type == "image/png"
+    ));
+
+    let notes = File::new(
+        "notes.txt".to_string(),
+        "text/plain".to_string(),
+        11,
+        "SGVsbG8gV29ybGQ=".to_string(),
+        "task-1".to_string(),
+    );
+    match notes.to_content_block() {
+        MessageContentBlock::Document { source, name, size } => {
+            assert_eq!(name.as_deref(), Some("notes.txt"));
+            assert_eq!(size, Some(11));
+            assert_eq!(source.decode_text().as_deref(), Some("Hello World"));
+        }
+        block => panic!("Expected document block, got {block:?}"),
+    }
+
+    let sheet = File::new(
+        "data.xlsx".to_string(),
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+        11,
+        "SGVsbG8gV29ybGQ=".to_string(),
+        "task-1".to_string(),
+    );
+    match sheet.to_content_block() {
+        MessageContentBlock::Document { source, .. } => assert!(source.decode_text().is_none()),
+        block => panic!("Expected document block, got {block:?}"),
+    }
+}
+
 #[test]
 fn test_validation_utilities_integration() {
     // Test email validation
@@ -203,6 +272,12 @@ fn test_dto_serialization_deserialization() {
             "name": "claude-3"
         })),
         files: None,
+        recurrence: None,
+        result_schema: None,
+        retry_policy: None,
+        depends_on: None,
+        budget: None,
+        labels: None,
     };
 
     let json_str = serde_json::to_string(&dto).unwrap();
@@ -221,6 +296,9 @@ fn test_dto_serialization_deserialization() {
         queued_at: Some(Utc::now()),
         executed_at: None,
         completed_at: None,
+        control: None,
+        budget: None,
+        labels: None,
     };
 
     let json_str = serde_json::to_string(&update_dto).unwrap();
@@ -269,3 +347,16 @@ fn test_api_response_structures() {
     assert_eq!(paginated.pagination.pages, 1);
     assert!(paginated.success);
 }
+
+#[test]
+fn test_task_status_uses_postgres_labels() {
+    for (status, label) in [
+        (TaskStatus::NeedsHelp, "NEEDS_HELP"),
+        (TaskStatus::NeedsReview, "NEEDS_REVIEW"),
+        (TaskStatus::Completed, "COMPLETED"),
+    ] {
+        assert_eq!(status.to_string(), label);
+        assert_eq!(label.parse::<TaskStatus>().unwrap(), status);
+        assert_eq!(serde_json::to_value(status).unwrap(), json!(label));
+    }
+}
@@ -4,11 +4,17 @@ pub mod keyboard;
 pub mod mouse;
 pub mod screen;
 
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
 use async_trait::async_trait;
 use bytebot_shared_rs::types::computer_action::{
     Application, Button, ComputerAction, Coordinates, Press, ScrollDirection,
 };
 use serde_json::Value;
+use tokio_util::sync::CancellationToken;
 
 use crate::error::AutomationError;
 
@@ -20,6 +26,32 @@ pub struct AutomationService {
     pub keyboard: keyboard::KeyboardService,
     pub applications: applications::ApplicationService,
     pub files: files::FileService,
+    /// Cancellation tokens of the actions in progress, by the ID their
+    /// requests were sent with
+    running_actions: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+/// Action registered under an ID while it runs; dropping it unregisters it
+pub struct RunningAction {
+    id: String,
+    token: CancellationToken,
+    running_actions: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+impl RunningAction {
+    /// Token that fires when the action is cancelled
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for RunningAction {
+    fn drop(&mut self) {
+        self.running_actions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
 }
 
 impl AutomationService {
@@ -30,9 +62,52 @@ impl AutomationService {
             keyboard: keyboard::KeyboardService::new()?,
             applications: applications::ApplicationService::new()?,
             files: files::FileService::new()?,
+            running_actions: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
+    /// Register an action under `id` so `cancel_action` can interrupt it,
+    /// until the returned guard is dropped
+    pub fn start_action(&self, id: &str) -> Result<RunningAction, AutomationError> {
+        let mut running_actions = self
+            .running_actions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if running_actions.contains_key(id) {
+            return Err(AutomationError::Validation(format!(
+                "Action {id} is already running"
+            )));
+        }
+
+        let token = CancellationToken::new();
+        running_actions.insert(id.to_string(), token.clone());
+
+        Ok(RunningAction {
+            id: id.to_string(),
+            token,
+            running_actions: self.running_actions.clone(),
+        })
+    }
+
+    /// Interrupt the action registered under `id`; other actions keep running.
+    ///
+    /// Returns whether such an action was in progress.
+    pub fn cancel_action(&self, id: &str) -> bool {
+        let token = self
+            .running_actions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+
+        match token {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Execute a computer action and return the result as JSON
     pub async fn execute_action(&self, action: ComputerAction) -> Result<Value, AutomationError> {
         match action {
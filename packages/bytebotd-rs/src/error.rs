@@ -38,6 +38,9 @@ pub enum AutomationError {
 
     #[error("Validation error: {0}")]
     Validation(String),
+
+    #[error("Action cancelled")]
+    Cancelled,
 }
 
 #[derive(Debug, Error)]
@@ -112,6 +115,11 @@ impl IntoResponse for ServiceError {
                 format!("System error: {msg}"),
                 "SYSTEM_ERROR",
             ),
+            ServiceError::Automation(AutomationError::Cancelled) => (
+                StatusCode::CONFLICT,
+                "Action cancelled".to_string(),
+                "ACTION_CANCELLED",
+            ),
             ServiceError::Serialization(e) => (
                 StatusCode::BAD_REQUEST,
                 format!("Invalid request format: {e}"),
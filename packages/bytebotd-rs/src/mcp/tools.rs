@@ -70,7 +70,7 @@ pub struct ClickMouseRequest {
     #[schemars(description = "The mouse button to click")]
     pub button: ButtonSchema,
     #[schemars(description = "Number of clicks to perform (e.g., 2 for double-click)")]
-    #[serde(default = "default_click_count")]
+    #[serde(default = "default_click_count", alias = "clickCount")]
     pub click_count: Option<u32>,
 }
 
@@ -116,6 +116,7 @@ pub struct ScrollRequest {
     #[schemars(description = "The direction to scroll")]
     pub direction: ScrollDirection,
     #[schemars(description = "The number of times to scroll")]
+    #[serde(alias = "scrollCount")]
     pub scroll_count: u32,
 }
 
@@ -818,6 +819,18 @@ mod tests {
         assert!(tool_names.contains(&"read_file"));
         assert!(tool_names.contains(&"write_file"));
         assert!(tool_names.contains(&"wait"));
+
+        // Every MCP tool is also offered to the models under the same name
+        let canonical: Vec<String> = ComputerAction::tool_definitions()
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        for name in tool_names {
+            assert!(
+                canonical.iter().any(|c| c == name),
+                "{name} has no canonical tool definition"
+            );
+        }
     }
 
     #[test]
@@ -1102,6 +1115,14 @@ mod tests {
         });
         let parsed: ClickMouseRequest = serde_json::from_value(click_request_json).unwrap();
         assert_eq!(parsed.click_count, Some(3));
+
+        // The agent's tool calls use the camelCase name from ComputerAction
+        let click_request_json = serde_json::json!({
+            "button": "left",
+            "clickCount": 2
+        });
+        let parsed: ClickMouseRequest = serde_json::from_value(click_request_json).unwrap();
+        assert_eq!(parsed.click_count, Some(2));
     }
 
     #[test]
@@ -1,19 +1,25 @@
 use std::sync::Arc;
 
-use axum::{extract::State, response::Json};
+use axum::{extract::State, http::HeaderMap, response::Json};
 use base64::Engine;
-use bytebot_shared_rs::types::computer_action::{Application, ComputerAction, Validate};
+use bytebot_shared_rs::types::computer_action::{
+    Application, CancelComputerActionDto, ComputerAction, Validate, ACTION_ID_HEADER,
+};
 use serde_json::{json, Value};
 use tracing::{debug, error, info, warn};
 
 use crate::{
     automation::{AutomationService, ComputerAutomation},
-    error::ServiceError,
+    error::{AutomationError, ServiceError},
 };
 
-/// Handle computer automation actions
+/// Handle computer automation actions.
+///
+/// A request sent with an `x-action-id` header can be interrupted through
+/// `/computer-use/cancel` with that ID.
 pub async fn handle_computer_action(
     State(automation_service): State<Arc<AutomationService>>,
+    headers: HeaderMap,
     Json(action): Json<ComputerAction>,
 ) -> Result<Json<Value>, ServiceError> {
     info!("Received computer action: {:?}", action);
@@ -21,11 +27,57 @@ pub async fn handle_computer_action(
     // Validate the action before processing
     if let Err(validation_error) = action.validate() {
         warn!("Invalid computer action received: {}", validation_error);
-        return Err(ServiceError::Automation(
-            crate::error::AutomationError::Validation(validation_error.to_string()),
-        ));
+        return Err(ServiceError::Automation(AutomationError::Validation(
+            validation_error.to_string(),
+        )));
     }
 
+    let action_id = headers
+        .get(ACTION_ID_HEADER)
+        .map(|value| {
+            value.to_str().map_err(|_| {
+                ServiceError::Automation(AutomationError::Validation(format!(
+                    "Invalid {ACTION_ID_HEADER} header"
+                )))
+            })
+        })
+        .transpose()?;
+
+    let Some(action_id) = action_id else {
+        return Ok(Json(perform_action(&automation_service, action).await?));
+    };
+
+    // Long actions such as typing with a delay, tracing a path or waiting stop
+    // at their next step when cancelled
+    let running = automation_service.start_action(action_id)?;
+    let result = tokio::select! {
+        result = perform_action(&automation_service, action) => result?,
+        _ = running.token().cancelled() => {
+            warn!("Computer action {} cancelled", action_id);
+            return Err(ServiceError::Automation(AutomationError::Cancelled));
+        }
+    };
+
+    Ok(Json(result))
+}
+
+/// Interrupt the computer action sent with the given ID; actions of other
+/// tasks keep running
+pub async fn cancel_computer_action(
+    State(automation_service): State<Arc<AutomationService>>,
+    Json(dto): Json<CancelComputerActionDto>,
+) -> Json<Value> {
+    info!("Cancelling computer action {}", dto.action_id);
+    let cancelled = automation_service.cancel_action(&dto.action_id);
+
+    Json(json!({ "success": true, "cancelled": cancelled }))
+}
+
+/// Perform a validated action and build the response payload
+async fn perform_action(
+    automation_service: &AutomationService,
+    action: ComputerAction,
+) -> Result<Value, ServiceError> {
     let result = match action {
         ComputerAction::Screenshot => {
             debug!("Taking screenshot");
@@ -502,7 +554,7 @@ pub async fn handle_computer_action(
         }
     };
 
-    Ok(Json(result))
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -518,7 +570,8 @@ mod tests {
             Arc::new(AutomationService::new().expect("Failed to create automation service"));
 
         let action = ComputerAction::Screenshot;
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         // In headless environments or CI, screenshot might fail
         // This is expected behavior, so we handle both cases
@@ -545,7 +598,8 @@ mod tests {
         let coordinates = Coordinates { x: 100, y: 200 };
         let action = ComputerAction::MoveMouse { coordinates };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Move mouse action should succeed");
 
@@ -569,7 +623,8 @@ mod tests {
             hold_keys: Some(vec!["ctrl".to_string()]),
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Click mouse action should succeed");
 
@@ -590,7 +645,8 @@ mod tests {
             sensitive: Some(false),
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Type text action should succeed");
 
@@ -610,7 +666,8 @@ mod tests {
             text: "Pasted content".to_string(),
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Paste text action should succeed");
 
@@ -632,7 +689,8 @@ mod tests {
             hold_keys: None,
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Scroll action should succeed");
 
@@ -652,7 +710,8 @@ mod tests {
             press: bytebot_shared_rs::types::computer_action::Press::Down,
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Press keys action should succeed");
 
@@ -680,7 +739,8 @@ mod tests {
             hold_keys: None,
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Drag mouse action should succeed");
 
@@ -702,7 +762,8 @@ mod tests {
             hold_keys: None,
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         assert!(result.is_ok(), "Trace mouse action should succeed");
 
@@ -721,7 +782,8 @@ mod tests {
             application: Application::Firefox,
         };
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         // Application switching may fail in test environment, but should not panic
         match result {
@@ -763,8 +825,12 @@ mod tests {
             data: base64_content.clone(),
         };
 
-        let write_result =
-            handle_computer_action(State(automation_service.clone()), Json(write_action)).await;
+        let write_result = handle_computer_action(
+            State(automation_service.clone()),
+            HeaderMap::new(),
+            Json(write_action),
+        )
+        .await;
         assert!(write_result.is_ok(), "Write file action should succeed");
 
         let write_response = write_result.unwrap().0;
@@ -781,8 +847,12 @@ mod tests {
             path: test_file_path.to_string(),
         };
 
-        let read_result =
-            handle_computer_action(State(automation_service), Json(read_action)).await;
+        let read_result = handle_computer_action(
+            State(automation_service),
+            HeaderMap::new(),
+            Json(read_action),
+        )
+        .await;
         assert!(read_result.is_ok(), "Read file action should succeed");
 
         let read_response = read_result.unwrap().0;
@@ -808,9 +878,12 @@ mod tests {
             path: "".to_string(),
         };
 
-        let result =
-            handle_computer_action(State(automation_service.clone()), Json(empty_path_action))
-                .await;
+        let result = handle_computer_action(
+            State(automation_service.clone()),
+            HeaderMap::new(),
+            Json(empty_path_action),
+        )
+        .await;
         assert!(result.is_err(), "Empty path should fail");
 
         // Test suspicious path
@@ -820,6 +893,7 @@ mod tests {
 
         let result = handle_computer_action(
             State(automation_service.clone()),
+            HeaderMap::new(),
             Json(suspicious_path_action),
         )
         .await;
@@ -831,8 +905,12 @@ mod tests {
             data: "invalid-base64-data!@#$%".to_string(),
         };
 
-        let result =
-            handle_computer_action(State(automation_service), Json(invalid_base64_action)).await;
+        let result = handle_computer_action(
+            State(automation_service),
+            HeaderMap::new(),
+            Json(invalid_base64_action),
+        )
+        .await;
         assert!(result.is_err(), "Invalid base64 data should fail");
     }
 
@@ -843,7 +921,8 @@ mod tests {
 
         let action = ComputerAction::CursorPosition;
 
-        let result = handle_computer_action(State(automation_service), Json(action)).await;
+        let result =
+            handle_computer_action(State(automation_service), HeaderMap::new(), Json(action)).await;
 
         // Cursor position may fail in headless environment, but should not panic
         match result {
@@ -859,4 +938,53 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_cancel_only_the_given_action() {
+        let automation_service =
+            Arc::new(AutomationService::new().expect("Failed to create automation service"));
+
+        let wait = |id: &'static str, duration: u64| {
+            let automation_service = automation_service.clone();
+            let mut headers = HeaderMap::new();
+            headers.insert(ACTION_ID_HEADER, id.parse().unwrap());
+            tokio::spawn(async move {
+                handle_computer_action(
+                    State(automation_service),
+                    headers,
+                    Json(ComputerAction::Wait { duration }),
+                )
+                .await
+            })
+        };
+
+        let task_a = wait("task-a", 60_000);
+        let task_b = wait("task-b", 200);
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let response = cancel_computer_action(
+            State(automation_service.clone()),
+            Json(CancelComputerActionDto {
+                action_id: "task-a".to_string(),
+            }),
+        )
+        .await;
+        assert_eq!(response.0["cancelled"], true);
+
+        assert!(matches!(
+            task_a.await.unwrap(),
+            Err(ServiceError::Automation(AutomationError::Cancelled))
+        ));
+        assert!(task_b.await.unwrap().is_ok());
+
+        // Finished actions are no longer registered
+        let response = cancel_computer_action(
+            State(automation_service),
+            Json(CancelComputerActionDto {
+                action_id: "task-b".to_string(),
+            }),
+        )
+        .await;
+        assert_eq!(response.0["cancelled"], false);
+    }
 }
@@ -29,6 +29,10 @@ pub fn create_routes(
         .route("/health", get(health::health_check))
         // Computer use endpoint
         .route("/computer-use", post(computer_use::handle_computer_action))
+        .route(
+            "/computer-use/cancel",
+            post(computer_use::cancel_computer_action),
+        )
         .nest("/", mcp_routes)
         .with_state(automation_service)
         // Enhanced health endpoints with metrics